@@ -2,6 +2,8 @@
 
 mod hello_bigmath;
 mod poly1305;
+mod rational;
 
 pub use self::hello_bigmath::factorial;
 pub use self::poly1305::Poly1305;
+pub use self::rational::Rational;
@@ -0,0 +1,260 @@
+/*
+Rational numbers:
+Fractions numerator / denominator of big integers, kept in lowest terms with a
+positive denominator, so that two equal numbers have the same representation.
+With them, math::solve_exact solves linear systems with integer or rational
+coefficients exactly, where floats accumulate rounding errors.
+https://en.wikipedia.org/wiki/Rational_number
+*/
+
+use crate::math::Field;
+use num_bigint::BigInt;
+use num_traits::{One, Signed, Zero};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: BigInt,
+    // Always positive
+    denominator: BigInt,
+}
+
+fn gcd(mut a: BigInt, mut b: BigInt) -> BigInt {
+    while !b.is_zero() {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    a.abs()
+}
+
+impl Rational {
+    /// The fraction numerator / denominator, reduced. Panics if the
+    /// denominator is zero.
+    pub fn new(numerator: impl Into<BigInt>, denominator: impl Into<BigInt>) -> Self {
+        let (numerator, denominator) = (numerator.into(), denominator.into());
+        assert!(!denominator.is_zero(), "the denominator must not be zero");
+        let divisor = gcd(numerator.clone(), denominator.clone());
+        let (numerator, denominator) = (numerator / &divisor, denominator / &divisor);
+        if denominator.is_negative() {
+            Rational {
+                numerator: -numerator,
+                denominator: -denominator,
+            }
+        } else {
+            Rational {
+                numerator,
+                denominator,
+            }
+        }
+    }
+
+    pub fn from_integer(value: impl Into<BigInt>) -> Self {
+        Rational {
+            numerator: value.into(),
+            denominator: BigInt::one(),
+        }
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator.is_one()
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, other: Rational) -> Rational {
+        Rational::new(
+            self.numerator * &other.denominator + other.numerator * &self.denominator,
+            self.denominator * other.denominator,
+        )
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, other: Rational) -> Rational {
+        self + -other
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, other: Rational) -> Rational {
+        Rational::new(
+            self.numerator * other.numerator,
+            self.denominator * other.denominator,
+        )
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    /// Panics on division by zero.
+    fn div(self, other: Rational) -> Rational {
+        assert!(!other.numerator.is_zero(), "division by zero");
+        Rational::new(
+            self.numerator * other.denominator,
+            self.denominator * other.numerator,
+        )
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numerator: -self.numerator,
+            denominator: self.denominator,
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl Field for Rational {
+    fn zero() -> Self {
+        Rational::from_integer(0)
+    }
+
+    fn one() -> Self {
+        Rational::from_integer(1)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::solve_exact;
+
+    fn integers(rows: &[&[i64]]) -> Vec<Vec<Rational>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&x| Rational::from_integer(x)).collect())
+            .collect()
+    }
+
+    fn product(a: &[Vec<Rational>], x: &[Rational]) -> Vec<Rational> {
+        a.iter()
+            .map(|row| {
+                row.iter()
+                    .zip(x)
+                    .fold(Rational::zero(), |sum, (a, x)| sum + a.clone() * x.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn arithmetic() {
+        let half = Rational::new(2, 4);
+        assert_eq!(half, Rational::new(-1, -2));
+        assert_eq!(half.to_string(), "1/2");
+        assert_eq!(Rational::new(3, -6).to_string(), "-1/2");
+        assert_eq!(Rational::new(0, -5), Rational::zero());
+        let third = Rational::new(1, 3);
+        assert_eq!(half.clone() + third.clone(), Rational::new(5, 6));
+        assert_eq!(half.clone() - third.clone(), Rational::new(1, 6));
+        assert_eq!(half.clone() * third.clone(), Rational::new(1, 6));
+        assert_eq!(half / third, Rational::new(3, 2));
+        assert_eq!((Rational::new(6, 3)).to_string(), "2");
+        assert!(Rational::new(6, 3).is_integer());
+        assert_eq!(*Rational::new(6, 4).numerator(), BigInt::from(3));
+        assert_eq!(*Rational::new(6, 4).denominator(), BigInt::from(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "the denominator must not be zero")]
+    fn zero_denominator() {
+        Rational::new(1, 0);
+    }
+
+    #[test]
+    fn hilbert_system() {
+        // The Hilbert matrix is so ill-conditioned that floats lose the
+        // solution: with n = 8, the answer of [1, ..., 1] is exact here
+        let n = 8;
+        let a: Vec<Vec<Rational>> = (0..n)
+            .map(|i| (0..n).map(|j| Rational::new(1, i + j + 1)).collect())
+            .collect();
+        let x = vec![Rational::one(); n];
+        let b = product(&a, &x);
+        let solution = solve_exact(&a, &b).unwrap();
+        assert_eq!(solution.particular, x);
+        assert!(solution.null_space.is_empty());
+    }
+
+    #[test]
+    fn integer_system_beyond_f32() {
+        // 2^24 + 1 is not a f32, but it is exact as a rational, and so is
+        // x = 1 in x + y = 2^24 + 2 and y = 2^24 + 1
+        let big = (1 << 24) + 1;
+        let a = integers(&[&[1, 1], &[0, 1]]);
+        let b = vec![Rational::from_integer(big + 1), Rational::from_integer(big)];
+        let solution = solve_exact(&a, &b).unwrap();
+        assert_eq!(
+            solution.particular,
+            vec![Rational::one(), Rational::from_integer(big)]
+        );
+    }
+
+    #[test]
+    fn free_variables() {
+        // x + 2y - z = 3 and 2x + 4y + z = 9: z = 1, x = 4 - 2y
+        let a = integers(&[&[1, 2, -1], &[2, 4, 1]]);
+        let b = vec![Rational::from_integer(3), Rational::from_integer(9)];
+        let solution = solve_exact(&a, &b).unwrap();
+        assert_eq!(
+            solution.particular,
+            vec![Rational::from_integer(4), Rational::zero(), Rational::one()]
+        );
+        assert_eq!(
+            solution.null_space,
+            vec![vec![
+                Rational::from_integer(-2),
+                Rational::one(),
+                Rational::zero()
+            ]]
+        );
+
+        // x + y = 1 and 2x + 2y = 3
+        let a = integers(&[&[1, 1], &[2, 2]]);
+        let b = vec![Rational::one(), Rational::from_integer(3)];
+        assert_eq!(solve_exact(&a, &b), None);
+
+        // Fractions in the answer: 3x = 1
+        let a = integers(&[&[3]]);
+        let solution = solve_exact(&a, &[Rational::one()]).unwrap();
+        assert_eq!(solution.particular, vec![Rational::new(1, 3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn division_by_zero() {
+        let _ = Rational::one() / Rational::zero();
+    }
+}
@@ -0,0 +1,236 @@
+use std::ops::Range;
+
+use super::LazyMonoid;
+
+/// A segment tree over the values of a `LazyMonoid`. It supports, in
+/// O(lg(n)) time:
+/// 1. Computing the aggregate of a range of values, in order.
+/// 2. Applying an update to every value of a range.
+/// 3. Reading or replacing a single value.
+///
+/// Updates on a range are stored in the O(lg(n)) nodes covering it, and only
+/// pushed to the children of a node when a later operation goes through it.
+pub struct LazySegmentTree<M: LazyMonoid> {
+    len: usize,
+    // The aggregate of each node, and the update pending on its children
+    aggregate: Vec<M::Value>,
+    lazy: Vec<Option<M::Update>>,
+}
+
+impl<M: LazyMonoid> LazySegmentTree<M> {
+    /// construct a tree of `len` identity values
+    pub fn new(len: usize) -> Self {
+        Self::from_vec(vec![M::identity(); len])
+    }
+
+    /// construct a tree holding `values`
+    pub fn from_vec(values: Vec<M::Value>) -> Self {
+        let len = values.len();
+        let size = 4 * len.max(1);
+        let mut tree = LazySegmentTree {
+            len,
+            aggregate: vec![M::identity(); size],
+            lazy: vec![None; size],
+        };
+        if len > 0 {
+            tree.build(1, 0..len, &values);
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn build(&mut self, node: usize, range: Range<usize>, values: &[M::Value]) {
+        if range.len() == 1 {
+            self.aggregate[node] = values[range.start].clone();
+            return;
+        }
+        let mid = (range.start + range.end) / 2;
+        self.build(2 * node, range.start..mid, values);
+        self.build(2 * node + 1, mid..range.end, values);
+        self.pull(node);
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.aggregate[node] = M::combine(&self.aggregate[2 * node], &self.aggregate[2 * node + 1]);
+    }
+
+    fn apply(&mut self, node: usize, size: usize, update: &M::Update) {
+        self.aggregate[node] = M::apply(update, &self.aggregate[node], size);
+        if size > 1 {
+            self.lazy[node] = Some(match &self.lazy[node] {
+                Some(older) => M::compose(update, older),
+                None => update.clone(),
+            });
+        }
+    }
+
+    fn push(&mut self, node: usize, range: &Range<usize>) {
+        if let Some(update) = self.lazy[node].take() {
+            let mid = (range.start + range.end) / 2;
+            self.apply(2 * node, mid - range.start, &update);
+            self.apply(2 * node + 1, range.end - mid, &update);
+        }
+    }
+
+    /// get the aggregate of the values in `range`, in order. An empty range
+    /// gives the identity
+    pub fn query(&mut self, range: Range<usize>) -> M::Value {
+        assert!(range.start <= range.end && range.end <= self.len);
+        if range.is_empty() {
+            return M::identity();
+        }
+        self.query_node(1, 0..self.len, &range)
+    }
+
+    fn query_node(&mut self, node: usize, range: Range<usize>, query: &Range<usize>) -> M::Value {
+        if query.start <= range.start && range.end <= query.end {
+            return self.aggregate[node].clone();
+        }
+        self.push(node, &range);
+        let mid = (range.start + range.end) / 2;
+        if query.end <= mid {
+            self.query_node(2 * node, range.start..mid, query)
+        } else if query.start >= mid {
+            self.query_node(2 * node + 1, mid..range.end, query)
+        } else {
+            let left = self.query_node(2 * node, range.start..mid, query);
+            let right = self.query_node(2 * node + 1, mid..range.end, query);
+            M::combine(&left, &right)
+        }
+    }
+
+    /// apply `update` to every value in `range`
+    pub fn update(&mut self, range: Range<usize>, update: &M::Update) {
+        assert!(range.start <= range.end && range.end <= self.len);
+        if !range.is_empty() {
+            self.update_node(1, 0..self.len, &range, update);
+        }
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        range: Range<usize>,
+        query: &Range<usize>,
+        update: &M::Update,
+    ) {
+        if query.start <= range.start && range.end <= query.end {
+            self.apply(node, range.len(), update);
+            return;
+        }
+        self.push(node, &range);
+        let mid = (range.start + range.end) / 2;
+        if query.start < mid {
+            self.update_node(2 * node, range.start..mid, query, update);
+        }
+        if query.end > mid {
+            self.update_node(2 * node + 1, mid..range.end, query, update);
+        }
+        self.pull(node);
+    }
+
+    /// get the value at `idx`
+    pub fn get(&mut self, idx: usize) -> M::Value {
+        self.query(idx..idx + 1)
+    }
+
+    /// replace the value at `idx` with `val`
+    pub fn set(&mut self, idx: usize, val: M::Value) {
+        assert!(idx < self.len);
+        self.set_node(1, 0..self.len, idx, val);
+    }
+
+    fn set_node(&mut self, node: usize, range: Range<usize>, idx: usize, val: M::Value) {
+        if range.len() == 1 {
+            self.aggregate[node] = val;
+            return;
+        }
+        self.push(node, &range);
+        let mid = (range.start + range.end) / 2;
+        if idx < mid {
+            self.set_node(2 * node, range.start..mid, idx, val);
+        } else {
+            self.set_node(2 * node + 1, mid..range.end, idx, val);
+        }
+        self.pull(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::test_monoids::{Concat, MinAdd};
+    use crate::math::PCG32;
+
+    #[test]
+    fn it_works() {
+        let vec = vec![1, 2, -4, 7, 3, -5, 6, 11, -20, 9, 14, 15, 5, 2, -8];
+        let mut tree: LazySegmentTree<MinAdd> = LazySegmentTree::from_vec(vec.clone());
+        assert_eq!(tree.len(), vec.len());
+        assert_eq!(tree.query(4..7), -5);
+        assert_eq!(tree.query(0..vec.len()), -20);
+        assert_eq!(tree.query(3..3), i64::MAX);
+        tree.update(5..9, &10);
+        assert_eq!(tree.query(4..7), 3);
+        assert_eq!(tree.query(0..vec.len()), -10);
+        tree.set(8, 100);
+        assert_eq!(tree.get(8), 100);
+        assert_eq!(tree.query(0..vec.len()), -8);
+    }
+
+    #[test]
+    fn keeps_order() {
+        let mut tree: LazySegmentTree<Concat> =
+            LazySegmentTree::from_vec("abcdefg".chars().map(String::from).collect());
+        assert_eq!(tree.query(1..5), "bcde");
+        tree.update(2..4, &'x');
+        tree.update(3..6, &'y');
+        assert_eq!(tree.query(0..7), "abxyyyg");
+        tree.set(4, String::from("z"));
+        assert_eq!(tree.query(2..7), "xyzyg");
+    }
+
+    #[test]
+    fn random_operations() {
+        let n = 37;
+        let mut rng = PCG32::new_default(2718);
+        let mut values: Vec<i64> = (0..n).map(|_| (rng.get_u32() % 100) as i64).collect();
+        let mut tree: LazySegmentTree<MinAdd> = LazySegmentTree::from_vec(values.clone());
+        for _ in 0..2000 {
+            let a = rng.get_u32() as usize % (n + 1);
+            let b = rng.get_u32() as usize % (n + 1);
+            let range = a.min(b)..a.max(b);
+            match rng.get_u32() % 3 {
+                0 => {
+                    let update = (rng.get_u32() % 21) as i64 - 10;
+                    tree.update(range.clone(), &update);
+                    values[range].iter_mut().for_each(|x| *x += update);
+                }
+                1 => {
+                    let idx = a.min(n - 1);
+                    values[idx] = (rng.get_u32() % 100) as i64;
+                    tree.set(idx, values[idx]);
+                }
+                _ => {
+                    let expected = values[range.clone()].iter().copied().min();
+                    assert_eq!(tree.query(range), expected.unwrap_or(i64::MAX));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let mut tree: LazySegmentTree<MinAdd> = LazySegmentTree::new(0);
+        assert!(tree.is_empty());
+        assert_eq!(tree.query(0..0), i64::MAX);
+        tree.update(0..0, &5);
+    }
+}
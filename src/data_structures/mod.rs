@@ -4,7 +4,9 @@ mod binary_search_tree;
 mod fenwick_tree;
 mod graph;
 mod heap;
+mod lazy_segment_tree;
 mod linked_list;
+mod monoid;
 mod queue;
 mod rb_tree;
 mod segment_tree;
@@ -18,9 +20,14 @@ pub use self::b_tree::BTree;
 pub use self::binary_search_tree::BinarySearchTree;
 pub use self::fenwick_tree::FenwickTree;
 pub use self::graph::DirectedGraph;
+pub use self::graph::Graph;
 pub use self::graph::UndirectedGraph;
 pub use self::heap::Heap;
+pub use self::lazy_segment_tree::LazySegmentTree;
 pub use self::linked_list::LinkedList;
+#[cfg(test)]
+pub(crate) use self::monoid::test_monoids;
+pub use self::monoid::{LazyMonoid, Monoid};
 pub use self::queue::Queue;
 pub use self::rb_tree::RBTree;
 pub use self::segment_tree::SegmentTree;
@@ -0,0 +1,102 @@
+/// A monoid is a set with an associative operation and an identity element,
+/// for example integers with addition and 0, or strings with concatenation
+/// and the empty string.
+///
+/// Data structures that aggregate ranges or paths are generic over a type
+/// implementing this trait. The operation does not need to be commutative
+/// unless the data structure says so.
+pub trait Monoid {
+    type Value: Clone;
+
+    /// combine(identity(), x) == combine(x, identity()) == x
+    fn identity() -> Self::Value;
+
+    /// combine(combine(a, b), c) == combine(a, combine(b, c))
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+}
+
+/// A monoid whose values can be modified in bulk by updates, for example
+/// adding a constant to, or assigning a constant to every value of a range.
+///
+/// Data structures use it to apply an update to the aggregate of a whole range
+/// at once, and to postpone pushing it down to the single values.
+pub trait LazyMonoid: Monoid {
+    type Update: Clone;
+
+    /// Returns the aggregate of `size` values after applying `update` to each
+    /// of them, given their aggregate `value` before the update.
+    fn apply(update: &Self::Update, value: &Self::Value, size: usize) -> Self::Value;
+
+    /// Returns the update equivalent to applying `older`, then `newer`.
+    fn compose(newer: &Self::Update, older: &Self::Update) -> Self::Update;
+}
+
+// Monoids shared by the tests of the data structures built on them
+#[cfg(test)]
+pub(crate) mod test_monoids {
+    use super::{LazyMonoid, Monoid};
+
+    // Sum, with additions as updates
+    pub struct Sum;
+    impl Monoid for Sum {
+        type Value = i64;
+        fn identity() -> i64 {
+            0
+        }
+        fn combine(left: &i64, right: &i64) -> i64 {
+            left + right
+        }
+    }
+    impl LazyMonoid for Sum {
+        type Update = i64;
+        fn apply(update: &i64, value: &i64, size: usize) -> i64 {
+            value + update * size as i64
+        }
+        fn compose(newer: &i64, older: &i64) -> i64 {
+            newer + older
+        }
+    }
+
+    // Minimum, with additions as updates
+    pub struct MinAdd;
+    impl Monoid for MinAdd {
+        type Value = i64;
+        fn identity() -> i64 {
+            i64::MAX
+        }
+        fn combine(left: &i64, right: &i64) -> i64 {
+            *left.min(right)
+        }
+    }
+    impl LazyMonoid for MinAdd {
+        type Update = i64;
+        fn apply(update: &i64, value: &i64, _size: usize) -> i64 {
+            value.saturating_add(*update)
+        }
+        fn compose(newer: &i64, older: &i64) -> i64 {
+            newer + older
+        }
+    }
+
+    // Concatenation, which is not commutative, with assignment of a
+    // character as updates
+    pub struct Concat;
+    impl Monoid for Concat {
+        type Value = String;
+        fn identity() -> String {
+            String::new()
+        }
+        fn combine(left: &String, right: &String) -> String {
+            format!("{left}{right}")
+        }
+    }
+    impl LazyMonoid for Concat {
+        type Update = char;
+        fn apply(update: &char, _value: &String, size: usize) -> String {
+            std::iter::repeat_n(*update, size).collect()
+        }
+        fn compose(newer: &char, _older: &char) -> char {
+            *newer
+        }
+    }
+}
@@ -1,4 +1,5 @@
 /// Fibonacci via Dynamic Programming
+use crate::math::Matrix;
 use std::collections::HashMap;
 
 /// fibonacci(n) returns the nth fibonacci number
@@ -133,51 +134,10 @@ fn _memoized_fibonacci(n: u32, cache: &mut HashMap<u32, u128>) -> u128 {
 ///
 /// Warning: This will overflow the 128-bit unsigned integer at n=186
 pub fn matrix_fibonacci(n: u32) -> u128 {
-    let multiplier: Vec<Vec<u128>> = vec![vec![1, 1], vec![1, 0]];
+    let multiplier: Matrix<u128> = Matrix::from(vec![vec![1, 1], vec![1, 0]]);
 
-    let multiplier = matrix_power(&multiplier, n);
-    let initial_fib_matrix: Vec<Vec<u128>> = vec![vec![1], vec![0]];
-
-    let res = matrix_multiply(&multiplier, &initial_fib_matrix);
-
-    res[1][0]
-}
-
-fn matrix_power(base: &Vec<Vec<u128>>, power: u32) -> Vec<Vec<u128>> {
-    let identity_matrix: Vec<Vec<u128>> = vec![vec![1, 0], vec![0, 1]];
-
-    vec![base; power as usize]
-        .iter()
-        .fold(identity_matrix, |acc, x| matrix_multiply(&acc, x))
-}
-
-// Copied from matrix_ops since u128 is required instead of i32
-#[allow(clippy::needless_range_loop)]
-fn matrix_multiply(multiplier: &[Vec<u128>], multiplicand: &[Vec<u128>]) -> Vec<Vec<u128>> {
-    // Multiply two matching matrices. The multiplier needs to have the same amount
-    // of columns as the multiplicand has rows.
-    let mut result: Vec<Vec<u128>> = vec![];
-    let mut temp;
-    // Using variable to compare lenghts of rows in multiplicand later
-    let row_right_length = multiplicand[0].len();
-    for row_left in 0..multiplier.len() {
-        if multiplier[row_left].len() != multiplicand.len() {
-            panic!("Matrix dimensions do not match");
-        }
-        result.push(vec![]);
-        for column_right in 0..multiplicand[0].len() {
-            temp = 0;
-            for row_right in 0..multiplicand.len() {
-                if row_right_length != multiplicand[row_right].len() {
-                    // If row is longer than a previous row cancel operation with error
-                    panic!("Matrix dimensions do not match");
-                }
-                temp += multiplier[row_left][row_right] * multiplicand[row_right][column_right];
-            }
-            result[row_left].push(temp);
-        }
-    }
-    result
+    // The power is [F(n + 1), F(n)], [F(n), F(n - 1)]
+    multiplier.pow(n as u64)[[1, 0]]
 }
 
 #[cfg(test)]
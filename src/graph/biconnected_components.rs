@@ -0,0 +1,296 @@
+/*
+Tarjan's algorithm for the connectivity structure of undirected graphs:
+With a single Depth First Search it finds bridges, articulation points,
+biconnected components (blocks) and 2-edge-connected components, all in
+O(n + m). The blocks can then be arranged into a block-cut tree.
+
+A bridge is an edge whose removal increases the number of connected
+components, and an articulation point is such a vertex. Each edge belongs to
+exactly one biconnected component, and articulation points are exactly the
+vertices shared by two or more of them. Removing all bridges splits the graph
+into its 2-edge-connected components.
+
+We assume that graph is represented using (compressed) adjacency matrix,
+each undirected edge appears in the lists of both of its endpoints and
+vertices are numbered from 1 to n. Parallel edges and self loops are allowed.
+If this is not the case, one can use `src/graph/graph_enumeration.rs` to
+convert their graph.
+*/
+
+type Adj = [Vec<usize>];
+
+pub struct BiconnectedComponents {
+    // The discover time of each vertex, starting from 1
+    pub discover_time: Vec<usize>,
+
+    // The minimum discover time reachable from the subtree of each vertex
+    // using at most one back edge
+    pub low_link: Vec<usize>,
+
+    // Whether removing the vertex disconnects its connected component
+    pub is_articulation_point: Vec<bool>,
+
+    // The list of bridges as (parent, child) pairs of the DFS tree
+    pub bridges: Vec<(usize, usize)>,
+
+    // The vertices of each biconnected component. Isolated vertices form a
+    // component on their own
+    pub biconnected_components: Vec<Vec<usize>>,
+
+    // The number of the 2-edge-connected component the vertex is in,
+    // starting from 1
+    pub two_edge_component: Vec<usize>,
+
+    // The total number of 2-edge-connected components
+    pub num_two_edge_components: usize,
+
+    // The stacks of vertices that DFS has seen (used internally)
+    block_stack: Vec<usize>,
+    two_edge_stack: Vec<usize>,
+    // Used internally during DFS to know the current discover time
+    current_time: usize,
+}
+
+impl BiconnectedComponents {
+    pub fn new(mut num_vertices: usize) -> Self {
+        num_vertices += 1; // Vertices are numbered from 1, not 0
+        BiconnectedComponents {
+            discover_time: vec![0; num_vertices],
+            low_link: vec![0; num_vertices],
+            is_articulation_point: vec![false; num_vertices],
+            bridges: vec![],
+            biconnected_components: vec![],
+            two_edge_component: vec![0; num_vertices],
+            num_two_edge_components: 0,
+            block_stack: vec![],
+            two_edge_stack: vec![],
+            current_time: 1,
+        }
+    }
+    fn pop_two_edge_component(&mut self, v: usize) {
+        self.num_two_edge_components += 1;
+        loop {
+            let u = self.two_edge_stack.pop().unwrap();
+            self.two_edge_component[u] = self.num_two_edge_components;
+            if u == v {
+                break;
+            }
+        }
+    }
+    fn dfs(&mut self, v: usize, parent: usize, adj: &Adj) {
+        self.discover_time[v] = self.current_time;
+        self.low_link[v] = self.current_time;
+        self.current_time += 1;
+        self.block_stack.push(v);
+        self.two_edge_stack.push(v);
+
+        let mut num_children = 0usize;
+        // Only one of the (possibly parallel) edges to the parent is the tree
+        // edge, the others are back edges.
+        let mut skipped_parent_edge = false;
+        for &u in adj[v].iter() {
+            if u == parent && !skipped_parent_edge {
+                skipped_parent_edge = true;
+                continue;
+            }
+            if self.discover_time[u] != 0 {
+                self.low_link[v] = std::cmp::min(self.low_link[v], self.discover_time[u]);
+                continue;
+            }
+            num_children += 1;
+            self.dfs(u, v, adj);
+            self.low_link[v] = std::cmp::min(self.low_link[v], self.low_link[u]);
+
+            if self.low_link[u] > self.discover_time[v] {
+                // Nothing in the subtree of `u` goes above `u`
+                self.bridges.push((v, u));
+                self.pop_two_edge_component(u);
+            }
+            if self.low_link[u] >= self.discover_time[v] {
+                // Nothing in the subtree of `u` goes above `v`, so `v` separates
+                // it from the rest of the graph
+                if parent != 0 {
+                    self.is_articulation_point[v] = true;
+                }
+                let mut block = vec![v];
+                loop {
+                    let w = self.block_stack.pop().unwrap();
+                    block.push(w);
+                    if w == u {
+                        break;
+                    }
+                }
+                self.biconnected_components.push(block);
+            }
+        }
+
+        if parent == 0 {
+            self.is_articulation_point[v] = num_children > 1;
+            if num_children == 0 {
+                self.biconnected_components.push(vec![v]);
+            }
+            self.block_stack.pop();
+            self.pop_two_edge_component(v);
+        }
+    }
+    pub fn find_components(&mut self, adj: &Adj) {
+        for v in 1..adj.len() {
+            if self.discover_time[v] == 0 {
+                self.dfs(v, 0, adj);
+            }
+        }
+    }
+    pub fn articulation_points(&self) -> Vec<usize> {
+        (1..self.is_articulation_point.len())
+            .filter(|&v| self.is_articulation_point[v])
+            .collect()
+    }
+    /// Returns the block-cut tree (a forest if the graph is disconnected) as
+    /// an adjacency list. Vertices 1 to n are the original vertices and
+    /// vertex n + i is the i'th biconnected component, which is connected to
+    /// all the vertices it contains. Should be called after
+    /// `find_components`.
+    pub fn block_cut_tree(&self) -> Vec<Vec<usize>> {
+        let num_vertices = self.discover_time.len() - 1;
+        let mut tree = vec![vec![]; num_vertices + self.biconnected_components.len() + 1];
+        for (i, block) in self.biconnected_components.iter().enumerate() {
+            let block_vertex = num_vertices + i + 1;
+            for &v in block {
+                tree[block_vertex].push(v);
+                tree[v].push(block_vertex);
+            }
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undirected(num_vertices: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+        let mut adj = vec![vec![]; num_vertices + 1];
+        for &(u, v) in edges {
+            adj[u].push(v);
+            adj[v].push(u);
+        }
+        adj
+    }
+
+    fn sorted_blocks(bcc: &BiconnectedComponents) -> Vec<Vec<usize>> {
+        let mut blocks: Vec<Vec<usize>> = bcc
+            .biconnected_components
+            .iter()
+            .map(|b| {
+                let mut b = b.clone();
+                b.sort_unstable();
+                b
+            })
+            .collect();
+        blocks.sort();
+        blocks
+    }
+
+    fn sorted_bridges(bcc: &BiconnectedComponents) -> Vec<(usize, usize)> {
+        let mut bridges: Vec<(usize, usize)> = bcc
+            .bridges
+            .iter()
+            .map(|&(u, v)| (u.min(v), u.max(v)))
+            .collect();
+        bridges.sort_unstable();
+        bridges
+    }
+
+    #[test]
+    fn cycle() {
+        let adj = undirected(4, &[(1, 2), (2, 3), (3, 4), (4, 1)]);
+        let mut bcc = BiconnectedComponents::new(4);
+        bcc.find_components(&adj);
+        assert!(bcc.bridges.is_empty());
+        assert!(bcc.articulation_points().is_empty());
+        assert_eq!(sorted_blocks(&bcc), vec![vec![1, 2, 3, 4]]);
+        assert_eq!(bcc.num_two_edge_components, 1);
+    }
+
+    #[test]
+    fn path() {
+        let adj = undirected(4, &[(1, 2), (2, 3), (3, 4)]);
+        let mut bcc = BiconnectedComponents::new(4);
+        bcc.find_components(&adj);
+        assert_eq!(sorted_bridges(&bcc), vec![(1, 2), (2, 3), (3, 4)]);
+        assert_eq!(bcc.articulation_points(), vec![2, 3]);
+        assert_eq!(
+            sorted_blocks(&bcc),
+            vec![vec![1, 2], vec![2, 3], vec![3, 4]]
+        );
+        assert_eq!(bcc.num_two_edge_components, 4);
+    }
+
+    #[test]
+    fn bowtie_with_tail() {
+        // Two triangles sharing vertex 3, and a tail 5 - 6
+        let adj = undirected(7, &[(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 3), (5, 6)]);
+        let mut bcc = BiconnectedComponents::new(7);
+        bcc.find_components(&adj);
+        assert_eq!(sorted_bridges(&bcc), vec![(5, 6)]);
+        assert_eq!(bcc.articulation_points(), vec![3, 5]);
+        assert_eq!(
+            sorted_blocks(&bcc),
+            vec![vec![1, 2, 3], vec![3, 4, 5], vec![5, 6], vec![7]]
+        );
+        assert_eq!(bcc.num_two_edge_components, 3);
+        let c = &bcc.two_edge_component;
+        assert!(c[1] == c[2] && c[2] == c[3] && c[3] == c[4] && c[4] == c[5]);
+        assert_ne!(c[5], c[6]);
+        assert_ne!(c[6], c[7]);
+    }
+
+    #[test]
+    fn parallel_edges() {
+        // The double edge 1 = 2 is not a bridge, but 2 - 3 is
+        let adj = undirected(3, &[(1, 2), (1, 2), (2, 3)]);
+        let mut bcc = BiconnectedComponents::new(3);
+        bcc.find_components(&adj);
+        assert_eq!(sorted_bridges(&bcc), vec![(2, 3)]);
+        assert_eq!(bcc.articulation_points(), vec![2]);
+        assert_eq!(bcc.num_two_edge_components, 2);
+        assert_eq!(bcc.two_edge_component[1], bcc.two_edge_component[2]);
+    }
+
+    #[test]
+    fn block_cut_tree() {
+        let adj = undirected(5, &[(1, 2), (2, 3), (3, 1), (3, 4), (4, 5)]);
+        let mut bcc = BiconnectedComponents::new(5);
+        bcc.find_components(&adj);
+        let tree = bcc.block_cut_tree();
+        // 5 vertices and 3 blocks
+        assert_eq!(tree.len(), 9);
+        let num_edges: usize = tree.iter().map(|e| e.len()).sum::<usize>() / 2;
+        // A tree on 8 vertices
+        assert_eq!(num_edges, 7);
+        // Articulation points 3 and 4 are adjacent to two blocks each
+        assert_eq!(tree[3].len(), 2);
+        assert_eq!(tree[4].len(), 2);
+        assert_eq!(tree[1].len(), 1);
+        assert_eq!(tree[5].len(), 1);
+    }
+
+    #[test]
+    fn empty_and_single_vertex() {
+        // No vertices
+        let mut bcc = BiconnectedComponents::new(0);
+        bcc.find_components(&[vec![]]);
+        assert!(bcc.biconnected_components.is_empty());
+        assert!(bcc.articulation_points().is_empty());
+        assert_eq!(bcc.num_two_edge_components, 0);
+        assert_eq!(bcc.block_cut_tree(), vec![vec![]]);
+
+        // A single vertex is a block and a 2-edge-connected component
+        let mut bcc = BiconnectedComponents::new(1);
+        bcc.find_components(&[vec![], vec![]]);
+        assert_eq!(sorted_blocks(&bcc), vec![vec![1]]);
+        assert!(bcc.articulation_points().is_empty());
+        assert_eq!(bcc.num_two_edge_components, 1);
+        assert_eq!(bcc.block_cut_tree(), vec![vec![], vec![2], vec![1]]);
+    }
+}
@@ -0,0 +1,256 @@
+/*
+Cliques and independent sets:
+A clique is a set of pairwise adjacent vertices, and an independent set is a
+set of pairwise non-adjacent vertices, that is a clique of the complement
+graph. Finding the largest ones is NP-hard, and the algorithms below take
+exponential time in the worst case:
+1. `maximal_cliques` lists every clique that can not be extended, with the
+   Bron–Kerbosch algorithm. It grows a clique R, with the candidates P that
+   can extend it and the vertices X that were already tried. To avoid listing
+   the same cliques many times, it picks a "pivot" u in P or X with the most
+   neighbours in P, and only branches on the vertices of P that are not
+   neighbours of u (Tomita et al.), which takes O(3^(n/3)) time.
+   https://en.wikipedia.org/wiki/Bron%E2%80%93Kerbosch_algorithm
+2. `maximum_independent_set` branches on a vertex of maximum degree, either
+   leaving it out or taking it and removing its neighbours, and always takes
+   the vertices of degree 0 or 1, which is never worse.
+
+The graph is an undirected adjacency list with vertices numbered from 1 to n,
+where every edge appears in the lists of both endpoints. The results can be
+checked with `is_clique` and `is_independent_set`.
+*/
+
+type Adj = [Vec<usize>];
+
+fn adjacency_matrix(adj: &Adj) -> Vec<Vec<bool>> {
+    let mut matrix = vec![vec![false; adj.len()]; adj.len()];
+    for (u, edges) in adj.iter().enumerate() {
+        for &v in edges.iter().filter(|&&v| v != u) {
+            matrix[u][v] = true;
+            matrix[v][u] = true;
+        }
+    }
+    matrix
+}
+
+/// Checks that the vertices are distinct and pairwise adjacent.
+pub fn is_clique(adj: &Adj, vertices: &[usize]) -> bool {
+    vertices.iter().enumerate().all(|(i, &u)| {
+        vertices[i + 1..]
+            .iter()
+            .all(|&v| u != v && (adj[u].contains(&v) || adj[v].contains(&u)))
+    })
+}
+
+/// Checks that the vertices are distinct and pairwise non-adjacent.
+pub fn is_independent_set(adj: &Adj, vertices: &[usize]) -> bool {
+    vertices.iter().enumerate().all(|(i, &u)| {
+        vertices[i + 1..]
+            .iter()
+            .all(|&v| u != v && !adj[u].contains(&v) && !adj[v].contains(&u))
+    })
+}
+
+fn bron_kerbosch(
+    matrix: &[Vec<bool>],
+    clique: &mut Vec<usize>,
+    mut candidates: Vec<usize>,
+    mut excluded: Vec<usize>,
+    result: &mut Vec<Vec<usize>>,
+) {
+    let pivot = match candidates
+        .iter()
+        .chain(excluded.iter())
+        .max_by_key(|&&u| candidates.iter().filter(|&&v| matrix[u][v]).count())
+    {
+        Some(&pivot) => pivot,
+        None => {
+            let mut found = clique.clone();
+            found.sort_unstable();
+            result.push(found);
+            return;
+        }
+    };
+    let branches: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&v| !matrix[pivot][v])
+        .collect();
+    for v in branches {
+        clique.push(v);
+        bron_kerbosch(
+            matrix,
+            clique,
+            candidates
+                .iter()
+                .copied()
+                .filter(|&u| matrix[v][u])
+                .collect(),
+            excluded.iter().copied().filter(|&u| matrix[v][u]).collect(),
+            result,
+        );
+        clique.pop();
+        candidates.retain(|&u| u != v);
+        excluded.push(v);
+    }
+}
+
+/// Returns all the maximal cliques, each one sorted.
+pub fn maximal_cliques(adj: &Adj) -> Vec<Vec<usize>> {
+    let matrix = adjacency_matrix(adj);
+    let mut result = vec![];
+    if adj.len() > 1 {
+        bron_kerbosch(
+            &matrix,
+            &mut vec![],
+            (1..adj.len()).collect(),
+            vec![],
+            &mut result,
+        );
+    }
+    result
+}
+
+/// Returns a clique with the most vertices, sorted.
+pub fn maximum_clique(adj: &Adj) -> Vec<usize> {
+    maximal_cliques(adj)
+        .into_iter()
+        .max_by_key(|clique| clique.len())
+        .unwrap_or_default()
+}
+
+fn independent_set(matrix: &[Vec<bool>], vertices: &[usize]) -> Vec<usize> {
+    let degree = |v: usize| vertices.iter().filter(|&&u| matrix[v][u]).count();
+    let v = match vertices.iter().copied().min_by_key(|&v| degree(v)) {
+        Some(v) => v,
+        None => return vec![],
+    };
+    let without_neighbours = |v: usize| -> Vec<usize> {
+        vertices
+            .iter()
+            .copied()
+            .filter(|&u| u != v && !matrix[v][u])
+            .collect()
+    };
+    if degree(v) <= 1 {
+        // Any independent set with a neighbour of `v` can take `v` instead
+        let mut result = independent_set(matrix, &without_neighbours(v));
+        result.push(v);
+        return result;
+    }
+    let v = vertices.iter().copied().max_by_key(|&v| degree(v)).unwrap();
+    let mut with_v = independent_set(matrix, &without_neighbours(v));
+    with_v.push(v);
+    let rest: Vec<usize> = vertices.iter().copied().filter(|&u| u != v).collect();
+    let without_v = independent_set(matrix, &rest);
+    if with_v.len() >= without_v.len() {
+        with_v
+    } else {
+        without_v
+    }
+}
+
+/// Returns an independent set with the most vertices, sorted.
+pub fn maximum_independent_set(adj: &Adj) -> Vec<usize> {
+    let matrix = adjacency_matrix(adj);
+    let vertices: Vec<usize> = (1..adj.len()).collect();
+    let mut result = independent_set(&matrix, &vertices);
+    result.sort_unstable();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{complete_graph, erdos_renyi, grid_graph};
+    use crate::math::PCG32;
+
+    // The largest subsets of vertices satisfying `check`
+    fn brute_force(adj: &Adj, check: fn(&Adj, &[usize]) -> bool) -> usize {
+        let n = adj.len() - 1;
+        (0..1usize << n)
+            .map(|mask| {
+                (1..=n)
+                    .filter(|&v| mask >> (v - 1) & 1 == 1)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|vertices| check(adj, vertices))
+            .map(|vertices| vertices.len())
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn small_graphs() {
+        // Two triangles 1 2 3 and 3 4 5 sharing vertex 3, and the edge 5 6
+        let adj = vec![
+            vec![],
+            vec![2, 3],
+            vec![1, 3],
+            vec![1, 2, 4, 5],
+            vec![3, 5],
+            vec![3, 4, 6],
+            vec![5],
+        ];
+        let mut cliques = maximal_cliques(&adj);
+        cliques.sort();
+        assert_eq!(cliques, vec![vec![1, 2, 3], vec![3, 4, 5], vec![5, 6]]);
+        assert_eq!(maximum_clique(&adj).len(), 3);
+        let independent = maximum_independent_set(&adj);
+        assert!(is_independent_set(&adj, &independent));
+        assert_eq!(independent.len(), 3);
+
+        assert_eq!(
+            maximal_cliques(&complete_graph(5)),
+            vec![vec![1, 2, 3, 4, 5]]
+        );
+        assert_eq!(maximum_independent_set(&complete_graph(5)).len(), 1);
+        assert_eq!(maximum_independent_set(&grid_graph(4, 4)).len(), 8);
+        assert_eq!(maximum_clique(&vec![vec![]; 3]).len(), 1);
+        assert_eq!(maximal_cliques(&[vec![]]), Vec::<Vec<usize>>::new());
+        assert_eq!(maximum_independent_set(&[vec![]]), vec![]);
+
+        assert!(!is_clique(&adj, &[1, 2, 4]));
+        assert!(!is_clique(&adj, &[1, 1]));
+        assert!(!is_independent_set(&adj, &[1, 4, 5]));
+    }
+
+    #[test]
+    fn random_graphs() {
+        let mut rng = PCG32::new_default(77);
+        for _ in 0..40 {
+            let n = 1 + rng.get_u32() as usize % 10;
+            let adj = erdos_renyi(n, 0.5, &mut rng);
+            let cliques = maximal_cliques(&adj);
+            for clique in cliques.iter() {
+                assert!(is_clique(&adj, clique));
+                // It can not be extended
+                assert!((1..=n)
+                    .filter(|v| !clique.contains(v))
+                    .all(|v| !clique.iter().all(|u| adj[v].contains(u))));
+            }
+            let mut unique = cliques.clone();
+            unique.sort();
+            unique.dedup();
+            assert_eq!(unique.len(), cliques.len());
+            assert_eq!(maximum_clique(&adj).len(), brute_force(&adj, is_clique));
+
+            let independent = maximum_independent_set(&adj);
+            assert!(is_independent_set(&adj, &independent));
+            assert_eq!(independent.len(), brute_force(&adj, is_independent_set));
+        }
+    }
+
+    #[test]
+    fn single_vertex_and_empty_set() {
+        // A single vertex, with or without a self loop
+        for adj in [vec![vec![], vec![]], vec![vec![], vec![1]]] {
+            assert_eq!(maximal_cliques(&adj), vec![vec![1]]);
+            assert_eq!(maximum_independent_set(&adj), vec![1]);
+        }
+        // The empty set is both
+        let adj = vec![vec![]; 4];
+        assert!(is_clique(&adj, &[]));
+        assert!(is_independent_set(&adj, &[]));
+    }
+}
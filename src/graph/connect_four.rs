@@ -0,0 +1,289 @@
+/*
+Connect four:
+Two players drop stones in turn into the columns of a vertical grid of 6
+rows and 7 columns, and the first one to align four stones horizontally,
+vertically or diagonally wins.
+
+The grid is stored as two bitboards, one for the stones of each player, where
+column c takes the bits 7c to 7c + 6, from the bottom: the 7th bit of each
+column stays empty, so that four aligned bits can be found with shifts (by 1
+vertically, 7 horizontally, 6 and 8 diagonally) without wrapping around to
+the next column.
+https://en.wikipedia.org/wiki/Connect_Four
+
+It implements the `Game` trait, so it can be searched with `alpha_beta`,
+`iterative_deepening` or Monte Carlo Tree Search.
+*/
+
+use super::{Game, ZobristKeys, WIN_SCORE};
+
+pub const ROWS: usize = 6;
+pub const COLUMNS: usize = 7;
+
+// The columns from the centre, where the stones are part of the most lines
+const COLUMN_ORDER: [usize; COLUMNS] = [3, 2, 4, 1, 5, 0, 6];
+
+#[derive(Debug, Clone)]
+pub struct ConnectFour {
+    // The stones of each player, player 0 moves first
+    stones: [u64; 2],
+    heights: [usize; COLUMNS],
+    to_move: usize,
+    moves_played: usize,
+    hash: u64,
+    keys: ZobristKeys,
+}
+
+fn bit(column: usize, row: usize) -> u64 {
+    1 << (column * (ROWS + 1) + row)
+}
+
+fn has_four(stones: u64) -> bool {
+    [1, ROWS + 1, ROWS, ROWS + 2].iter().any(|&shift| {
+        let pairs = stones & (stones >> shift);
+        pairs & (pairs >> (2 * shift)) != 0
+    })
+}
+
+impl ConnectFour {
+    pub fn new() -> Self {
+        ConnectFour {
+            stones: [0; 2],
+            heights: [0; COLUMNS],
+            to_move: 0,
+            moves_played: 0,
+            hash: 0,
+            // One key for each square and player, and one for the second
+            // player to move
+            keys: ZobristKeys::new(ROWS * COLUMNS * 2 + 1, 4),
+        }
+    }
+
+    /// Plays the given columns, from the empty grid.
+    pub fn from_moves(columns: &[usize]) -> Self {
+        let mut game = ConnectFour::new();
+        for &column in columns {
+            assert!(game.can_play(column), "column {column} is full");
+            game.apply(column);
+        }
+        game
+    }
+
+    pub fn can_play(&self, column: usize) -> bool {
+        column < COLUMNS && self.heights[column] < ROWS
+    }
+
+    /// The player to move, 0 for the first one and 1 for the second one.
+    pub fn to_move(&self) -> usize {
+        self.to_move
+    }
+
+    /// The player who has a stone at this square, if any.
+    pub fn stone(&self, column: usize, row: usize) -> Option<usize> {
+        (0..2).find(|&player| self.stones[player] & bit(column, row) != 0)
+    }
+
+    fn square_key(&self, column: usize, row: usize, player: usize) -> u64 {
+        self.keys.key((column * ROWS + row) * 2 + player)
+    }
+}
+
+impl Default for ConnectFour {
+    fn default() -> Self {
+        ConnectFour::new()
+    }
+}
+
+impl Game for ConnectFour {
+    type Move = usize;
+
+    fn moves(&self) -> Vec<usize> {
+        if self.terminal_score().is_some() {
+            return vec![];
+        }
+        COLUMN_ORDER
+            .iter()
+            .copied()
+            .filter(|&column| self.can_play(column))
+            .collect()
+    }
+
+    fn apply(&mut self, column: usize) {
+        let row = self.heights[column];
+        self.stones[self.to_move] |= bit(column, row);
+        self.hash ^= self.square_key(column, row, self.to_move) ^ self.keys.key(ROWS * COLUMNS * 2);
+        self.heights[column] += 1;
+        self.to_move ^= 1;
+        self.moves_played += 1;
+    }
+
+    fn undo(&mut self, column: usize) {
+        self.moves_played -= 1;
+        self.to_move ^= 1;
+        self.heights[column] -= 1;
+        let row = self.heights[column];
+        self.stones[self.to_move] ^= bit(column, row);
+        self.hash ^= self.square_key(column, row, self.to_move) ^ self.keys.key(ROWS * COLUMNS * 2);
+    }
+
+    fn terminal_score(&self) -> Option<i32> {
+        // Only the player who just moved can have won
+        if has_four(self.stones[self.to_move ^ 1]) {
+            Some(-WIN_SCORE)
+        } else if self.moves_played == ROWS * COLUMNS {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    /// The number of lines of four squares that the player to move could
+    /// still complete, minus the ones of the opponent, where the lines with
+    /// more stones count more.
+    fn evaluate(&self) -> i32 {
+        let mut score = 0;
+        for column in 0..COLUMNS {
+            for row in 0..ROWS {
+                for (dc, dr) in [(1, 0), (0, 1), (1, 1), (1, -1)] {
+                    let end_column = column as i32 + 3 * dc;
+                    let end_row = row as i32 + 3 * dr;
+                    if end_column >= COLUMNS as i32 || !(0..ROWS as i32).contains(&end_row) {
+                        continue;
+                    }
+                    let line = (0..4).fold(0, |line, i| {
+                        let c = (column as i32 + i * dc) as usize;
+                        let r = (row as i32 + i * dr) as usize;
+                        line | bit(c, r)
+                    });
+                    let mine = (self.stones[self.to_move] & line).count_ones() as i32;
+                    let theirs = (self.stones[self.to_move ^ 1] & line).count_ones() as i32;
+                    if theirs == 0 {
+                        score += mine * mine;
+                    } else if mine == 0 {
+                        score -= theirs * theirs;
+                    }
+                }
+            }
+        }
+        score
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{alpha_beta, iterative_deepening, negamax, TranspositionTable};
+    use crate::math::PCG32;
+
+    #[test]
+    fn alignments() {
+        // Horizontal, for the first player on the bottom row
+        let game = ConnectFour::from_moves(&[0, 0, 1, 1, 2, 2, 3]);
+        assert_eq!(game.terminal_score(), Some(-WIN_SCORE));
+        assert_eq!(game.moves(), vec![]);
+        // Vertical, for the second player
+        let game = ConnectFour::from_moves(&[0, 1, 2, 1, 3, 1, 5, 1]);
+        assert_eq!(game.terminal_score(), Some(-WIN_SCORE));
+        assert_eq!(game.stone(1, 3), Some(1));
+        // Diagonal
+        let game = ConnectFour::from_moves(&[0, 1, 1, 2, 3, 2, 2, 3, 4, 3, 3]);
+        assert_eq!(game.terminal_score(), Some(-WIN_SCORE));
+        // Three in the first column and one at the bottom of the second one
+        // are not aligned
+        let game = ConnectFour::from_moves(&[0, 6, 0, 6, 0, 5, 1, 0]);
+        assert_eq!(game.terminal_score(), None);
+        assert_eq!(game.to_move(), 0);
+    }
+
+    #[test]
+    fn apply_and_undo() {
+        let mut rng = PCG32::new_default(39);
+        let mut game = ConnectFour::new();
+        let mut played = vec![];
+        let mut hashes = vec![game.hash()];
+        loop {
+            let moves = game.moves();
+            if moves.is_empty() {
+                break;
+            }
+            let column = moves[rng.get_u32() as usize % moves.len()];
+            game.apply(column);
+            played.push(column);
+            hashes.push(game.hash());
+        }
+        let mut replayed = ConnectFour::from_moves(&played);
+        assert_eq!(replayed.hash(), game.hash());
+        while let Some(column) = played.pop() {
+            hashes.pop();
+            replayed.undo(column);
+            assert_eq!(Some(&replayed.hash()), hashes.last());
+        }
+        assert_eq!(replayed.stones, [0, 0]);
+        assert_eq!(replayed.hash(), 0);
+    }
+
+    #[test]
+    fn search() {
+        // The first player has three in a row at the bottom, open at both
+        // ends: the second player can only block one, and loses
+        let mut game = ConnectFour::from_moves(&[2, 2, 3, 3]);
+        let result = alpha_beta(&mut game, 4, None);
+        assert_eq!(result.score, WIN_SCORE - 3);
+        assert!(result.best_move == Some(1) || result.best_move == Some(4));
+        game.apply(4);
+        assert_eq!(alpha_beta(&mut game, 4, None).score, -WIN_SCORE + 2);
+
+        // The second player must block the column
+        let mut game = ConnectFour::from_moves(&[0, 3, 0, 3, 0]);
+        let mut table = TranspositionTable::new(1 << 16);
+        assert_eq!(
+            iterative_deepening(&mut game, 6, &mut table).best_move,
+            Some(0)
+        );
+
+        // Alpha-beta finds the same scores as negamax, with fewer nodes
+        let mut rng = PCG32::new_default(4);
+        for _ in 0..5 {
+            let moves: Vec<usize> = (0..8).map(|_| rng.get_u32() as usize % 7).collect();
+            let mut game = ConnectFour::from_moves(&moves);
+            if game.terminal_score().is_some() {
+                continue;
+            }
+            let full = negamax(&mut game, 4);
+            let pruned = alpha_beta(&mut game, 4, None);
+            let mut table = TranspositionTable::new(1 << 12);
+            let deepened = iterative_deepening(&mut game, 4, &mut table);
+            assert_eq!(pruned.score, full.score);
+            assert_eq!(deepened.score, full.score);
+            assert!(pruned.nodes < full.nodes);
+        }
+    }
+
+    #[test]
+    fn full_grid_and_columns() {
+        // A random game that ended in a draw
+        let mut game = ConnectFour::from_moves(&[
+            2, 2, 1, 4, 1, 5, 0, 4, 4, 0, 5, 2, 0, 3, 0, 5, 6, 1, 4, 3, 6, 6, 2, 0, 6, 6, 3, 3, 1,
+            0, 4, 6, 1, 2, 1, 2, 3, 4, 3, 5, 5, 5,
+        ]);
+        assert_eq!(game.terminal_score(), Some(0));
+        assert_eq!(game.moves(), vec![]);
+        let result = alpha_beta(&mut game, 3, None);
+        assert_eq!((result.score, result.best_move), (0, None));
+
+        let game = ConnectFour::from_moves(&[0; 6]);
+        assert!(!game.can_play(0));
+        assert!(!game.can_play(COLUMNS));
+        assert_eq!(game.moves().len(), COLUMNS - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "column 0 is full")]
+    fn full_column() {
+        ConnectFour::from_moves(&[0; 7]);
+    }
+}
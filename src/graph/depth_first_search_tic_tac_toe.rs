@@ -21,6 +21,7 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use super::{Game, ZobristKeys, WIN_SCORE};
 #[allow(unused_imports)]
 use std::io;
 
@@ -31,9 +32,9 @@ use std::io;
 //use rand::Rng;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
-struct Position {
-    x: u8,
-    y: u8,
+pub struct Position {
+    pub x: u8,
+    pub y: u8,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -327,6 +328,113 @@ fn append_playaction(
     }
 }
 
+fn opponent(side: Players) -> Players {
+    match side {
+        Players::PlayerX => Players::PlayerO,
+        Players::PlayerO => Players::PlayerX,
+        Players::Blank => panic!("A blank square has no opponent."),
+    }
+}
+
+//The same board as for minimax, as an implementation of the Game trait, so
+//that it can be searched with negamax, alpha-beta or Monte Carlo Tree Search.
+#[derive(Clone, Debug)]
+pub struct TicTacToe {
+    pub board: Vec<Vec<Players>>,
+    pub side: Players,
+    hash: u64,
+    keys: ZobristKeys,
+}
+
+impl TicTacToe {
+    pub fn new() -> Self {
+        TicTacToe::from_board(vec![vec![Players::Blank; 3]; 3], Players::PlayerX)
+    }
+
+    pub fn from_board(board: Vec<Vec<Players>>, side: Players) -> Self {
+        assert!(side != Players::Blank, "Blank cannot be the side to move.");
+        //One key for each square and player, and one for PlayerO to move.
+        let keys = ZobristKeys::new(19, 3);
+        let mut game = TicTacToe {
+            board: vec![vec![Players::Blank; 3]; 3],
+            side,
+            hash: 0,
+            keys,
+        };
+        for (y, board_row) in board.iter().enumerate() {
+            for (x, &board_cell) in board_row.iter().enumerate() {
+                if board_cell != Players::Blank {
+                    game.board[y][x] = board_cell;
+                    game.hash ^= game.square_key(x, y, board_cell);
+                }
+            }
+        }
+        if side == Players::PlayerO {
+            game.hash ^= game.keys.key(18);
+        }
+        game
+    }
+
+    fn square_key(&self, x: usize, y: usize, side: Players) -> u64 {
+        let player = usize::from(side == Players::PlayerO);
+        self.keys.key((y * 3 + x) * 2 + player)
+    }
+
+    pub fn winner(&self) -> Players {
+        if win_check(Players::PlayerX, &self.board) {
+            Players::PlayerX
+        } else if win_check(Players::PlayerO, &self.board) {
+            Players::PlayerO
+        } else {
+            Players::Blank
+        }
+    }
+}
+
+impl Default for TicTacToe {
+    fn default() -> Self {
+        TicTacToe::new()
+    }
+}
+
+impl Game for TicTacToe {
+    type Move = Position;
+
+    fn moves(&self) -> Vec<Position> {
+        if self.winner() != Players::Blank {
+            return vec![];
+        }
+        available_positions(&self.board)
+    }
+
+    fn apply(&mut self, m: Position) {
+        let (x, y) = (m.x as usize, m.y as usize);
+        self.board[y][x] = self.side;
+        self.hash ^= self.square_key(x, y, self.side) ^ self.keys.key(18);
+        self.side = opponent(self.side);
+    }
+
+    fn undo(&mut self, m: Position) {
+        let (x, y) = (m.x as usize, m.y as usize);
+        self.side = opponent(self.side);
+        self.board[y][x] = Players::Blank;
+        self.hash ^= self.square_key(x, y, self.side) ^ self.keys.key(18);
+    }
+
+    fn terminal_score(&self) -> Option<i32> {
+        match self.winner() {
+            Players::Blank if available_positions(&self.board).is_empty() => Some(0),
+            Players::Blank => None,
+            winner if winner == self.side => Some(WIN_SCORE),
+            _ => Some(-WIN_SCORE),
+        }
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -386,6 +494,45 @@ mod test {
         );
     }
 
+    #[test]
+    fn game_trait_agrees_with_minimax() {
+        use crate::graph::{alpha_beta, iterative_deepening, negamax, TranspositionTable};
+        let mut board = vec![vec![Players::Blank; 3]; 3];
+        board[0][1] = Players::PlayerX;
+        board[0][2] = Players::PlayerO;
+        board[2][0] = Players::PlayerO;
+        let mut game = TicTacToe::from_board(board.clone(), Players::PlayerX);
+        let hash = game.hash();
+        //Only the centre avoids a loss, and it leads to a draw.
+        let result = negamax(&mut game, 9);
+        assert_eq!(result.score, 0);
+        assert_eq!(result.best_move, Some(Position { x: 1, y: 1 }));
+        let pruned = alpha_beta(&mut game, 9, None);
+        assert_eq!((pruned.score, pruned.best_move), (0, result.best_move));
+        assert!(pruned.nodes < result.nodes);
+        assert_eq!(game.hash(), hash);
+        assert_eq!(game.board, board);
+
+        //X wins in one move, or else O does.
+        board[0][0] = Players::PlayerX;
+        board[1][2] = Players::PlayerO;
+        board[2][2] = Players::PlayerO;
+        board[0][2] = Players::Blank;
+        let mut game = TicTacToe::from_board(board, Players::PlayerX);
+        let mut table = TranspositionTable::new(1 << 10);
+        let result = iterative_deepening(&mut game, 9, &mut table);
+        assert_eq!(result.score, WIN_SCORE - 1);
+        assert_eq!(result.best_move, Some(Position { x: 2, y: 0 }));
+        game.apply(Position { x: 1, y: 1 });
+        assert_eq!(alpha_beta(&mut game, 9, None).score, WIN_SCORE - 1);
+
+        //The empty board is a draw.
+        let mut table = TranspositionTable::new(1 << 12);
+        let result = iterative_deepening(&mut TicTacToe::new(), 9, &mut table);
+        assert_eq!(result.score, 0);
+        assert_eq!(TicTacToe::new().terminal_score(), None);
+    }
+
     #[test]
     fn expected_loss() {
         let mut board = vec![vec![Players::Blank; 3]; 3];
@@ -408,4 +555,10 @@ mod test {
             })
         );
     }
+
+    #[test]
+    #[should_panic(expected = "Blank cannot be the side to move.")]
+    fn blank_side_to_move() {
+        TicTacToe::from_board(vec![vec![Players::Blank; 3]; 3], Players::Blank);
+    }
 }
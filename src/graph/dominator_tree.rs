@@ -0,0 +1,497 @@
+/*
+Dominator tree:
+In a directed graph with a root (for example the entry block of a
+control-flow graph), a vertex u dominates a vertex v if every path from the
+root to v goes through u. The dominators of v form a chain, and the closest
+one to v (other than v) is its immediate dominator, which is its parent in
+the dominator tree.
+
+The tree is built with the Lengauer–Tarjan algorithm, in O(m lg(n)) time:
+1. Number the vertices in Depth First Search preorder.
+2. Compute the semidominator of each vertex w: the vertex v with the smallest
+   number from which there is a path to w whose inner vertices all have a
+   larger number than w. It is found with a forest of the processed
+   vertices, where `eval` returns the vertex with the smallest semidominator
+   on a path (with path compression).
+3. Deduce the immediate dominators from the semidominators.
+https://en.wikipedia.org/wiki/Dominator_(graph_theory)
+
+On top of the tree, it also computes:
+- Dominance frontiers: the dominance frontier of u is the set of vertices w
+  such that u dominates a predecessor of w, but does not strictly dominate w.
+  This is where phi functions go when building SSA form.
+- Natural loops: an edge (u, h) is a back edge if h dominates u. The loop of
+  header h is made of h and of all the vertices that can reach one of its
+  back edges without going through h. Natural loops are either disjoint or
+  nested, and each one knows the loop directly containing it.
+
+We assume that the graph is represented using (compressed) adjacency matrix
+and its vertices are numbered from 1 to n. If this is not the case, one
+can use `src/graph/graph_enumeration.rs` to convert their graph.
+*/
+
+type Adj = [Vec<usize>];
+
+pub struct DominatorTree {
+    pub root: usize,
+
+    // The immediate dominator of each vertex. It is 0 for the root, and for
+    // the vertices that are not reachable from it
+    pub immediate_dominator: Vec<usize>,
+
+    // The Depth First Search preorder number of each vertex, starting from
+    // 1. It is 0 for the vertices that are not reachable from the root
+    pub preorder: Vec<usize>,
+
+    // The children of each vertex in the dominator tree
+    pub children: Vec<Vec<usize>>,
+
+    // When each vertex is entered and left in a traversal of the dominator
+    // tree, used to answer `dominates` in constant time
+    enter_time: Vec<usize>,
+    exit_time: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NaturalLoop {
+    pub header: usize,
+    // The sources of the back edges to the header
+    pub latches: Vec<usize>,
+    // The vertices of the loop, including the header, sorted
+    pub body: Vec<usize>,
+    // The index of the loop directly containing this one, if any
+    pub parent: Option<usize>,
+    // 1 for the outermost loops
+    pub depth: usize,
+}
+
+fn predecessors(adj: &Adj) -> Vec<Vec<usize>> {
+    let mut result = vec![vec![]; adj.len()];
+    for (u, edges) in adj.iter().enumerate() {
+        for &v in edges {
+            result[v].push(u);
+        }
+    }
+    result
+}
+
+impl DominatorTree {
+    pub fn new(num_vertices: usize) -> Self {
+        let num_vertices = num_vertices + 1; // Vertices are numbered from 1
+        DominatorTree {
+            root: 0,
+            immediate_dominator: vec![0; num_vertices],
+            preorder: vec![0; num_vertices],
+            children: vec![vec![]; num_vertices],
+            enter_time: vec![0; num_vertices],
+            exit_time: vec![0; num_vertices],
+        }
+    }
+
+    // Numbers the vertices in preorder, and returns the vertex of each number
+    // and the parent of each vertex in the Depth First Search tree
+    fn number_vertices(&mut self, root: usize, adj: &Adj) -> (Vec<usize>, Vec<usize>) {
+        let mut vertex = vec![0];
+        let mut parent = vec![0; adj.len()];
+        let mut stack = vec![(root, 0)];
+        while let Some((v, p)) = stack.pop() {
+            if self.preorder[v] != 0 {
+                continue;
+            }
+            vertex.push(v);
+            self.preorder[v] = vertex.len() - 1;
+            parent[v] = p;
+            // Reversed, so that the neighbours are visited in order
+            for &u in adj[v].iter().rev() {
+                if self.preorder[u] == 0 {
+                    stack.push((u, v));
+                }
+            }
+        }
+        (vertex, parent)
+    }
+
+    pub fn build(&mut self, root: usize, adj: &Adj) {
+        *self = DominatorTree::new(adj.len() - 1);
+        self.root = root;
+        let (vertex, parent) = self.number_vertices(root, adj);
+        let predecessors = predecessors(adj);
+        let n = adj.len();
+        // The semidominator number of each vertex, initially its own number
+        let mut semi = self.preorder.clone();
+        let mut ancestor = vec![0; n];
+        let mut label: Vec<usize> = (0..n).collect();
+        let mut bucket = vec![vec![]; n];
+        let mut stack = vec![];
+
+        // The vertex with the smallest semidominator on the path from `v` to
+        // the root of its tree in the forest, excluding that root
+        let mut eval =
+            |v: usize, ancestor: &mut Vec<usize>, label: &mut Vec<usize>, semi: &[usize]| {
+                if ancestor[v] == 0 {
+                    return v;
+                }
+                let mut x = v;
+                while ancestor[ancestor[x]] != 0 {
+                    stack.push(x);
+                    x = ancestor[x];
+                }
+                while let Some(x) = stack.pop() {
+                    let a = ancestor[x];
+                    if semi[label[a]] < semi[label[x]] {
+                        label[x] = label[a];
+                    }
+                    ancestor[x] = ancestor[a];
+                }
+                label[v]
+            };
+
+        for i in (2..vertex.len()).rev() {
+            let w = vertex[i];
+            for &v in predecessors[w].iter().filter(|&&v| self.preorder[v] != 0) {
+                let u = eval(v, &mut ancestor, &mut label, &semi);
+                semi[w] = semi[w].min(semi[u]);
+            }
+            bucket[vertex[semi[w]]].push(w);
+            ancestor[w] = parent[w];
+            for v in std::mem::take(&mut bucket[parent[w]]) {
+                let u = eval(v, &mut ancestor, &mut label, &semi);
+                self.immediate_dominator[v] = if semi[u] < semi[v] { u } else { parent[w] };
+            }
+        }
+        for &w in vertex.iter().skip(2) {
+            if self.immediate_dominator[w] != vertex[semi[w]] {
+                self.immediate_dominator[w] = self.immediate_dominator[self.immediate_dominator[w]];
+            }
+        }
+        for &w in vertex.iter().skip(2) {
+            self.children[self.immediate_dominator[w]].push(w);
+        }
+
+        // Number the dominator tree
+        let mut time = 0;
+        let mut stack = vec![(root, 0)];
+        while let Some((v, child)) = stack.pop() {
+            if child == 0 {
+                time += 1;
+                self.enter_time[v] = time;
+            }
+            if child < self.children[v].len() {
+                stack.push((v, child + 1));
+                stack.push((self.children[v][child], 0));
+            } else {
+                self.exit_time[v] = time;
+            }
+        }
+    }
+
+    pub fn is_reachable(&self, v: usize) -> bool {
+        self.preorder[v] != 0
+    }
+
+    /// Returns true if `u` dominates `v`, which includes u == v. Vertices that
+    /// are not reachable from the root are not dominated.
+    pub fn dominates(&self, u: usize, v: usize) -> bool {
+        self.is_reachable(u)
+            && self.is_reachable(v)
+            && self.enter_time[u] <= self.enter_time[v]
+            && self.exit_time[v] <= self.exit_time[u]
+    }
+
+    /// Returns the dominance frontier of each vertex, each one sorted.
+    pub fn dominance_frontiers(&self, adj: &Adj) -> Vec<Vec<usize>> {
+        let mut frontiers = vec![vec![]; adj.len()];
+        for (w, predecessors) in predecessors(adj).iter().enumerate() {
+            if !self.is_reachable(w) {
+                continue;
+            }
+            // Walk up from each predecessor to the immediate dominator of
+            // `w`, `w` is in the frontier of all the vertices on the way
+            for &p in predecessors.iter().filter(|&&p| self.is_reachable(p)) {
+                let mut runner = p;
+                while runner != 0 && !(runner != w && self.dominates(runner, w)) {
+                    let frontier: &mut Vec<usize> = &mut frontiers[runner];
+                    if frontier.last() != Some(&w) {
+                        frontier.push(w);
+                    }
+                    runner = self.immediate_dominator[runner];
+                }
+            }
+        }
+        frontiers.iter_mut().for_each(|frontier| {
+            frontier.sort_unstable();
+            frontier.dedup();
+        });
+        frontiers
+    }
+
+    /// Returns the natural loops, with one loop for each header. A loop
+    /// comes after all the loops containing it.
+    pub fn natural_loops(&self, adj: &Adj) -> Vec<NaturalLoop> {
+        let predecessors = predecessors(adj);
+        let mut headers: Vec<usize> = (1..adj.len())
+            .filter(|&h| predecessors[h].iter().any(|&u| self.dominates(h, u)))
+            .collect();
+        // A header comes after the headers dominating it
+        headers.sort_unstable_by_key(|&h| self.preorder[h]);
+        let mut loops: Vec<NaturalLoop> = vec![];
+        for header in headers {
+            let latches: Vec<usize> = predecessors[header]
+                .iter()
+                .copied()
+                .filter(|&u| self.dominates(header, u))
+                .collect();
+            let mut in_body = vec![false; adj.len()];
+            in_body[header] = true;
+            let mut stack = latches.clone();
+            while let Some(v) = stack.pop() {
+                if in_body[v] {
+                    continue;
+                }
+                in_body[v] = true;
+                stack.extend(predecessors[v].iter().filter(|&&u| self.is_reachable(u)));
+            }
+            let body: Vec<usize> = (1..adj.len()).filter(|&v| in_body[v]).collect();
+            // The innermost loop containing the header, which comes last
+            let parent = (0..loops.len())
+                .rev()
+                .find(|&i| loops[i].body.binary_search(&header).is_ok());
+            let depth = parent.map_or(1, |i| loops[i].depth + 1);
+            loops.push(NaturalLoop {
+                header,
+                latches,
+                body,
+                parent,
+                depth,
+            });
+        }
+        loops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{enumerate_graph, erdos_renyi, random_dag};
+    use crate::math::PCG32;
+    use std::collections::BTreeMap;
+
+    // The vertices reachable from `root` without going through `removed`
+    fn reachable(adj: &Adj, root: usize, removed: usize) -> Vec<bool> {
+        let mut seen = vec![false; adj.len()];
+        if root == removed {
+            return seen;
+        }
+        seen[root] = true;
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            for &u in adj[v].iter() {
+                if u != removed && !seen[u] {
+                    seen[u] = true;
+                    stack.push(u);
+                }
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn lengauer_tarjan_example() {
+        // The example of the original paper
+        let edges = [
+            ("R", "A"),
+            ("R", "B"),
+            ("R", "C"),
+            ("A", "D"),
+            ("B", "A"),
+            ("B", "D"),
+            ("B", "E"),
+            ("C", "F"),
+            ("C", "G"),
+            ("D", "L"),
+            ("E", "H"),
+            ("F", "I"),
+            ("G", "I"),
+            ("G", "J"),
+            ("H", "E"),
+            ("H", "K"),
+            ("I", "K"),
+            ("J", "I"),
+            ("K", "I"),
+            ("K", "R"),
+            ("L", "H"),
+        ];
+        let mut graph: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (u, v) in edges {
+            graph.entry(u).or_default().push(v);
+            graph.entry(v).or_default();
+        }
+        let names: Vec<&str> = graph.keys().copied().collect();
+        let adj = enumerate_graph(&graph);
+        let index = |name: &str| names.iter().position(|&x| x == name).unwrap() + 1;
+        let name = |v: usize| if v == 0 { "-" } else { names[v - 1] };
+
+        let mut tree = DominatorTree::new(names.len());
+        tree.build(index("R"), &adj);
+        let idoms: String = "RABCDEFGHIJKL"
+            .chars()
+            .map(|c| name(tree.immediate_dominator[index(&c.to_string())]))
+            .collect();
+        assert_eq!(idoms, "-RRRRRCCRRGRD");
+        assert!(tree.dominates(index("C"), index("J")));
+        assert!(!tree.dominates(index("B"), index("E")));
+
+        // H -> E and K -> I are not back edges, as E and I are entered from
+        // elsewhere too, so the only natural loop is the whole graph
+        let loops = tree.natural_loops(&adj);
+        assert_eq!(loops.len(), 1);
+        assert_eq!((loops[0].header, loops[0].depth), (index("R"), 1));
+        assert_eq!(loops[0].body.len(), 13);
+        assert_eq!(loops[0].latches, vec![index("K")]);
+
+        let frontiers = tree.dominance_frontiers(&adj);
+        let frontier =
+            |v: &str| -> Vec<&str> { frontiers[index(v)].iter().map(|&u| name(u)).collect() };
+        assert_eq!(frontier("B"), vec!["A", "D", "E"]);
+        assert_eq!(frontier("L"), vec!["H"]);
+        assert_eq!(frontier("K"), vec!["I", "R"]);
+    }
+
+    #[test]
+    fn nested_loops() {
+        // 1 -> 2 -> 3 -> 4 -> 5 -> 6, with the back edges 4 -> 3 and 5 -> 2
+        let adj = vec![
+            vec![],
+            vec![2],
+            vec![3],
+            vec![4],
+            vec![3, 5],
+            vec![2, 6],
+            vec![],
+            vec![1],
+        ];
+        let mut tree = DominatorTree::new(7);
+        tree.build(1, &adj);
+        assert_eq!(tree.immediate_dominator, vec![0, 0, 1, 2, 3, 4, 5, 0]);
+        assert!(!tree.is_reachable(7));
+        assert!(!tree.dominates(7, 1));
+        assert_eq!(tree.children[4], vec![5]);
+
+        let loops = tree.natural_loops(&adj);
+        assert_eq!(
+            loops,
+            vec![
+                NaturalLoop {
+                    header: 2,
+                    latches: vec![5],
+                    body: vec![2, 3, 4, 5],
+                    parent: None,
+                    depth: 1,
+                },
+                NaturalLoop {
+                    header: 3,
+                    latches: vec![4],
+                    body: vec![3, 4],
+                    parent: Some(0),
+                    depth: 2,
+                },
+            ]
+        );
+        let frontiers = tree.dominance_frontiers(&adj);
+        assert_eq!(frontiers[3], vec![2, 3]);
+        assert_eq!(frontiers[4], vec![2, 3]);
+        assert_eq!(frontiers[5], vec![2]);
+        assert_eq!(frontiers[6], vec![]);
+        assert_eq!(frontiers[7], vec![]);
+    }
+
+    #[test]
+    fn random_graphs() {
+        let mut rng = PCG32::new_default(55);
+        for round in 0..60 {
+            let n = 1 + rng.get_u32() as usize % 12;
+            let adj = if round & 1 == 0 {
+                random_dag(n, 0.3, &mut rng)
+            } else {
+                // Make the undirected edges directed, to get cycles
+                let mut adj = erdos_renyi(n, 0.25, &mut rng);
+                for edges in adj.iter_mut() {
+                    edges.retain(|_| !rng.get_u32().is_multiple_of(3));
+                }
+                adj
+            };
+            let root = 1 + rng.get_u32() as usize % n;
+            let mut tree = DominatorTree::new(n);
+            tree.build(root, &adj);
+            let reached = reachable(&adj, root, 0);
+            // dominated[u][v]: v can not be reached without going through u
+            let dominated: Vec<Vec<bool>> = (0..=n)
+                .map(|u| {
+                    let without_u = reachable(&adj, root, u);
+                    (0..=n)
+                        .map(|v| reached[v] && (u == v || !without_u[v]))
+                        .collect()
+                })
+                .collect();
+            for v in 1..=n {
+                assert_eq!(tree.is_reachable(v), reached[v]);
+                for u in 1..=n {
+                    assert_eq!(tree.dominates(u, v), reached[u] && dominated[u][v]);
+                }
+                let idom = tree.immediate_dominator[v];
+                if reached[v] && v != root {
+                    // Every strict dominator of `v` dominates its immediate
+                    // dominator
+                    assert!(dominated[idom][v] && idom != v);
+                    assert!((1..=n).all(|u| u == v || !dominated[u][v] || dominated[u][idom]));
+                } else {
+                    assert_eq!(idom, 0);
+                }
+            }
+            let frontiers = tree.dominance_frontiers(&adj);
+            for x in 1..=n {
+                let expected: Vec<usize> = (1..=n)
+                    .filter(|&y| {
+                        reached[x]
+                            && (1..=n).any(|p| adj[p].contains(&y) && dominated[x][p])
+                            && !(x != y && dominated[x][y])
+                    })
+                    .collect();
+                assert_eq!(frontiers[x], expected);
+            }
+            for natural_loop in tree.natural_loops(&adj) {
+                for &v in natural_loop.body.iter() {
+                    assert!(tree.dominates(natural_loop.header, v));
+                }
+                if let Some(parent) = natural_loop.parent {
+                    let outer = &tree.natural_loops(&adj)[parent];
+                    assert!(natural_loop.body.iter().all(|v| outer.body.contains(v)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_vertex() {
+        let mut tree = DominatorTree::new(1);
+        tree.build(1, &[vec![], vec![]]);
+        assert_eq!(tree.immediate_dominator, vec![0, 0]);
+        assert!(tree.dominates(1, 1));
+        assert!(tree.natural_loops(&[vec![], vec![]]).is_empty());
+
+        // A self loop on the root is a loop of its own
+        let adj = vec![vec![], vec![1]];
+        tree.build(1, &adj);
+        assert_eq!(tree.dominance_frontiers(&adj), vec![vec![], vec![1]]);
+        assert_eq!(
+            tree.natural_loops(&adj),
+            vec![NaturalLoop {
+                header: 1,
+                latches: vec![1],
+                body: vec![1],
+                parent: None,
+                depth: 1,
+            }]
+        );
+    }
+}
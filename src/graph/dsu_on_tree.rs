@@ -0,0 +1,236 @@
+/*
+DSU on tree (small to large):
+Answers offline queries about every subtree of a rooted tree, like the number
+of distinct colours in each subtree, when the answer is easy to maintain for
+a set of vertices with insertions and deletions, but not to merge.
+
+For each vertex, the "heavy" child is the one with the largest subtree. The
+vertices are visited in Depth First Search order, and the state of the heavy
+child's subtree is kept for its parent, while the subtrees of the light
+children are cleared after being answered, and added again one vertex at a
+time. A vertex is only added again when it is in a light subtree, and there
+are at most lg(n) light edges on the path to the root, so there are
+O(n lg(n)) insertions and deletions in total.
+https://codeforces.com/blog/entry/44351
+
+The state is a `SubtreeState`, and `visit(v, state)` is called when the state
+holds exactly the vertices of the subtree of v. The tree is an undirected
+adjacency list with vertices numbered from 1 to n.
+*/
+
+type Adj = [Vec<usize>];
+
+pub trait SubtreeState {
+    fn add(&mut self, v: usize);
+    fn remove(&mut self, v: usize);
+}
+
+/// Visits every vertex of the tree containing `root` with the state of its
+/// subtree, children before their parent.
+pub fn dsu_on_tree<S, F>(adj: &Adj, root: usize, state: &mut S, mut visit: F)
+where
+    S: SubtreeState,
+    F: FnMut(usize, &S),
+{
+    let n = adj.len();
+    let mut parent = vec![0; n];
+    // The vertices in preorder, so that each subtree is a range of `order`
+    let mut order = vec![];
+    let mut stack = vec![root];
+    let mut visited = vec![false; n];
+    visited[root] = true;
+    while let Some(v) = stack.pop() {
+        order.push(v);
+        for &u in adj[v].iter() {
+            if !visited[u] {
+                visited[u] = true;
+                parent[u] = v;
+                stack.push(u);
+            }
+        }
+    }
+    let mut position = vec![0; n];
+    for (i, &v) in order.iter().enumerate() {
+        position[v] = i;
+    }
+    let mut size = vec![1; n];
+    let mut heavy = vec![0; n];
+    for &v in order.iter().skip(1).rev() {
+        size[parent[v]] += size[v];
+    }
+    for &v in order.iter().skip(1) {
+        let p = parent[v];
+        if heavy[p] == 0 || size[v] > size[heavy[p]] {
+            heavy[p] = v;
+        }
+    }
+    let subtree = |v: usize| &order[position[v]..position[v] + size[v]];
+    let (parent, heavy) = (&parent, &heavy);
+
+    // (vertex, whether its state is kept, whether its children were visited)
+    let mut stack = vec![(root, true, false)];
+    while let Some((v, keep, children_done)) = stack.pop() {
+        let light = || {
+            adj[v]
+                .iter()
+                .copied()
+                .filter(move |&u| parent[u] == v && u != heavy[v] && u != parent[v])
+        };
+        if !children_done {
+            stack.push((v, keep, true));
+            // The heavy child is visited last, so its state stays
+            if heavy[v] != 0 {
+                stack.push((heavy[v], true, false));
+            }
+            stack.extend(light().map(|u| (u, false, false)));
+            continue;
+        }
+        for u in light() {
+            subtree(u).iter().for_each(|&w| state.add(w));
+        }
+        state.add(v);
+        visit(v, state);
+        if !keep {
+            subtree(v).iter().for_each(|&w| state.remove(w));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::barabasi_albert;
+    use crate::math::PCG32;
+
+    // The number of distinct colours among the added vertices
+    struct DistinctColors {
+        color: Vec<usize>,
+        count: Vec<usize>,
+        distinct: usize,
+        operations: usize,
+    }
+
+    impl DistinctColors {
+        fn new(color: Vec<usize>) -> Self {
+            let num_colors = color.iter().max().unwrap() + 1;
+            DistinctColors {
+                color,
+                count: vec![0; num_colors],
+                distinct: 0,
+                operations: 0,
+            }
+        }
+    }
+
+    impl SubtreeState for DistinctColors {
+        fn add(&mut self, v: usize) {
+            self.operations += 1;
+            self.count[self.color[v]] += 1;
+            if self.count[self.color[v]] == 1 {
+                self.distinct += 1;
+            }
+        }
+        fn remove(&mut self, v: usize) {
+            self.operations += 1;
+            self.count[self.color[v]] -= 1;
+            if self.count[self.color[v]] == 0 {
+                self.distinct -= 1;
+            }
+        }
+    }
+
+    fn brute_force(adj: &Adj, root: usize, color: &[usize]) -> Vec<usize> {
+        // Each vertex is in the subtree of all the vertices on its path to
+        // the root
+        let mut parent = vec![0; adj.len()];
+        let mut stack = vec![root];
+        let mut visited = vec![false; adj.len()];
+        visited[root] = true;
+        while let Some(v) = stack.pop() {
+            for &u in adj[v].iter() {
+                if !visited[u] {
+                    visited[u] = true;
+                    parent[u] = v;
+                    stack.push(u);
+                }
+            }
+        }
+        let mut colors = vec![vec![]; adj.len()];
+        for (v, &c) in color.iter().enumerate().skip(1) {
+            let mut u = v;
+            while u != 0 {
+                colors[u].push(c);
+                u = parent[u];
+            }
+        }
+        colors
+            .iter_mut()
+            .map(|seen| {
+                seen.sort_unstable();
+                seen.dedup();
+                seen.len()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn distinct_colors() {
+        //      1
+        //    /   \
+        //   2     3
+        //  / \    |
+        // 4   5   6
+        let adj = vec![
+            vec![],
+            vec![2, 3],
+            vec![1, 4, 5],
+            vec![1, 6],
+            vec![2],
+            vec![2],
+            vec![3],
+        ];
+        let color = vec![0, 1, 2, 1, 2, 3, 2];
+        let mut state = DistinctColors::new(color);
+        let mut answer = vec![0; 7];
+        let mut visited = vec![];
+        dsu_on_tree(&adj, 1, &mut state, |v, state| {
+            answer[v] = state.distinct;
+            visited.push(v);
+        });
+        assert_eq!(answer, vec![0, 3, 2, 2, 1, 1, 1]);
+        assert_eq!(visited.last(), Some(&1));
+        // The state of the root is kept
+        assert_eq!(state.distinct, 3);
+    }
+
+    #[test]
+    fn random_trees() {
+        let mut rng = PCG32::new_default(44);
+        for _ in 0..20 {
+            let n = 1 + rng.get_u32() as usize % 60;
+            let adj = barabasi_albert(n, 1, &mut rng);
+            let color: Vec<usize> = (0..=n).map(|_| rng.get_u32() as usize % 5).collect();
+            let root = 1 + rng.get_u32() as usize % n;
+            let mut state = DistinctColors::new(color.clone());
+            let mut answer = vec![0; n + 1];
+            dsu_on_tree(&adj, root, &mut state, |v, state| {
+                answer[v] = state.distinct
+            });
+            assert_eq!(answer, brute_force(&adj, root, &color));
+            let log = (usize::BITS - n.leading_zeros()) as usize;
+            assert!(state.operations <= 2 * n * (log + 1));
+        }
+    }
+
+    #[test]
+    fn forest() {
+        // Only the tree containing the root is visited
+        let adj = vec![vec![], vec![2], vec![1], vec![4], vec![3]];
+        let mut state = DistinctColors::new(vec![0, 1, 1, 2, 3]);
+        let mut visited = vec![];
+        dsu_on_tree(&adj, 4, &mut state, |v, state| {
+            visited.push((v, state.distinct))
+        });
+        assert_eq!(visited, vec![(3, 1), (4, 2)]);
+    }
+}
@@ -0,0 +1,370 @@
+/*
+Euler Tour Tree:
+It maintains a forest under edge insertions (`link`) and deletions (`cut`),
+and answers connectivity and subtree aggregate queries, all in expected
+O(lg(n)) time.
+
+Each tree is stored as its Euler tour: the sequence of its vertices and of
+both directions of its edges in Depth First Search order. The tour is kept in
+a treap keyed by position, so it can be cut and concatenated quickly. Rerooting
+a tree is a rotation of its tour, linking concatenates two tours around the
+new edge, and cutting an edge splits out the part of the tour between its two
+directions. That part is the subtree on the other side of the edge, which is
+why subtree queries are easy here but path queries are not (for those, see
+`LinkCutTree`).
+
+Vertex values are aggregated with a `Monoid`, which should be commutative
+since the order of the vertices in a tour depends on the history of the
+operations.
+
+Vertices are numbered from 1 to n, 0 is used as the "null" node.
+*/
+
+use std::collections::HashMap;
+
+use crate::data_structures::Monoid;
+use crate::math::PCG32;
+
+pub struct EulerTourTree<M: Monoid> {
+    // Nodes 1 to n are the vertices, the rest are directed edges
+    left: Vec<usize>,
+    right: Vec<usize>,
+    parent: Vec<usize>,
+    priority: Vec<u32>,
+    size: Vec<usize>,
+    value: Vec<M::Value>,
+    aggregate: Vec<M::Value>,
+    // The node of each directed edge
+    edge_node: HashMap<(usize, usize), usize>,
+    // Edge nodes that were freed by `cut`
+    free_nodes: Vec<usize>,
+    rng: PCG32,
+}
+
+impl<M: Monoid> EulerTourTree<M> {
+    /// Creates a forest of `num_vertices` isolated vertices, with identity
+    /// values.
+    pub fn new(num_vertices: usize) -> Self {
+        Self::from_values(vec![M::identity(); num_vertices])
+    }
+    /// Creates a forest of isolated vertices, where values[i] is the value of
+    /// vertex i + 1.
+    pub fn from_values(values: Vec<M::Value>) -> Self {
+        let len = values.len() + 1;
+        let mut value = Vec::with_capacity(len);
+        value.push(M::identity());
+        value.extend(values);
+        let mut rng = PCG32::new_default(len as u64);
+        let mut size = vec![1; len];
+        size[0] = 0;
+        EulerTourTree {
+            left: vec![0; len],
+            right: vec![0; len],
+            parent: vec![0; len],
+            priority: (0..len).map(|_| rng.get_u32()).collect(),
+            size,
+            aggregate: value.clone(),
+            value,
+            edge_node: HashMap::new(),
+            free_nodes: vec![],
+            rng,
+        }
+    }
+    fn new_node(&mut self) -> usize {
+        let priority = self.rng.get_u32();
+        match self.free_nodes.pop() {
+            Some(x) => {
+                self.priority[x] = priority;
+                x
+            }
+            None => {
+                self.left.push(0);
+                self.right.push(0);
+                self.parent.push(0);
+                self.priority.push(priority);
+                self.size.push(1);
+                self.value.push(M::identity());
+                self.aggregate.push(M::identity());
+                self.left.len() - 1
+            }
+        }
+    }
+    fn update(&mut self, x: usize) {
+        let (l, r) = (self.left[x], self.right[x]);
+        self.size[x] = self.size[l] + self.size[r] + 1;
+        // Index 0 always holds the identity
+        self.aggregate[x] = M::combine(
+            &M::combine(&self.aggregate[l], &self.value[x]),
+            &self.aggregate[r],
+        );
+    }
+    fn merge_nodes(&mut self, a: usize, b: usize) -> usize {
+        if a == 0 || b == 0 {
+            return a + b;
+        }
+        if self.priority[a] > self.priority[b] {
+            let r = self.merge_nodes(self.right[a], b);
+            self.right[a] = r;
+            self.parent[r] = a;
+            self.update(a);
+            a
+        } else {
+            let l = self.merge_nodes(a, self.left[b]);
+            self.left[b] = l;
+            self.parent[l] = b;
+            self.update(b);
+            b
+        }
+    }
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        let root = self.merge_nodes(a, b);
+        self.parent[root] = 0;
+        root
+    }
+    // Splits the tour into its first `k` nodes and the rest
+    fn split_nodes(&mut self, t: usize, k: usize) -> (usize, usize) {
+        if t == 0 {
+            return (0, 0);
+        }
+        let l = self.left[t];
+        if self.size[l] >= k {
+            let (a, b) = self.split_nodes(l, k);
+            self.left[t] = b;
+            self.parent[b] = t;
+            self.update(t);
+            (a, t)
+        } else {
+            let (a, b) = self.split_nodes(self.right[t], k - self.size[l] - 1);
+            self.right[t] = a;
+            self.parent[a] = t;
+            self.update(t);
+            (t, b)
+        }
+    }
+    fn split(&mut self, t: usize, k: usize) -> (usize, usize) {
+        let (a, b) = self.split_nodes(t, k);
+        self.parent[a] = 0;
+        self.parent[b] = 0;
+        (a, b)
+    }
+    fn root(&self, mut x: usize) -> usize {
+        while self.parent[x] != 0 {
+            x = self.parent[x];
+        }
+        x
+    }
+    // The position of `x` in its tour
+    fn index(&self, mut x: usize) -> usize {
+        let mut index = self.size[self.left[x]];
+        while self.parent[x] != 0 {
+            let p = self.parent[x];
+            if self.right[p] == x {
+                index += self.size[self.left[p]] + 1;
+            }
+            x = p;
+        }
+        index
+    }
+    // Rotates the tour of the tree containing `v` so that it starts at `v`
+    fn reroot(&mut self, v: usize) -> usize {
+        let root = self.root(v);
+        let index = self.index(v);
+        let (a, b) = self.split(root, index);
+        self.merge(b, a)
+    }
+    pub fn connected(&self, u: usize, v: usize) -> bool {
+        self.root(u) == self.root(v)
+    }
+    /// Adds the edge (u, v). Returns false, and does nothing, if `u` and `v`
+    /// are already connected.
+    pub fn link(&mut self, u: usize, v: usize) -> bool {
+        if self.connected(u, v) {
+            return false;
+        }
+        let tour_u = self.reroot(u);
+        let tour_v = self.reroot(v);
+        let uv = self.new_node();
+        let vu = self.new_node();
+        self.edge_node.insert((u, v), uv);
+        self.edge_node.insert((v, u), vu);
+        let tour = self.merge(tour_u, uv);
+        let tour = self.merge(tour, tour_v);
+        self.merge(tour, vu);
+        true
+    }
+    /// Removes the edge (u, v). Returns false, and does nothing, if there is
+    /// no such edge.
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        let (uv, vu) = match (self.edge_node.get(&(u, v)), self.edge_node.get(&(v, u))) {
+            (Some(&uv), Some(&vu)) => (uv, vu),
+            _ => return false,
+        };
+        let root = self.root(uv);
+        let (mut i, mut j) = (self.index(uv), self.index(vu));
+        if i > j {
+            std::mem::swap(&mut i, &mut j);
+        }
+        // tour = before + edge + inside + edge + after
+        let (before, rest) = self.split(root, i);
+        let (first_edge, rest) = self.split(rest, 1);
+        let (_inside, rest) = self.split(rest, j - i - 1);
+        let (second_edge, after) = self.split(rest, 1);
+        self.merge(before, after);
+        for x in [first_edge, second_edge] {
+            self.size[x] = 1;
+            self.free_nodes.push(x);
+        }
+        self.edge_node.remove(&(u, v));
+        self.edge_node.remove(&(v, u));
+        true
+    }
+    pub fn value(&self, v: usize) -> &M::Value {
+        &self.value[v]
+    }
+    pub fn set_value(&mut self, v: usize, value: M::Value) {
+        self.value[v] = value;
+        let mut x = v;
+        while x != 0 {
+            self.update(x);
+            x = self.parent[x];
+        }
+    }
+    /// Returns the number of vertices in the tree containing `v`.
+    pub fn tree_size(&self, v: usize) -> usize {
+        // A tree with k vertices has k - 1 edges, and 3k - 2 nodes in its tour
+        self.size[self.root(v)].div_ceil(3)
+    }
+    /// Returns the aggregate of the values of the tree containing `v`.
+    pub fn tree_aggregate(&self, v: usize) -> M::Value {
+        self.aggregate[self.root(v)].clone()
+    }
+    /// Returns the aggregate of the values in the subtree of `v` when the
+    /// tree is rooted at its neighbour `parent`, or None if there is no edge
+    /// (parent, v).
+    pub fn subtree_aggregate(&mut self, v: usize, parent: usize) -> Option<M::Value> {
+        let (down, up) = match (
+            self.edge_node.get(&(parent, v)),
+            self.edge_node.get(&(v, parent)),
+        ) {
+            (Some(&down), Some(&up)) => (down, up),
+            _ => return None,
+        };
+        // After rerooting, the subtree is between the two directions of the
+        // edge (parent, v)
+        let root = self.reroot(parent);
+        let (i, j) = (self.index(down), self.index(up));
+        let (before, rest) = self.split(root, i + 1);
+        let (inside, after) = self.split(rest, j - i - 1);
+        let result = self.aggregate[inside].clone();
+        let tour = self.merge(before, inside);
+        self.merge(tour, after);
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::test_monoids::Sum;
+
+    // Returns the vertices reachable from `v` without going through `blocked`
+    fn naive_component(adj: &[Vec<usize>], v: usize, blocked: usize) -> Vec<usize> {
+        let mut seen = vec![false; adj.len()];
+        seen[v] = true;
+        seen[blocked] = true;
+        let mut stack = vec![v];
+        let mut result = vec![];
+        while let Some(x) = stack.pop() {
+            result.push(x);
+            for &y in adj[x].iter() {
+                if !seen[y] {
+                    seen[y] = true;
+                    stack.push(y);
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn small_tree() {
+        let mut ett: EulerTourTree<Sum> = EulerTourTree::from_values(vec![1, 2, 4, 8, 16]);
+        assert!(ett.link(1, 2));
+        assert!(ett.link(2, 3));
+        assert!(ett.link(2, 4));
+        assert!(ett.link(5, 4));
+        assert!(!ett.link(3, 5));
+        assert_eq!(ett.tree_size(1), 5);
+        assert_eq!(ett.tree_aggregate(3), 31);
+        assert_eq!(ett.subtree_aggregate(2, 1), Some(30));
+        assert_eq!(ett.subtree_aggregate(1, 2), Some(1));
+        assert_eq!(ett.subtree_aggregate(4, 2), Some(24));
+        assert_eq!(ett.subtree_aggregate(4, 1), None);
+        assert!(ett.cut(4, 2));
+        assert!(!ett.cut(4, 2));
+        assert!(!ett.connected(1, 5));
+        assert_eq!(ett.tree_size(5), 2);
+        ett.set_value(5, 100);
+        assert_eq!(ett.tree_aggregate(4), 108);
+        assert_eq!(ett.tree_aggregate(1), 7);
+    }
+
+    #[test]
+    fn random_operations() {
+        let n = 40;
+        let mut rng = PCG32::new_default(271828);
+        let mut ett: EulerTourTree<Sum> = EulerTourTree::new(n);
+        let mut values = vec![0i64; n + 1];
+        let mut adj: Vec<Vec<usize>> = vec![vec![]; n + 1];
+        for _ in 0..3000 {
+            let u = (rng.get_u32() as usize % n) + 1;
+            let v = (rng.get_u32() as usize % n) + 1;
+            match rng.get_u32() % 4 {
+                0 => {
+                    let connected = naive_component(&adj, u, 0).contains(&v);
+                    assert_eq!(ett.link(u, v), !connected);
+                    if !connected {
+                        adj[u].push(v);
+                        adj[v].push(u);
+                    }
+                }
+                1 => {
+                    let v = adj[u].first().copied().unwrap_or(v);
+                    assert_eq!(ett.cut(u, v), adj[u].contains(&v));
+                    adj[u].retain(|&x| x != v);
+                    adj[v].retain(|&x| x != u);
+                }
+                2 => {
+                    let value = (rng.get_u32() % 1000) as i64 - 500;
+                    ett.set_value(u, value);
+                    values[u] = value;
+                }
+                _ => {
+                    let component = naive_component(&adj, u, 0);
+                    assert_eq!(ett.connected(u, v), component.contains(&v));
+                    assert_eq!(ett.tree_size(u), component.len());
+                    let total: i64 = component.iter().map(|&x| values[x]).sum();
+                    assert_eq!(ett.tree_aggregate(u), total);
+                    if let Some(&p) = adj[u].first() {
+                        let subtree: i64 =
+                            naive_component(&adj, u, p).iter().map(|&x| values[x]).sum();
+                        assert_eq!(ett.subtree_aggregate(u, p), Some(subtree));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_vertex() {
+        let mut ett: EulerTourTree<Sum> = EulerTourTree::from_values(vec![5]);
+        assert!(ett.connected(1, 1));
+        assert!(!ett.link(1, 1));
+        assert!(!ett.cut(1, 1));
+        assert_eq!(ett.tree_size(1), 1);
+        assert_eq!(ett.tree_aggregate(1), 5);
+        assert_eq!(ett.subtree_aggregate(1, 1), None);
+        ett.set_value(1, 7);
+        assert_eq!(ett.tree_aggregate(1), 7);
+    }
+}
@@ -0,0 +1,310 @@
+/*
+Hierholzer's algorithm for Eulerian paths and circuits:
+An Eulerian path visits every edge of the graph exactly once, and an Eulerian
+circuit is an Eulerian path that starts and ends at the same vertex. Such a
+path exists if and only if all the edges are in a single connected component
+and the degrees of the vertices are balanced:
+- In a directed graph, every vertex has equal in and out degrees, except for
+  (at most) a start vertex with one extra outgoing edge and an end vertex with
+  one extra incoming edge.
+- In an undirected graph, the number of vertices with odd degree is 0 or 2.
+For circuits no exception is allowed. The algorithm walks along unused edges
+until it gets stuck, and splices the remaining cycles into the path while
+backtracking. It runs in O(n + m).
+
+We assume that graph is represented using (compressed) adjacency matrix and
+its vertices are numbered from 1 to n. For undirected graphs, each edge
+should appear in the lists of both of its endpoints (so a self loop appears
+twice in the list of its vertex). If this is not the case, one can use
+`src/graph/graph_enumeration.rs` to convert their graph.
+*/
+
+use std::fmt;
+
+type Adj = [Vec<usize>];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EulerianPathError {
+    // The degrees of the vertices make such a path impossible
+    UnbalancedDegrees,
+    // The edges are not all in a single connected component
+    Disconnected,
+}
+
+impl fmt::Display for EulerianPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EulerianPathError::UnbalancedDegrees => {
+                write!(f, "the vertex degrees do not allow an Eulerian path")
+            }
+            EulerianPathError::Disconnected => {
+                write!(f, "the edges are not in a single connected component")
+            }
+        }
+    }
+}
+
+/// Returns the vertices of an Eulerian path of the directed graph, in the
+/// order they are visited. A graph without edges has a path consisting of
+/// vertex 1 alone.
+pub fn directed_eulerian_path(adj: &Adj) -> Result<Vec<usize>, EulerianPathError> {
+    directed(adj, false)
+}
+
+/// Returns the vertices of an Eulerian circuit of the directed graph. The
+/// first and last vertices of the result are the same.
+pub fn directed_eulerian_circuit(adj: &Adj) -> Result<Vec<usize>, EulerianPathError> {
+    directed(adj, true)
+}
+
+/// Returns the vertices of an Eulerian path of the undirected graph, in the
+/// order they are visited.
+pub fn undirected_eulerian_path(adj: &Adj) -> Result<Vec<usize>, EulerianPathError> {
+    undirected(adj, false)
+}
+
+/// Returns the vertices of an Eulerian circuit of the undirected graph. The
+/// first and last vertices of the result are the same.
+pub fn undirected_eulerian_circuit(adj: &Adj) -> Result<Vec<usize>, EulerianPathError> {
+    undirected(adj, true)
+}
+
+fn directed(adj: &Adj, circuit: bool) -> Result<Vec<usize>, EulerianPathError> {
+    let num_vertices = adj.len().saturating_sub(1);
+    let mut balance = vec![0i64; num_vertices + 1];
+    // incidence[v] contains (other endpoint, edge id) pairs
+    let mut incidence = vec![vec![]; num_vertices + 1];
+    let mut num_edges = 0usize;
+    for v in 1..adj.len() {
+        for &u in adj[v].iter() {
+            balance[v] += 1;
+            balance[u] -= 1;
+            incidence[v].push((u, num_edges));
+            num_edges += 1;
+        }
+    }
+
+    let mut start = 0usize;
+    let mut num_starts = 0usize;
+    let mut num_ends = 0usize;
+    for (v, &b) in balance.iter().enumerate().skip(1) {
+        match b {
+            0 => {}
+            1 => {
+                start = v;
+                num_starts += 1;
+            }
+            -1 => num_ends += 1,
+            _ => return Err(EulerianPathError::UnbalancedDegrees),
+        }
+    }
+    if num_starts != num_ends || num_starts > 1 || (circuit && num_starts != 0) {
+        return Err(EulerianPathError::UnbalancedDegrees);
+    }
+    if start == 0 {
+        start = (1..=num_vertices)
+            .find(|&v| !incidence[v].is_empty())
+            .unwrap_or(1);
+    }
+    hierholzer(&incidence, num_edges, start)
+}
+
+fn undirected(adj: &Adj, circuit: bool) -> Result<Vec<usize>, EulerianPathError> {
+    let num_vertices = adj.len().saturating_sub(1);
+    let mut incidence = vec![vec![]; num_vertices + 1];
+    let mut num_edges = 0usize;
+    for v in 1..adj.len() {
+        // Each edge appears twice, once from each endpoint. Only keep the
+        // copy from the smaller endpoint (and half of the self loops)
+        let mut skip_self_loop = false;
+        for &u in adj[v].iter() {
+            if v < u || (v == u && !skip_self_loop) {
+                incidence[v].push((u, num_edges));
+                incidence[u].push((v, num_edges));
+                num_edges += 1;
+            }
+            if v == u {
+                skip_self_loop = !skip_self_loop;
+            }
+        }
+    }
+
+    let odd_vertices: Vec<usize> = (1..=num_vertices)
+        .filter(|&v| adj[v].len() % 2 == 1)
+        .collect();
+    let start = match odd_vertices.len() {
+        0 => (1..=num_vertices)
+            .find(|&v| !incidence[v].is_empty())
+            .unwrap_or(1),
+        2 if !circuit => odd_vertices[0],
+        _ => return Err(EulerianPathError::UnbalancedDegrees),
+    };
+    hierholzer(&incidence, num_edges, start)
+}
+
+fn hierholzer(
+    incidence: &[Vec<(usize, usize)>],
+    num_edges: usize,
+    start: usize,
+) -> Result<Vec<usize>, EulerianPathError> {
+    if incidence.len() <= 1 {
+        return Ok(vec![]);
+    }
+    let mut used = vec![false; num_edges];
+    // The index of the next edge to try for each vertex
+    let mut next_edge = vec![0usize; incidence.len()];
+    let mut stack = vec![start];
+    let mut path = Vec::with_capacity(num_edges + 1);
+    while let Some(&v) = stack.last() {
+        while next_edge[v] < incidence[v].len() && used[incidence[v][next_edge[v]].1] {
+            next_edge[v] += 1;
+        }
+        if next_edge[v] == incidence[v].len() {
+            // We are stuck, so `v` is the next vertex of the path (backwards)
+            path.push(v);
+            stack.pop();
+        } else {
+            let (u, e) = incidence[v][next_edge[v]];
+            used[e] = true;
+            stack.push(u);
+        }
+    }
+    // Some edges were not reachable from the start
+    if path.len() != num_edges + 1 {
+        return Err(EulerianPathError::Disconnected);
+    }
+    path.reverse();
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undirected_adj(num_vertices: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+        let mut adj = vec![vec![]; num_vertices + 1];
+        for &(u, v) in edges {
+            adj[u].push(v);
+            adj[v].push(u);
+        }
+        adj
+    }
+
+    fn check_directed(adj: &Adj, path: &[usize]) {
+        let mut remaining: Vec<Vec<usize>> = adj.to_vec();
+        for w in path.windows(2) {
+            let pos = remaining[w[0]].iter().position(|&u| u == w[1]).unwrap();
+            remaining[w[0]].swap_remove(pos);
+        }
+        assert!(remaining.iter().all(|e| e.is_empty()));
+    }
+
+    fn check_undirected(adj: &Adj, path: &[usize]) {
+        let mut remaining: Vec<Vec<usize>> = adj.to_vec();
+        for w in path.windows(2) {
+            let pos = remaining[w[0]].iter().position(|&u| u == w[1]).unwrap();
+            remaining[w[0]].swap_remove(pos);
+            let pos = remaining[w[1]].iter().position(|&u| u == w[0]).unwrap();
+            remaining[w[1]].swap_remove(pos);
+        }
+        assert!(remaining.iter().all(|e| e.is_empty()));
+    }
+
+    #[test]
+    fn directed_circuit() {
+        // Two cycles sharing vertex 1
+        let adj = vec![vec![], vec![2, 4], vec![3], vec![1], vec![5], vec![1]];
+        let circuit = directed_eulerian_circuit(&adj).unwrap();
+        assert_eq!(circuit.len(), 7);
+        assert_eq!(circuit.first(), circuit.last());
+        check_directed(&adj, &circuit);
+    }
+
+    #[test]
+    fn directed_path() {
+        let adj = vec![vec![], vec![2], vec![3, 4], vec![2], vec![]];
+        let path = directed_eulerian_path(&adj).unwrap();
+        assert_eq!(path, vec![1, 2, 3, 2, 4]);
+        assert_eq!(
+            directed_eulerian_circuit(&adj),
+            Err(EulerianPathError::UnbalancedDegrees)
+        );
+    }
+
+    #[test]
+    fn directed_errors() {
+        let adj = vec![vec![], vec![2, 3], vec![], vec![]];
+        assert_eq!(
+            directed_eulerian_path(&adj),
+            Err(EulerianPathError::UnbalancedDegrees)
+        );
+        let adj = vec![vec![], vec![2], vec![1], vec![4], vec![3]];
+        assert_eq!(
+            directed_eulerian_path(&adj),
+            Err(EulerianPathError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn undirected_path_and_circuit() {
+        // The "house": a square with a roof, vertices 1 and 2 have odd degree
+        let adj = undirected_adj(
+            5,
+            &[
+                (1, 2),
+                (2, 3),
+                (3, 4),
+                (4, 1),
+                (1, 3),
+                (2, 4),
+                (3, 5),
+                (4, 5),
+            ],
+        );
+        let path = undirected_eulerian_path(&adj).unwrap();
+        assert_eq!(path.len(), 9);
+        assert!(path[0] == 1 || path[0] == 2);
+        check_undirected(&adj, &path);
+        assert_eq!(
+            undirected_eulerian_circuit(&adj),
+            Err(EulerianPathError::UnbalancedDegrees)
+        );
+
+        let adj = undirected_adj(3, &[(1, 2), (2, 3), (3, 1), (2, 2)]);
+        let circuit = undirected_eulerian_circuit(&adj).unwrap();
+        assert_eq!(circuit.len(), 5);
+        assert_eq!(circuit.first(), circuit.last());
+        check_undirected(&adj, &circuit);
+    }
+
+    #[test]
+    fn undirected_errors() {
+        let adj = undirected_adj(4, &[(1, 2), (1, 3), (1, 4)]);
+        assert_eq!(
+            undirected_eulerian_path(&adj),
+            Err(EulerianPathError::UnbalancedDegrees)
+        );
+        let adj = undirected_adj(6, &[(1, 2), (2, 3), (3, 1), (4, 5), (5, 6), (6, 4)]);
+        assert_eq!(
+            undirected_eulerian_circuit(&adj),
+            Err(EulerianPathError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn no_edges() {
+        let adj = vec![vec![], vec![], vec![]];
+        assert_eq!(undirected_eulerian_circuit(&adj), Ok(vec![1]));
+        assert_eq!(directed_eulerian_path(&adj), Ok(vec![1]));
+        // Without vertices the path is empty
+        let adj: Vec<Vec<usize>> = vec![vec![]];
+        assert_eq!(directed_eulerian_circuit(&adj), Ok(vec![]));
+        assert_eq!(undirected_eulerian_path(&adj), Ok(vec![]));
+        // Isolated vertices next to a lone self loop do not make the graph
+        // disconnected
+        let adj = vec![vec![], vec![], vec![2]];
+        assert_eq!(directed_eulerian_circuit(&adj), Ok(vec![2, 2]));
+        let adj = undirected_adj(3, &[(2, 2)]);
+        assert_eq!(undirected_eulerian_circuit(&adj), Ok(vec![2, 2]));
+    }
+}
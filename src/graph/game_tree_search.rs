@@ -0,0 +1,422 @@
+/*
+Game tree search:
+For two-player, zero-sum games where the players take turns (tic-tac-toe,
+connect four, chess...), the value of a position for the player to move is
+the largest of the values of its moves, and the value of a move is minus the
+value of the position it leads to for the opponent. This is negamax, the
+symmetric form of minimax, which explores the whole tree of moves down to a
+given depth, where positions are scored by a heuristic.
+
+On top of it:
+1. Alpha-beta pruning keeps a window [alpha, beta] of the scores that can
+   still change the result: once a move scores at least beta, the opponent
+   will never allow this position, and the other moves are skipped. With the
+   best moves tried first, it searches about the square root of the nodes.
+2. A transposition table remembers the positions already searched, indexed
+   by their hash, with their score (or a bound on it when there was a cutoff)
+   and their best move, which is tried first the next time.
+3. Iterative deepening searches with depth 1, 2, 3... keeping the table
+   between searches, so the best moves from the shallower searches order the
+   deeper ones, which usually makes the whole thing faster than a single
+   deep search.
+https://www.chessprogramming.org/Alpha-Beta
+
+The positions are hashed with Zobrist hashing: a random 64-bit key is drawn
+for each (square, piece) pair and for the player to move, and the hash of a
+position is the xor of the keys of what it contains. It is updated in O(1)
+when a move is applied or undone.
+https://en.wikipedia.org/wiki/Zobrist_hashing
+
+The games implement the `Game` trait, see `TicTacToe` and `ConnectFour`.
+*/
+
+use crate::math::PCG32;
+
+/// The score of a won game. The search subtracts the number of moves played
+/// before the end, so that faster wins (and slower losses) are preferred.
+pub const WIN_SCORE: i32 = 1 << 20;
+
+// Scores this close to WIN_SCORE are wins or losses found by the search
+const MAX_PLY: i32 = 1 << 10;
+const INFINITY: i32 = WIN_SCORE + 1;
+
+pub trait Game {
+    type Move: Copy + Eq;
+
+    /// The legal moves of the player to move, the most promising ones first.
+    /// It is empty when the game is over.
+    fn moves(&self) -> Vec<Self::Move>;
+    fn apply(&mut self, m: Self::Move);
+    /// Undoes `m`, which must be the last move applied.
+    fn undo(&mut self, m: Self::Move);
+    /// The score of a finished game for the player to move: WIN_SCORE if
+    /// they won, -WIN_SCORE if they lost and 0 for a draw. It is None while
+    /// the game goes on.
+    fn terminal_score(&self) -> Option<i32>;
+    /// A heuristic score for the player to move, when the search does not go
+    /// deeper. It must be far smaller than WIN_SCORE.
+    fn evaluate(&self) -> i32 {
+        0
+    }
+    /// The Zobrist hash of the position, including the player to move.
+    fn hash(&self) -> u64;
+}
+
+/// Random keys for Zobrist hashing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZobristKeys {
+    keys: Vec<u64>,
+}
+
+impl ZobristKeys {
+    pub fn new(count: usize, seed: u64) -> Self {
+        let mut rng = PCG32::new_default(seed);
+        ZobristKeys {
+            keys: (0..count).map(|_| rng.get_u64()).collect(),
+        }
+    }
+
+    pub fn key(&self, index: usize) -> u64 {
+        self.keys[index]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    // The score is at least the stored one
+    Lower,
+    // The score is at most the stored one
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry<M> {
+    hash: u64,
+    depth: u32,
+    score: i32,
+    bound: Bound,
+    best_move: Option<M>,
+}
+
+/// A fixed size table of searched positions, where a new entry replaces the
+/// one with the same index.
+pub struct TranspositionTable<M> {
+    entries: Vec<Option<Entry<M>>>,
+}
+
+impl<M: Copy> TranspositionTable<M> {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "the table needs at least one entry");
+        TranspositionTable {
+            entries: vec![None; size],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        (hash % self.entries.len() as u64) as usize
+    }
+
+    fn probe(&self, hash: u64) -> Option<Entry<M>> {
+        self.entries[self.index(hash)].filter(|entry| entry.hash == hash)
+    }
+
+    fn store(&mut self, entry: Entry<M>) {
+        let index = self.index(entry.hash);
+        self.entries[index] = Some(entry);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchResult<M> {
+    // The score of the position for the player to move
+    pub score: i32,
+    // None when the game is over, or when the depth is 0
+    pub best_move: Option<M>,
+    // The number of positions visited
+    pub nodes: u64,
+}
+
+// Wins and losses are stored in the table relative to the position, not to
+// the root, as the same position can be reached after more or fewer moves
+fn to_table(score: i32, ply: i32) -> i32 {
+    if score >= WIN_SCORE - MAX_PLY {
+        score + ply
+    } else if score <= -WIN_SCORE + MAX_PLY {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i32, ply: i32) -> i32 {
+    to_table(score, -ply)
+}
+
+fn negamax_search<G: Game>(
+    game: &mut G,
+    depth: u32,
+    ply: i32,
+    nodes: &mut u64,
+) -> (i32, Option<G::Move>) {
+    *nodes += 1;
+    if let Some(score) = game.terminal_score() {
+        return (from_table(score, ply), None);
+    }
+    if depth == 0 {
+        return (game.evaluate(), None);
+    }
+    let mut best = (-INFINITY, None);
+    for m in game.moves() {
+        game.apply(m);
+        let score = -negamax_search(game, depth - 1, ply + 1, nodes).0;
+        game.undo(m);
+        if score > best.0 {
+            best = (score, Some(m));
+        }
+    }
+    best
+}
+
+/// Searches every move sequence up to `depth` moves.
+pub fn negamax<G: Game>(game: &mut G, depth: u32) -> SearchResult<G::Move> {
+    let mut nodes = 0;
+    let (score, best_move) = negamax_search(game, depth, 0, &mut nodes);
+    SearchResult {
+        score,
+        best_move,
+        nodes,
+    }
+}
+
+struct AlphaBeta<'a, M> {
+    table: Option<&'a mut TranspositionTable<M>>,
+    nodes: u64,
+}
+
+impl<M: Copy + Eq> AlphaBeta<'_, M> {
+    fn search<G: Game<Move = M>>(
+        &mut self,
+        game: &mut G,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> (i32, Option<M>) {
+        self.nodes += 1;
+        if let Some(score) = game.terminal_score() {
+            return (from_table(score, ply), None);
+        }
+        if depth == 0 {
+            return (game.evaluate(), None);
+        }
+        let original_alpha = alpha;
+        let hash = game.hash();
+        let mut table_move = None;
+        if let Some(entry) = self.table.as_ref().and_then(|table| table.probe(hash)) {
+            table_move = entry.best_move;
+            if entry.depth >= depth {
+                let score = from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return (score, table_move),
+                    Bound::Lower => alpha = alpha.max(score),
+                    Bound::Upper => beta = beta.min(score),
+                }
+                if alpha >= beta {
+                    return (score, table_move);
+                }
+            }
+        }
+
+        let mut moves = game.moves();
+        // The best move of an earlier search is tried first
+        if let Some(i) = moves.iter().position(|&m| Some(m) == table_move) {
+            moves[..=i].rotate_right(1);
+        }
+        let mut best = (-INFINITY, None);
+        for m in moves {
+            game.apply(m);
+            let score = -self.search(game, depth - 1, ply + 1, -beta, -alpha).0;
+            game.undo(m);
+            if score > best.0 {
+                best = (score, Some(m));
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if let Some(table) = self.table.as_mut() {
+            let bound = if best.0 <= original_alpha {
+                Bound::Upper
+            } else if best.0 >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            table.store(Entry {
+                hash,
+                depth,
+                score: to_table(best.0, ply),
+                bound,
+                best_move: best.1,
+            });
+        }
+        best
+    }
+}
+
+/// Searches up to `depth` moves with alpha-beta pruning, and with the
+/// transposition table if there is one. The score is the same as the one of
+/// `negamax`, but the best move can be another one with the same score.
+pub fn alpha_beta<G: Game>(
+    game: &mut G,
+    depth: u32,
+    table: Option<&mut TranspositionTable<G::Move>>,
+) -> SearchResult<G::Move> {
+    let mut search = AlphaBeta { table, nodes: 0 };
+    let (score, best_move) = search.search(game, depth, 0, -INFINITY, INFINITY);
+    SearchResult {
+        score,
+        best_move,
+        nodes: search.nodes,
+    }
+}
+
+/// Searches with alpha-beta pruning with depth 1, 2... up to `max_depth`,
+/// or until the result of the game is known.
+pub fn iterative_deepening<G: Game>(
+    game: &mut G,
+    max_depth: u32,
+    table: &mut TranspositionTable<G::Move>,
+) -> SearchResult<G::Move> {
+    let mut result = alpha_beta(game, 0, Some(table));
+    for depth in 1..=max_depth {
+        if result.score.abs() >= WIN_SCORE - MAX_PLY {
+            break;
+        }
+        let nodes = result.nodes;
+        result = alpha_beta(game, depth, Some(table));
+        result.nodes += nodes;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A pile of stones where each player takes 1, 2 or 3 of them, and the
+    // one taking the last stone wins. The player to move loses iff the
+    // number of stones is a multiple of 4
+    struct Nim {
+        stones: u32,
+        keys: ZobristKeys,
+    }
+
+    impl Game for Nim {
+        type Move = u32;
+
+        fn moves(&self) -> Vec<u32> {
+            (1..=self.stones.min(3)).collect()
+        }
+        fn apply(&mut self, m: u32) {
+            self.stones -= m;
+        }
+        fn undo(&mut self, m: u32) {
+            self.stones += m;
+        }
+        fn terminal_score(&self) -> Option<i32> {
+            // The previous player took the last stone
+            (self.stones == 0).then_some(-WIN_SCORE)
+        }
+        fn hash(&self) -> u64 {
+            self.keys.key(self.stones as usize)
+        }
+    }
+
+    #[test]
+    fn nim() {
+        for stones in 1..=15 {
+            let mut game = Nim {
+                stones,
+                keys: ZobristKeys::new(16, 7),
+            };
+            let full = negamax(&mut game, 20);
+            let pruned = alpha_beta(&mut game, 20, None);
+            let mut table = TranspositionTable::new(64);
+            let deepened = iterative_deepening(&mut game, 20, &mut table);
+            for result in [full, pruned, deepened] {
+                if stones.is_multiple_of(4) {
+                    // The game lasts as long as possible
+                    assert_eq!(result.score, -WIN_SCORE + stones as i32 / 2);
+                } else {
+                    assert_eq!(result.best_move, Some(stones % 4));
+                    assert_eq!(result.score, WIN_SCORE - 1 - (stones as i32 - 1) / 4 * 2);
+                }
+            }
+            assert!(pruned.nodes <= full.nodes);
+            assert_eq!(game.stones, stones);
+        }
+    }
+
+    #[test]
+    fn table() {
+        let mut table = TranspositionTable::new(4);
+        let entry = Entry {
+            hash: 6,
+            depth: 2,
+            score: 5,
+            bound: Bound::Exact,
+            best_move: Some(1),
+        };
+        table.store(entry);
+        assert_eq!(table.probe(6).map(|e| e.score), Some(5));
+        // Same index, different hash
+        assert!(table.probe(10).is_none());
+        table.clear();
+        assert!(table.probe(6).is_none());
+        assert_eq!(to_table(from_table(WIN_SCORE - 3, 2), 2), WIN_SCORE - 3);
+        assert_eq!(from_table(-WIN_SCORE, 5), -WIN_SCORE + 5);
+        assert_eq!(to_table(17, 5), 17);
+    }
+
+    #[test]
+    fn finished_game_and_depth_zero() {
+        let mut game = Nim {
+            stones: 0,
+            keys: ZobristKeys::new(16, 7),
+        };
+        let mut table = TranspositionTable::new(64);
+        let lost = SearchResult {
+            score: -WIN_SCORE,
+            best_move: None,
+            nodes: 1,
+        };
+        assert_eq!(negamax(&mut game, 5), lost);
+        assert_eq!(alpha_beta(&mut game, 5, None), lost);
+        assert_eq!(iterative_deepening(&mut game, 5, &mut table), lost);
+
+        // Without searching, only the heuristic is known
+        game.stones = 5;
+        let unknown = SearchResult {
+            score: 0,
+            best_move: None,
+            nodes: 1,
+        };
+        assert_eq!(negamax(&mut game, 0), unknown);
+        assert_eq!(alpha_beta(&mut game, 0, Some(&mut table)), unknown);
+        assert_eq!(iterative_deepening(&mut game, 0, &mut table), unknown);
+    }
+
+    #[test]
+    #[should_panic(expected = "the table needs at least one entry")]
+    fn empty_table() {
+        TranspositionTable::<u32>::new(0);
+    }
+}
@@ -0,0 +1,314 @@
+/*
+Graph colouring:
+A colouring assigns a colour to each vertex, so that adjacent vertices have
+different colours. Finding one with the fewest colours (the chromatic number)
+is NP-hard, so there are two kinds of algorithms here:
+1. Heuristics, that are fast but can use more colours than needed:
+   - Greedy colouring: visit the vertices in a given order, and give each one
+     the smallest colour not used by its neighbours.
+   - DSATUR: always colour next the vertex with the most distinct colours
+     among its neighbours (its "saturation"), which is exact for bipartite
+     graphs, cycles and wheels, and usually better than the greedy colouring.
+2. `chromatic_number`, which is exact, for graphs of up to about 25 vertices.
+   A graph is k-colourable iff its vertices can be covered by k independent
+   sets, and by inclusion–exclusion the number of such covers is the sum,
+   over the subsets S of vertices, of (-1)^(n - |S|) * i(S)^k, where i(S) is
+   the number of independent sets inside S. This sum is computed modulo a
+   large prime, for increasing k, in O(2^n * n) time and memory. A colouring
+   with that many colours is then found by backtracking, which takes O(k^n)
+   time in the worst case, but is usually fast once k is known to suffice.
+   https://en.wikipedia.org/wiki/Graph_coloring#Inclusion%E2%80%93exclusion
+
+The graph is an undirected adjacency list with vertices numbered from 1 to n,
+where every edge appears in the lists of both endpoints. Colours are numbered
+from 1, and colour 0 is given to the unused vertex 0. The colourings can be
+checked with `Coloring::is_valid`.
+*/
+
+type Adj = [Vec<usize>];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coloring {
+    pub num_colors: usize,
+    // The colour of each vertex, from 1 to num_colors
+    pub color: Vec<usize>,
+}
+
+impl Coloring {
+    fn from_colors(color: Vec<usize>) -> Self {
+        Coloring {
+            num_colors: color.iter().copied().max().unwrap_or(0),
+            color,
+        }
+    }
+    /// Checks that every vertex has a colour between 1 and `num_colors`, and
+    /// that adjacent vertices have different colours.
+    pub fn is_valid(&self, adj: &Adj) -> bool {
+        self.color.len() == adj.len()
+            && (1..adj.len()).all(|v| {
+                (1..=self.num_colors).contains(&self.color[v])
+                    && adj[v]
+                        .iter()
+                        .all(|&u| u == v || self.color[u] != self.color[v])
+            })
+    }
+}
+
+// The smallest colour not used by the neighbours of `v`
+fn smallest_free_color(adj: &Adj, color: &[usize], v: usize) -> usize {
+    let mut used = vec![false; adj[v].len() + 2];
+    for &u in adj[v].iter() {
+        if color[u] < used.len() {
+            used[color[u]] = true;
+        }
+    }
+    (1..used.len()).find(|&c| !used[c]).unwrap()
+}
+
+/// Colours the vertices greedily, in the given order, which must contain
+/// every vertex once.
+pub fn greedy_coloring(adj: &Adj, order: &[usize]) -> Coloring {
+    let mut color = vec![0; adj.len()];
+    for &v in order {
+        color[v] = smallest_free_color(adj, &color, v);
+    }
+    Coloring::from_colors(color)
+}
+
+// The order in which DSATUR colours the vertices, and the colouring
+fn dsatur(adj: &Adj) -> (Vec<usize>, Vec<usize>) {
+    let n = adj.len().saturating_sub(1);
+    let mut color = vec![0; adj.len()];
+    let mut order = Vec::with_capacity(n);
+    // The colours used by the neighbours of each vertex
+    let mut neighbour_colors: Vec<Vec<bool>> = vec![vec![]; adj.len()];
+    let mut saturation = vec![0; adj.len()];
+    for _ in 0..n {
+        let v = (1..=n)
+            .filter(|&v| color[v] == 0)
+            .max_by_key(|&v| (saturation[v], adj[v].len(), std::cmp::Reverse(v)))
+            .unwrap();
+        color[v] = smallest_free_color(adj, &color, v);
+        order.push(v);
+        for &u in adj[v].iter() {
+            let seen = &mut neighbour_colors[u];
+            if seen.len() <= color[v] {
+                seen.resize(color[v] + 1, false);
+            }
+            if !seen[color[v]] {
+                seen[color[v]] = true;
+                saturation[u] += 1;
+            }
+        }
+    }
+    (order, color)
+}
+
+/// Colours the vertices with the DSATUR heuristic.
+pub fn dsatur_coloring(adj: &Adj) -> Coloring {
+    Coloring::from_colors(dsatur(adj).1)
+}
+
+const PRIME: u64 = (1 << 61) - 1;
+
+fn multiply_mod(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % PRIME as u128) as u64
+}
+
+// Tries to colour the vertices in `order` with `num_colors` colours
+fn backtrack(
+    adj: &Adj,
+    order: &[usize],
+    num_colors: usize,
+    color: &mut [usize],
+    colors_used: usize,
+) -> bool {
+    let (&v, rest) = match order.split_first() {
+        Some(split) => split,
+        None => return true,
+    };
+    // A colour that was never used is as good as any other unused one
+    for c in 1..=num_colors.min(colors_used + 1) {
+        if adj[v].iter().all(|&u| color[u] != c) {
+            color[v] = c;
+            if backtrack(adj, rest, num_colors, color, colors_used.max(c)) {
+                return true;
+            }
+        }
+    }
+    color[v] = 0;
+    false
+}
+
+/// Returns a colouring with the fewest possible colours, so that its
+/// `num_colors` is the chromatic number of the graph. The colourings are
+/// counted modulo a 61-bit prime, so there is a tiny chance (about n / 2^61)
+/// that a count is a multiple of it, and the colouring uses too many colours.
+pub fn chromatic_number(adj: &Adj) -> Coloring {
+    let n = adj.len().saturating_sub(1);
+    assert!(n <= 30, "the graph is too large");
+    // Vertex v is bit v - 1. The closed neighbourhood of each vertex
+    let neighbourhood: Vec<usize> = (1..=n)
+        .map(|v| {
+            adj[v]
+                .iter()
+                .fold(1 << (v - 1), |mask, &u| mask | (1 << (u - 1)))
+        })
+        .collect();
+    // The number of independent sets of each subset, either without its
+    // lowest vertex, or with it and without its neighbours
+    let mut independent_sets = vec![1u64; 1 << n];
+    for set in 1..1usize << n {
+        let v = set.trailing_zeros() as usize;
+        independent_sets[set] =
+            independent_sets[set & !(1 << v)] + independent_sets[set & !neighbourhood[v]];
+    }
+    let mut power = vec![1u64; 1 << n];
+    let mut num_colors = 0;
+    while num_colors < n {
+        num_colors += 1;
+        let mut covers = 0;
+        for (set, p) in power.iter_mut().enumerate() {
+            *p = multiply_mod(*p, independent_sets[set] % PRIME);
+            if (n - set.count_ones() as usize) & 1 == 0 {
+                covers = (covers + *p) % PRIME;
+            } else {
+                covers = (covers + PRIME - *p) % PRIME;
+            }
+        }
+        if covers != 0 {
+            break;
+        }
+    }
+    let order = dsatur(adj).0;
+    let mut color = vec![0; adj.len()];
+    // A nonzero sum means that there is a colouring with num_colors colours.
+    // If the sum for fewer colours was a multiple of the prime by chance, that
+    // number was skipped, and this colouring is not optimal
+    let found = backtrack(adj, &order, num_colors, &mut color, 0);
+    assert!(found, "the number of colourings was miscounted");
+    Coloring { num_colors, color }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{complete_graph, erdos_renyi, grid_graph};
+    use crate::math::PCG32;
+
+    fn cycle(n: usize) -> Vec<Vec<usize>> {
+        let mut adj = vec![vec![]; n + 1];
+        for v in 1..=n {
+            let u = v % n + 1;
+            adj[v].push(u);
+            adj[u].push(v);
+        }
+        adj
+    }
+
+    // Tries every assignment of k colours, for increasing k
+    fn brute_force_chromatic_number(adj: &Adj) -> usize {
+        let n = adj.len().saturating_sub(1);
+        (1..=n)
+            .find(|&k| {
+                (0..k.pow(n as u32)).any(|code| {
+                    let mut color = vec![0; n + 1];
+                    let mut code = code;
+                    for c in color.iter_mut().skip(1) {
+                        *c = code % k + 1;
+                        code /= k;
+                    }
+                    Coloring::from_colors(color).is_valid(adj)
+                })
+            })
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn small_graphs() {
+        // Crown graph, where the greedy colouring in the worst order uses a
+        // colour for each pair of vertices, but the graph is bipartite
+        let n = 4;
+        let mut crown = vec![vec![]; 2 * n + 1];
+        for i in 1..=n {
+            for j in 1..=n {
+                if i != j {
+                    crown[i].push(n + j);
+                    crown[n + j].push(i);
+                }
+            }
+        }
+        let order: Vec<usize> = (1..=n).flat_map(|i| [i, n + i]).collect();
+        let greedy = greedy_coloring(&crown, &order);
+        assert!(greedy.is_valid(&crown));
+        assert_eq!(greedy.num_colors, n);
+        assert_eq!(dsatur_coloring(&crown).num_colors, 2);
+        assert_eq!(chromatic_number(&crown).num_colors, 2);
+
+        for (adj, expected) in [
+            (cycle(5), 3),
+            (cycle(6), 2),
+            (complete_graph(6), 6),
+            (grid_graph(3, 3), 2),
+            (vec![vec![]; 4], 1),
+            (vec![vec![]], 0),
+        ] {
+            let coloring = chromatic_number(&adj);
+            assert!(coloring.is_valid(&adj));
+            assert_eq!(coloring.num_colors, expected);
+            assert!(dsatur_coloring(&adj).is_valid(&adj));
+        }
+
+        let coloring = Coloring {
+            num_colors: 2,
+            color: vec![0, 1, 2, 2],
+        };
+        assert!(!coloring.is_valid(&cycle(3)));
+    }
+
+    #[test]
+    fn edge_cases() {
+        // No vertex, not even the unused vertex 0
+        let empty: Vec<Vec<usize>> = vec![];
+        for coloring in [
+            chromatic_number(&empty),
+            dsatur_coloring(&empty),
+            greedy_coloring(&empty, &[]),
+        ] {
+            assert_eq!(coloring.num_colors, 0);
+            assert!(coloring.color.is_empty());
+            assert!(coloring.is_valid(&empty));
+        }
+        // A single vertex, and a single edge
+        let single = vec![vec![], vec![]];
+        assert_eq!(chromatic_number(&single).color, vec![0, 1]);
+        assert_eq!(dsatur_coloring(&single).color, vec![0, 1]);
+        let edge = vec![vec![], vec![2], vec![1]];
+        assert_eq!(chromatic_number(&edge).color, vec![0, 1, 2]);
+        assert_eq!(greedy_coloring(&edge, &[2, 1]).color, vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn random_graphs() {
+        let mut rng = PCG32::new_default(8);
+        for _ in 0..30 {
+            let n = 1 + rng.get_u32() as usize % 7;
+            let adj = erdos_renyi(n, 0.5, &mut rng);
+            let exact = chromatic_number(&adj);
+            assert!(exact.is_valid(&adj));
+            assert_eq!(exact.num_colors, brute_force_chromatic_number(&adj));
+            let order: Vec<usize> = (1..=n).collect();
+            for heuristic in [dsatur_coloring(&adj), greedy_coloring(&adj, &order)] {
+                assert!(heuristic.is_valid(&adj));
+                assert!(heuristic.num_colors >= exact.num_colors);
+            }
+        }
+        // Larger graphs, where the heuristics give an upper bound
+        for _ in 0..3 {
+            let adj = erdos_renyi(18, 0.5, &mut rng);
+            let exact = chromatic_number(&adj);
+            assert!(exact.is_valid(&adj));
+            assert!(exact.num_colors <= dsatur_coloring(&adj).num_colors);
+        }
+    }
+}
@@ -0,0 +1,943 @@
+/*
+Reading and writing graphs in some common text formats:
+- Edge lists: one `u v [weight]` edge per line (a vertex alone on a line is
+  an isolated vertex), and lines starting with `#` are comments.
+- Graphviz DOT: https://graphviz.org/doc/info/lang.html
+  Only nodes, edges (possibly chained as `a -> b -> c`) and attribute lists
+  are supported. The weight of an edge is read from its `weight` attribute.
+- DIMACS max-flow and shortest path formats, as used by the first and ninth
+  DIMACS implementation challenges. Vertices are numbered from 1 to n.
+- DIMACS CNF, for the formulas of `solve_satisfiability`. Variables are
+  numbered from 1 to n.
+
+Edge lists, DOT and DIMACS shortest path files are read into the weighted
+graph type used by `dijkstra`, `bellman_ford` and `prim`. Every vertex has an
+entry in the map, and for parallel edges only the lightest one is kept.
+DIMACS max-flow files are read into a `DinicMaxFlow`, and
+`to_directed_graph` / `from_directed_graph` convert to and from
+`data_structures::DirectedGraph`.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Write};
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use super::DinicMaxFlow;
+use crate::data_structures::{DirectedGraph, Graph as _};
+
+type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GraphParseError {
+    // The line of the input where the error happened, starting from 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl GraphParseError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        GraphParseError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for GraphParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn parse_value<T: FromStr>(
+    field: Option<&str>,
+    line: usize,
+    what: &str,
+) -> Result<T, GraphParseError> {
+    match field {
+        Some(field) => field
+            .parse()
+            .map_err(|_| GraphParseError::new(line, format!("invalid {what} `{field}`"))),
+        None => Err(GraphParseError::new(line, format!("missing {what}"))),
+    }
+}
+
+fn add_vertex<V: Ord, E>(graph: &mut Graph<V, E>, v: V) {
+    graph.entry(v).or_default();
+}
+
+fn add_edge<V: Ord + Clone, E: Ord + Copy>(graph: &mut Graph<V, E>, u: V, v: V, weight: E) {
+    add_vertex(graph, v.clone());
+    let edges = graph.entry(u).or_default();
+    match edges.get(&v) {
+        Some(&old) if old <= weight => {}
+        _ => {
+            edges.insert(v, weight);
+        }
+    }
+}
+
+/// Reads a directed graph from an edge list. Edges without a weight get
+/// weight 1.
+pub fn parse_edge_list<V, E>(input: &str) -> Result<Graph<V, E>, GraphParseError>
+where
+    V: FromStr + Ord + Clone,
+    E: FromStr + Ord + Copy + From<u8>,
+{
+    let mut graph = Graph::new();
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let mut fields = text.split_whitespace();
+        let u: V = parse_value(fields.next(), line, "vertex")?;
+        let v: V = match fields.next() {
+            Some(field) => parse_value(Some(field), line, "vertex")?,
+            None => {
+                add_vertex(&mut graph, u);
+                continue;
+            }
+        };
+        let weight: E = match fields.next() {
+            Some(field) => parse_value(Some(field), line, "weight")?,
+            None => E::from(1),
+        };
+        if let Some(field) = fields.next() {
+            return Err(GraphParseError::new(
+                line,
+                format!("unexpected field `{field}`"),
+            ));
+        }
+        add_edge(&mut graph, u, v, weight);
+    }
+    Ok(graph)
+}
+
+/// Writes a graph as an edge list, which can be read back by
+/// `parse_edge_list`.
+pub fn write_edge_list<V: Display + Ord, E: Display>(graph: &Graph<V, E>) -> String {
+    let mut result = String::new();
+    let targets: BTreeSet<&V> = graph.values().flat_map(|edges| edges.keys()).collect();
+    for (u, edges) in graph {
+        if edges.is_empty() && !targets.contains(u) {
+            writeln!(result, "{u}").unwrap();
+        }
+        for (v, weight) in edges {
+            writeln!(result, "{u} {v} {weight}").unwrap();
+        }
+    }
+    result
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DotToken {
+    Id(String),
+    Arrow,
+    Line,
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Equals,
+    Separator,
+}
+
+fn tokenize_dot(input: &str) -> Result<Vec<(DotToken, usize)>, GraphParseError> {
+    let mut tokens = vec![];
+    let chars: Vec<char> = input.chars().collect();
+    let mut line = 1usize;
+    let mut i = 0usize;
+    let is_id_char = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                let start_line = line;
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                if i == chars.len() {
+                    return Err(GraphParseError::new(start_line, "unterminated comment"));
+                }
+                i += 2;
+            }
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' => {
+                let token = match c {
+                    '{' => DotToken::OpenBrace,
+                    '}' => DotToken::CloseBrace,
+                    '[' => DotToken::OpenBracket,
+                    ']' => DotToken::CloseBracket,
+                    '=' => DotToken::Equals,
+                    _ => DotToken::Separator,
+                };
+                tokens.push((token, line));
+                i += 1;
+            }
+            '-' if next == Some('>') => {
+                tokens.push((DotToken::Arrow, line));
+                i += 2;
+            }
+            '-' if next == Some('-') => {
+                tokens.push((DotToken::Line, line));
+                i += 2;
+            }
+            '"' => {
+                let start_line = line;
+                let mut id = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(GraphParseError::new(start_line, "unterminated string"))
+                        }
+                        Some('"') => break,
+                        Some('\\') if chars.get(i + 1) == Some(&'"') => {
+                            id.push('"');
+                            i += 1;
+                        }
+                        Some(&c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            id.push(c);
+                        }
+                    }
+                    i += 1;
+                }
+                tokens.push((DotToken::Id(id), start_line));
+                i += 1;
+            }
+            c if is_id_char(c) || c == '-' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && is_id_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push((DotToken::Id(chars[start..i].iter().collect()), line));
+            }
+            c => {
+                return Err(GraphParseError::new(
+                    line,
+                    format!("unexpected character `{c}`"),
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct DotParser {
+    tokens: Vec<(DotToken, usize)>,
+    position: usize,
+}
+
+impl DotParser {
+    fn peek(&self) -> Option<&DotToken> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+    fn line(&self) -> usize {
+        match self.tokens.get(self.position) {
+            Some(&(_, line)) => line,
+            None => self.tokens.last().map_or(1, |&(_, line)| line),
+        }
+    }
+    fn next(&mut self) -> Option<&DotToken> {
+        self.position += 1;
+        self.tokens.get(self.position - 1).map(|(token, _)| token)
+    }
+    fn expect_id(&mut self) -> Result<(String, usize), GraphParseError> {
+        let line = self.line();
+        match self.next() {
+            Some(DotToken::Id(id)) => Ok((id.clone(), line)),
+            _ => Err(GraphParseError::new(line, "expected an identifier")),
+        }
+    }
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(DotToken::Id(id)) if id.eq_ignore_ascii_case(keyword))
+    }
+    // Returns (name, value, line of value) triples of `[a = b, c = d][e = f]`
+    fn attributes(&mut self) -> Result<Vec<(String, String, usize)>, GraphParseError> {
+        let mut attributes = vec![];
+        while self.peek() == Some(&DotToken::OpenBracket) {
+            self.next();
+            loop {
+                match self.peek() {
+                    Some(DotToken::CloseBracket) => {
+                        self.next();
+                        break;
+                    }
+                    Some(DotToken::Separator) => {
+                        self.next();
+                    }
+                    _ => {
+                        let (name, line) = self.expect_id()?;
+                        if self.peek() == Some(&DotToken::Equals) {
+                            self.next();
+                            let (value, line) = self.expect_id()?;
+                            attributes.push((name, value, line));
+                        } else {
+                            attributes.push((name, String::from("true"), line));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// Reads a graph in DOT format. For undirected graphs (`graph { ... }`) each
+/// edge is added in both directions. Edges without a `weight` attribute get
+/// weight 1.
+pub fn parse_dot<V, E>(input: &str) -> Result<Graph<V, E>, GraphParseError>
+where
+    V: FromStr + Ord + Clone,
+    E: FromStr + Ord + Copy + From<u8>,
+{
+    let mut parser = DotParser {
+        tokens: tokenize_dot(input)?,
+        position: 0,
+    };
+    if parser.is_keyword("strict") {
+        parser.next();
+    }
+    let directed = if parser.is_keyword("digraph") {
+        true
+    } else if parser.is_keyword("graph") {
+        false
+    } else {
+        return Err(GraphParseError::new(
+            parser.line(),
+            "expected `graph` or `digraph`",
+        ));
+    };
+    parser.next();
+    if let Some(DotToken::Id(_)) = parser.peek() {
+        parser.next();
+    }
+    let line = parser.line();
+    if parser.next() != Some(&DotToken::OpenBrace) {
+        return Err(GraphParseError::new(line, "expected `{`"));
+    }
+
+    let mut graph = Graph::new();
+    loop {
+        let line = parser.line();
+        match parser.peek() {
+            None => return Err(GraphParseError::new(line, "expected `}`")),
+            Some(DotToken::CloseBrace) => {
+                parser.next();
+                break;
+            }
+            Some(DotToken::Separator) => {
+                parser.next();
+            }
+            Some(DotToken::Id(_)) if parser.is_keyword("subgraph") => {
+                return Err(GraphParseError::new(line, "subgraphs are not supported"));
+            }
+            Some(DotToken::Id(_))
+                if parser.is_keyword("graph")
+                    || parser.is_keyword("node")
+                    || parser.is_keyword("edge") =>
+            {
+                // Default attributes do not change the structure of the graph
+                parser.next();
+                parser.attributes()?;
+            }
+            Some(DotToken::Id(_)) => {
+                let (id, line) = parser.expect_id()?;
+                if parser.peek() == Some(&DotToken::Equals) {
+                    // A graph attribute
+                    parser.next();
+                    parser.expect_id()?;
+                    continue;
+                }
+                let mut chain = vec![(id, line)];
+                while let Some(&(DotToken::Arrow | DotToken::Line, op_line)) =
+                    parser.tokens.get(parser.position)
+                {
+                    if (parser.peek() == Some(&DotToken::Arrow)) != directed {
+                        return Err(GraphParseError::new(
+                            op_line,
+                            if directed {
+                                "`--` used in a directed graph"
+                            } else {
+                                "`->` used in an undirected graph"
+                            },
+                        ));
+                    }
+                    parser.next();
+                    chain.push(parser.expect_id()?);
+                }
+                let mut weight = E::from(1);
+                for (name, value, line) in parser.attributes()? {
+                    if name == "weight" {
+                        weight = parse_value(Some(&value), line, "weight")?;
+                    }
+                }
+                let mut vertices = vec![];
+                for (id, line) in chain {
+                    vertices.push(parse_value::<V>(Some(&id), line, "vertex")?);
+                }
+                if vertices.len() == 1 {
+                    add_vertex(&mut graph, vertices.pop().unwrap());
+                }
+                for pair in vertices.windows(2) {
+                    add_edge(&mut graph, pair[0].clone(), pair[1].clone(), weight);
+                    if !directed {
+                        add_edge(&mut graph, pair[1].clone(), pair[0].clone(), weight);
+                    }
+                }
+            }
+            Some(_) => return Err(GraphParseError::new(line, "expected a statement")),
+        }
+    }
+    if parser.peek().is_some() {
+        return Err(GraphParseError::new(
+            parser.line(),
+            "unexpected input after the graph",
+        ));
+    }
+    Ok(graph)
+}
+
+fn quote_dot(id: &impl Display) -> String {
+    format!("\"{}\"", id.to_string().replace('"', "\\\""))
+}
+
+/// Writes a graph in DOT format, which can be read back by `parse_dot`. If
+/// `directed` is false, the graph should be symmetric and each edge is
+/// written once.
+pub fn write_dot<V: Display + Ord, E: Display>(graph: &Graph<V, E>, directed: bool) -> String {
+    let mut result = String::from(if directed { "digraph {\n" } else { "graph {\n" });
+    for u in graph.keys() {
+        writeln!(result, "    {};", quote_dot(u)).unwrap();
+    }
+    for (u, edges) in graph {
+        for (v, weight) in edges {
+            if directed {
+                writeln!(
+                    result,
+                    "    {} -> {} [weight={weight}];",
+                    quote_dot(u),
+                    quote_dot(v)
+                )
+                .unwrap();
+            } else if u <= v {
+                writeln!(
+                    result,
+                    "    {} -- {} [weight={weight}];",
+                    quote_dot(u),
+                    quote_dot(v)
+                )
+                .unwrap();
+            }
+        }
+    }
+    result.push_str("}\n");
+    result
+}
+
+// Reads the lines of a DIMACS file, and returns the number of vertices, the
+// arcs and the node designators (`n` lines)
+#[allow(clippy::type_complexity)]
+fn parse_dimacs<E: FromStr>(
+    input: &str,
+    problem: &str,
+) -> Result<(usize, Vec<(usize, usize, E)>, Vec<(usize, String, usize)>), GraphParseError> {
+    let mut header: Option<(usize, usize, usize)> = None;
+    let mut arcs = vec![];
+    let mut designators = vec![];
+    let mut last_line = 1usize;
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        last_line = line;
+        let mut fields = text.split_whitespace();
+        let kind = match fields.next() {
+            None | Some("c") => continue,
+            Some(kind) => kind,
+        };
+        if kind == "p" {
+            if header.is_some() {
+                return Err(GraphParseError::new(line, "duplicate problem line"));
+            }
+            let found: String = parse_value(fields.next(), line, "problem type")?;
+            if found != problem {
+                return Err(GraphParseError::new(
+                    line,
+                    format!("expected problem type `{problem}`, found `{found}`"),
+                ));
+            }
+            let num_vertices = parse_value(fields.next(), line, "number of vertices")?;
+            let num_arcs = parse_value(fields.next(), line, "number of arcs")?;
+            header = Some((num_vertices, num_arcs, line));
+            continue;
+        }
+        let num_vertices = match header {
+            Some((num_vertices, _, _)) => num_vertices,
+            None => return Err(GraphParseError::new(line, "missing problem line")),
+        };
+        let mut vertex = || -> Result<usize, GraphParseError> {
+            let v: usize = parse_value(fields.next(), line, "vertex")?;
+            if v == 0 || v > num_vertices {
+                return Err(GraphParseError::new(
+                    line,
+                    format!("vertex {v} is not between 1 and {num_vertices}"),
+                ));
+            }
+            Ok(v)
+        };
+        match kind {
+            "a" => {
+                let u = vertex()?;
+                let v = vertex()?;
+                let weight = parse_value(fields.next(), line, "arc value")?;
+                arcs.push((u, v, weight));
+            }
+            "n" if problem == "max" => {
+                let v = vertex()?;
+                let designator = parse_value(fields.next(), line, "node designator")?;
+                designators.push((v, designator, line));
+            }
+            _ => {
+                return Err(GraphParseError::new(
+                    line,
+                    format!("unknown line type `{kind}`"),
+                ))
+            }
+        }
+        if let Some(field) = fields.next() {
+            return Err(GraphParseError::new(
+                line,
+                format!("unexpected field `{field}`"),
+            ));
+        }
+    }
+    let (num_vertices, num_arcs, header_line) = match header {
+        Some(header) => header,
+        None => return Err(GraphParseError::new(last_line, "missing problem line")),
+    };
+    if arcs.len() != num_arcs {
+        return Err(GraphParseError::new(
+            header_line,
+            format!("expected {num_arcs} arcs, found {}", arcs.len()),
+        ));
+    }
+    Ok((num_vertices, arcs, designators))
+}
+
+/// Reads a DIMACS shortest path (`p sp`) file.
+pub fn parse_dimacs_shortest_path<E>(input: &str) -> Result<Graph<usize, E>, GraphParseError>
+where
+    E: FromStr + Ord + Copy,
+{
+    let (num_vertices, arcs, _) = parse_dimacs(input, "sp")?;
+    let mut graph = Graph::new();
+    for v in 1..=num_vertices {
+        add_vertex(&mut graph, v);
+    }
+    for (u, v, weight) in arcs {
+        add_edge(&mut graph, u, v, weight);
+    }
+    Ok(graph)
+}
+
+/// Writes a graph with vertices numbered from 1 to n as a DIMACS shortest
+/// path file, where n is the largest vertex, even if it only appears as the
+/// end of an edge. Panics if there is a vertex 0.
+pub fn write_dimacs_shortest_path<E: Display>(graph: &Graph<usize, E>) -> String {
+    let endpoints = graph.values().flat_map(|edges| edges.keys());
+    let num_vertices = graph.keys().chain(endpoints).max().copied().unwrap_or(0);
+    assert!(
+        !graph.contains_key(&0) && graph.values().all(|edges| !edges.contains_key(&0)),
+        "DIMACS vertices are numbered from 1"
+    );
+    let num_arcs: usize = graph.values().map(|edges| edges.len()).sum();
+    let mut result = format!("p sp {num_vertices} {num_arcs}\n");
+    for (u, edges) in graph {
+        for (v, weight) in edges {
+            writeln!(result, "a {u} {v} {weight}").unwrap();
+        }
+    }
+    result
+}
+
+/// Reads a DIMACS max-flow (`p max`) file into a flow network, with the
+/// source and sink given by its `n` lines.
+pub fn parse_dimacs_max_flow<T>(input: &str) -> Result<DinicMaxFlow<T>, GraphParseError>
+where
+    T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord + Default + FromStr,
+{
+    let (num_vertices, arcs, designators) = parse_dimacs::<T>(input, "max")?;
+    let mut source = None;
+    let mut sink = None;
+    for (v, designator, line) in designators {
+        let terminal = match designator.as_str() {
+            "s" => &mut source,
+            "t" => &mut sink,
+            _ => {
+                return Err(GraphParseError::new(
+                    line,
+                    format!("invalid node designator `{designator}`"),
+                ))
+            }
+        };
+        if terminal.is_some() {
+            return Err(GraphParseError::new(
+                line,
+                format!("duplicate node designator `{designator}`"),
+            ));
+        }
+        *terminal = Some(v);
+    }
+    let last_line = input.lines().count().max(1);
+    let source = source.ok_or_else(|| GraphParseError::new(last_line, "missing source"))?;
+    let sink = sink.ok_or_else(|| GraphParseError::new(last_line, "missing sink"))?;
+    let mut flow = DinicMaxFlow::new(source, sink, num_vertices);
+    for (u, v, capacity) in arcs {
+        flow.add_edge(u, v, capacity);
+    }
+    Ok(flow)
+}
+
+/// Writes the edges (and their capacities) of a flow network as a DIMACS
+/// max-flow file.
+pub fn write_dimacs_max_flow<T>(flow: &DinicMaxFlow<T>) -> String
+where
+    T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord + Default + Display,
+{
+    let mut result = format!("p max {} {}\n", flow.num_vertices, flow.num_edges / 2);
+    writeln!(result, "n {} s", flow.source).unwrap();
+    writeln!(result, "n {} t", flow.sink).unwrap();
+    for (u, edges) in flow.adj.iter().enumerate() {
+        // Odd edges are the reverse edges of the residual network
+        for &e in edges.iter().filter(|&&e| e & 1 == 0) {
+            let edge = &flow.edges[e];
+            writeln!(result, "a {} {} {}", u, edge.sink, edge.capacity).unwrap();
+        }
+    }
+    result
+}
+
+/// Reads a DIMACS CNF (`p cnf`) file, and returns the number of variables and
+/// the clauses. A line starting with `%` ends the formula.
+pub fn parse_dimacs_cnf(input: &str) -> Result<(usize, Vec<Vec<i64>>), GraphParseError> {
+    let mut header: Option<(usize, usize, usize)> = None;
+    let mut clauses = vec![];
+    let mut clause = vec![];
+    let mut last_line = 1usize;
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        last_line = line;
+        let mut fields = text.split_whitespace().peekable();
+        match fields.peek() {
+            None | Some(&"c") => continue,
+            Some(field) if field.starts_with('%') => break,
+            Some(&"p") => {
+                fields.next();
+                if header.is_some() {
+                    return Err(GraphParseError::new(line, "duplicate problem line"));
+                }
+                let found: String = parse_value(fields.next(), line, "problem type")?;
+                if found != "cnf" {
+                    return Err(GraphParseError::new(
+                        line,
+                        format!("expected problem type `cnf`, found `{found}`"),
+                    ));
+                }
+                let num_variables = parse_value(fields.next(), line, "number of variables")?;
+                let num_clauses = parse_value(fields.next(), line, "number of clauses")?;
+                header = Some((num_variables, num_clauses, line));
+                continue;
+            }
+            _ => {}
+        }
+        let num_variables = match header {
+            Some((num_variables, _, _)) => num_variables,
+            None => return Err(GraphParseError::new(line, "missing problem line")),
+        };
+        // A clause ends with 0, and can span several lines
+        for field in fields {
+            let literal: i64 = parse_value(Some(field), line, "literal")?;
+            if literal == 0 {
+                clauses.push(std::mem::take(&mut clause));
+            } else if literal.unsigned_abs() as usize > num_variables {
+                return Err(GraphParseError::new(
+                    line,
+                    format!(
+                        "variable {} is not between 1 and {num_variables}",
+                        literal.abs()
+                    ),
+                ));
+            } else {
+                clause.push(literal);
+            }
+        }
+    }
+    let (num_variables, num_clauses, header_line) = match header {
+        Some(header) => header,
+        None => return Err(GraphParseError::new(last_line, "missing problem line")),
+    };
+    if !clause.is_empty() {
+        return Err(GraphParseError::new(last_line, "unterminated clause"));
+    }
+    if clauses.len() != num_clauses {
+        return Err(GraphParseError::new(
+            header_line,
+            format!("expected {num_clauses} clauses, found {}", clauses.len()),
+        ));
+    }
+    Ok((num_variables, clauses))
+}
+
+/// Writes a formula as a DIMACS CNF file.
+pub fn write_dimacs_cnf(num_variables: usize, clauses: &[Vec<i64>]) -> String {
+    let mut result = format!("p cnf {num_variables} {}\n", clauses.len());
+    for clause in clauses {
+        for literal in clause {
+            write!(result, "{literal} ").unwrap();
+        }
+        result.push_str("0\n");
+    }
+    result
+}
+
+/// Converts a graph into a `DirectedGraph` of the `data_structures` module.
+pub fn to_directed_graph<V: Display>(graph: &Graph<V, i32>) -> DirectedGraph {
+    let mut result = DirectedGraph::new();
+    for (u, edges) in graph {
+        let u = u.to_string();
+        result.add_node(&u);
+        for (v, &weight) in edges {
+            result.add_edge((&u, &v.to_string(), weight));
+        }
+    }
+    result
+}
+
+/// Converts a `DirectedGraph` of the `data_structures` module into a graph.
+pub fn from_directed_graph(graph: &DirectedGraph) -> Graph<String, i32> {
+    let mut result = Graph::new();
+    for node in graph.nodes() {
+        add_vertex(&mut result, node.clone());
+    }
+    for (u, v, weight) in graph.edges() {
+        add_edge(&mut result, u.clone(), v.clone(), weight);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra;
+
+    #[test]
+    fn edge_list() {
+        let input = "# a comment\n1 2 7\n2 3\n\n3 1 4\n4\n";
+        let graph: Graph<u32, i64> = parse_edge_list(input).unwrap();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph[&1][&2], 7);
+        assert_eq!(graph[&2][&3], 1);
+        assert!(graph[&4].is_empty());
+        let output = write_edge_list(&graph);
+        assert_eq!(output, "1 2 7\n2 3 1\n3 1 4\n4\n");
+        assert_eq!(parse_edge_list::<u32, i64>(&output), Ok(graph));
+    }
+
+    #[test]
+    fn edge_list_errors() {
+        let error = parse_edge_list::<u32, i64>("1 2\n2 x\n").unwrap_err();
+        assert_eq!(error.line, 2);
+        let error = parse_edge_list::<u32, i64>("1 2 3\n\n2 3 4 5\n").unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.to_string(), "line 3: unexpected field `5`");
+    }
+
+    #[test]
+    fn dot() {
+        let input = r#"
+            /* A small
+               road network */
+            digraph roads {
+                rankdir = LR;
+                node [shape=circle];
+                a -> b [weight=2, color=red];
+                b -> c -> d [weight=3]
+                "a" -> d [weight = 10]; e
+            }
+        "#;
+        let graph: Graph<char, u64> = parse_dot(input).unwrap();
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph[&'a'][&'b'], 2);
+        assert_eq!(graph[&'b'][&'c'], 3);
+        assert_eq!(graph[&'c'][&'d'], 3);
+        assert_eq!(graph[&'a'][&'d'], 10);
+        let distances = dijkstra(&graph, &'a');
+        assert_eq!(distances[&'d'], Some(('c', 8)));
+
+        let output = write_dot(&graph, true);
+        assert_eq!(parse_dot::<char, u64>(&output), Ok(graph));
+    }
+
+    #[test]
+    fn undirected_dot() {
+        let graph: Graph<u32, u32> = parse_dot("graph { 1 -- 2 -- 3; 3 -- 1 [weight=5] }").unwrap();
+        assert_eq!(graph[&2][&1], 1);
+        assert_eq!(graph[&1][&3], 5);
+        let output = write_dot(&graph, false);
+        assert_eq!(output.matches("--").count(), 3);
+        assert_eq!(parse_dot::<u32, u32>(&output), Ok(graph));
+    }
+
+    #[test]
+    fn dot_errors() {
+        let error = parse_dot::<String, u32>("digraph {\n a -> b;\n a -- c;\n}").unwrap_err();
+        assert_eq!(error.line, 3);
+        let error = parse_dot::<String, u32>("digraph {\n a -> b\n [weight=x]\n}").unwrap_err();
+        assert_eq!(error.line, 3);
+        let error = parse_dot::<String, u32>("digraph {\n a -> b;\n\n").unwrap_err();
+        assert_eq!(error, GraphParseError::new(2, "expected `}`"));
+        let error = parse_dot::<u32, u32>("graph {\n 1 -- \"one\"\n}").unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn dimacs_shortest_path() {
+        let input = "c sample\np sp 4 4\na 1 2 5\na 2 3 1\na 1 3 9\na 3 4 2\n";
+        let graph: Graph<usize, u64> = parse_dimacs_shortest_path(input).unwrap();
+        assert_eq!(graph.len(), 4);
+        let distances = dijkstra(&graph, &1);
+        assert_eq!(distances[&4], Some((3, 8)));
+        let output = write_dimacs_shortest_path(&graph);
+        assert_eq!(parse_dimacs_shortest_path::<u64>(&output), Ok(graph));
+
+        let error = parse_dimacs_shortest_path::<u64>("p sp 2 1\na 1 3 5\n").unwrap_err();
+        assert_eq!(error.line, 2);
+        let error = parse_dimacs_shortest_path::<u64>("p sp 2 2\na 1 2 5\n").unwrap_err();
+        assert_eq!(error.line, 1);
+        let error = parse_dimacs_shortest_path::<u64>("a 1 2 5\n").unwrap_err();
+        assert_eq!(error, GraphParseError::new(1, "missing problem line"));
+    }
+
+    #[test]
+    fn dimacs_max_flow() {
+        let input = "p max 4 5\nn 1 s\nn 4 t\na 1 2 3\na 1 3 2\na 2 3 1\na 2 4 2\na 3 4 3\n";
+        let mut flow: DinicMaxFlow<i32> = parse_dimacs_max_flow(input).unwrap();
+        assert_eq!(flow.find_maxflow(i32::MAX), 5);
+
+        let output = write_dimacs_max_flow(&flow);
+        let mut flow: DinicMaxFlow<i32> = parse_dimacs_max_flow(&output).unwrap();
+        assert_eq!(flow.find_maxflow(i32::MAX), 5);
+
+        let error = parse_dimacs_max_flow::<i32>("p max 2 1\nn 1 s\nn 2 x\na 1 2 3\n");
+        assert_eq!(error.err().unwrap().line, 3);
+        let error = parse_dimacs_max_flow::<i32>("p max 2 1\nn 1 s\na 1 2 3\n");
+        assert_eq!(error.err(), Some(GraphParseError::new(3, "missing sink")));
+    }
+
+    #[test]
+    fn directed_graph_conversion() {
+        let graph: Graph<String, i32> = parse_edge_list("a b 5\nb c 10\nc a 7\nd\n").unwrap();
+        let directed = to_directed_graph(&graph);
+        assert_eq!(directed.nodes().len(), 4);
+        assert_eq!(
+            directed.neighbours("a").unwrap(),
+            &vec![(String::from("b"), 5)]
+        );
+        assert_eq!(from_directed_graph(&directed), graph);
+    }
+
+    #[test]
+    fn dimacs_cnf() {
+        let input = "c A small formula\np cnf 3 3\n1 -2 0\n2 3\n-1 0 -3 0\n%\n0\n";
+        let (num_variables, clauses) = parse_dimacs_cnf(input).unwrap();
+        assert_eq!(num_variables, 3);
+        assert_eq!(clauses, vec![vec![1, -2], vec![2, 3, -1], vec![-3]]);
+        let output = write_dimacs_cnf(num_variables, &clauses);
+        assert_eq!(parse_dimacs_cnf(&output), Ok((num_variables, clauses)));
+
+        let error = parse_dimacs_cnf("p cnf 2 1\n1 -3 0\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: variable 3 is not between 1 and 2"
+        );
+        let error = parse_dimacs_cnf("p cnf 2 1\n1 2\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unterminated clause");
+        let error = parse_dimacs_cnf("p cnf 2 2\n1 2 0\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected 2 clauses, found 1");
+        let error = parse_dimacs_cnf("p sp 2 2\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1: expected problem type `cnf`, found `sp`"
+        );
+    }
+
+    #[test]
+    fn empty_inputs() {
+        let graph: Graph<u32, i64> = parse_edge_list("").unwrap();
+        assert!(graph.is_empty());
+        assert_eq!(write_edge_list(&graph), "");
+
+        let graph: Graph<u32, i64> = parse_dot("digraph {}").unwrap();
+        assert!(graph.is_empty());
+        assert_eq!(parse_dot::<u32, i64>(&write_dot(&graph, true)), Ok(graph));
+        let error = parse_dot::<u32, i64>("").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected `graph` or `digraph`");
+
+        let graph: Graph<usize, u64> = parse_dimacs_shortest_path("p sp 0 0\n").unwrap();
+        assert!(graph.is_empty());
+        assert_eq!(write_dimacs_shortest_path(&graph), "p sp 0 0\n");
+        // A network without vertices has no source
+        let error = parse_dimacs_max_flow::<i32>("p max 0 0\n").err();
+        assert_eq!(error, Some(GraphParseError::new(1, "missing source")));
+
+        // An empty formula is satisfiable, an empty clause is not
+        assert_eq!(parse_dimacs_cnf("p cnf 0 0\n"), Ok((0, vec![])));
+        assert_eq!(parse_dimacs_cnf("p cnf 1 1\n0\n"), Ok((1, vec![vec![]])));
+        assert_eq!(write_dimacs_cnf(1, &[vec![]]), "p cnf 1 1\n0\n");
+    }
+
+    #[test]
+    fn dimacs_shortest_path_vertices() {
+        // Vertex 3 is only the end of an edge
+        let mut graph: Graph<usize, u64> = BTreeMap::new();
+        add_edge(&mut graph, 1, 3, 4);
+        let output = write_dimacs_shortest_path(&graph);
+        assert_eq!(output, "p sp 3 1\na 1 3 4\n");
+        graph.insert(2, BTreeMap::new());
+        graph.insert(3, BTreeMap::new());
+        assert_eq!(parse_dimacs_shortest_path::<u64>(&output), Ok(graph));
+    }
+
+    #[test]
+    #[should_panic(expected = "DIMACS vertices are numbered from 1")]
+    fn dimacs_shortest_path_vertex_zero() {
+        let mut graph: Graph<usize, u64> = BTreeMap::new();
+        add_edge(&mut graph, 1, 0, 4);
+        write_dimacs_shortest_path(&graph);
+    }
+}
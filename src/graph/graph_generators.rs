@@ -0,0 +1,455 @@
+/*
+Random and regular graph generators, for tests and benchmarks.
+
+The random generators take a `PCG32`, so the same seed always gives the same
+graph. Unweighted graphs are returned as adjacency lists of vertices numbered
+from 1 to n, where undirected edges appear in the lists of both endpoints,
+like the input of `BiconnectedComponents` or `HeavyLightDecomposition`.
+Weighted graphs are returned as the directed graph type used by `dijkstra`
+and `bellman_ford`, where every vertex has an entry.
+
+None of the generated graphs have self-loops or parallel edges.
+*/
+
+use std::collections::BTreeMap;
+
+use crate::math::PCG32;
+
+type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+// A uniform number in [0, bound)
+fn below(rng: &mut PCG32, bound: usize) -> usize {
+    (rng.get_u64() % bound as u64) as usize
+}
+
+// True with probability `p`
+fn chance(rng: &mut PCG32, p: f64) -> bool {
+    (rng.get_u32() as f64) < p * (u32::MAX as f64 + 1.0)
+}
+
+fn add_undirected_edge(adj: &mut [Vec<usize>], u: usize, v: usize) {
+    if u != v && !adj[u].contains(&v) {
+        adj[u].push(v);
+        adj[v].push(u);
+    }
+}
+
+/// Returns the complete graph on `n` vertices.
+pub fn complete_graph(n: usize) -> Vec<Vec<usize>> {
+    let mut adj = vec![vec![]; n + 1];
+    for (u, edges) in adj.iter_mut().enumerate().skip(1) {
+        edges.extend((1..=n).filter(|&v| v != u));
+    }
+    adj
+}
+
+/// Returns the `rows` x `columns` grid, where the vertex in row r and column
+/// c (from 0) is r * columns + c + 1.
+pub fn grid_graph(rows: usize, columns: usize) -> Vec<Vec<usize>> {
+    let mut adj = vec![vec![]; rows * columns + 1];
+    let vertex = |r: usize, c: usize| r * columns + c + 1;
+    for r in 0..rows {
+        for c in 0..columns {
+            if r + 1 < rows {
+                add_undirected_edge(&mut adj, vertex(r, c), vertex(r + 1, c));
+            }
+            if c + 1 < columns {
+                add_undirected_edge(&mut adj, vertex(r, c), vertex(r, c + 1));
+            }
+        }
+    }
+    adj
+}
+
+/// Returns the `rows` x `columns` torus, that is the grid where the last row
+/// and column are also connected to the first ones.
+pub fn torus_graph(rows: usize, columns: usize) -> Vec<Vec<usize>> {
+    let mut adj = vec![vec![]; rows * columns + 1];
+    let vertex = |r: usize, c: usize| r * columns + c + 1;
+    for r in 0..rows {
+        for c in 0..columns {
+            add_undirected_edge(&mut adj, vertex(r, c), vertex((r + 1) % rows, c));
+            add_undirected_edge(&mut adj, vertex(r, c), vertex(r, (c + 1) % columns));
+        }
+    }
+    adj
+}
+
+/// Returns an Erdős–Rényi random graph G(n, p), where each of the possible
+/// edges is present with probability `p`.
+pub fn erdos_renyi(n: usize, p: f64, rng: &mut PCG32) -> Vec<Vec<usize>> {
+    let mut adj = vec![vec![]; n + 1];
+    for u in 1..=n {
+        for v in u + 1..=n {
+            if chance(rng, p) {
+                add_undirected_edge(&mut adj, u, v);
+            }
+        }
+    }
+    adj
+}
+
+/// Returns a Barabási–Albert random graph on `n` vertices. It starts with a
+/// complete graph on `m` + 1 vertices, then each new vertex is connected to
+/// `m` existing vertices, chosen with a probability proportional to their
+/// degree (preferential attachment). This gives a few vertices with a very
+/// high degree, like in many real-world networks.
+pub fn barabasi_albert(n: usize, m: usize, rng: &mut PCG32) -> Vec<Vec<usize>> {
+    assert!(m >= 1, "each new vertex needs at least one edge");
+    let initial = (m + 1).min(n);
+    let mut adj = complete_graph(initial);
+    adj.resize(n + 1, vec![]);
+    // Each vertex appears once for every edge it has, so that picking a
+    // uniform element picks a vertex proportionally to its degree
+    let mut endpoints: Vec<usize> = (1..=initial)
+        .flat_map(|v| std::iter::repeat_n(v, initial - 1))
+        .collect();
+    for v in initial + 1..=n {
+        let mut targets: Vec<usize> = vec![];
+        while targets.len() < m {
+            let u = endpoints[below(rng, endpoints.len())];
+            if !targets.contains(&u) {
+                targets.push(u);
+            }
+        }
+        for u in targets {
+            add_undirected_edge(&mut adj, u, v);
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+    adj
+}
+
+const REGULAR_ATTEMPTS: usize = 100;
+
+/// Returns a random `d`-regular graph on `n` vertices, where every vertex has
+/// exactly `d` neighbours, or None if there is none (n * d is odd, or d >= n).
+///
+/// Each vertex starts with `d` free "half-edges", and random pairs of them
+/// that make a valid edge are joined. If no valid pair is left before all are
+/// joined, it starts over, and it gives up and returns None after 100
+/// attempts, which is unlikely. Dead ends are frequent in dense graphs, so
+/// when d > (n - 1) / 2 the complement of a random (n - 1 - d)-regular graph
+/// is returned instead.
+pub fn random_regular(n: usize, d: usize, rng: &mut PCG32) -> Option<Vec<Vec<usize>>> {
+    if n.checked_mul(d)? & 1 == 1 || (d >= n && d > 0) {
+        return None;
+    }
+    if d > 0 && 2 * d + 1 > n {
+        let complement = random_regular(n, n - 1 - d, rng)?;
+        return Some(
+            complement
+                .iter()
+                .enumerate()
+                .map(|(u, edges)| {
+                    (1..=n)
+                        .filter(|&v| u > 0 && v != u && !edges.contains(&v))
+                        .collect()
+                })
+                .collect(),
+        );
+    }
+    'attempt: for _ in 0..REGULAR_ATTEMPTS {
+        let mut adj = vec![vec![]; n + 1];
+        let mut points: Vec<usize> = (1..=n).flat_map(|v| std::iter::repeat_n(v, d)).collect();
+        while !points.is_empty() {
+            let valid = |adj: &[Vec<usize>], u: usize, v: usize| u != v && !adj[u].contains(&v);
+            let mut pair = None;
+            for _ in 0..100 {
+                let (i, j) = (below(rng, points.len()), below(rng, points.len()));
+                if valid(&adj, points[i], points[j]) {
+                    pair = Some((i, j));
+                    break;
+                }
+            }
+            if pair.is_none() {
+                // Make sure that there is no valid pair before giving up
+                pair = (0..points.len())
+                    .flat_map(|i| (i + 1..points.len()).map(move |j| (i, j)))
+                    .find(|&(i, j)| valid(&adj, points[i], points[j]));
+            }
+            let (i, j) = match pair {
+                Some(pair) => pair,
+                None => continue 'attempt,
+            };
+            add_undirected_edge(&mut adj, points[i], points[j]);
+            // Remove the larger index first, so that the other one is kept
+            points.swap_remove(i.max(j));
+            points.swap_remove(i.min(j));
+        }
+        return Some(adj);
+    }
+    None
+}
+
+/// Returns a random directed acyclic graph on `n` vertices. The vertices are
+/// put in a random order, and each edge going forward in this order is
+/// present with probability `p`.
+pub fn random_dag(n: usize, p: f64, rng: &mut PCG32) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (1..=n).collect();
+    // Fisher–Yates shuffle
+    for i in (1..n).rev() {
+        order.swap(i, below(rng, i + 1));
+    }
+    let mut adj = vec![vec![]; n + 1];
+    for i in 0..n {
+        for j in i + 1..n {
+            if chance(rng, p) {
+                adj[order[i]].push(order[j]);
+            }
+        }
+    }
+    adj
+}
+
+/// Returns a random directed graph on `n` vertices, where each edge is present
+/// with probability `p`, with a uniform weight in [0, max_weight].
+pub fn random_weighted_graph(
+    n: usize,
+    p: f64,
+    max_weight: u32,
+    rng: &mut PCG32,
+) -> Graph<usize, i64> {
+    let mut graph: Graph<usize, i64> = (1..=n).map(|v| (v, BTreeMap::new())).collect();
+    for u in 1..=n {
+        for v in (1..=n).filter(|&v| v != u) {
+            if chance(rng, p) {
+                let weight = below(rng, max_weight as usize + 1) as i64;
+                graph.get_mut(&u).unwrap().insert(v, weight);
+            }
+        }
+    }
+    graph
+}
+
+/// Returns a random weighted graph like `random_weighted_graph`, with a
+/// planted cycle of negative total weight going through vertex 1. The cycle
+/// is also returned, as the list of its vertices starting with 1.
+pub fn random_graph_with_negative_cycle(
+    n: usize,
+    p: f64,
+    max_weight: u32,
+    rng: &mut PCG32,
+) -> (Graph<usize, i64>, Vec<usize>) {
+    assert!(n >= 2, "a cycle needs at least two vertices");
+    let mut graph = random_weighted_graph(n, p, max_weight, rng);
+    let mut others: Vec<usize> = (2..=n).collect();
+    for i in (1..others.len()).rev() {
+        others.swap(i, below(rng, i + 1));
+    }
+    let length = 2 + below(rng, n - 1);
+    let mut cycle = vec![1];
+    cycle.extend_from_slice(&others[..length - 1]);
+    // Random weights, then the last edge makes the total weight negative
+    let mut total = 0;
+    for i in 0..length {
+        let (u, v) = (cycle[i], cycle[(i + 1) % length]);
+        let weight = if i + 1 < length {
+            below(rng, 2 * max_weight as usize + 1) as i64 - max_weight as i64
+        } else {
+            -total - 1 - below(rng, max_weight as usize + 1) as i64
+        };
+        total += weight;
+        graph.get_mut(&u).unwrap().insert(v, weight);
+    }
+    (graph, cycle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{bellman_ford, dijkstra, topological_sort, DinicMaxFlow};
+
+    fn num_edges(adj: &[Vec<usize>]) -> usize {
+        adj.iter().map(|edges| edges.len()).sum::<usize>() / 2
+    }
+
+    fn is_simple_undirected(adj: &[Vec<usize>]) -> bool {
+        adj.iter().enumerate().all(|(u, edges)| {
+            let mut sorted = edges.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            sorted.len() == edges.len() && edges.iter().all(|&v| v != u && adj[v].contains(&u))
+        })
+    }
+
+    #[test]
+    fn regular_structures() {
+        let complete = complete_graph(5);
+        assert_eq!(num_edges(&complete), 10);
+        assert!(is_simple_undirected(&complete));
+
+        let grid = grid_graph(3, 4);
+        assert_eq!(num_edges(&grid), 3 * 3 + 2 * 4);
+        assert_eq!(grid[1], vec![5, 2]);
+        assert!(is_simple_undirected(&grid));
+
+        let torus = torus_graph(3, 4);
+        assert_eq!(num_edges(&torus), 2 * 12);
+        assert!(torus.iter().skip(1).all(|edges| edges.len() == 4));
+        assert!(is_simple_undirected(&torus_graph(2, 5)));
+        assert!(is_simple_undirected(&torus_graph(1, 1)));
+    }
+
+    #[test]
+    fn random_structures() {
+        let mut rng = PCG32::new_default(42);
+        let graph = erdos_renyi(100, 0.1, &mut rng);
+        assert!(is_simple_undirected(&graph));
+        // The expected number of edges is 495
+        assert!((400..600).contains(&num_edges(&graph)));
+        assert_eq!(erdos_renyi(10, 0.0, &mut rng), vec![vec![]; 11]);
+        assert_eq!(erdos_renyi(10, 1.0, &mut rng).len(), 11);
+        assert_eq!(num_edges(&erdos_renyi(10, 1.0, &mut rng)), 45);
+
+        let graph = barabasi_albert(200, 3, &mut rng);
+        assert!(is_simple_undirected(&graph));
+        assert_eq!(num_edges(&graph), 6 + 3 * 196);
+        assert!(graph.iter().skip(1).all(|edges| edges.len() >= 3));
+
+        for (n, d) in [
+            (10, 3),
+            (7, 4),
+            (20, 0),
+            (50, 5),
+            (6, 5),
+            (40, 38),
+            (100, 97),
+        ] {
+            let graph = random_regular(n, d, &mut rng).unwrap();
+            assert!(is_simple_undirected(&graph));
+            assert!(graph.iter().skip(1).all(|edges| edges.len() == d));
+        }
+        assert_eq!(random_regular(7, 3, &mut rng), None);
+        assert_eq!(random_regular(4, 4, &mut rng), None);
+        assert_eq!(random_regular(usize::MAX, 3, &mut rng), None);
+
+        let dag = random_dag(30, 0.3, &mut rng);
+        let dag: BTreeMap<usize, Vec<(usize, ())>> = (1..=30)
+            .map(|u| (u, dag[u].iter().map(|&v| (v, ())).collect()))
+            .collect();
+        // Kahn's algorithm only sorts all the vertices if there is no cycle
+        assert_eq!(topological_sort(&dag).len(), 30);
+    }
+
+    #[test]
+    fn edge_cases() {
+        let mut rng = PCG32::new_default(0);
+        // No vertex at all
+        assert_eq!(complete_graph(0), vec![vec![]]);
+        assert_eq!(grid_graph(0, 5), vec![vec![]]);
+        assert_eq!(erdos_renyi(0, 0.5, &mut rng), vec![vec![]]);
+        assert_eq!(random_regular(0, 0, &mut rng), Some(vec![vec![]]));
+        assert_eq!(random_dag(0, 0.5, &mut rng), vec![vec![]]);
+        assert!(random_weighted_graph(0, 0.5, 3, &mut rng).is_empty());
+        // A single vertex
+        assert_eq!(complete_graph(1), vec![vec![], vec![]]);
+        assert_eq!(barabasi_albert(1, 2, &mut rng), vec![vec![], vec![]]);
+        assert_eq!(random_regular(1, 0, &mut rng), Some(vec![vec![], vec![]]));
+        assert_eq!(random_regular(1, 1, &mut rng), None);
+        // The only 2-regular graph on 3 vertices is the triangle
+        assert_eq!(
+            random_regular(3, 2, &mut rng).map(|adj| adj.iter().map(Vec::len).sum::<usize>()),
+            Some(6)
+        );
+        // Weights of 0 only
+        let graph = random_weighted_graph(5, 1.0, 0, &mut rng);
+        assert!(graph
+            .values()
+            .flat_map(|edges| edges.values())
+            .all(|&w| w == 0));
+        // The planted cycle on two vertices is 1 -> 2 -> 1
+        let (graph, cycle) = random_graph_with_negative_cycle(2, 0.0, 0, &mut rng);
+        assert_eq!(cycle, vec![1, 2]);
+        assert!(graph[&1][&2] + graph[&2][&1] < 0);
+    }
+
+    #[test]
+    fn same_seed_same_graph() {
+        let graph = erdos_renyi(30, 0.2, &mut PCG32::new_default(7));
+        assert_eq!(graph, erdos_renyi(30, 0.2, &mut PCG32::new_default(7)));
+        assert_ne!(graph, erdos_renyi(30, 0.2, &mut PCG32::new_default(8)));
+    }
+
+    #[test]
+    fn dijkstra_matches_bellman_ford() {
+        let mut rng = PCG32::new_default(2024);
+        for _ in 0..30 {
+            let n = 2 + below(&mut rng, 30);
+            let graph = random_weighted_graph(n, 0.2, 20, &mut rng);
+            let start = 1 + below(&mut rng, n);
+            let expected = bellman_ford(&graph, &start).unwrap();
+            let actual = dijkstra(&graph, &start);
+            // The predecessors can differ between shortest paths
+            let distances = |result: &BTreeMap<usize, Option<(usize, i64)>>| {
+                result
+                    .iter()
+                    .map(|(&v, entry)| (v, entry.map_or(0, |(_, d)| d)))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(distances(&actual), distances(&expected));
+        }
+    }
+
+    #[test]
+    fn bellman_ford_finds_negative_cycles() {
+        let mut rng = PCG32::new_default(99);
+        for _ in 0..30 {
+            let n = 2 + below(&mut rng, 20);
+            let (graph, cycle) = random_graph_with_negative_cycle(n, 0.2, 20, &mut rng);
+            let total: i64 = (0..cycle.len())
+                .map(|i| graph[&cycle[i]][&cycle[(i + 1) % cycle.len()]])
+                .sum();
+            assert!(total < 0);
+            assert_eq!(bellman_ford(&graph, &1), None);
+        }
+    }
+
+    #[test]
+    fn max_flow_matches_min_cut() {
+        let mut rng = PCG32::new_default(31337);
+        for _ in 0..30 {
+            let n = 2 + below(&mut rng, 8);
+            let graph = random_weighted_graph(n, 0.4, 10, &mut rng);
+            let mut flow = DinicMaxFlow::new(1, n, n);
+            for (&u, edges) in graph.iter() {
+                for (&v, &capacity) in edges.iter() {
+                    flow.add_edge(u, v, capacity);
+                }
+            }
+            // Try every cut with the source on one side and the sink on the
+            // other one
+            let min_cut = (0..1usize << n)
+                .filter(|&mask| mask & 1 == 1 && mask >> (n - 1) & 1 == 0)
+                .map(|mask| {
+                    let side = |v: usize| mask >> (v - 1) & 1 == 1;
+                    graph
+                        .iter()
+                        .flat_map(|(&u, edges)| edges.iter().map(move |(&v, &c)| (u, v, c)))
+                        .filter(|&(u, v, _)| side(u) && !side(v))
+                        .map(|(_, _, c)| c)
+                        .sum::<i64>()
+                })
+                .min()
+                .unwrap();
+            assert_eq!(flow.find_maxflow(i64::MAX), min_cut);
+            // The sink is reachable iff some flow can get to it, through
+            // edges of positive capacity
+            let positive: Graph<usize, i64> = graph
+                .iter()
+                .map(|(&u, edges)| {
+                    (
+                        u,
+                        edges
+                            .iter()
+                            .filter(|(_, &c)| c > 0)
+                            .map(|(&v, &c)| (v, c))
+                            .collect(),
+                    )
+                })
+                .collect();
+            assert_eq!(dijkstra(&positive, &1).contains_key(&n), min_cut > 0);
+        }
+    }
+}
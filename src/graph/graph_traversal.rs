@@ -0,0 +1,416 @@
+/*
+Iterative Breadth First Search and Depth First Search with visitors:
+Instead of looking for a single target, the traversals report what they see
+to a visitor, which can stop the traversal early by returning
+`ControlFlow::Break(())` from any callback, and the returned forest records
+in `stopped` whether it did. All the callbacks have empty default
+implementations, so a visitor only implements the ones it needs.
+
+The Depth First Search uses an explicit stack, so it does not overflow the
+call stack on deep graphs. It assigns each vertex a discover and a finish
+time from a single clock (as in CLRS), and classifies every edge of a
+directed graph as a tree, back, forward or cross edge.
+
+The Breadth First Search can start from multiple sources at once, in which
+case the distance of a vertex is the distance from its closest source.
+`zero_one_breadth_first_search` finds shortest paths in graphs whose edge
+weights are all 0 or 1 in O(n + m), using a deque instead of a priority queue.
+
+We assume that graph is represented using (compressed) adjacency matrix
+and its vertices are numbered from 1 to n. If this is not the case, one
+can use `src/graph/graph_enumeration.rs` to convert their graph.
+*/
+
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+
+type Adj = [Vec<usize>];
+
+pub trait DepthFirstVisitor {
+    fn discover_vertex(&mut self, _v: usize, _time: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    fn finish_vertex(&mut self, _v: usize, _time: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    // `v` is discovered through this edge
+    fn tree_edge(&mut self, _u: usize, _v: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    // `v` is an ancestor of `u` which is not finished yet, so there is a cycle
+    fn back_edge(&mut self, _u: usize, _v: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    // `v` is an already finished descendant of `u`
+    fn forward_edge(&mut self, _u: usize, _v: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    // `v` is already finished and is not a descendant of `u`
+    fn cross_edge(&mut self, _u: usize, _v: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+// Only records the discover and finish times
+impl DepthFirstVisitor for () {}
+
+pub trait BreadthFirstVisitor {
+    fn discover_vertex(&mut self, _v: usize, _distance: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    // All the edges going out of `v` have been examined
+    fn finish_vertex(&mut self, _v: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    // `v` is discovered through this edge
+    fn tree_edge(&mut self, _u: usize, _v: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    // `v` has already been discovered
+    fn non_tree_edge(&mut self, _u: usize, _v: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+// Only records the distances and parents
+impl BreadthFirstVisitor for () {}
+
+pub struct DepthFirstForest {
+    // The time each vertex was discovered, or 0 if it was not visited
+    pub discover_time: Vec<usize>,
+    // The time all the descendants of each vertex were finished, or 0 if it
+    // was not finished
+    pub finish_time: Vec<usize>,
+    // The parent of each vertex in the DFS forest, 0 for roots
+    pub parent: Vec<usize>,
+    // Whether the visitor stopped the traversal early
+    pub stopped: bool,
+}
+
+pub struct BreadthFirstForest {
+    // The distance of each vertex from the closest source
+    pub distance: Vec<Option<usize>>,
+    // The parent of each vertex in the BFS forest, 0 for sources
+    pub parent: Vec<usize>,
+    // Whether the visitor stopped the traversal early
+    pub stopped: bool,
+}
+
+/// Runs Depth First Search from each of the `sources` (in order) that has not
+/// been visited yet. To visit the whole graph, pass all of its vertices.
+pub fn depth_first_traversal<T: DepthFirstVisitor>(
+    adj: &Adj,
+    sources: &[usize],
+    visitor: &mut T,
+) -> DepthFirstForest {
+    let mut forest = DepthFirstForest {
+        discover_time: vec![0; adj.len()],
+        finish_time: vec![0; adj.len()],
+        parent: vec![0; adj.len()],
+        stopped: false,
+    };
+    forest.stopped = depth_first_visit(adj, sources, visitor, &mut forest).is_break();
+    forest
+}
+
+fn depth_first_visit<T: DepthFirstVisitor>(
+    adj: &Adj,
+    sources: &[usize],
+    visitor: &mut T,
+    forest: &mut DepthFirstForest,
+) -> ControlFlow<()> {
+    let mut time = 0usize;
+    // The vertices on the current path, and the index of their next edge
+    let mut stack: Vec<(usize, usize)> = vec![];
+    for &source in sources {
+        if forest.discover_time[source] != 0 {
+            continue;
+        }
+        time += 1;
+        forest.discover_time[source] = time;
+        visitor.discover_vertex(source, time)?;
+        stack.push((source, 0));
+
+        while let Some((v, next_edge)) = stack.last_mut() {
+            let v = *v;
+            if *next_edge == adj[v].len() {
+                stack.pop();
+                time += 1;
+                forest.finish_time[v] = time;
+                visitor.finish_vertex(v, time)?;
+                continue;
+            }
+            let u = adj[v][*next_edge];
+            *next_edge += 1;
+            if forest.discover_time[u] == 0 {
+                visitor.tree_edge(v, u)?;
+                forest.parent[u] = v;
+                time += 1;
+                forest.discover_time[u] = time;
+                visitor.discover_vertex(u, time)?;
+                stack.push((u, 0));
+            } else if forest.finish_time[u] == 0 {
+                visitor.back_edge(v, u)?;
+            } else if forest.discover_time[v] < forest.discover_time[u] {
+                visitor.forward_edge(v, u)?;
+            } else {
+                visitor.cross_edge(v, u)?;
+            }
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// Runs Breadth First Search starting from all the `sources` at once.
+pub fn breadth_first_traversal<T: BreadthFirstVisitor>(
+    adj: &Adj,
+    sources: &[usize],
+    visitor: &mut T,
+) -> BreadthFirstForest {
+    let mut forest = BreadthFirstForest {
+        distance: vec![None; adj.len()],
+        parent: vec![0; adj.len()],
+        stopped: false,
+    };
+    forest.stopped = breadth_first_visit(adj, sources, visitor, &mut forest).is_break();
+    forest
+}
+
+fn breadth_first_visit<T: BreadthFirstVisitor>(
+    adj: &Adj,
+    sources: &[usize],
+    visitor: &mut T,
+    forest: &mut BreadthFirstForest,
+) -> ControlFlow<()> {
+    let mut queue = VecDeque::new();
+    for &source in sources {
+        if forest.distance[source].is_none() {
+            forest.distance[source] = Some(0);
+            visitor.discover_vertex(source, 0)?;
+            queue.push_back(source);
+        }
+    }
+    while let Some(v) = queue.pop_front() {
+        let distance = forest.distance[v].unwrap() + 1;
+        for &u in adj[v].iter() {
+            if forest.distance[u].is_some() {
+                visitor.non_tree_edge(v, u)?;
+                continue;
+            }
+            visitor.tree_edge(v, u)?;
+            forest.distance[u] = Some(distance);
+            forest.parent[u] = v;
+            visitor.discover_vertex(u, distance)?;
+            queue.push_back(u);
+        }
+        visitor.finish_vertex(v)?;
+    }
+    ControlFlow::Continue(())
+}
+
+/// Returns the distance of each vertex from the closest source, where
+/// `adj[v]` contains the (destination, weight) pairs of the edges leaving
+/// `v`, or None if a weight is neither 0 nor 1.
+pub fn zero_one_breadth_first_search(
+    adj: &[Vec<(usize, usize)>],
+    sources: &[usize],
+) -> Option<Vec<Option<usize>>> {
+    if adj.iter().flatten().any(|&(_, weight)| weight > 1) {
+        return None;
+    }
+    let mut distance: Vec<Option<usize>> = vec![None; adj.len()];
+    let mut deque = VecDeque::new();
+    for &source in sources {
+        distance[source] = Some(0);
+        deque.push_back(source);
+    }
+    // A vertex may be in the deque more than once, but the first time it is
+    // popped its distance is final
+    let mut done = vec![false; adj.len()];
+    while let Some(v) = deque.pop_front() {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        let dist_v = distance[v].unwrap();
+        for &(u, weight) in adj[v].iter() {
+            let dist_u = dist_v + weight;
+            if distance[u].is_none_or(|old| dist_u < old) {
+                distance[u] = Some(dist_u);
+                if weight == 0 {
+                    deque.push_front(u);
+                } else {
+                    deque.push_back(u);
+                }
+            }
+        }
+    }
+    Some(distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct EdgeRecorder {
+        discovered: Vec<usize>,
+        finished: Vec<usize>,
+        tree: Vec<(usize, usize)>,
+        back: Vec<(usize, usize)>,
+        forward: Vec<(usize, usize)>,
+        cross: Vec<(usize, usize)>,
+    }
+
+    impl DepthFirstVisitor for EdgeRecorder {
+        fn discover_vertex(&mut self, v: usize, _time: usize) -> ControlFlow<()> {
+            self.discovered.push(v);
+            ControlFlow::Continue(())
+        }
+        fn finish_vertex(&mut self, v: usize, _time: usize) -> ControlFlow<()> {
+            self.finished.push(v);
+            ControlFlow::Continue(())
+        }
+        fn tree_edge(&mut self, u: usize, v: usize) -> ControlFlow<()> {
+            self.tree.push((u, v));
+            ControlFlow::Continue(())
+        }
+        fn back_edge(&mut self, u: usize, v: usize) -> ControlFlow<()> {
+            self.back.push((u, v));
+            ControlFlow::Continue(())
+        }
+        fn forward_edge(&mut self, u: usize, v: usize) -> ControlFlow<()> {
+            self.forward.push((u, v));
+            ControlFlow::Continue(())
+        }
+        fn cross_edge(&mut self, u: usize, v: usize) -> ControlFlow<()> {
+            self.cross.push((u, v));
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn edge_classification() {
+        let adj = vec![vec![], vec![2, 3, 4], vec![4], vec![4], vec![1, 2], vec![3]];
+        let mut recorder = EdgeRecorder::default();
+        let forest = depth_first_traversal(&adj, &[1, 2, 3, 4, 5], &mut recorder);
+        assert_eq!(recorder.discovered, vec![1, 2, 4, 3, 5]);
+        assert_eq!(recorder.finished, vec![4, 2, 3, 1, 5]);
+        assert_eq!(recorder.tree, vec![(1, 2), (2, 4), (1, 3)]);
+        assert_eq!(recorder.back, vec![(4, 1), (4, 2)]);
+        assert_eq!(recorder.forward, vec![(1, 4)]);
+        assert_eq!(recorder.cross, vec![(3, 4), (5, 3)]);
+        assert_eq!(forest.discover_time, vec![0, 1, 2, 6, 3, 9]);
+        assert_eq!(forest.finish_time, vec![0, 8, 5, 7, 4, 10]);
+        assert_eq!(forest.parent, vec![0, 0, 1, 1, 2, 0]);
+    }
+
+    #[test]
+    fn early_termination() {
+        struct FindTarget(usize);
+        impl DepthFirstVisitor for FindTarget {
+            fn discover_vertex(&mut self, v: usize, _time: usize) -> ControlFlow<()> {
+                if v == self.0 {
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            }
+        }
+        let adj = vec![vec![], vec![2, 3], vec![4], vec![], vec![]];
+        let forest = depth_first_traversal(&adj, &[1], &mut FindTarget(4));
+        assert!(forest.stopped);
+        // The path to the target is still on the stack
+        assert_eq!(forest.parent[4], 2);
+        assert_eq!(forest.finish_time[4], 0);
+        assert_eq!(forest.discover_time[3], 0);
+    }
+
+    #[test]
+    fn deep_path() {
+        let n = 200_000;
+        let mut adj: Vec<Vec<usize>> = (0..=n).map(|v| vec![v + 1]).collect();
+        adj[0].clear();
+        adj[n].clear();
+        let forest = depth_first_traversal(&adj, &[1], &mut ());
+        assert!(!forest.stopped);
+        assert_eq!(forest.discover_time[n], n);
+        assert_eq!(forest.finish_time[1], 2 * n);
+    }
+
+    #[test]
+    fn multi_source_breadth_first() {
+        // A path 1 - 2 - 3 - 4 - 5 - 6 with sources on both ends
+        let mut adj = vec![vec![]; 7];
+        for v in 1..6 {
+            adj[v].push(v + 1);
+            adj[v + 1].push(v);
+        }
+        let forest = breadth_first_traversal(&adj, &[1, 6], &mut ());
+        assert_eq!(
+            forest.distance,
+            vec![None, Some(0), Some(1), Some(2), Some(2), Some(1), Some(0)]
+        );
+        assert_eq!(forest.parent, vec![0, 0, 1, 2, 5, 6, 0]);
+    }
+
+    #[test]
+    fn breadth_first_visitor() {
+        struct Recorder(Vec<(usize, usize)>);
+        impl BreadthFirstVisitor for Recorder {
+            fn discover_vertex(&mut self, v: usize, distance: usize) -> ControlFlow<()> {
+                self.0.push((v, distance));
+                if v == 4 {
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            }
+        }
+        let adj = vec![vec![], vec![2, 3], vec![4], vec![5], vec![], vec![]];
+        let mut recorder = Recorder(vec![]);
+        let forest = breadth_first_traversal(&adj, &[1], &mut recorder);
+        assert!(forest.stopped);
+        assert_eq!(recorder.0, vec![(1, 0), (2, 1), (3, 1), (4, 2)]);
+        assert_eq!(forest.distance[5], None);
+        // Vertex 4 cannot be reached from 3, so the traversal completes
+        let mut recorder = Recorder(vec![]);
+        let forest = breadth_first_traversal(&adj, &[3], &mut recorder);
+        assert!(!forest.stopped);
+        assert_eq!(recorder.0, vec![(3, 0), (5, 1)]);
+    }
+
+    #[test]
+    fn zero_one_weights() {
+        let adj = vec![
+            vec![],
+            vec![(2, 1), (3, 0)],
+            vec![(4, 1)],
+            vec![(2, 0), (4, 1)],
+            vec![],
+            vec![(1, 1)],
+        ];
+        assert_eq!(
+            zero_one_breadth_first_search(&adj, &[1]),
+            Some(vec![None, Some(0), Some(0), Some(0), Some(1), None])
+        );
+        // A weight of 2, even on an edge that is never reached
+        let adj = vec![vec![], vec![(2, 1)], vec![], vec![(1, 2)]];
+        assert_eq!(zero_one_breadth_first_search(&adj, &[1]), None);
+        assert_eq!(zero_one_breadth_first_search(&[], &[]), Some(vec![]));
+    }
+
+    #[test]
+    fn edge_cases() {
+        // No source, and a single vertex with a self-loop
+        let adj = vec![vec![], vec![1]];
+        let forest = depth_first_traversal(&adj, &[], &mut ());
+        assert_eq!(forest.discover_time, vec![0, 0]);
+        assert!(!forest.stopped);
+        let mut recorder = EdgeRecorder::default();
+        let forest = depth_first_traversal(&adj, &[1, 1], &mut recorder);
+        assert_eq!(recorder.back, vec![(1, 1)]);
+        assert_eq!((forest.discover_time[1], forest.finish_time[1]), (1, 2));
+        let forest = breadth_first_traversal(&adj, &[1], &mut ());
+        assert_eq!(forest.distance, vec![None, Some(0)]);
+        assert!(!forest.stopped);
+    }
+}
@@ -0,0 +1,287 @@
+/*
+Hamiltonian paths and cycles using dynamic programming over subsets:
+A Hamiltonian path visits every vertex of the graph exactly once, and a
+Hamiltonian cycle is such a path with an edge from its last vertex back to
+its first one. Finding them is NP-hard, but for small graphs (up to about 20
+vertices) the Held-Karp algorithm solves it in O(2^n * n^2) time and
+O(2^n * n) memory: best[mask][v] is the length of the shortest path that
+visits exactly the vertices of `mask` and ends in `v`.
+
+Since the algorithm finds the shortest such cycle, it also solves the
+travelling salesman problem.
+
+We assume that graph is represented using (compressed) adjacency matrix and
+its vertices are numbered from 1 to n. The graphs are directed, so for
+undirected graphs each edge should appear in the lists of both of its
+endpoints. If this is not the case, one can use
+`src/graph/graph_enumeration.rs` to convert their graph.
+*/
+
+use std::ops::Add;
+
+// Beyond this, the O(2^n * n) table does not fit in memory
+const MAX_VERTICES: usize = 20;
+
+/// Returns the vertices of a Hamiltonian path of the graph, if there is any.
+/// Panics if the graph has more than 20 vertices.
+pub fn hamiltonian_path(adj: &[Vec<usize>]) -> Option<Vec<usize>> {
+    held_karp(&unit_weights(adj), false).map(|(_, path)| path)
+}
+
+/// Returns the vertices of a Hamiltonian cycle of the graph, if there is any.
+/// The cycle starts at vertex 1, which is not repeated at the end. The cycle
+/// needs an edge back to vertex 1, so a graph with a single vertex only has
+/// one if it has a self-loop, and the empty graph has none. Panics if the
+/// graph has more than 20 vertices.
+pub fn hamiltonian_cycle(adj: &[Vec<usize>]) -> Option<Vec<usize>> {
+    held_karp(&unit_weights(adj), true).map(|(_, cycle)| cycle)
+}
+
+/// Returns the length and the vertices of the shortest Hamiltonian cycle
+/// (the optimal travelling salesman tour) of the weighted graph, where
+/// `adj[v]` contains the (destination, weight) pairs of the edges leaving
+/// `v`. The cycle starts at vertex 1, which is not repeated at the end.
+/// We assume that E::default() gives "zero" length. Panics if the graph has
+/// more than 20 vertices.
+pub fn travelling_salesman<E: Ord + Copy + Add<Output = E> + Default>(
+    adj: &[Vec<(usize, E)>],
+) -> Option<(E, Vec<usize>)> {
+    let num_vertices = num_vertices(adj);
+    let mut weight = vec![vec![None; num_vertices]; num_vertices];
+    for v in 1..adj.len() {
+        for &(u, w) in adj[v].iter() {
+            // Only the lightest one of parallel edges matters
+            let entry: &mut Option<E> = &mut weight[v - 1][u - 1];
+            if entry.is_none_or(|old| w < old) {
+                *entry = Some(w);
+            }
+        }
+    }
+    held_karp(&weight, true)
+}
+
+// The number of vertices, after checking that Held-Karp can handle them
+fn num_vertices<T>(adj: &[Vec<T>]) -> usize {
+    let n = adj.len().saturating_sub(1);
+    assert!(n <= MAX_VERTICES, "the graph is too large");
+    n
+}
+
+fn unit_weights(adj: &[Vec<usize>]) -> Vec<Vec<Option<usize>>> {
+    let num_vertices = num_vertices(adj);
+    let mut weight = vec![vec![None; num_vertices]; num_vertices];
+    for v in 1..adj.len() {
+        for &u in adj[v].iter() {
+            weight[v - 1][u - 1] = Some(1);
+        }
+    }
+    weight
+}
+
+// Works on a (0-indexed) weight matrix and returns 1-indexed vertices
+fn held_karp<E: Ord + Copy + Add<Output = E> + Default>(
+    weight: &[Vec<Option<E>>],
+    cycle: bool,
+) -> Option<(E, Vec<usize>)> {
+    let n = weight.len();
+    if n == 0 {
+        return None;
+    }
+    let full = (1usize << n) - 1;
+    let mut best: Vec<Option<E>> = vec![None; (full + 1) * n];
+    let mut previous = vec![usize::MAX; (full + 1) * n];
+    if cycle {
+        // A cycle can be rotated to start at any vertex, so fix the first one
+        best[n] = Some(E::default());
+    } else {
+        for v in 0..n {
+            best[(1 << v) * n + v] = Some(E::default());
+        }
+    }
+
+    for mask in 1..=full {
+        for v in 0..n {
+            let dist = match best[mask * n + v] {
+                Some(dist) => dist,
+                None => continue,
+            };
+            for (u, &w) in weight[v].iter().enumerate() {
+                if mask & (1 << u) != 0 {
+                    continue;
+                }
+                if let Some(w) = w {
+                    let next = (mask | (1 << u)) * n + u;
+                    if best[next].is_none_or(|old| dist + w < old) {
+                        best[next] = Some(dist + w);
+                        previous[next] = v;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut answer: Option<(E, usize)> = None;
+    for v in 0..n {
+        let total = match (best[full * n + v], cycle) {
+            (Some(dist), false) => Some(dist),
+            (Some(dist), true) => weight[v][0].map(|w| dist + w),
+            (None, _) => None,
+        };
+        if let Some(total) = total {
+            if answer.is_none_or(|(old, _)| total < old) {
+                answer = Some((total, v));
+            }
+        }
+    }
+
+    let (total, mut v) = answer?;
+    let mut mask = full;
+    let mut path = Vec::with_capacity(n);
+    loop {
+        path.push(v + 1);
+        let prev = previous[mask * n + v];
+        if prev == usize::MAX {
+            break;
+        }
+        mask ^= 1 << v;
+        v = prev;
+    }
+    path.reverse();
+    Some((total, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undirected(num_vertices: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+        let mut adj = vec![vec![]; num_vertices + 1];
+        for &(u, v) in edges {
+            adj[u].push(v);
+            adj[v].push(u);
+        }
+        adj
+    }
+
+    fn is_path(adj: &[Vec<usize>], path: &[usize]) -> bool {
+        let mut seen = vec![false; adj.len()];
+        for &v in path {
+            if seen[v] {
+                return false;
+            }
+            seen[v] = true;
+        }
+        path.len() == adj.len() - 1 && path.windows(2).all(|w| adj[w[0]].contains(&w[1]))
+    }
+
+    #[test]
+    fn path_graph() {
+        let adj = undirected(4, &[(1, 3), (3, 2), (2, 4)]);
+        let path = hamiltonian_path(&adj).unwrap();
+        assert!(is_path(&adj, &path));
+        assert_eq!(hamiltonian_cycle(&adj), None);
+    }
+
+    #[test]
+    fn star_has_no_path() {
+        let adj = undirected(4, &[(1, 2), (1, 3), (1, 4)]);
+        assert_eq!(hamiltonian_path(&adj), None);
+    }
+
+    #[test]
+    fn directed_cycle() {
+        let adj = vec![vec![], vec![3], vec![1], vec![4], vec![2]];
+        assert_eq!(hamiltonian_cycle(&adj), Some(vec![1, 3, 4, 2]));
+        let path = hamiltonian_path(&adj).unwrap();
+        assert!(is_path(&adj, &path));
+    }
+
+    #[test]
+    fn petersen_graph() {
+        // The Petersen graph has a Hamiltonian path but no Hamiltonian cycle
+        let adj = undirected(
+            10,
+            &[
+                (1, 2),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 1),
+                (1, 6),
+                (2, 7),
+                (3, 8),
+                (4, 9),
+                (5, 10),
+                (6, 8),
+                (8, 10),
+                (10, 7),
+                (7, 9),
+                (9, 6),
+            ],
+        );
+        assert_eq!(hamiltonian_cycle(&adj), None);
+        let path = hamiltonian_path(&adj).unwrap();
+        assert!(is_path(&adj, &path));
+    }
+
+    #[test]
+    fn travelling_salesman_square() {
+        // A square with cheap sides and expensive diagonals
+        let mut adj = vec![vec![]; 5];
+        for &(u, v, w) in &[
+            (1, 2, 1),
+            (2, 3, 2),
+            (3, 4, 3),
+            (4, 1, 4),
+            (1, 3, 10),
+            (2, 4, 10),
+        ] {
+            adj[u].push((v, w));
+            adj[v].push((u, w));
+        }
+        let (length, tour) = travelling_salesman(&adj).unwrap();
+        assert_eq!(length, 10);
+        assert!(tour == vec![1, 2, 3, 4] || tour == vec![1, 4, 3, 2]);
+    }
+
+    #[test]
+    fn travelling_salesman_asymmetric() {
+        let adj = vec![
+            vec![],
+            vec![(2, 1), (3, 5)],
+            vec![(3, 1), (1, 5)],
+            vec![(1, 1), (2, 5)],
+        ];
+        assert_eq!(travelling_salesman(&adj), Some((3, vec![1, 2, 3])));
+        let adj = vec![vec![], vec![(2, 1)], vec![(3, 1)], vec![]];
+        assert_eq!(travelling_salesman(&adj), None);
+    }
+
+    #[test]
+    fn tiny_graphs() {
+        // No vertex
+        assert_eq!(hamiltonian_path(&[vec![]]), None);
+        assert_eq!(hamiltonian_cycle(&[vec![]]), None);
+        assert_eq!(hamiltonian_cycle(&[]), None);
+        // A single vertex is a path, but only a cycle with a self-loop
+        assert_eq!(hamiltonian_path(&[vec![], vec![]]), Some(vec![1]));
+        assert_eq!(hamiltonian_cycle(&[vec![], vec![]]), None);
+        assert_eq!(hamiltonian_cycle(&[vec![], vec![1]]), Some(vec![1]));
+        assert_eq!(
+            travelling_salesman(&[vec![], vec![(1, 7)]]),
+            Some((7, vec![1]))
+        );
+        // Two vertices need an edge each way for a cycle
+        assert_eq!(hamiltonian_cycle(&[vec![], vec![2], vec![]]), None);
+        assert_eq!(
+            hamiltonian_cycle(&undirected(2, &[(1, 2)])),
+            Some(vec![1, 2])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the graph is too large")]
+    fn too_many_vertices() {
+        hamiltonian_cycle(&vec![vec![]; 22]);
+    }
+}
@@ -0,0 +1,651 @@
+/*
+k shortest paths:
+When the single shortest path given by Dijkstra's algorithm is not enough,
+for example to have fallback routes, there are three related questions:
+1. Yen's algorithm finds the k shortest loopless paths from s to t. Each
+   new path is found from the previous one: for each of its vertices (the
+   "spur"), a shortest path from the spur to t is searched, avoiding the
+   vertices before the spur, and the next edge of every path already found
+   with the same beginning. The best of all these candidates is the next
+   path. It takes O(k n (m + n lg(n))) time.
+   https://en.wikipedia.org/wiki/Yen%27s_algorithm
+2. Eppstein's algorithm finds the k shortest walks, which may go through a
+   vertex several times. With a shortest path tree towards t, taking an edge
+   (u, v) of weight w instead of the tree edge out of u costs
+   w + d(v) - d(u) more, where d is the distance to t. A walk is then a
+   sequence of these "sidetrack" edges, and the walks are enumerated in order
+   with a priority queue, where each walk leads to a few slightly longer ones:
+   either its last sidetrack is replaced by the next best one, or another
+   sidetrack is added after it. The sidetracks available from each vertex are
+   stored in persistent heaps, that share most of their nodes, so the whole
+   algorithm takes O(m lg(m) + k lg(k)) time (without writing the walks).
+   https://en.wikipedia.org/wiki/K_shortest_path_routing
+3. `shortest_path_dag` gives all the shortest paths from a source at once:
+   the edges (u, v) with d(u) + w = d(v) form a Directed Acyclic Graph (if
+   there is no cycle of weight 0), and the number of shortest paths reaching
+   each vertex is the sum of the numbers of its predecessors.
+
+The graph is directed, with non-negative weights, like for `dijkstra`. A path
+is returned as its total weight and the list of its vertices. We assume that
+E::default() gives "zero" length.
+*/
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
+use std::ops::{Add, Sub};
+use std::rc::Rc;
+
+type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+// The graph with its vertices numbered from 0, in increasing order
+struct IndexedGraph<V, E> {
+    vertices: Vec<V>,
+    adj: Vec<Vec<(usize, E)>>,
+}
+
+impl<V: Ord + Copy, E: Copy> IndexedGraph<V, E> {
+    fn new(graph: &Graph<V, E>) -> Self {
+        let vertices: Vec<V> = graph
+            .iter()
+            .flat_map(|(u, edges)| std::iter::once(u).chain(edges.keys()))
+            .copied()
+            .collect::<BTreeSet<V>>()
+            .into_iter()
+            .collect();
+        let index = |v: &V| vertices.binary_search(v).unwrap();
+        let adj = vertices
+            .iter()
+            .map(|u| {
+                graph.get(u).map_or(vec![], |edges| {
+                    edges.iter().map(|(v, &w)| (index(v), w)).collect()
+                })
+            })
+            .collect();
+        IndexedGraph { vertices, adj }
+    }
+
+    fn index(&self, v: &V) -> Option<usize> {
+        self.vertices.binary_search(v).ok()
+    }
+
+    fn path(&self, path: &[usize]) -> Vec<V> {
+        path.iter().map(|&v| self.vertices[v]).collect()
+    }
+
+    fn weight(&self, u: usize, v: usize) -> E {
+        self.adj[u].iter().find(|e| e.0 == v).unwrap().1
+    }
+}
+
+// Dijkstra's algorithm, avoiding some vertices and edges. Returns the
+// distances, the parent of each reached vertex, and the reached vertices in
+// the order in which their distance was found
+fn dijkstra_from<E>(
+    adj: &[Vec<(usize, E)>],
+    source: usize,
+    banned_vertices: &[bool],
+    banned_edges: &BTreeSet<(usize, usize)>,
+) -> (Vec<Option<E>>, Vec<usize>, Vec<usize>)
+where
+    E: Ord + Copy + Add<Output = E> + Default,
+{
+    let mut distance: Vec<Option<E>> = vec![None; adj.len()];
+    let mut parent = vec![usize::MAX; adj.len()];
+    let mut done = vec![false; adj.len()];
+    let mut order = vec![];
+    distance[source] = Some(E::default());
+    let mut queue = BinaryHeap::from([Reverse((E::default(), source))]);
+    while let Some(Reverse((d, u))) = queue.pop() {
+        if done[u] {
+            continue;
+        }
+        done[u] = true;
+        order.push(u);
+        for &(v, w) in adj[u].iter() {
+            if banned_vertices[v] || banned_edges.contains(&(u, v)) {
+                continue;
+            }
+            if distance[v].is_none_or(|dv| d + w < dv) {
+                distance[v] = Some(d + w);
+                parent[v] = u;
+                queue.push(Reverse((d + w, v)));
+            }
+        }
+    }
+    (distance, parent, order)
+}
+
+/// Returns the `k` shortest paths without repeated vertices from `source` to
+/// `target`, by increasing weight, with Yen's algorithm. There are fewer if
+/// the graph does not have that many, and none if `source` or `target` is not
+/// in the graph.
+pub fn yen_k_shortest_paths<V, E>(
+    graph: &Graph<V, E>,
+    source: &V,
+    target: &V,
+    k: usize,
+) -> Vec<(E, Vec<V>)>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Default,
+{
+    let graph = IndexedGraph::new(graph);
+    let (s, t) = match (graph.index(source), graph.index(target)) {
+        (Some(s), Some(t)) => (s, t),
+        _ => return vec![],
+    };
+    let n = graph.vertices.len();
+    let shortest_path = |from: usize, banned_vertices: &[bool], banned_edges: &BTreeSet<_>| {
+        let (distance, parent, _) = dijkstra_from(&graph.adj, from, banned_vertices, banned_edges);
+        distance[t].map(|d| {
+            let mut path = vec![t];
+            while *path.last().unwrap() != from {
+                path.push(parent[*path.last().unwrap()]);
+            }
+            path.reverse();
+            (d, path)
+        })
+    };
+
+    let mut found: Vec<(E, Vec<usize>)> = vec![];
+    // Ordered by weight, then by vertices, which also removes duplicates
+    let mut candidates: BTreeSet<(E, Vec<usize>)> = BTreeSet::new();
+    candidates.extend(shortest_path(s, &vec![false; n], &BTreeSet::new()));
+    while found.len() < k {
+        let (weight, path) = match candidates.pop_first() {
+            Some(best) => best,
+            None => break,
+        };
+        let mut banned_vertices = vec![false; n];
+        let mut root_weight = E::default();
+        for i in 0..path.len() - 1 {
+            let root = &path[..=i];
+            // Leave the root by an edge that no path found so far takes
+            let banned_edges: BTreeSet<(usize, usize)> = found
+                .iter()
+                .map(|(_, p)| p)
+                .chain(std::iter::once(&path))
+                .filter(|p| p.len() > i + 1 && p[..=i] == *root)
+                .map(|p| (p[i], p[i + 1]))
+                .collect();
+            if let Some((spur_weight, spur_path)) =
+                shortest_path(path[i], &banned_vertices, &banned_edges)
+            {
+                let mut candidate = path[..i].to_vec();
+                candidate.extend(spur_path);
+                candidates.insert((root_weight + spur_weight, candidate));
+            }
+            banned_vertices[path[i]] = true;
+            root_weight = root_weight + graph.weight(path[i], path[i + 1]);
+        }
+        found.push((weight, path));
+    }
+    found
+        .into_iter()
+        .map(|(weight, path)| (weight, graph.path(&path)))
+        .collect()
+}
+
+// A node of a persistent leftist heap of sidetrack edges, by their extra cost
+struct HeapNode<E> {
+    key: E,
+    edge: (usize, usize),
+    rank: usize,
+    left: Option<Rc<HeapNode<E>>>,
+    right: Option<Rc<HeapNode<E>>>,
+}
+
+type Heap<E> = Option<Rc<HeapNode<E>>>;
+
+fn rank<E>(heap: &Heap<E>) -> usize {
+    heap.as_ref().map_or(0, |node| node.rank)
+}
+
+// Merges two heaps, only copying the nodes on the right spine of the result
+fn merge<E: Ord + Copy>(a: Heap<E>, b: Heap<E>) -> Heap<E> {
+    match (a, b) {
+        (None, heap) | (heap, None) => heap,
+        (Some(a), Some(b)) => {
+            let (a, b) = if a.key <= b.key { (a, b) } else { (b, a) };
+            let right = merge(a.right.clone(), Some(b));
+            let left = a.left.clone();
+            let (left, right) = if rank(&left) >= rank(&right) {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            Some(Rc::new(HeapNode {
+                key: a.key,
+                edge: a.edge,
+                rank: rank(&right) + 1,
+                left,
+                right,
+            }))
+        }
+    }
+}
+
+// The sidetracks of a walk, the last one first
+struct Sidetracks {
+    edge: (usize, usize),
+    previous: Walk,
+}
+
+// None for the shortest path, which has no sidetrack
+type Walk = Option<Rc<Sidetracks>>;
+
+/// Returns the `k` shortest walks from `source` to `target`, by increasing
+/// weight, with Eppstein's algorithm. Unlike paths, walks can go through the
+/// same vertex several times, so there are fewer only if no cycle can be
+/// added to the walks.
+pub fn k_shortest_walks<V, E>(
+    graph: &Graph<V, E>,
+    source: &V,
+    target: &V,
+    k: usize,
+) -> Vec<(E, Vec<V>)>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Sub<Output = E> + Default,
+{
+    let graph = IndexedGraph::new(graph);
+    let (s, t) = match (graph.index(source), graph.index(target)) {
+        (Some(s), Some(t)) => (s, t),
+        _ => return vec![],
+    };
+    let n = graph.vertices.len();
+    // The shortest path tree towards `target`, on the reversed graph, where
+    // `next` is the parent of each vertex
+    let mut reversed = vec![vec![]; n];
+    for (u, edges) in graph.adj.iter().enumerate() {
+        for &(v, w) in edges.iter() {
+            reversed[v].push((u, w));
+        }
+    }
+    let (distance, next, order) = dijkstra_from(&reversed, t, &vec![false; n], &BTreeSet::new());
+    let d_s = match distance[s] {
+        Some(d) => d,
+        None => return vec![],
+    };
+
+    // heaps[v] holds the sidetracks out of the vertices on the tree path
+    // from `v` to `target`, and shares its nodes with heaps[next[v]]
+    let mut heaps: Vec<Heap<E>> = vec![None; n];
+    for &u in order.iter() {
+        let mut heap = if u == t { None } else { heaps[next[u]].clone() };
+        let d_u = distance[u].unwrap();
+        for &(v, w) in graph.adj[u].iter() {
+            if let Some(d_v) = distance[v] {
+                if u == t || v != next[u] {
+                    let node = HeapNode {
+                        key: w + d_v - d_u,
+                        edge: (u, v),
+                        rank: 1,
+                        left: None,
+                        right: None,
+                    };
+                    heap = merge(heap, Some(Rc::new(node)));
+                }
+            }
+        }
+        heaps[u] = heap;
+    }
+
+    let walk = |sidetracks: &Walk| {
+        let mut edges = vec![];
+        let mut current = sidetracks;
+        while let Some(sidetrack) = current {
+            edges.push(sidetrack.edge);
+            current = &sidetrack.previous;
+        }
+        let mut path = vec![s];
+        let mut v = s;
+        for &(a, b) in edges.iter().rev() {
+            while v != a {
+                v = next[v];
+                path.push(v);
+            }
+            v = b;
+            path.push(v);
+        }
+        while v != t {
+            v = next[v];
+            path.push(v);
+        }
+        graph.path(&path)
+    };
+
+    let mut result = vec![];
+    if k > 0 {
+        result.push((d_s, walk(&None)));
+    }
+    // The queue holds the weight of each candidate walk and its index in
+    // `states`: the heap node of its last sidetrack and the ones before it
+    let mut states: Vec<(Rc<HeapNode<E>>, Walk)> = vec![];
+    let mut queue = BinaryHeap::new();
+    if let Some(root) = &heaps[s] {
+        queue.push(Reverse((d_s + root.key, states.len())));
+        states.push((Rc::clone(root), None));
+    }
+    while result.len() < k {
+        let (weight, id) = match queue.pop() {
+            Some(Reverse(candidate)) => candidate,
+            None => break,
+        };
+        let (node, previous) = states[id].clone();
+        let sidetracks = Some(Rc::new(Sidetracks {
+            edge: node.edge,
+            previous: previous.clone(),
+        }));
+        result.push((weight, walk(&sidetracks)));
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            queue.push(Reverse((weight - node.key + child.key, states.len())));
+            states.push((Rc::clone(child), previous.clone()));
+        }
+        if let Some(root) = &heaps[node.edge.1] {
+            queue.push(Reverse((weight + root.key, states.len())));
+            states.push((Rc::clone(root), sidetracks));
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPathDag<V, E> {
+    // The distance from the source of each reachable vertex
+    pub distance: BTreeMap<V, E>,
+    // The vertices before each reachable vertex on its shortest paths
+    pub predecessors: BTreeMap<V, Vec<V>>,
+    // The number of shortest paths to each reachable vertex, saturating at
+    // u64::MAX, which is also used when a cycle of weight 0 gives infinitely
+    // many of them
+    pub path_count: BTreeMap<V, u64>,
+}
+
+/// Returns all the shortest paths from `source`, as a Directed Acyclic Graph.
+pub fn shortest_path_dag<V, E>(graph: &Graph<V, E>, source: &V) -> ShortestPathDag<V, E>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Default,
+{
+    let mut dag = ShortestPathDag {
+        distance: BTreeMap::new(),
+        predecessors: BTreeMap::new(),
+        path_count: BTreeMap::new(),
+    };
+    let graph = IndexedGraph::new(graph);
+    let s = match graph.index(source) {
+        Some(s) => s,
+        None => {
+            dag.distance.insert(*source, E::default());
+            dag.predecessors.insert(*source, vec![]);
+            dag.path_count.insert(*source, 1);
+            return dag;
+        }
+    };
+    let n = graph.vertices.len();
+    let (distance, _, order) = dijkstra_from(&graph.adj, s, &vec![false; n], &BTreeSet::new());
+    let mut successors = vec![vec![]; n];
+    let mut predecessors = vec![vec![]; n];
+    for &u in order.iter() {
+        for &(v, w) in graph.adj[u].iter() {
+            if distance[u].map(|d| d + w) == distance[v] {
+                successors[u].push(v);
+                predecessors[v].push(u);
+            }
+        }
+    }
+    // Count in topological order, the vertices left are after a cycle
+    let mut count = vec![u64::MAX; n];
+    let mut in_degree: Vec<usize> = predecessors.iter().map(|p| p.len()).collect();
+    let mut queue: VecDeque<usize> = order
+        .iter()
+        .copied()
+        .filter(|&v| in_degree[v] == 0)
+        .collect();
+    while let Some(u) = queue.pop_front() {
+        count[u] = if u == s {
+            1
+        } else {
+            predecessors[u]
+                .iter()
+                .fold(0u64, |total, &p| total.saturating_add(count[p]))
+        };
+        for &v in successors[u].iter() {
+            in_degree[v] -= 1;
+            if in_degree[v] == 0 {
+                queue.push_back(v);
+            }
+        }
+    }
+    for &v in order.iter() {
+        let vertex = graph.vertices[v];
+        dag.distance.insert(vertex, distance[v].unwrap());
+        dag.predecessors
+            .insert(vertex, graph.path(&predecessors[v]));
+        dag.path_count.insert(vertex, count[v]);
+    }
+    dag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{dijkstra, random_weighted_graph};
+    use crate::math::PCG32;
+
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
+    }
+
+    fn weight_of(graph: &Graph<usize, i64>, path: &[usize]) -> i64 {
+        path.windows(2).map(|e| graph[&e[0]][&e[1]]).sum()
+    }
+
+    // The weights of all the paths without repeated vertices from `u` to `t`
+    fn all_paths(graph: &Graph<usize, i64>, path: &mut Vec<usize>, t: usize, out: &mut Vec<i64>) {
+        let u = *path.last().unwrap();
+        if u == t {
+            out.push(weight_of(graph, path));
+            return;
+        }
+        for &v in graph[&u].keys() {
+            if !path.contains(&v) {
+                path.push(v);
+                all_paths(graph, path, t, out);
+                path.pop();
+            }
+        }
+    }
+
+    // The weights of the k shortest walks, by extending the lightest walks
+    // first, for positive weights. The walks can only go through vertices
+    // that can reach `t`, so that there is a cycle to go around if there are
+    // infinitely many walks
+    fn brute_force_walks(graph: &Graph<usize, i64>, s: usize, t: usize, k: usize) -> Vec<i64> {
+        let mut result = vec![];
+        let mut queue = BinaryHeap::from([Reverse((0, s))]);
+        while let Some(Reverse((weight, u))) = queue.pop() {
+            if result.len() == k || !dijkstra(graph, &u).contains_key(&t) {
+                continue;
+            }
+            if u == t {
+                result.push(weight);
+            }
+            for (&v, &w) in graph[&u].iter() {
+                queue.push(Reverse((weight + w, v)));
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn yen_example() {
+        // The example of the Wikipedia page
+        let mut graph = BTreeMap::new();
+        for (u, v, w) in [
+            ('C', 'D', 3),
+            ('C', 'E', 2),
+            ('D', 'F', 4),
+            ('E', 'D', 1),
+            ('E', 'F', 2),
+            ('E', 'G', 3),
+            ('F', 'G', 2),
+            ('F', 'H', 1),
+            ('G', 'H', 2),
+        ] {
+            add_edge(&mut graph, u, v, w);
+        }
+        let paths = yen_k_shortest_paths(&graph, &'C', &'H', 3);
+        let paths: Vec<(i32, String)> = paths
+            .into_iter()
+            .map(|(w, p)| (w, p.into_iter().collect()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (5, "CEFH".to_string()),
+                (7, "CEGH".to_string()),
+                (8, "CDFH".to_string()),
+            ]
+        );
+        assert_eq!(yen_k_shortest_paths(&graph, &'C', &'H', 10).len(), 7);
+        assert_eq!(yen_k_shortest_paths(&graph, &'H', &'C', 10), vec![]);
+        assert_eq!(
+            yen_k_shortest_paths(&graph, &'C', &'C', 10),
+            vec![(0, vec!['C'])]
+        );
+        assert_eq!(yen_k_shortest_paths(&graph, &'C', &'Z', 10), vec![]);
+    }
+
+    #[test]
+    fn walks_around_a_cycle() {
+        // 1 -> 2 -> 3, with the cycle 2 -> 4 -> 2
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 1, 2, 1);
+        add_edge(&mut graph, 2, 3, 1);
+        add_edge(&mut graph, 2, 4, 2);
+        add_edge(&mut graph, 4, 2, 3);
+        let walks = k_shortest_walks(&graph, &1, &3, 3);
+        assert_eq!(
+            walks,
+            vec![
+                (2, vec![1, 2, 3]),
+                (7, vec![1, 2, 4, 2, 3]),
+                (12, vec![1, 2, 4, 2, 4, 2, 3]),
+            ]
+        );
+        assert_eq!(yen_k_shortest_paths(&graph, &1, &3, 3).len(), 1);
+        assert_eq!(k_shortest_walks(&graph, &3, &1, 3), vec![]);
+        assert_eq!(k_shortest_walks(&graph, &1, &3, 0), vec![]);
+        let around: Vec<i32> = k_shortest_walks(&graph, &2, &2, 3)
+            .iter()
+            .map(|w| w.0)
+            .collect();
+        assert_eq!(around, vec![0, 5, 10]);
+    }
+
+    #[test]
+    fn grid_path_counts() {
+        // A 4 x 4 grid with edges going right and down, where there are
+        // binomial(i + j, i) shortest paths to (i, j)
+        let mut graph = BTreeMap::new();
+        for i in 0..4 {
+            for j in 0..4 {
+                if i + 1 < 4 {
+                    add_edge(&mut graph, (i, j), (i + 1, j), 1);
+                }
+                if j + 1 < 4 {
+                    add_edge(&mut graph, (i, j), (i, j + 1), 1);
+                }
+            }
+        }
+        let dag = shortest_path_dag(&graph, &(0, 0));
+        assert_eq!(dag.path_count[&(3, 3)], 20);
+        assert_eq!(dag.path_count[&(1, 2)], 3);
+        assert_eq!(dag.distance[&(2, 3)], 5);
+        assert_eq!(dag.predecessors[&(1, 1)], vec![(0, 1), (1, 0)]);
+        assert_eq!(yen_k_shortest_paths(&graph, &(0, 0), &(3, 3), 30).len(), 20);
+
+        // A cycle of weight 0 gives infinitely many shortest paths
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 1, 2, 1);
+        add_edge(&mut graph, 2, 3, 0);
+        add_edge(&mut graph, 3, 2, 0);
+        add_edge(&mut graph, 3, 4, 1);
+        add_edge(&mut graph, 5, 4, 1);
+        let dag = shortest_path_dag(&graph, &1);
+        assert_eq!(dag.path_count[&1], 1);
+        assert_eq!(dag.path_count[&4], u64::MAX);
+        assert!(!dag.distance.contains_key(&5));
+    }
+
+    #[test]
+    fn random_graphs() {
+        let mut rng = PCG32::new_default(38);
+        for _ in 0..30 {
+            let n = 2 + rng.get_u32() as usize % 6;
+            let mut graph = random_weighted_graph(n, 0.5, 5, &mut rng);
+            // Positive weights for the brute force walks
+            for edges in graph.values_mut() {
+                edges.values_mut().for_each(|w| *w += 1);
+            }
+            let (s, t) = (1, n);
+            let mut weights = vec![];
+            all_paths(&graph, &mut vec![s], t, &mut weights);
+            weights.sort_unstable();
+            weights.truncate(6);
+
+            let paths = yen_k_shortest_paths(&graph, &s, &t, 6);
+            let found: Vec<i64> = paths.iter().map(|p| p.0).collect();
+            assert_eq!(found, weights);
+            for (weight, path) in paths.iter() {
+                assert_eq!((path[0], *path.last().unwrap()), (s, t));
+                assert_eq!(weight_of(&graph, path), *weight);
+                let mut vertices = path.clone();
+                vertices.sort_unstable();
+                vertices.dedup();
+                assert_eq!(vertices.len(), path.len());
+            }
+
+            let walks = k_shortest_walks(&graph, &s, &t, 10);
+            let found: Vec<i64> = walks.iter().map(|w| w.0).collect();
+            assert_eq!(found, brute_force_walks(&graph, s, t, 10));
+            for (weight, walk) in walks.iter() {
+                assert_eq!((walk[0], *walk.last().unwrap()), (s, t));
+                assert_eq!(weight_of(&graph, walk), *weight);
+            }
+
+            let dag = shortest_path_dag(&graph, &s);
+            let shortest = dijkstra(&graph, &s);
+            for (v, distance) in dag.distance.iter() {
+                assert_eq!(shortest[v].map_or(0, |p| p.1), *distance);
+                let mut paths = vec![];
+                all_paths(&graph, &mut vec![s], *v, &mut paths);
+                let count = paths.iter().filter(|&w| w == distance).count();
+                assert_eq!(dag.path_count[v], count as u64);
+            }
+            assert_eq!(dag.distance.len(), shortest.len());
+        }
+    }
+
+    #[test]
+    fn single_vertex() {
+        let mut graph: Graph<usize, i64> = BTreeMap::new();
+        assert_eq!(yen_k_shortest_paths(&graph, &1, &1, 3), vec![]);
+        assert_eq!(k_shortest_walks(&graph, &1, &1, 3), vec![]);
+
+        graph.insert(1, BTreeMap::new());
+        assert_eq!(yen_k_shortest_paths(&graph, &1, &1, 3), vec![(0, vec![1])]);
+        assert_eq!(k_shortest_walks(&graph, &1, &1, 3), vec![(0, vec![1])]);
+
+        // A self loop makes a walk, but not a path
+        add_edge(&mut graph, 1, 1, 2);
+        assert_eq!(yen_k_shortest_paths(&graph, &1, &1, 3), vec![(0, vec![1])]);
+        assert_eq!(
+            k_shortest_walks(&graph, &1, &1, 3),
+            vec![(0, vec![1]), (2, vec![1, 1]), (4, vec![1, 1, 1])]
+        );
+    }
+}
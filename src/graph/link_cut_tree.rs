@@ -0,0 +1,361 @@
+/*
+Link-Cut Tree:
+It maintains a forest under edge insertions (`link`) and deletions (`cut`),
+and answers connectivity, path aggregate and lowest common ancestor queries,
+all in amortised O(lg(n)) time.
+
+The forest is decomposed into vertex-disjoint "preferred paths", and each of
+them is stored in a splay tree keyed by depth. The splay tree of a path keeps
+a pointer to the parent of the topmost vertex of the path ("path-parent").
+`access(v)` makes the path from the root to `v` preferred, so that it is a
+single splay tree rooted at `v`. The trees are unrooted from the user's point
+of view: `make_root` (also known as evert) reverses the path to the current
+root, and the reversals are propagated lazily.
+
+Vertex values are aggregated with a `Monoid`, which does not need to be
+commutative: `path_aggregate(u, v)` combines the values in order from `u` to
+`v`.
+
+Vertices are numbered from 1 to n, 0 is used as the "null" vertex.
+*/
+
+use crate::data_structures::Monoid;
+
+pub struct LinkCutTree<M: Monoid> {
+    // Children and parent in the splay trees. For the root of a splay tree,
+    // `parent` is its path-parent
+    left: Vec<usize>,
+    right: Vec<usize>,
+    parent: Vec<usize>,
+    // The subtree should be reversed, its own children are already swapped
+    reversed: Vec<bool>,
+    value: Vec<M::Value>,
+    // The aggregate of the splay subtree, in order and in reverse order
+    aggregate: Vec<M::Value>,
+    reverse_aggregate: Vec<M::Value>,
+}
+
+impl<M: Monoid> LinkCutTree<M> {
+    /// Creates a forest of `num_vertices` isolated vertices, with identity
+    /// values.
+    pub fn new(num_vertices: usize) -> Self {
+        Self::from_values(vec![M::identity(); num_vertices])
+    }
+    /// Creates a forest of isolated vertices, where values[i] is the value of
+    /// vertex i + 1.
+    pub fn from_values(values: Vec<M::Value>) -> Self {
+        let len = values.len() + 1;
+        let mut value = Vec::with_capacity(len);
+        value.push(M::identity());
+        value.extend(values);
+        LinkCutTree {
+            left: vec![0; len],
+            right: vec![0; len],
+            parent: vec![0; len],
+            reversed: vec![false; len],
+            aggregate: value.clone(),
+            reverse_aggregate: value.clone(),
+            value,
+        }
+    }
+    #[inline]
+    fn is_splay_root(&self, x: usize) -> bool {
+        let p = self.parent[x];
+        p == 0 || (self.left[p] != x && self.right[p] != x)
+    }
+    fn update(&mut self, x: usize) {
+        let (l, r) = (self.left[x], self.right[x]);
+        // Index 0 always holds the identity
+        self.aggregate[x] = M::combine(
+            &M::combine(&self.aggregate[l], &self.value[x]),
+            &self.aggregate[r],
+        );
+        self.reverse_aggregate[x] = M::combine(
+            &M::combine(&self.reverse_aggregate[r], &self.value[x]),
+            &self.reverse_aggregate[l],
+        );
+    }
+    fn reverse(&mut self, x: usize) {
+        if x == 0 {
+            return;
+        }
+        std::mem::swap(&mut self.left[x], &mut self.right[x]);
+        std::mem::swap(&mut self.aggregate[x], &mut self.reverse_aggregate[x]);
+        self.reversed[x] ^= true;
+    }
+    fn push(&mut self, x: usize) {
+        if self.reversed[x] {
+            self.reverse(self.left[x]);
+            self.reverse(self.right[x]);
+            self.reversed[x] = false;
+        }
+    }
+    fn rotate(&mut self, x: usize) {
+        let p = self.parent[x];
+        let g = self.parent[p];
+        if !self.is_splay_root(p) {
+            if self.left[g] == p {
+                self.left[g] = x;
+            } else {
+                self.right[g] = x;
+            }
+        }
+        if self.left[p] == x {
+            self.left[p] = self.right[x];
+            if self.right[x] != 0 {
+                self.parent[self.right[x]] = p;
+            }
+            self.right[x] = p;
+        } else {
+            self.right[p] = self.left[x];
+            if self.left[x] != 0 {
+                self.parent[self.left[x]] = p;
+            }
+            self.left[x] = p;
+        }
+        self.parent[p] = x;
+        self.parent[x] = g;
+        self.update(p);
+        self.update(x);
+    }
+    fn splay(&mut self, x: usize) {
+        // Push the pending reversals down from the root of the splay tree
+        let mut path = vec![x];
+        let mut y = x;
+        while !self.is_splay_root(y) {
+            y = self.parent[y];
+            path.push(y);
+        }
+        for &y in path.iter().rev() {
+            self.push(y);
+        }
+        while !self.is_splay_root(x) {
+            let p = self.parent[x];
+            if !self.is_splay_root(p) {
+                let g = self.parent[p];
+                if (self.left[g] == p) == (self.left[p] == x) {
+                    self.rotate(p);
+                } else {
+                    self.rotate(x);
+                }
+            }
+            self.rotate(x);
+        }
+    }
+    // Makes the path from the root to `x` preferred, and returns the last
+    // path-parent that was jumped over
+    fn access(&mut self, x: usize) -> usize {
+        let mut last = 0;
+        let mut y = x;
+        while y != 0 {
+            self.splay(y);
+            self.right[y] = last;
+            self.update(y);
+            last = y;
+            y = self.parent[y];
+        }
+        self.splay(x);
+        last
+    }
+    /// Makes `v` the root of its tree.
+    pub fn make_root(&mut self, v: usize) {
+        self.access(v);
+        self.reverse(v);
+    }
+    /// Returns the current root of the tree containing `v`.
+    pub fn find_root(&mut self, v: usize) -> usize {
+        self.access(v);
+        let mut x = v;
+        loop {
+            self.push(x);
+            if self.left[x] == 0 {
+                break;
+            }
+            x = self.left[x];
+        }
+        self.splay(x);
+        x
+    }
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        self.find_root(u) == self.find_root(v)
+    }
+    /// Adds the edge (u, v). Returns false, and does nothing, if `u` and `v`
+    /// are already connected.
+    pub fn link(&mut self, u: usize, v: usize) -> bool {
+        if self.connected(u, v) {
+            return false;
+        }
+        self.make_root(u);
+        self.parent[u] = v;
+        true
+    }
+    /// Removes the edge (u, v). Returns false, and does nothing, if there is
+    /// no such edge.
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        if u == v {
+            return false;
+        }
+        self.make_root(u);
+        self.access(v);
+        // The path from `u` to `v` is in the splay tree of `v`, and `u` is
+        // adjacent to `v` if it is the only other vertex on it
+        let l = self.left[v];
+        if l != u {
+            return false;
+        }
+        self.push(u);
+        if self.right[u] != 0 {
+            return false;
+        }
+        self.left[v] = 0;
+        self.parent[u] = 0;
+        self.update(v);
+        true
+    }
+    pub fn value(&self, v: usize) -> &M::Value {
+        &self.value[v]
+    }
+    pub fn set_value(&mut self, v: usize, value: M::Value) {
+        self.access(v);
+        self.value[v] = value;
+        self.update(v);
+    }
+    /// Returns the aggregate of the values on the path from `u` to `v` (in
+    /// this order), or None if they are not connected.
+    pub fn path_aggregate(&mut self, u: usize, v: usize) -> Option<M::Value> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.make_root(u);
+        self.access(v);
+        Some(self.aggregate[v].clone())
+    }
+    /// Returns the lowest common ancestor of `u` and `v` when the tree is
+    /// rooted at `root`, or None if they are not all connected.
+    pub fn lca(&mut self, root: usize, u: usize, v: usize) -> Option<usize> {
+        if !self.connected(root, u) || !self.connected(root, v) {
+            return None;
+        }
+        self.make_root(root);
+        self.access(u);
+        Some(self.access(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::test_monoids::{Concat, Sum};
+    use crate::math::PCG32;
+
+    // Returns the path from `u` to `v` in a forest, if there is one
+    fn naive_path(adj: &[Vec<usize>], u: usize, v: usize) -> Option<Vec<usize>> {
+        let mut parent = vec![usize::MAX; adj.len()];
+        let mut stack = vec![u];
+        parent[u] = 0;
+        while let Some(x) = stack.pop() {
+            for &y in adj[x].iter() {
+                if parent[y] == usize::MAX {
+                    parent[y] = x;
+                    stack.push(y);
+                }
+            }
+        }
+        if parent[v] == usize::MAX {
+            return None;
+        }
+        let mut path = vec![v];
+        let mut x = v;
+        while x != u {
+            x = parent[x];
+            path.push(x);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    #[test]
+    fn path_order() {
+        let names = ["a", "b", "c", "d", "e"];
+        let mut lct: LinkCutTree<Concat> =
+            LinkCutTree::from_values(names.iter().map(|s| s.to_string()).collect());
+        assert!(lct.link(1, 2));
+        assert!(lct.link(2, 3));
+        assert!(lct.link(3, 4));
+        assert!(lct.link(5, 3));
+        assert!(!lct.link(1, 4));
+        assert_eq!(lct.path_aggregate(1, 4), Some(String::from("abcd")));
+        assert_eq!(lct.path_aggregate(4, 1), Some(String::from("dcba")));
+        assert_eq!(lct.path_aggregate(5, 1), Some(String::from("ecba")));
+        assert_eq!(lct.lca(1, 4, 5), Some(3));
+        assert_eq!(lct.lca(4, 1, 2), Some(2));
+        assert!(!lct.cut(1, 3));
+        assert!(lct.cut(3, 2));
+        assert_eq!(lct.path_aggregate(1, 4), None);
+        assert_eq!(lct.lca(1, 2, 4), None);
+        lct.set_value(4, String::from("D"));
+        assert_eq!(lct.path_aggregate(5, 4), Some(String::from("ecD")));
+    }
+
+    #[test]
+    fn random_operations() {
+        let n = 40;
+        let mut rng = PCG32::new_default(314159);
+        let mut lct: LinkCutTree<Sum> = LinkCutTree::new(n);
+        let mut values = vec![0i64; n + 1];
+        let mut adj: Vec<Vec<usize>> = vec![vec![]; n + 1];
+        for _ in 0..3000 {
+            let u = (rng.get_u32() as usize % n) + 1;
+            let v = (rng.get_u32() as usize % n) + 1;
+            match rng.get_u32() % 4 {
+                0 => {
+                    let path = naive_path(&adj, u, v);
+                    assert_eq!(lct.link(u, v), path.is_none());
+                    if path.is_none() {
+                        adj[u].push(v);
+                        adj[v].push(u);
+                    }
+                }
+                1 => {
+                    // Prefer cutting existing edges
+                    let v = adj[u].first().copied().unwrap_or(v);
+                    let exists = adj[u].contains(&v);
+                    assert_eq!(lct.cut(u, v), exists);
+                    adj[u].retain(|&x| x != v);
+                    adj[v].retain(|&x| x != u);
+                }
+                2 => {
+                    let value = (rng.get_u32() % 1000) as i64 - 500;
+                    lct.set_value(u, value);
+                    values[u] = value;
+                }
+                _ => {
+                    let expected = naive_path(&adj, u, v)
+                        .map(|path| path.iter().map(|&x| values[x]).sum::<i64>());
+                    assert_eq!(lct.path_aggregate(u, v), expected);
+                    assert_eq!(lct.connected(u, v), expected.is_some());
+                    let root = (rng.get_u32() as usize % n) + 1;
+                    let expected_lca = match (naive_path(&adj, root, u), naive_path(&adj, root, v))
+                    {
+                        (Some(pu), Some(pv)) => {
+                            let common = pu.iter().zip(pv.iter()).take_while(|(a, b)| a == b);
+                            common.last().map(|(&a, _)| a)
+                        }
+                        _ => None,
+                    };
+                    assert_eq!(lct.lca(root, u, v), expected_lca);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_vertex() {
+        let mut lct: LinkCutTree<Sum> = LinkCutTree::from_values(vec![5]);
+        assert_eq!(lct.find_root(1), 1);
+        assert!(!lct.link(1, 1));
+        assert!(!lct.cut(1, 1));
+        assert_eq!(lct.path_aggregate(1, 1), Some(5));
+        assert_eq!(lct.lca(1, 1, 1), Some(1));
+    }
+}
@@ -1,24 +1,29 @@
-use super::DisjointSetUnion;
+/*
+Minimum spanning trees of undirected graphs, and minimum spanning
+arborescences of directed graphs.
 
-#[derive(Debug)]
-pub struct Edge {
-    source: i64,
-    destination: i64,
-    cost: i64,
-}
+The graphs are given as lists of edges. The vertices can be of any type
+implementing `Ord`, they are the endpoints of the edges. The costs can be of
+any `Ord` type, and `E::default()` is used as the zero cost.
 
-impl PartialEq for Edge {
-    fn eq(&self, other: &Self) -> bool {
-        self.source == other.source
-            && self.destination == other.destination
-            && self.cost == other.cost
-    }
-}
+If the graph is disconnected, `kruskal` and `boruvka` return a minimum
+spanning forest, with a tree for each connected component.
+*/
 
-impl Eq for Edge {}
+use std::collections::BTreeMap;
+use std::ops::{Add, Sub};
+
+use super::DisjointSetUnion;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge<V, E> {
+    pub source: V,
+    pub destination: V,
+    pub cost: E,
+}
 
-impl Edge {
-    fn new(source: i64, destination: i64, cost: i64) -> Self {
+impl<V, E> Edge<V, E> {
+    pub fn new(source: V, destination: V, cost: E) -> Self {
         Self {
             source,
             destination,
@@ -27,38 +32,219 @@ impl Edge {
     }
 }
 
-pub fn kruskal(mut edges: Vec<Edge>, number_of_vertices: i64) -> (i64, Vec<Edge>) {
-    let mut dsu = DisjointSetUnion::new(number_of_vertices as usize);
+// Numbers the endpoints of the edges from 0, and returns the edges as
+// (source, destination) numbers
+fn enumerate_vertices<V: Ord + Clone, E>(
+    edges: &[Edge<V, E>],
+    vertices: &mut BTreeMap<V, usize>,
+) -> Vec<(usize, usize)> {
+    let mut index = |v: &V| {
+        let next = vertices.len();
+        *vertices.entry(v.clone()).or_insert(next)
+    };
+    edges
+        .iter()
+        .map(|edge| (index(&edge.source), index(&edge.destination)))
+        .collect()
+}
+
+fn total_cost<V, E: Copy + Add<Output = E> + Default>(edges: &[Edge<V, E>]) -> E {
+    edges
+        .iter()
+        .fold(E::default(), |total, edge| total + edge.cost)
+}
 
-    edges.sort_unstable_by(|a, b| a.cost.cmp(&b.cost));
-    let mut total_cost: i64 = 0;
-    let mut final_edges: Vec<Edge> = Vec::new();
-    let mut merge_count: i64 = 0;
-    for edge in edges.iter() {
-        if merge_count >= number_of_vertices - 1 {
+/// Returns the total cost and the edges of a minimum spanning forest, with the
+/// edges sorted by cost.
+pub fn kruskal<V, E>(mut edges: Vec<Edge<V, E>>) -> (E, Vec<Edge<V, E>>)
+where
+    V: Ord + Clone,
+    E: Ord + Copy + Add<Output = E> + Default,
+{
+    // Stable, so that edges of the same cost are taken in the given order
+    edges.sort_by_key(|edge| edge.cost);
+    let mut vertices = BTreeMap::new();
+    let endpoints = enumerate_vertices(&edges, &mut vertices);
+    let mut dsu = DisjointSetUnion::new(vertices.len());
+    let mut final_edges: Vec<Edge<V, E>> = Vec::new();
+    for (edge, (source, destination)) in edges.into_iter().zip(endpoints) {
+        if final_edges.len() + 1 >= vertices.len() {
             break;
         }
+        if dsu.merge(source, destination) < usize::MAX {
+            final_edges.push(edge);
+        }
+    }
+    (total_cost(&final_edges), final_edges)
+}
 
-        let source: i64 = edge.source;
-        let destination: i64 = edge.destination;
-        if dsu.merge(source as usize, destination as usize) < std::usize::MAX {
-            merge_count += 1;
-            let cost: i64 = edge.cost;
-            total_cost += cost;
-            let final_edge: Edge = Edge::new(source, destination, cost);
-            final_edges.push(final_edge);
+/// Returns the total cost and the edges of a minimum spanning forest, using
+/// Borůvka's algorithm.
+///
+/// In each of its O(lg(n)) rounds, every component picks its cheapest
+/// outgoing edge, and all of them are added at once. The rounds are
+/// independent of the order of the components, which makes it easy to run in
+/// parallel.
+pub fn boruvka<V, E>(edges: Vec<Edge<V, E>>) -> (E, Vec<Edge<V, E>>)
+where
+    V: Ord + Clone,
+    E: Ord + Copy + Add<Output = E> + Default,
+{
+    let mut vertices = BTreeMap::new();
+    let endpoints = enumerate_vertices(&edges, &mut vertices);
+    let mut dsu = DisjointSetUnion::new(vertices.len());
+    let mut in_forest = vec![false; edges.len()];
+    loop {
+        // The cheapest edge leaving each component, ties are broken by index
+        // so that the chosen edges never make a cycle
+        let mut cheapest: Vec<Option<usize>> = vec![None; vertices.len()];
+        for (i, &(source, destination)) in endpoints.iter().enumerate() {
+            let a = dsu.find_set(source);
+            let b = dsu.find_set(destination);
+            if a == b {
+                continue;
+            }
+            for c in [a, b] {
+                if cheapest[c].is_none_or(|j| edges[i].cost < edges[j].cost) {
+                    cheapest[c] = Some(i);
+                }
+            }
+        }
+        let mut merged = false;
+        for i in cheapest.into_iter().flatten() {
+            let (source, destination) = endpoints[i];
+            if dsu.merge(source, destination) < usize::MAX {
+                in_forest[i] = true;
+                merged = true;
+            }
+        }
+        if !merged {
+            break;
         }
     }
-    (total_cost, final_edges)
+    let final_edges: Vec<Edge<V, E>> = edges
+        .into_iter()
+        .zip(in_forest)
+        .filter_map(|(edge, used)| used.then_some(edge))
+        .collect();
+    (total_cost(&final_edges), final_edges)
+}
+
+/// Returns the total cost and the edges of a minimum spanning arborescence of
+/// a directed graph rooted at `root`, that is a set of edges of minimum cost
+/// with a single path from `root` to every vertex, using the Chu–Liu/Edmonds
+/// algorithm in O(VE) time.
+///
+/// Returns None if some vertex can not be reached from `root`.
+pub fn minimum_spanning_arborescence<V, E>(
+    edges: Vec<Edge<V, E>>,
+    root: V,
+) -> Option<(E, Vec<Edge<V, E>>)>
+where
+    V: Ord + Clone,
+    E: Ord + Copy + Add<Output = E> + Sub<Output = E> + Default,
+{
+    let mut vertices = BTreeMap::new();
+    vertices.insert(root.clone(), 0);
+    let endpoints = enumerate_vertices(&edges, &mut vertices);
+    let indexed: Vec<(usize, usize, E)> = endpoints
+        .iter()
+        .zip(edges.iter())
+        .map(|(&(source, destination), edge)| (source, destination, edge.cost))
+        .collect();
+    let mut chosen = chu_liu_edmonds(vertices.len(), 0, &indexed)?;
+    chosen.sort_unstable();
+    let final_edges: Vec<Edge<V, E>> = chosen.into_iter().map(|i| edges[i].clone()).collect();
+    Some((total_cost(&final_edges), final_edges))
+}
+
+// Returns the indices of the edges of a minimum arborescence. Every vertex
+// other than the root picks its cheapest incoming edge. If that makes no
+// cycle, it is the answer. Otherwise every cycle is contracted into a single
+// vertex, where entering the cycle at `v` costs the difference with the edge
+// it replaces, and the contracted graph is solved recursively
+fn chu_liu_edmonds<E>(
+    num_vertices: usize,
+    root: usize,
+    edges: &[(usize, usize, E)],
+) -> Option<Vec<usize>>
+where
+    E: Ord + Copy + Sub<Output = E>,
+{
+    let mut best: Vec<Option<usize>> = vec![None; num_vertices];
+    for (i, &(u, v, cost)) in edges.iter().enumerate() {
+        if u != v && v != root && best[v].is_none_or(|j| cost < edges[j].2) {
+            best[v] = Some(i);
+        }
+    }
+    if (0..num_vertices).any(|v| v != root && best[v].is_none()) {
+        return None;
+    }
+    let parent = |v: usize| edges[best[v].unwrap()].0;
+
+    // The component of each vertex in the contracted graph
+    let mut component = vec![usize::MAX; num_vertices];
+    let mut visited_from = vec![usize::MAX; num_vertices];
+    let mut in_cycle = vec![false; num_vertices];
+    let mut num_components = 0;
+    for v in 0..num_vertices {
+        let mut x = v;
+        while x != root && component[x] == usize::MAX && visited_from[x] != v {
+            visited_from[x] = v;
+            x = parent(x);
+        }
+        if x != root && component[x] == usize::MAX {
+            // The walk from `v` came back to `x`
+            let mut y = x;
+            loop {
+                component[y] = num_components;
+                in_cycle[y] = true;
+                y = parent(y);
+                if y == x {
+                    break;
+                }
+            }
+            num_components += 1;
+        }
+    }
+    if num_components == 0 {
+        return Some(best.into_iter().flatten().collect());
+    }
+    for c in component.iter_mut().filter(|c| **c == usize::MAX) {
+        *c = num_components;
+        num_components += 1;
+    }
+
+    let mut contracted = vec![];
+    let mut origin = vec![];
+    for (i, &(u, v, cost)) in edges.iter().enumerate() {
+        if component[u] != component[v] && v != root {
+            let replaced = edges[best[v].unwrap()].2;
+            contracted.push((component[u], component[v], cost - replaced));
+            origin.push(i);
+        }
+    }
+    let chosen = chu_liu_edmonds(num_components, component[root], &contracted)?;
+    let mut result: Vec<usize> = chosen.into_iter().map(|i| origin[i]).collect();
+    // Every cycle is entered once, and keeps its other edges
+    let mut entered = vec![false; num_vertices];
+    for &i in result.iter() {
+        entered[edges[i].1] = true;
+    }
+    for v in (0..num_vertices).filter(|&v| in_cycle[v] && !entered[v]) {
+        result.push(best[v].unwrap());
+    }
+    Some(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::PCG32;
 
     #[test]
     fn test_seven_vertices_eleven_edges() {
-        let mut edges: Vec<Edge> = Vec::new();
+        let mut edges: Vec<Edge<i64, i64>> = Vec::new();
         edges.push(Edge::new(0, 1, 7));
         edges.push(Edge::new(0, 3, 5));
         edges.push(Edge::new(1, 2, 8));
@@ -71,10 +257,8 @@ mod tests {
         edges.push(Edge::new(4, 6, 9));
         edges.push(Edge::new(5, 6, 11));
 
-        let number_of_vertices: i64 = 7;
-
         let expected_total_cost = 39;
-        let mut expected_used_edges: Vec<Edge> = Vec::new();
+        let mut expected_used_edges: Vec<Edge<i64, i64>> = Vec::new();
         expected_used_edges.push(Edge::new(0, 3, 5));
         expected_used_edges.push(Edge::new(2, 4, 5));
         expected_used_edges.push(Edge::new(3, 5, 6));
@@ -82,7 +266,7 @@ mod tests {
         expected_used_edges.push(Edge::new(1, 4, 7));
         expected_used_edges.push(Edge::new(4, 6, 9));
 
-        let (actual_total_cost, actual_final_edges) = kruskal(edges, number_of_vertices);
+        let (actual_total_cost, actual_final_edges) = kruskal(edges);
 
         assert_eq!(actual_total_cost, expected_total_cost);
         assert_eq!(actual_final_edges, expected_used_edges);
@@ -90,7 +274,7 @@ mod tests {
 
     #[test]
     fn test_ten_vertices_twenty_edges() {
-        let mut edges: Vec<Edge> = Vec::new();
+        let mut edges: Vec<Edge<i64, i64>> = Vec::new();
         edges.push(Edge::new(0, 1, 3));
         edges.push(Edge::new(0, 3, 6));
         edges.push(Edge::new(0, 4, 9));
@@ -112,8 +296,6 @@ mod tests {
         edges.push(Edge::new(7, 9, 4));
         edges.push(Edge::new(8, 9, 3));
 
-        let number_of_vertices: i64 = 10;
-
         let expected_total_cost = 38;
         let mut expected_used_edges = Vec::new();
         expected_used_edges.push(Edge::new(7, 8, 1));
@@ -126,9 +308,187 @@ mod tests {
         expected_used_edges.push(Edge::new(2, 5, 8));
         expected_used_edges.push(Edge::new(4, 5, 8));
 
-        let (actual_total_cost, actual_final_edges) = kruskal(edges, number_of_vertices);
+        let (actual_total_cost, actual_final_edges) = kruskal(edges);
 
         assert_eq!(actual_total_cost, expected_total_cost);
         assert_eq!(actual_final_edges, expected_used_edges);
     }
+
+    #[test]
+    fn spanning_forest_of_disconnected_graph() {
+        let edges = vec![
+            Edge::new("a", "b", 4u32),
+            Edge::new("b", "c", 1),
+            Edge::new("a", "c", 2),
+            Edge::new("x", "y", 7),
+            Edge::new("y", "z", 3),
+            Edge::new("z", "x", 5),
+            Edge::new("c", "c", 0),
+        ];
+        // The self-loop never merges anything
+        let expected_used_edges = [
+            Edge::new("b", "c", 1),
+            Edge::new("a", "c", 2),
+            Edge::new("y", "z", 3),
+            Edge::new("z", "x", 5),
+        ];
+        let (total_cost, used_edges) = kruskal(edges.clone());
+        assert_eq!(total_cost, 11);
+        assert_eq!(used_edges, expected_used_edges);
+        let (total_cost, mut used_edges) = boruvka(edges);
+        used_edges.sort_by_key(|edge| edge.cost);
+        assert_eq!(total_cost, 11);
+        assert_eq!(used_edges, expected_used_edges);
+    }
+
+    #[test]
+    fn boruvka_matches_kruskal() {
+        let mut rng = PCG32::new_default(1729);
+        for _ in 0..50 {
+            let n = 1 + rng.get_u32() as usize % 15;
+            let m = rng.get_u32() as usize % 40;
+            let edges: Vec<Edge<usize, i64>> = (0..m)
+                .map(|_| {
+                    let u = rng.get_u32() as usize % n;
+                    let v = rng.get_u32() as usize % n;
+                    // Few distinct costs, to have many ties
+                    Edge::new(u, v, (rng.get_u32() % 5) as i64 - 2)
+                })
+                .collect();
+            let (kruskal_cost, kruskal_edges) = kruskal(edges.clone());
+            let (boruvka_cost, boruvka_edges) = boruvka(edges);
+            assert_eq!(kruskal_cost, boruvka_cost);
+            // Both must be forests with the same number of trees
+            assert_eq!(kruskal_edges.len(), boruvka_edges.len());
+            let mut dsu = DisjointSetUnion::new(n);
+            for edge in boruvka_edges {
+                assert!(dsu.merge(edge.source, edge.destination) < usize::MAX);
+            }
+        }
+    }
+
+    #[test]
+    fn arborescence() {
+        let edges = vec![
+            Edge::new(0, 1, 10),
+            Edge::new(0, 2, 8),
+            Edge::new(1, 2, 2),
+            Edge::new(2, 3, 3),
+            Edge::new(3, 1, 1),
+            Edge::new(3, 4, 6),
+            Edge::new(4, 0, 1),
+        ];
+        // The cheapest incoming edges make the cycle 1 -> 2 -> 3 -> 1, which
+        // is best entered at 2
+        let (total_cost, used_edges) = minimum_spanning_arborescence(edges.clone(), 0).unwrap();
+        assert_eq!(total_cost, 18);
+        assert_eq!(
+            used_edges,
+            vec![
+                Edge::new(0, 2, 8),
+                Edge::new(2, 3, 3),
+                Edge::new(3, 1, 1),
+                Edge::new(3, 4, 6),
+            ]
+        );
+        let (total_cost, _) = minimum_spanning_arborescence(edges.clone(), 4).unwrap();
+        assert_eq!(total_cost, 13);
+        // Nothing enters 5
+        let mut edges = edges;
+        edges.push(Edge::new(5, 4, 1));
+        assert_eq!(minimum_spanning_arborescence(edges, 0), None);
+    }
+
+    // Tries every choice of one incoming edge per vertex, other than the root 0
+    fn brute_force_arborescence(edges: &[Edge<usize, i64>]) -> Option<i64> {
+        let mut vertices: Vec<usize> = edges
+            .iter()
+            .flat_map(|e| [e.source, e.destination])
+            .filter(|&v| v != 0)
+            .collect();
+        vertices.sort_unstable();
+        vertices.dedup();
+        let incoming: BTreeMap<usize, Vec<&Edge<usize, i64>>> = vertices
+            .iter()
+            .map(|&v| (v, edges.iter().filter(|e| e.destination == v).collect()))
+            .collect();
+        if incoming.values().any(|edges| edges.is_empty()) {
+            return None;
+        }
+        let mut best = None;
+        let mut choice: BTreeMap<usize, usize> = vertices.iter().map(|&v| (v, 0)).collect();
+        loop {
+            let parent = |v: usize| incoming[&v][choice[&v]];
+            // Every vertex must reach the root by following its parents
+            let reaches_root = vertices.iter().all(|&v| {
+                let mut x = v;
+                for _ in 0..vertices.len() {
+                    if x != 0 {
+                        x = parent(x).source;
+                    }
+                }
+                x == 0
+            });
+            if reaches_root {
+                let cost: i64 = vertices.iter().map(|&v| parent(v).cost).sum();
+                best = Some(best.map_or(cost, |b: i64| b.min(cost)));
+            }
+            // Next choice, like an odometer
+            let next = vertices
+                .iter()
+                .find(|&&v| choice[&v] + 1 < incoming[&v].len());
+            match next {
+                Some(&v) => {
+                    for u in vertices.iter().take_while(|&&u| u != v) {
+                        choice.insert(*u, 0);
+                    }
+                    *choice.get_mut(&v).unwrap() += 1;
+                }
+                None => return best,
+            }
+        }
+    }
+
+    #[test]
+    fn arborescence_matches_brute_force() {
+        let mut rng = PCG32::new_default(4104);
+        for _ in 0..200 {
+            let n = 2 + rng.get_u32() as usize % 4;
+            let m = rng.get_u32() as usize % 12;
+            let edges: Vec<Edge<usize, i64>> = (0..m)
+                .map(|_| {
+                    let u = rng.get_u32() as usize % n;
+                    let v = rng.get_u32() as usize % n;
+                    Edge::new(u, v, (rng.get_u32() % 10) as i64)
+                })
+                .collect();
+            let expected = brute_force_arborescence(&edges);
+            let actual = minimum_spanning_arborescence(edges, 0);
+            if let Some((cost, used_edges)) = &actual {
+                assert_eq!(*cost, used_edges.iter().map(|e| e.cost).sum::<i64>());
+            }
+            assert_eq!(actual.map(|r| r.0), expected);
+        }
+    }
+
+    #[test]
+    fn edge_cases() {
+        let no_edges: Vec<Edge<usize, i64>> = vec![];
+        assert_eq!(kruskal(no_edges.clone()), (0, vec![]));
+        assert_eq!(boruvka(no_edges.clone()), (0, vec![]));
+        // The root alone is an arborescence
+        assert_eq!(
+            minimum_spanning_arborescence(no_edges, 7),
+            Some((0, vec![]))
+        );
+
+        // Self loops are never used
+        let loops = vec![Edge::new(1, 1, -5), Edge::new(2, 2, 1), Edge::new(1, 2, 3)];
+        assert_eq!(
+            minimum_spanning_arborescence(loops.clone(), 1),
+            Some((3, vec![Edge::new(1, 2, 3)]))
+        );
+        // Nothing but a self loop enters 1
+        assert_eq!(minimum_spanning_arborescence(loops, 2), None);
+    }
 }
@@ -1,38 +1,98 @@
 mod bellman_ford;
+mod biconnected_components;
 mod bipartite_matching;
 mod breadth_first_search;
 mod centroid_decomposition;
+mod cliques;
+mod connect_four;
 mod depth_first_search;
 mod depth_first_search_tic_tac_toe;
 mod dijkstra;
 mod dinic_maxflow;
 mod disjoint_set_union;
+mod dominator_tree;
+mod dsu_on_tree;
+mod euler_tour_tree;
+mod eulerian_path;
 mod floyd_warshall;
+mod game_tree_search;
+mod graph_coloring;
 mod graph_enumeration;
+mod graph_formats;
+mod graph_generators;
+mod graph_traversal;
+mod hamiltonian_path;
 mod heavy_light_decomposition;
+mod k_shortest_paths;
+mod link_cut_tree;
 mod lowest_common_ancestor;
 mod minimum_spanning_tree;
+mod monte_carlo_tree_search;
 mod prim;
 mod prufer_code;
+mod rerooting;
+mod satisfiability;
 mod strongly_connected_components;
 mod topological_sort;
+mod tree_diameter;
+mod tree_path_query;
 mod two_satisfiability;
 pub use self::bellman_ford::bellman_ford;
+pub use self::biconnected_components::BiconnectedComponents;
 pub use self::bipartite_matching::BipartiteMatching;
 pub use self::breadth_first_search::breadth_first_search;
 pub use self::centroid_decomposition::CentroidDecomposition;
+pub use self::cliques::{
+    is_clique, is_independent_set, maximal_cliques, maximum_clique, maximum_independent_set,
+};
+pub use self::connect_four::ConnectFour;
 pub use self::depth_first_search::depth_first_search;
-pub use self::depth_first_search_tic_tac_toe::minimax;
+pub use self::depth_first_search_tic_tac_toe::{minimax, Players, Position, TicTacToe};
 pub use self::dijkstra::dijkstra;
 pub use self::dinic_maxflow::DinicMaxFlow;
 pub use self::disjoint_set_union::DisjointSetUnion;
+pub use self::dominator_tree::{DominatorTree, NaturalLoop};
+pub use self::dsu_on_tree::{dsu_on_tree, SubtreeState};
+pub use self::euler_tour_tree::EulerTourTree;
+pub use self::eulerian_path::{
+    directed_eulerian_circuit, directed_eulerian_path, undirected_eulerian_circuit,
+    undirected_eulerian_path, EulerianPathError,
+};
 pub use self::floyd_warshall::floyd_warshall;
+pub use self::game_tree_search::{
+    alpha_beta, iterative_deepening, negamax, Game, SearchResult, TranspositionTable, ZobristKeys,
+    WIN_SCORE,
+};
+pub use self::graph_coloring::{chromatic_number, dsatur_coloring, greedy_coloring, Coloring};
 pub use self::graph_enumeration::enumerate_graph;
+pub use self::graph_formats::{
+    from_directed_graph, parse_dimacs_cnf, parse_dimacs_max_flow, parse_dimacs_shortest_path,
+    parse_dot, parse_edge_list, to_directed_graph, write_dimacs_cnf, write_dimacs_max_flow,
+    write_dimacs_shortest_path, write_dot, write_edge_list, GraphParseError,
+};
+pub use self::graph_generators::{
+    barabasi_albert, complete_graph, erdos_renyi, grid_graph, random_dag,
+    random_graph_with_negative_cycle, random_regular, random_weighted_graph, torus_graph,
+};
+pub use self::graph_traversal::{
+    breadth_first_traversal, depth_first_traversal, zero_one_breadth_first_search,
+    BreadthFirstForest, BreadthFirstVisitor, DepthFirstForest, DepthFirstVisitor,
+};
+pub use self::hamiltonian_path::{hamiltonian_cycle, hamiltonian_path, travelling_salesman};
 pub use self::heavy_light_decomposition::HeavyLightDecomposition;
+pub use self::k_shortest_paths::{
+    k_shortest_walks, shortest_path_dag, yen_k_shortest_paths, ShortestPathDag,
+};
+pub use self::link_cut_tree::LinkCutTree;
 pub use self::lowest_common_ancestor::{LowestCommonAncestorOffline, LowestCommonAncestorOnline};
-pub use self::minimum_spanning_tree::kruskal;
+pub use self::minimum_spanning_tree::{boruvka, kruskal, minimum_spanning_arborescence, Edge};
+pub use self::monte_carlo_tree_search::{MctsResult, MonteCarloTreeSearch};
 pub use self::prim::{prim, prim_with_start};
 pub use self::prufer_code::{prufer_decode, prufer_encode};
+pub use self::rerooting::rerooting;
+pub use self::satisfiability::solve_satisfiability;
 pub use self::strongly_connected_components::StronglyConnectedComponents;
 pub use self::topological_sort::topological_sort;
+pub use self::tree_diameter::{tree_center, tree_diameter, tree_radius};
+pub use self::tree_path_query::TreePathQuery;
 pub use self::two_satisfiability::solve_two_satisfiability;
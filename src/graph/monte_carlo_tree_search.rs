@@ -0,0 +1,287 @@
+/*
+Monte Carlo Tree Search:
+Instead of exploring every move down to a fixed depth like alpha-beta, MCTS
+grows a tree of the positions that look the most promising, and scores them
+by playing random games (rollouts) to the end. Each iteration has 4 steps:
+1. Selection: from the root, go down the tree while every move of the
+   position has been tried, choosing the child maximising the UCT (Upper
+   Confidence bounds applied to Trees) formula
+       wins / visits + c * sqrt(ln(parent visits) / visits),
+   which balances the moves that won most often (exploitation) and the ones
+   that were rarely tried (exploration).
+2. Expansion: add a child for one of the untried moves.
+3. Simulation: play random moves from there until the game is over.
+4. Backpropagation: add the result to the statistics of the nodes on the
+   path, as a win (1), a draw (1/2) or a loss (0) for the player who made
+   the move leading to each node.
+The move played is the most visited child of the root.
+https://en.wikipedia.org/wiki/Monte_Carlo_tree_search
+
+It works with any `Game`, without a heuristic, and stops after a number of
+iterations or a time limit. The rollouts are seeded, so that the search is
+reproducible with an iteration budget.
+*/
+
+use super::Game;
+use crate::math::PCG32;
+use std::time::{Duration, Instant};
+
+struct Node<M> {
+    parent: usize,
+    children: Vec<usize>,
+    // The move leading to this node, None for the root
+    last_move: Option<M>,
+    untried: Vec<M>,
+    visits: u32,
+    // The total reward for the player who made `last_move`
+    reward: f64,
+}
+
+impl<M> Node<M> {
+    fn new(parent: usize, last_move: Option<M>, untried: Vec<M>) -> Self {
+        Node {
+            parent,
+            children: vec![],
+            last_move,
+            untried,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsResult<M> {
+    // None when the game is over
+    pub best_move: Option<M>,
+    // The average reward of the best move, from 0 (lost) to 1 (won)
+    pub win_rate: f64,
+    pub iterations: usize,
+}
+
+pub struct MonteCarloTreeSearch {
+    pub max_iterations: usize,
+    pub time_limit: Option<Duration>,
+    // The constant c of the UCT formula
+    pub exploration: f64,
+    rng: PCG32,
+}
+
+impl MonteCarloTreeSearch {
+    pub fn new(max_iterations: usize, seed: u64) -> Self {
+        MonteCarloTreeSearch {
+            max_iterations,
+            time_limit: None,
+            exploration: std::f64::consts::SQRT_2,
+            rng: PCG32::new_default(seed),
+        }
+    }
+
+    /// Also stops the search after `time_limit`.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.rng.get_u32() as usize % n
+    }
+
+    // The child of `node` with the best UCT score
+    fn select<M>(&self, tree: &[Node<M>], node: usize) -> usize {
+        let log_visits = (tree[node].visits as f64).ln();
+        let uct = |child: &Node<M>| {
+            let visits = child.visits as f64;
+            child.reward / visits + self.exploration * (log_visits / visits).sqrt()
+        };
+        *tree[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(&tree[a]).total_cmp(&uct(&tree[b])))
+            .unwrap()
+    }
+
+    /// Searches the best move for the player to move. The game is left as it
+    /// was.
+    pub fn search<G: Game>(&mut self, game: &mut G) -> MctsResult<G::Move> {
+        let start = Instant::now();
+        let mut tree = vec![Node::new(0, None, game.moves())];
+        let mut iterations = 0;
+        let mut played = vec![];
+        while iterations < self.max_iterations
+            && self.time_limit.is_none_or(|limit| start.elapsed() < limit)
+            && (!tree[0].untried.is_empty() || !tree[0].children.is_empty())
+        {
+            iterations += 1;
+            let mut node = 0;
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                node = self.select(&tree, node);
+                let m = tree[node].last_move.unwrap();
+                game.apply(m);
+                played.push(m);
+            }
+            if !tree[node].untried.is_empty() {
+                let i = self.below(tree[node].untried.len());
+                let m = tree[node].untried.swap_remove(i);
+                game.apply(m);
+                played.push(m);
+                tree.push(Node::new(node, Some(m), game.moves()));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                node = child;
+            }
+
+            // Random moves until the end, counting them to know who won
+            let mut rollout_length = 0;
+            loop {
+                let moves = game.moves();
+                if moves.is_empty() {
+                    break;
+                }
+                let m = moves[self.below(moves.len())];
+                game.apply(m);
+                played.push(m);
+                rollout_length += 1;
+            }
+            let score = game.terminal_score().unwrap_or(0);
+            // The reward of the player to move at the end, and then of the
+            // player who made the move leading to `node`
+            let mut reward = match score.signum() {
+                1 => 1.0,
+                0 => 0.5,
+                _ => 0.0,
+            };
+            if rollout_length & 1 == 0 {
+                reward = 1.0 - reward;
+            }
+            while let Some(m) = played.pop() {
+                game.undo(m);
+            }
+
+            loop {
+                tree[node].visits += 1;
+                tree[node].reward += reward;
+                if node == 0 {
+                    break;
+                }
+                reward = 1.0 - reward;
+                node = tree[node].parent;
+            }
+        }
+
+        let best = tree[0]
+            .children
+            .iter()
+            .copied()
+            .max_by_key(|&child| tree[child].visits);
+        MctsResult {
+            best_move: best.and_then(|child| tree[child].last_move),
+            win_rate: best.map_or(0.0, |child| tree[child].reward / tree[child].visits as f64),
+            iterations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{ConnectFour, Players, Position, TicTacToe};
+
+    // Plays a game between MCTS and a random player, and returns the winner
+    fn play_against_random(mcts_side: Players, seed: u64) -> Players {
+        let mut mcts = MonteCarloTreeSearch::new(1500, seed);
+        let mut rng = PCG32::new_default(seed + 1000);
+        let mut game = TicTacToe::new();
+        while !game.moves().is_empty() {
+            let m = if game.side == mcts_side {
+                mcts.search(&mut game).best_move.unwrap()
+            } else {
+                let moves = game.moves();
+                moves[rng.get_u32() as usize % moves.len()]
+            };
+            game.apply(m);
+        }
+        game.winner()
+    }
+
+    #[test]
+    fn never_loses_against_random_player() {
+        for seed in 0..10 {
+            assert_ne!(
+                play_against_random(Players::PlayerX, seed),
+                Players::PlayerO
+            );
+            assert_ne!(
+                play_against_random(Players::PlayerO, seed),
+                Players::PlayerX
+            );
+        }
+    }
+
+    #[test]
+    fn tic_tac_toe_tactics() {
+        // X wins at the top right, and must also stop O there
+        let mut board = vec![vec![Players::Blank; 3]; 3];
+        board[0][0] = Players::PlayerX;
+        board[0][1] = Players::PlayerX;
+        board[1][2] = Players::PlayerO;
+        board[2][2] = Players::PlayerO;
+        let mut game = TicTacToe::from_board(board.clone(), Players::PlayerX);
+        let mut mcts = MonteCarloTreeSearch::new(500, 1);
+        let result = mcts.search(&mut game);
+        assert_eq!(result.best_move, Some(Position { x: 2, y: 0 }));
+        assert!(result.win_rate > 0.9);
+        assert_eq!(result.iterations, 500);
+        assert_eq!(game.board, board);
+
+        let mut game = TicTacToe::from_board(board, Players::PlayerO);
+        let result = mcts.search(&mut game);
+        assert_eq!(result.best_move, Some(Position { x: 2, y: 0 }));
+
+        // The game is over
+        game.apply(Position { x: 2, y: 0 });
+        let result = mcts.search(&mut game);
+        assert_eq!((result.best_move, result.iterations), (None, 0));
+    }
+
+    #[test]
+    fn connect_four() {
+        // The second player must block the first column
+        let mut game = ConnectFour::from_moves(&[0, 3, 0, 3, 0]);
+        let mut mcts = MonteCarloTreeSearch::new(2000, 7);
+        assert_eq!(mcts.search(&mut game).best_move, Some(0));
+
+        // Stops after the time limit, well before the iterations
+        let mut mcts =
+            MonteCarloTreeSearch::new(usize::MAX, 7).with_time_limit(Duration::from_millis(20));
+        let start = Instant::now();
+        let result = mcts.search(&mut ConnectFour::new());
+        assert!(result.best_move.is_some());
+        assert!(result.iterations > 0);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn edge_cases() {
+        let mut mcts = MonteCarloTreeSearch::new(0, 1);
+        let result = mcts.search(&mut TicTacToe::new());
+        assert_eq!(
+            result,
+            MctsResult {
+                best_move: None,
+                win_rate: 0.0,
+                iterations: 0,
+            }
+        );
+
+        // A single move is left, and the game ends in a draw
+        let (x, o) = (Players::PlayerX, Players::PlayerO);
+        let board = vec![vec![x, o, x], vec![x, o, o], vec![o, x, Players::Blank]];
+        let mut game = TicTacToe::from_board(board, x);
+        let mut mcts = MonteCarloTreeSearch::new(10, 1);
+        let result = mcts.search(&mut game);
+        assert_eq!(result.best_move, Some(Position { x: 2, y: 2 }));
+        assert_eq!(result.win_rate, 0.5);
+        assert_eq!(result.iterations, 10);
+    }
+}
@@ -0,0 +1,199 @@
+/*
+Rerooting:
+Many tree problems are solved with a dynamic programming over the subtrees
+of a rooted tree, for example the sum of the distances from the root to all
+the vertices. Running it from every root takes O(n^2) time, but rerooting
+computes the value for every root in O(n) calls:
+1. Compute the value of each subtree for an arbitrary root, bottom up.
+2. Going top down, compute for each vertex the value of the "upper" subtree,
+   made of its parent and everything that is not below it. For a vertex v
+   with k neighbours, the value of each neighbour's side without one of them
+   is a product of a prefix and a suffix of the other k - 1 values, so all
+   of them take O(k) time.
+
+The dynamic programming is given by two closures:
+- `combine(a, b)`, which merges the values of two neighbouring subtrees of a
+  vertex, associative with `identity` as the neutral element,
+- `apply(merged, v)`, which turns the merged values of the subtrees below v
+  into the value of the subtree rooted at v.
+
+The tree (or forest) is an undirected adjacency list with vertices numbered
+from 1 to n. If this is not the case, one can use
+`src/graph/graph_enumeration.rs` to convert their graph.
+*/
+
+type Adj = [Vec<usize>];
+
+/// Returns the value of the dynamic programming for each vertex taken as the
+/// root of its tree. The value of the unused vertex 0 is `identity`.
+pub fn rerooting<T, C, A>(adj: &Adj, identity: T, combine: C, apply: A) -> Vec<T>
+where
+    T: Clone,
+    C: Fn(&T, &T) -> T,
+    A: Fn(&T, usize) -> T,
+{
+    let n = adj.len();
+    let mut parent = vec![0; n];
+    let mut visited = vec![false; n];
+    // Breadth First Search order, so that parents come before children
+    let mut order = Vec::with_capacity(n);
+    for root in 1..n {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let start = order.len();
+        order.push(root);
+        let mut i = start;
+        while i < order.len() {
+            let v = order[i];
+            for &u in adj[v].iter() {
+                if !visited[u] {
+                    visited[u] = true;
+                    parent[u] = v;
+                    order.push(u);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    // The value of the subtree of each vertex, for the first root
+    let mut down = vec![identity.clone(); n];
+    for &v in order.iter().rev() {
+        let merged = adj[v]
+            .iter()
+            .filter(|&&u| parent[u] == v)
+            .fold(identity.clone(), |acc, &u| combine(&acc, &down[u]));
+        down[v] = apply(&merged, v);
+    }
+
+    // The value of the upper subtree of each vertex, rooted at its parent
+    let mut up = vec![identity.clone(); n];
+    let mut result = vec![identity.clone(); n];
+    for &v in order.iter() {
+        let children: Vec<usize> = adj[v].iter().copied().filter(|&u| parent[u] == v).collect();
+        let mut values: Vec<&T> = children.iter().map(|&u| &down[u]).collect();
+        if parent[v] != 0 {
+            values.push(&up[v]);
+        }
+        // suffix[i] merges the values from i on
+        let mut suffix = vec![identity.clone(); values.len() + 1];
+        for i in (0..values.len()).rev() {
+            suffix[i] = combine(values[i], &suffix[i + 1]);
+        }
+        result[v] = apply(&suffix[0], v);
+        let mut prefix = identity.clone();
+        let mut child_up = Vec::with_capacity(children.len());
+        for (i, &u) in children.iter().enumerate() {
+            child_up.push((u, apply(&combine(&prefix, &suffix[i + 1]), v)));
+            prefix = combine(&prefix, values[i]);
+        }
+        for (u, value) in child_up {
+            up[u] = value;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::barabasi_albert;
+    use crate::math::PCG32;
+
+    fn distances(adj: &Adj, source: usize) -> Vec<Option<usize>> {
+        let mut distance = vec![None; adj.len()];
+        distance[source] = Some(0);
+        let mut queue = std::collections::VecDeque::from([source]);
+        while let Some(v) = queue.pop_front() {
+            for &u in adj[v].iter() {
+                if distance[u].is_none() {
+                    distance[u] = Some(distance[v].unwrap() + 1);
+                    queue.push_back(u);
+                }
+            }
+        }
+        distance
+    }
+
+    // The number of vertices and the sum of their distances to the root
+    fn sum_of_distances(adj: &Adj) -> Vec<(usize, usize)> {
+        rerooting(
+            adj,
+            (0, 0),
+            |a, b| (a.0 + b.0, a.1 + b.1),
+            |&(count, sum), _| (count + 1, sum + count),
+        )
+    }
+
+    // One more than the largest distance from the root to a vertex
+    fn eccentricity(adj: &Adj) -> Vec<usize> {
+        rerooting(adj, 0, |a, b| *a.max(b), |a, _| a + 1)
+    }
+
+    #[test]
+    fn small_tree() {
+        //     1
+        //   / | \
+        //  2  3  4
+        //  |     |
+        //  5     6 - 7
+        let adj = vec![
+            vec![],
+            vec![2, 3, 4],
+            vec![1, 5],
+            vec![1],
+            vec![1, 6],
+            vec![2],
+            vec![4, 7],
+            vec![6],
+        ];
+        let sums: Vec<usize> = sum_of_distances(&adj).iter().map(|s| s.1).collect();
+        assert_eq!(sums, vec![0, 10, 13, 15, 11, 18, 14, 19]);
+        assert_eq!(eccentricity(&adj), vec![0, 4, 5, 5, 4, 6, 5, 6]);
+        // The labels of the vertices in the order of a path from the root
+        let labels = rerooting(
+            &adj,
+            String::new(),
+            |a, b| format!("{a}{b}"),
+            |a, v| format!("({v}{a})"),
+        );
+        assert_eq!(labels[7], "(7(6(4(1(2(5))(3)))))");
+        assert_eq!(rerooting(&[vec![]], 0, |a, b| a + b, |a, _| a + 1), vec![0]);
+    }
+
+    #[test]
+    fn random_forests() {
+        let mut rng = PCG32::new_default(37);
+        for _ in 0..20 {
+            let n = 1 + rng.get_u32() as usize % 40;
+            let mut adj = barabasi_albert(n, 1, &mut rng);
+            // A second tree, to make a forest
+            let m = rng.get_u32() as usize % 5;
+            for v in n + 1..=n + m {
+                adj.push(vec![]);
+                if v > n + 1 {
+                    let u = n + 1 + rng.get_u32() as usize % (v - n - 1);
+                    adj[u].push(v);
+                    adj[v].push(u);
+                }
+            }
+            let sums = sum_of_distances(&adj);
+            let eccentricities = eccentricity(&adj);
+            for v in 1..adj.len() {
+                let distance: Vec<usize> = distances(&adj, v).into_iter().flatten().collect();
+                assert_eq!(sums[v], (distance.len(), distance.iter().sum()));
+                assert_eq!(eccentricities[v], distance.iter().max().unwrap() + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn isolated_vertices() {
+        // Every vertex is the only vertex of its tree
+        let adj = vec![vec![]; 4];
+        assert_eq!(sum_of_distances(&adj), vec![(0, 0), (1, 0), (1, 0), (1, 0)]);
+        assert_eq!(eccentricity(&adj), vec![0, 1, 1, 1]);
+    }
+}
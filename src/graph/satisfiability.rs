@@ -0,0 +1,613 @@
+/*
+Boolean satisfiability:
+Given a formula in conjunctive normal form, that is a list of clauses that
+must all be satisfied, each clause being a list of literals of which at least
+one must be true, find an assignment of the variables satisfying all the
+clauses, or prove that there is none.
+
+When every clause has at most two literals, the problem is solved in linear
+time by `solve_two_satisfiability`. Otherwise, it is NP-complete, and it is
+solved by Conflict-Driven Clause Learning (CDCL):
+1. Unit propagation: when all the literals of a clause but one are false, the
+   last one must be true. Each clause only "watches" two of its literals that
+   are not false, and it is visited only when one of them becomes false.
+2. When no clause propagates anything, a variable is chosen and assigned
+   (a "decision"), preferring the variables involved in recent conflicts
+   (VSIDS) and the value they had last time (phase saving).
+3. When a clause becomes false (a "conflict"), the decisions that caused it
+   are analysed to learn a new clause, which is added to the formula. The
+   solver then backtracks to the level where this clause propagates (first
+   unique implication point).
+4. The search restarts from scratch from time to time, following the Luby
+   sequence, keeping the learnt clauses.
+The formula is unsatisfiable when a conflict happens with no decision.
+
+Like in `solve_two_satisfiability`, variables are numbered from 1 to n, and a
+negative number -m corresponds to the negated variable m. For more
+information, please visit: https://en.wikipedia.org/wiki/Conflict-driven_clause_learning
+*/
+
+use super::solve_two_satisfiability;
+
+// Literals are numbered like in `solve_two_satisfiability`: 2m for the
+// variable m, and 2m + 1 for its negation
+#[inline]
+fn literal(var: i64) -> usize {
+    if var < 0 {
+        (((-var) << 1) + 1) as usize
+    } else {
+        (var << 1) as usize
+    }
+}
+
+// The element of the Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, ...
+fn luby(mut index: u64) -> u64 {
+    let mut size = 1;
+    let mut power = 1;
+    while size < index + 1 {
+        size = 2 * size + 1;
+        power *= 2;
+    }
+    while size - 1 != index {
+        size = (size - 1) / 2;
+        power /= 2;
+        index %= size;
+    }
+    power
+}
+
+const RESTART_INTERVAL: u64 = 100;
+const ACTIVITY_DECAY: f64 = 0.95;
+const NOT_IN_HEAP: usize = usize::MAX;
+
+// A binary max-heap of variables keyed by their activity, which also knows
+// where each variable is, so that a bumped variable can be moved up
+struct VariableOrder {
+    heap: Vec<usize>,
+    // The index of each variable in the heap, or NOT_IN_HEAP
+    position: Vec<usize>,
+}
+
+impl VariableOrder {
+    fn new(num_variables: usize) -> Self {
+        VariableOrder {
+            heap: (1..=num_variables).collect(),
+            position: (0..=num_variables)
+                .map(|var| var.checked_sub(1).unwrap_or(NOT_IN_HEAP))
+                .collect(),
+        }
+    }
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.position[self.heap[i]] = i;
+        self.position[self.heap[j]] = j;
+    }
+    fn sift_up(&mut self, mut i: usize, activity: &[f64]) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if activity[self.heap[parent]] >= activity[self.heap[i]] {
+                break;
+            }
+            self.swap(i, parent);
+            i = parent;
+        }
+    }
+    fn sift_down(&mut self, mut i: usize, activity: &[f64]) {
+        loop {
+            let mut largest = i;
+            for child in [2 * i + 1, 2 * i + 2] {
+                if child < self.heap.len()
+                    && activity[self.heap[child]] > activity[self.heap[largest]]
+                {
+                    largest = child;
+                }
+            }
+            if largest == i {
+                break;
+            }
+            self.swap(i, largest);
+            i = largest;
+        }
+    }
+    fn insert(&mut self, var: usize, activity: &[f64]) {
+        if self.position[var] == NOT_IN_HEAP {
+            self.position[var] = self.heap.len();
+            self.heap.push(var);
+            self.sift_up(self.heap.len() - 1, activity);
+        }
+    }
+    // Restores the heap after the activity of `var` increased
+    fn increased(&mut self, var: usize, activity: &[f64]) {
+        if self.position[var] != NOT_IN_HEAP {
+            self.sift_up(self.position[var], activity);
+        }
+    }
+    fn pop(&mut self, activity: &[f64]) -> Option<usize> {
+        let last = self.heap.len().checked_sub(1)?;
+        self.swap(0, last);
+        let var = self.heap.pop().unwrap();
+        self.position[var] = NOT_IN_HEAP;
+        self.sift_down(0, activity);
+        Some(var)
+    }
+}
+
+struct Solver {
+    clauses: Vec<Vec<usize>>,
+    // The clauses watching each literal, they are visited when it becomes
+    // false. The watched literals of a clause are its first two
+    watches: Vec<Vec<usize>>,
+    value: Vec<Option<bool>>,
+    level: Vec<usize>,
+    // The clause that propagated each variable, None for decisions
+    reason: Vec<Option<usize>>,
+    // The assigned literals, in order, and where each decision level starts
+    trail: Vec<usize>,
+    trail_limits: Vec<usize>,
+    // The trail before this index has been propagated
+    propagated: usize,
+    activity: Vec<f64>,
+    activity_increment: f64,
+    // The unassigned variables are always in the heap, the assigned ones
+    // are removed lazily when they reach the top
+    order: VariableOrder,
+    saved_phase: Vec<bool>,
+    // The variables seen while analysing a conflict, all false in between
+    seen: Vec<bool>,
+}
+
+impl Solver {
+    fn new(num_variables: usize) -> Self {
+        let num_literals = (num_variables + 1) << 1;
+        Solver {
+            clauses: vec![],
+            watches: vec![vec![]; num_literals],
+            value: vec![None; num_variables + 1],
+            level: vec![0; num_variables + 1],
+            reason: vec![None; num_variables + 1],
+            trail: vec![],
+            trail_limits: vec![],
+            propagated: 0,
+            activity: vec![0.0; num_variables + 1],
+            activity_increment: 1.0,
+            order: VariableOrder::new(num_variables),
+            saved_phase: vec![false; num_variables + 1],
+            seen: vec![false; num_variables + 1],
+        }
+    }
+    #[inline]
+    fn literal_value(&self, lit: usize) -> Option<bool> {
+        self.value[lit >> 1].map(|value| value ^ (lit & 1 == 1))
+    }
+    fn decision_level(&self) -> usize {
+        self.trail_limits.len()
+    }
+    fn assign(&mut self, lit: usize, reason: Option<usize>) {
+        let var = lit >> 1;
+        self.value[var] = Some(lit & 1 == 0);
+        self.level[var] = self.decision_level();
+        self.reason[var] = reason;
+        self.trail.push(lit);
+    }
+    // Adds a clause with at least two literals, the first two are watched
+    fn add_clause(&mut self, clause: Vec<usize>) -> usize {
+        let index = self.clauses.len();
+        self.watches[clause[0]].push(index);
+        self.watches[clause[1]].push(index);
+        self.clauses.push(clause);
+        index
+    }
+    // Returns a clause that became false, if any
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_lit = self.trail[self.propagated] ^ 1;
+            self.propagated += 1;
+            let watching = std::mem::take(&mut self.watches[false_lit]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = None;
+            for (i, &c) in watching.iter().enumerate() {
+                if conflict.is_some() {
+                    kept.extend_from_slice(&watching[i..]);
+                    break;
+                }
+                let clause = &mut self.clauses[c];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let other = clause[0];
+                if self.value[other >> 1].map(|value| value ^ (other & 1 == 1)) == Some(true) {
+                    kept.push(c);
+                    continue;
+                }
+                // Look for a new literal to watch
+                let replacement = (2..clause.len()).find(|&k| {
+                    let lit = clause[k];
+                    self.value[lit >> 1].map(|value| value ^ (lit & 1 == 1)) != Some(false)
+                });
+                if let Some(k) = replacement {
+                    clause.swap(1, k);
+                    let lit = clause[1];
+                    self.watches[lit].push(c);
+                    continue;
+                }
+                kept.push(c);
+                match self.literal_value(other) {
+                    Some(false) => conflict = Some(c),
+                    _ => self.assign(other, Some(c)),
+                }
+            }
+            self.watches[false_lit] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+    fn bump_activity(&mut self, var: usize) {
+        self.activity[var] += self.activity_increment;
+        // Scaling all the activities keeps the heap ordered
+        if self.activity[var] > 1e100 {
+            self.activity.iter_mut().for_each(|a| *a *= 1e-100);
+            self.activity_increment *= 1e-100;
+        }
+        self.order.increased(var, &self.activity);
+    }
+    // Returns the clause learnt from a conflict, with the literal it
+    // propagates first, and the level to backtrack to
+    fn analyze(&mut self, conflict: usize) -> (Vec<usize>, usize) {
+        let mut learnt = vec![0];
+        // The number of literals of the current level left to resolve
+        let mut pending = 0;
+        let mut clause = conflict;
+        let mut index = self.trail.len();
+        let mut resolved = None;
+        loop {
+            // The first literal of a reason is the one it propagated
+            let skip = resolved.is_some() as usize;
+            for k in skip..self.clauses[clause].len() {
+                let lit = self.clauses[clause][k];
+                let var = lit >> 1;
+                if !self.seen[var] && self.level[var] > 0 {
+                    self.seen[var] = true;
+                    self.bump_activity(var);
+                    if self.level[var] == self.decision_level() {
+                        pending += 1;
+                    } else {
+                        learnt.push(lit);
+                    }
+                }
+            }
+            // The last assigned literal of the current level in the clause
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index] >> 1] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            self.seen[lit >> 1] = false;
+            resolved = Some(lit);
+            pending -= 1;
+            if pending == 0 {
+                break;
+            }
+            clause = self.reason[lit >> 1].unwrap();
+        }
+        learnt[0] = resolved.unwrap() ^ 1;
+        // The variables of the current level were cleared when resolved
+        for &lit in learnt[1..].iter() {
+            self.seen[lit >> 1] = false;
+        }
+        // Watch the literal of the highest level after the first one
+        let mut backtrack_level = 0;
+        if learnt.len() > 1 {
+            let highest = (1..learnt.len())
+                .max_by_key(|&k| self.level[learnt[k] >> 1])
+                .unwrap();
+            learnt.swap(1, highest);
+            backtrack_level = self.level[learnt[1] >> 1];
+        }
+        (learnt, backtrack_level)
+    }
+    fn backtrack(&mut self, level: usize) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let limit = self.trail_limits[level];
+        for &lit in self.trail[limit..].iter() {
+            let var = lit >> 1;
+            self.saved_phase[var] = lit & 1 == 0;
+            self.value[var] = None;
+            self.reason[var] = None;
+            self.order.insert(var, &self.activity);
+        }
+        self.trail.truncate(limit);
+        self.trail_limits.truncate(level);
+        self.propagated = limit;
+    }
+    // The unassigned variable with the highest activity
+    fn pick_branching_variable(&mut self) -> Option<usize> {
+        while let Some(var) = self.order.pop(&self.activity) {
+            if self.value[var].is_none() {
+                return Some(var);
+            }
+        }
+        None
+    }
+    fn solve(&mut self) -> Option<Vec<bool>> {
+        let mut restarts = 0;
+        let mut conflicts = 0;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                if self.decision_level() == 0 {
+                    return None;
+                }
+                conflicts += 1;
+                let (learnt, backtrack_level) = self.analyze(conflict);
+                self.backtrack(backtrack_level);
+                if learnt.len() == 1 {
+                    self.assign(learnt[0], None);
+                } else {
+                    let lit = learnt[0];
+                    let index = self.add_clause(learnt);
+                    self.assign(lit, Some(index));
+                }
+                self.activity_increment /= ACTIVITY_DECAY;
+            } else if conflicts >= RESTART_INTERVAL * luby(restarts) {
+                restarts += 1;
+                conflicts = 0;
+                self.backtrack(0);
+            } else {
+                let var = match self.pick_branching_variable() {
+                    Some(var) => var,
+                    None => return Some(self.value.iter().map(|v| v.unwrap_or(false)).collect()),
+                };
+                self.trail_limits.push(self.trail.len());
+                let lit = (var << 1) | (!self.saved_phase[var]) as usize;
+                self.assign(lit, None);
+            }
+        }
+    }
+}
+
+/// Returns an assignment that satisfies all the clauses, or None if there is
+/// none. Variables should be numbered from 1 to n, and a negative number -m
+/// corresponds to the negated variable m. The assignment of variable m is at
+/// index m of the result. None is also returned if a literal is 0 or its
+/// variable is above `num_variables`, as such a clause has no meaning.
+pub fn solve_satisfiability(clauses: &[Vec<i64>], num_variables: usize) -> Option<Vec<bool>> {
+    let valid = |&var: &i64| var != 0 && var.unsigned_abs() <= num_variables as u64;
+    if !clauses.iter().flatten().all(valid) {
+        return None;
+    }
+    if clauses.iter().any(|clause| clause.is_empty()) {
+        return None;
+    }
+    if clauses.iter().all(|clause| clause.len() <= 2) {
+        let conditions: Vec<(i64, i64)> = clauses
+            .iter()
+            .map(|clause| (clause[0], *clause.last().unwrap()))
+            .collect();
+        return solve_two_satisfiability(&conditions, num_variables).ok();
+    }
+    let mut solver = Solver::new(num_variables);
+    for clause in clauses {
+        let mut clause: Vec<usize> = clause.iter().map(|&var| literal(var)).collect();
+        clause.sort_unstable();
+        clause.dedup();
+        // A clause with both a literal and its negation is always satisfied
+        if clause.windows(2).any(|w| w[0] ^ 1 == w[1]) {
+            continue;
+        }
+        if clause.len() == 1 {
+            match solver.literal_value(clause[0]) {
+                Some(false) => return None,
+                Some(true) => {}
+                None => solver.assign(clause[0], None),
+            }
+        } else {
+            solver.add_clause(clause);
+        }
+    }
+    solver.solve()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::parse_dimacs_cnf;
+    use crate::math::PCG32;
+
+    fn check_answer(clauses: &[Vec<i64>], answers: &[bool]) -> bool {
+        clauses.iter().all(|clause| {
+            clause.iter().any(|&var| {
+                if var < 0 {
+                    !answers[-var as usize]
+                } else {
+                    answers[var as usize]
+                }
+            })
+        })
+    }
+
+    fn brute_force(clauses: &[Vec<i64>], num_variables: usize) -> bool {
+        (0..1u32 << num_variables).any(|mask| {
+            let answers: Vec<bool> = (0..=num_variables)
+                .map(|var| var > 0 && mask >> (var - 1) & 1 == 1)
+                .collect();
+            check_answer(clauses, &answers)
+        })
+    }
+
+    // Putting n + 1 pigeons in n holes, the variable of pigeon p in hole h is
+    // p * n + h + 1
+    fn pigeonhole(n: usize) -> (Vec<Vec<i64>>, usize) {
+        let var = |p: usize, h: usize| (p * n + h + 1) as i64;
+        let mut clauses = vec![];
+        for p in 0..=n {
+            clauses.push((0..n).map(|h| var(p, h)).collect());
+        }
+        for h in 0..n {
+            for p in 0..=n {
+                for q in p + 1..=n {
+                    clauses.push(vec![-var(p, h), -var(q, h)]);
+                }
+            }
+        }
+        (clauses, (n + 1) * n)
+    }
+
+    #[test]
+    fn basic_test() {
+        let clauses = vec![
+            vec![1, 2, 3],
+            vec![-1, -2],
+            vec![-1, -3],
+            vec![-2, -3],
+            vec![-1],
+        ];
+        let res = solve_satisfiability(&clauses, 3).unwrap();
+        assert!(check_answer(&clauses, &res));
+
+        let clauses = vec![vec![1, 2, 3], vec![-1], vec![-2], vec![-3, -3]];
+        assert_eq!(solve_satisfiability(&clauses, 3), None);
+        assert_eq!(solve_satisfiability(&[vec![1, 2, 3], vec![]], 3), None);
+        assert_eq!(solve_satisfiability(&[], 2).map(|res| res.len()), Some(3));
+        // Tautologies and repeated literals
+        let clauses = vec![
+            vec![1, -1, 2],
+            vec![2, 2, 2],
+            vec![-2, 3, -2],
+            vec![-3, 1, 4],
+        ];
+        let res = solve_satisfiability(&clauses, 4).unwrap();
+        assert!(check_answer(&clauses, &res));
+    }
+
+    #[test]
+    fn two_satisfiability_fast_path() {
+        let clauses = vec![vec![1, 2], vec![-1, 3], vec![-3], vec![-2, 4]];
+        let res = solve_satisfiability(&clauses, 4).unwrap();
+        assert!(check_answer(&clauses, &res));
+        let clauses = vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]];
+        assert_eq!(solve_satisfiability(&clauses, 2), None);
+    }
+
+    #[test]
+    fn pigeonhole_is_unsatisfiable() {
+        for n in 1..=6 {
+            let (clauses, num_variables) = pigeonhole(n);
+            assert_eq!(solve_satisfiability(&clauses, num_variables), None);
+            // With one pigeon less, it fits
+            let (clauses, num_variables) = pigeonhole(n);
+            let clauses: Vec<Vec<i64>> = clauses
+                .into_iter()
+                .filter(|clause| {
+                    clause
+                        .iter()
+                        .all(|&var| var.unsigned_abs() as usize <= n * n)
+                })
+                .collect();
+            let res = solve_satisfiability(&clauses, num_variables).unwrap();
+            assert!(check_answer(&clauses, &res));
+        }
+    }
+
+    #[test]
+    fn random_three_satisfiability() {
+        let mut rng = PCG32::new_default(1234);
+        let num_variables = 12;
+        let (mut satisfiable, mut unsatisfiable) = (0, 0);
+        for _ in 0..200 {
+            // Around the ratio of 4.26 clauses per variable, where random
+            // formulas go from satisfiable to unsatisfiable
+            let clauses: Vec<Vec<i64>> = (0..51)
+                .map(|_| {
+                    (0..3)
+                        .map(|_| {
+                            let var = (rng.get_u32() % num_variables as u32) as i64 + 1;
+                            if rng.get_u32() & 1 == 0 {
+                                var
+                            } else {
+                                -var
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            match solve_satisfiability(&clauses, num_variables) {
+                Some(res) => {
+                    assert!(check_answer(&clauses, &res));
+                    satisfiable += 1;
+                }
+                None => {
+                    assert!(!brute_force(&clauses, num_variables));
+                    unsatisfiable += 1;
+                }
+            }
+        }
+        assert!(satisfiable > 0 && unsatisfiable > 0);
+    }
+
+    #[test]
+    fn dimacs_input() {
+        let input = "c Exactly one of 1, 2, 3 and not 1\np cnf 3 5\n1 2 3 0\n-1 -2 0\n-1 -3 0\n-2 -3 0\n-1 0\n";
+        let (num_variables, clauses) = parse_dimacs_cnf(input).unwrap();
+        let res = solve_satisfiability(&clauses, num_variables).unwrap();
+        assert!(check_answer(&clauses, &res));
+        assert_eq!(res.iter().filter(|&&value| value).count(), 1);
+    }
+
+    #[test]
+    fn edge_cases() {
+        // Only tautologies, with no variable left to constrain
+        let res = solve_satisfiability(&[vec![1, -1, 2]], 2).unwrap();
+        assert_eq!(res.len(), 3);
+        // Contradictory unit clauses next to a longer clause
+        let clauses = vec![vec![1, 2, 3], vec![1], vec![-1]];
+        assert_eq!(solve_satisfiability(&clauses, 3), None);
+        // The unit clauses decide everything before any search
+        let clauses = vec![vec![1, 2, 3], vec![-1], vec![-2], vec![3]];
+        assert_eq!(
+            solve_satisfiability(&clauses, 3),
+            Some(vec![false, false, false, true])
+        );
+    }
+
+    #[test]
+    fn variable_order() {
+        let mut activity = vec![0.0, 3.0, 1.0, 4.0, 1.5, 9.0];
+        let mut order = VariableOrder::new(5);
+        for var in 1..=5 {
+            order.increased(var, &activity);
+        }
+        assert_eq!(order.pop(&activity), Some(5));
+        assert_eq!(order.pop(&activity), Some(3));
+        activity[2] = 10.0;
+        order.increased(2, &activity);
+        assert_eq!(order.pop(&activity), Some(2));
+        // Inserting twice is a no-op
+        order.insert(3, &activity);
+        order.insert(3, &activity);
+        let popped: Vec<usize> = std::iter::from_fn(|| order.pop(&activity)).collect();
+        assert_eq!(popped, vec![3, 1, 4]);
+        assert_eq!(order.pop(&activity), None);
+    }
+
+    #[test]
+    fn luby_sequence() {
+        let sequence: Vec<u64> = (0..15).map(luby).collect();
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn invalid_literals() {
+        // Both with the 2-SAT shortcut and with CDCL
+        assert_eq!(solve_satisfiability(&[vec![1, 0]], 2), None);
+        assert_eq!(solve_satisfiability(&[vec![1, -3]], 2), None);
+        assert_eq!(solve_satisfiability(&[vec![1, 2, 3]], 2), None);
+        assert_eq!(solve_satisfiability(&[vec![1, 2, i64::MIN]], 2), None);
+        assert_eq!(solve_satisfiability(&[vec![1]], 0), None);
+        assert!(solve_satisfiability(&[vec![1, -2, 2]], 2).is_some());
+    }
+}
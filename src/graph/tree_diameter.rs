@@ -0,0 +1,134 @@
+/*
+Tree diameter and centre:
+The diameter of a tree is a longest path between two of its vertices. The
+farthest vertex from any vertex is always an end of a diameter, so two
+Breadth First Searches find one: from any vertex to its farthest vertex a,
+then from a to its farthest vertex b.
+
+The centre of a tree is the set of vertices whose largest distance to
+another vertex (their eccentricity) is the smallest. It is made of the one
+or two middle vertices of any diameter, and the smallest eccentricity (the
+radius) is half the length of a diameter, rounded up.
+
+The tree is an undirected adjacency list with vertices numbered from 1 to n.
+Only the tree containing the first vertex is considered.
+*/
+
+type Adj = [Vec<usize>];
+
+// The distances from `source`, and the parent of each vertex on a shortest
+// path from it, with usize::MAX for the unreachable vertices
+fn breadth_first_search(adj: &Adj, source: usize) -> (Vec<usize>, Vec<usize>) {
+    let mut distance = vec![usize::MAX; adj.len()];
+    let mut parent = vec![0; adj.len()];
+    distance[source] = 0;
+    let mut queue = std::collections::VecDeque::from([source]);
+    while let Some(v) = queue.pop_front() {
+        for &u in adj[v].iter() {
+            if distance[u] == usize::MAX {
+                distance[u] = distance[v] + 1;
+                parent[u] = v;
+                queue.push_back(u);
+            }
+        }
+    }
+    (distance, parent)
+}
+
+fn farthest(distance: &[usize]) -> usize {
+    (1..distance.len())
+        .filter(|&v| distance[v] != usize::MAX)
+        .max_by_key(|&v| (distance[v], std::cmp::Reverse(v)))
+        .unwrap()
+}
+
+/// Returns the vertices of a longest path of the tree, in order. Its length
+/// is one less than its number of vertices.
+pub fn tree_diameter(adj: &Adj) -> Vec<usize> {
+    if adj.len() <= 1 {
+        return vec![];
+    }
+    let a = farthest(&breadth_first_search(adj, 1).0);
+    let (distance, parent) = breadth_first_search(adj, a);
+    let mut path = vec![farthest(&distance)];
+    while *path.last().unwrap() != a {
+        path.push(parent[*path.last().unwrap()]);
+    }
+    path
+}
+
+/// Returns the one or two vertices of the centre of the tree, sorted.
+pub fn tree_center(adj: &Adj) -> Vec<usize> {
+    let path = tree_diameter(adj);
+    let mut center = if path.len() & 1 == 1 {
+        vec![path[path.len() / 2]]
+    } else if path.is_empty() {
+        vec![]
+    } else {
+        vec![path[path.len() / 2 - 1], path[path.len() / 2]]
+    };
+    center.sort_unstable();
+    center
+}
+
+/// Returns the smallest eccentricity of a vertex of the tree.
+pub fn tree_radius(adj: &Adj) -> usize {
+    tree_diameter(adj).len() / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{barabasi_albert, rerooting};
+    use crate::math::PCG32;
+
+    #[test]
+    fn small_trees() {
+        //     1
+        //   / | \
+        //  2  3  4
+        //  |     |
+        //  5     6 - 7
+        let adj = vec![
+            vec![],
+            vec![2, 3, 4],
+            vec![1, 5],
+            vec![1],
+            vec![1, 6],
+            vec![2],
+            vec![4, 7],
+            vec![6],
+        ];
+        assert_eq!(tree_diameter(&adj), vec![5, 2, 1, 4, 6, 7]);
+        assert_eq!(tree_center(&adj), vec![1, 4]);
+        assert_eq!(tree_radius(&adj), 3);
+
+        let star = vec![vec![], vec![2, 3, 4], vec![1], vec![1], vec![1]];
+        assert_eq!(tree_diameter(&star).len(), 3);
+        assert_eq!(tree_center(&star), vec![1]);
+        assert_eq!(tree_radius(&star), 1);
+
+        assert_eq!(tree_diameter(&[vec![], vec![]]), vec![1]);
+        assert_eq!(tree_center(&[vec![], vec![]]), vec![1]);
+        assert_eq!(tree_radius(&[vec![], vec![]]), 0);
+        assert_eq!(tree_center(&[vec![]]), vec![]);
+    }
+
+    #[test]
+    fn random_trees() {
+        let mut rng = PCG32::new_default(12);
+        for _ in 0..30 {
+            let n = 1 + rng.get_u32() as usize % 50;
+            let adj = barabasi_albert(n, 1, &mut rng);
+            // One more than the eccentricity of each vertex
+            let eccentricity = rerooting(&adj, 0, |a, b| *a.max(b), |a, _| a + 1);
+            let diameter = tree_diameter(&adj);
+            assert_eq!(diameter.len(), *eccentricity.iter().max().unwrap());
+            assert!(diameter.windows(2).all(|e| adj[e[0]].contains(&e[1])));
+            let radius = *eccentricity[1..].iter().min().unwrap();
+            assert_eq!(tree_radius(&adj) + 1, radius);
+            let center: Vec<usize> = (1..=n).filter(|&v| eccentricity[v] == radius).collect();
+            assert_eq!(tree_center(&adj), center);
+        }
+    }
+}
@@ -0,0 +1,445 @@
+/*
+Tree Path Query:
+It answers aggregate queries and applies updates on the paths and subtrees of
+a static tree, in O(lg(n)^2) and O(lg(n)) time respectively.
+
+The tree is split with a `HeavyLightDecomposition`, so that every path is made
+of O(lg(n)) ranges of positions, and every subtree is a single range. The
+values are then kept in a `LazySegmentTree` indexed by position.
+
+The values can either belong to the vertices, or to the edges. In the latter
+case, the value of an edge is stored in its lower vertex, and the topmost
+vertex of a path or a subtree is skipped.
+
+Since the ranges of a path are read from bottom to top on the way up from `u`
+and from top to bottom on the way down to `v`, the segment tree also keeps the
+aggregate of each range in reverse order. So `path_aggregate(u, v)` is in path
+order even if the monoid is not commutative. Subtree aggregates are in
+Depth First Search order.
+
+Vertices are numbered from 1 to n, like in `HeavyLightDecomposition`.
+*/
+
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use super::HeavyLightDecomposition;
+use crate::data_structures::{LazyMonoid, LazySegmentTree, Monoid};
+
+type Adj = [Vec<usize>];
+
+// The aggregate of a range in order, and in reverse order
+struct Directed<M>(PhantomData<M>);
+
+impl<M: Monoid> Monoid for Directed<M> {
+    type Value = (M::Value, M::Value);
+    fn identity() -> Self::Value {
+        (M::identity(), M::identity())
+    }
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value {
+        (M::combine(&left.0, &right.0), M::combine(&right.1, &left.1))
+    }
+}
+
+impl<M: LazyMonoid> LazyMonoid for Directed<M> {
+    type Update = M::Update;
+    fn apply(update: &M::Update, value: &Self::Value, size: usize) -> Self::Value {
+        (
+            M::apply(update, &value.0, size),
+            M::apply(update, &value.1, size),
+        )
+    }
+    fn compose(newer: &M::Update, older: &M::Update) -> M::Update {
+        M::compose(newer, older)
+    }
+}
+
+pub struct TreePathQuery<M: LazyMonoid> {
+    pub hld: HeavyLightDecomposition,
+    pub parent: Vec<usize>,
+    pub depth: Vec<usize>,
+    pub subtree_size: Vec<usize>,
+    on_edges: bool,
+    tree: LazySegmentTree<Directed<M>>,
+}
+
+impl<M: LazyMonoid> TreePathQuery<M> {
+    /// Builds the queries for the tree `adj` rooted at `root`, where
+    /// values[i] is the value of vertex i + 1.
+    pub fn with_vertex_values(adj: &Adj, root: usize, values: Vec<M::Value>) -> Self {
+        let mut query = Self::build(adj, root, false);
+        query.fill(
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| (i + 1, value)),
+        );
+        query
+    }
+    /// Builds the queries for the tree `adj` rooted at `root`, where every
+    /// (u, v, value) of `edges` gives the value of the edge (u, v). The other
+    /// edges hold the identity.
+    pub fn with_edge_values(adj: &Adj, root: usize, edges: Vec<(usize, usize, M::Value)>) -> Self {
+        let mut query = Self::build(adj, root, true);
+        let values: Vec<(usize, M::Value)> = edges
+            .into_iter()
+            .map(|(u, v, value)| (query.lower_endpoint(u, v), value))
+            .collect();
+        query.fill(values);
+        query
+    }
+    fn build(adj: &Adj, root: usize, on_edges: bool) -> Self {
+        let num_vertices = adj.len() - 1;
+        let mut hld = HeavyLightDecomposition::new(num_vertices);
+        hld.decompose(root, adj);
+        let mut parent = vec![0; num_vertices + 1];
+        let mut depth = vec![0; num_vertices + 1];
+        let mut subtree_size = vec![1; num_vertices + 1];
+        let mut order = vec![root];
+        let mut i = 0;
+        while i < order.len() {
+            let v = order[i];
+            i += 1;
+            for &u in adj[v].iter() {
+                if u != parent[v] {
+                    parent[u] = v;
+                    depth[u] = depth[v] + 1;
+                    order.push(u);
+                }
+            }
+        }
+        for &v in order.iter().rev().filter(|&&v| v != root) {
+            subtree_size[parent[v]] += subtree_size[v];
+        }
+        TreePathQuery {
+            hld,
+            parent,
+            depth,
+            subtree_size,
+            on_edges,
+            // Filled by `fill`
+            tree: LazySegmentTree::new(0),
+        }
+    }
+    // Builds the segment tree in O(n) from the (vertex, value) pairs, the
+    // other vertices holding the identity
+    fn fill(&mut self, values: impl IntoIterator<Item = (usize, M::Value)>) {
+        let mut ordered = vec![Directed::<M>::identity(); self.parent.len() - 1];
+        for (v, value) in values {
+            ordered[self.index(v)] = (value.clone(), value);
+        }
+        self.tree = LazySegmentTree::from_vec(ordered);
+    }
+    // The index of `v` in the segment tree
+    fn index(&self, v: usize) -> usize {
+        self.hld.position[v] - 1
+    }
+    // Splits the path from `u` to `v` into ranges of the segment tree, in
+    // path order. The ranges going up from `u` are read backwards, and the
+    // ones going down to `v` are read forwards
+    fn path_ranges(&self, mut u: usize, mut v: usize) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+        let mut up = vec![];
+        let mut down = vec![];
+        let head = &self.hld.head;
+        while head[u] != head[v] {
+            if self.depth[head[u]] >= self.depth[head[v]] {
+                up.push(self.index(head[u])..self.index(u) + 1);
+                u = self.parent[head[u]];
+            } else {
+                down.push(self.index(head[v])..self.index(v) + 1);
+                v = self.parent[head[v]];
+            }
+        }
+        // Skip the lowest common ancestor for edges
+        let skip = self.on_edges as usize;
+        if self.depth[u] >= self.depth[v] {
+            up.push(self.index(v) + skip..self.index(u) + 1);
+        } else {
+            down.push(self.index(u) + skip..self.index(v) + 1);
+        }
+        down.reverse();
+        (up, down)
+    }
+    // The range of the segment tree holding the subtree of `v`
+    fn subtree_range(&self, v: usize) -> Range<usize> {
+        let start = self.index(v);
+        start + self.on_edges as usize..start + self.subtree_size[v]
+    }
+    // The lower endpoint of the edge (u, v)
+    fn lower_endpoint(&self, u: usize, v: usize) -> usize {
+        if self.parent[v] == u {
+            v
+        } else {
+            assert_eq!(self.parent[u], v, "({u}, {v}) is not an edge of the tree");
+            u
+        }
+    }
+    pub fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        let head = &self.hld.head;
+        while head[u] != head[v] {
+            if self.depth[head[u]] >= self.depth[head[v]] {
+                u = self.parent[head[u]];
+            } else {
+                v = self.parent[head[v]];
+            }
+        }
+        if self.depth[u] <= self.depth[v] {
+            u
+        } else {
+            v
+        }
+    }
+    /// Returns the value of vertex `v`, or of the edge from `v` to its parent
+    /// if the values are on the edges.
+    pub fn value(&mut self, v: usize) -> M::Value {
+        self.tree.get(self.index(v)).0
+    }
+    /// Replaces the value of vertex `v`, or of the edge from `v` to its parent
+    /// if the values are on the edges.
+    pub fn set_value(&mut self, v: usize, value: M::Value) {
+        let index = self.index(v);
+        self.tree.set(index, (value.clone(), value));
+    }
+    pub fn edge_value(&mut self, u: usize, v: usize) -> M::Value {
+        self.value(self.lower_endpoint(u, v))
+    }
+    pub fn set_edge_value(&mut self, u: usize, v: usize, value: M::Value) {
+        self.set_value(self.lower_endpoint(u, v), value);
+    }
+    /// Returns the aggregate of the values on the path from `u` to `v`, in
+    /// this order.
+    pub fn path_aggregate(&mut self, u: usize, v: usize) -> M::Value {
+        let (up, down) = self.path_ranges(u, v);
+        let mut result = M::identity();
+        for range in up {
+            result = M::combine(&result, &self.tree.query(range).1);
+        }
+        for range in down {
+            result = M::combine(&result, &self.tree.query(range).0);
+        }
+        result
+    }
+    /// Applies `update` to every value on the path from `u` to `v`.
+    pub fn update_path(&mut self, u: usize, v: usize, update: &M::Update) {
+        let (up, down) = self.path_ranges(u, v);
+        for range in up.into_iter().chain(down) {
+            self.tree.update(range, update);
+        }
+    }
+    /// Returns the aggregate of the values in the subtree of `v`.
+    pub fn subtree_aggregate(&mut self, v: usize) -> M::Value {
+        let range = self.subtree_range(v);
+        self.tree.query(range).0
+    }
+    /// Applies `update` to every value in the subtree of `v`.
+    pub fn update_subtree(&mut self, v: usize, update: &M::Update) {
+        let range = self.subtree_range(v);
+        self.tree.update(range, update);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::test_monoids::{Concat, Sum};
+    use crate::math::PCG32;
+
+    fn random_tree(n: usize, rng: &mut PCG32) -> (Vec<Vec<usize>>, Vec<usize>) {
+        let mut adj = vec![vec![]; n + 1];
+        let mut parent = vec![0; n + 1];
+        for v in 2..=n {
+            let p = (rng.get_u32() as usize % (v - 1)) + 1;
+            parent[v] = p;
+            adj[p].push(v);
+            adj[v].push(p);
+        }
+        (adj, parent)
+    }
+
+    // The vertices on the path from `u` to `v` when the tree is rooted at 1
+    fn naive_path(parent: &[usize], u: usize, v: usize) -> Vec<usize> {
+        let ancestors = |mut x: usize| {
+            let mut result = vec![x];
+            while parent[x] != 0 {
+                x = parent[x];
+                result.push(x);
+            }
+            result
+        };
+        let mut from_u = ancestors(u);
+        let mut from_v = ancestors(v);
+        while from_u.len() > 1
+            && from_v.len() > 1
+            && from_u[from_u.len() - 2] == from_v[from_v.len() - 2]
+        {
+            from_u.pop();
+            from_v.pop();
+        }
+        from_v.pop();
+        from_u.extend(from_v.into_iter().rev());
+        from_u
+    }
+
+    fn in_subtree(parent: &[usize], mut x: usize, v: usize) -> bool {
+        while x != 0 && x != v {
+            x = parent[x];
+        }
+        x == v
+    }
+
+    #[test]
+    fn small_tree() {
+        //      1
+        //     / \
+        //    2   3
+        //   / \   \
+        //  4   5   6
+        let adj = vec![
+            vec![],
+            vec![2, 3],
+            vec![1, 4, 5],
+            vec![1, 6],
+            vec![2],
+            vec![2],
+            vec![3],
+        ];
+        let values = "abcdef".chars().map(String::from).collect();
+        let mut query: TreePathQuery<Concat> = TreePathQuery::with_vertex_values(&adj, 1, values);
+        assert_eq!(query.path_aggregate(4, 6), "dbacf");
+        assert_eq!(query.path_aggregate(6, 5), "fcabe");
+        assert_eq!(query.path_aggregate(5, 5), "e");
+        assert_eq!(query.lca(4, 5), 2);
+        assert_eq!(query.lca(6, 3), 3);
+        query.update_path(5, 3, &'x');
+        assert_eq!(query.path_aggregate(4, 6), "dxxxf");
+        query.update_subtree(2, &'y');
+        assert_eq!(query.path_aggregate(4, 6), "yyxxf");
+        assert_eq!(query.subtree_aggregate(3), "xf");
+
+        let edges = vec![(1, 2, 1), (2, 4, 2), (5, 2, 4), (1, 3, 8), (3, 6, 16)];
+        let mut query: TreePathQuery<Sum> = TreePathQuery::with_edge_values(&adj, 1, edges);
+        assert_eq!(query.path_aggregate(4, 6), 27);
+        assert_eq!(query.path_aggregate(4, 5), 6);
+        assert_eq!(query.path_aggregate(4, 4), 0);
+        assert_eq!(query.subtree_aggregate(2), 6);
+        assert_eq!(query.subtree_aggregate(1), 31);
+        query.update_path(4, 1, &100);
+        assert_eq!(query.edge_value(2, 1), 101);
+        assert_eq!(query.subtree_aggregate(2), 106);
+        query.set_edge_value(6, 3, 0);
+        assert_eq!(query.path_aggregate(6, 1), 8);
+    }
+
+    #[test]
+    fn random_vertex_values() {
+        let n = 60;
+        let mut rng = PCG32::new_default(161803);
+        let (adj, parent) = random_tree(n, &mut rng);
+        let mut values: Vec<char> = (0..=n).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+        let mut query: TreePathQuery<Concat> = TreePathQuery::with_vertex_values(
+            &adj,
+            1,
+            values[1..].iter().map(|&c| String::from(c)).collect(),
+        );
+        for _ in 0..1000 {
+            let u = (rng.get_u32() as usize % n) + 1;
+            let v = (rng.get_u32() as usize % n) + 1;
+            let c = (b'A' + (rng.get_u32() % 26) as u8) as char;
+            let path = naive_path(&parent, u, v);
+            match rng.get_u32() % 4 {
+                0 => {
+                    query.update_path(u, v, &c);
+                    path.iter().for_each(|&x| values[x] = c);
+                }
+                1 => {
+                    query.update_subtree(v, &c);
+                    (1..=n)
+                        .filter(|&x| in_subtree(&parent, x, v))
+                        .for_each(|x| values[x] = c);
+                }
+                2 => {
+                    query.set_value(u, String::from(c));
+                    values[u] = c;
+                }
+                _ => {
+                    let expected: String = path.iter().map(|&x| values[x]).collect();
+                    assert_eq!(query.path_aggregate(u, v), expected);
+                    assert_eq!(
+                        query.lca(u, v),
+                        *path.iter().min_by_key(|&&x| query.depth[x]).unwrap()
+                    );
+                    let mut subtree = query.subtree_aggregate(v).into_bytes();
+                    let mut expected: Vec<u8> = (1..=n)
+                        .filter(|&x| in_subtree(&parent, x, v))
+                        .map(|x| values[x] as u8)
+                        .collect();
+                    subtree.sort_unstable();
+                    expected.sort_unstable();
+                    assert_eq!(subtree, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn random_edge_values() {
+        let n = 60;
+        let mut rng = PCG32::new_default(141421);
+        let (adj, parent) = random_tree(n, &mut rng);
+        // The value of the edge from each vertex to its parent
+        let mut values = vec![0i64; n + 1];
+        let mut query: TreePathQuery<Sum> = TreePathQuery::with_edge_values(&adj, 1, vec![]);
+        for _ in 0..1000 {
+            let u = (rng.get_u32() as usize % n) + 1;
+            let v = (rng.get_u32() as usize % n) + 1;
+            let x = (rng.get_u32() % 100) as i64 - 50;
+            let path = naive_path(&parent, u, v);
+            let lca = *path.iter().min_by_key(|&&x| query.depth[x]).unwrap();
+            match rng.get_u32() % 4 {
+                0 => {
+                    query.update_path(u, v, &x);
+                    path.iter()
+                        .filter(|&&y| y != lca)
+                        .for_each(|&y| values[y] += x);
+                }
+                1 => {
+                    query.update_subtree(v, &x);
+                    (1..=n)
+                        .filter(|&y| y != v && in_subtree(&parent, y, v))
+                        .for_each(|y| values[y] += x);
+                }
+                2 => {
+                    if u != 1 {
+                        query.set_edge_value(parent[u], u, x);
+                        values[u] = x;
+                    }
+                }
+                _ => {
+                    let expected: i64 =
+                        path.iter().filter(|&&y| y != lca).map(|&y| values[y]).sum();
+                    assert_eq!(query.path_aggregate(u, v), expected);
+                    let expected: i64 = (1..=n)
+                        .filter(|&y| y != v && in_subtree(&parent, y, v))
+                        .map(|y| values[y])
+                        .sum();
+                    assert_eq!(query.subtree_aggregate(v), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_vertex() {
+        let adj = vec![vec![], vec![]];
+        let mut query: TreePathQuery<Sum> = TreePathQuery::with_vertex_values(&adj, 1, vec![5]);
+        assert_eq!(query.lca(1, 1), 1);
+        assert_eq!(query.path_aggregate(1, 1), 5);
+        query.update_subtree(1, &2);
+        assert_eq!(query.subtree_aggregate(1), 7);
+        // Without edges every aggregate is the identity
+        let mut query: TreePathQuery<Sum> = TreePathQuery::with_edge_values(&adj, 1, vec![]);
+        query.update_path(1, 1, &2);
+        assert_eq!(query.path_aggregate(1, 1), 0);
+        assert_eq!(query.subtree_aggregate(1), 0);
+    }
+}
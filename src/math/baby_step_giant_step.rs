@@ -7,6 +7,7 @@
 /// Wikipedia reference: https://en.wikipedia.org/wiki/Baby-step_giant-step
 /// When a is the primitive root modulo n, the answer is unique.
 /// Otherwise it will return the smallest positive solution
+use super::DynModInt;
 use std::collections::HashMap;
 
 pub fn baby_step_giant_step(a: usize, b: usize, n: usize) -> Option<usize> {
@@ -16,11 +17,13 @@ pub fn baby_step_giant_step(a: usize, b: usize, n: usize) -> Option<usize> {
 
     let mut h_map = HashMap::new();
     let m = (n as f64).sqrt().ceil() as usize;
+    let a = DynModInt::new(a as u64, n as u64);
+    let b = DynModInt::new(b as u64, n as u64);
     // baby step
-    let mut step = 1;
+    let mut step = DynModInt::new(1, n as u64);
     for i in 0..m {
-        h_map.insert((step * b) % n, i);
-        step = (step * a) % n;
+        h_map.insert(step * b, i);
+        step *= a;
     }
     // Now step = a^m (mod n), giant step
     let giant_step = step;
@@ -28,7 +31,7 @@ pub fn baby_step_giant_step(a: usize, b: usize, n: usize) -> Option<usize> {
         if let Some(v) = h_map.get(&step) {
             return Some(i - v);
         }
-        step = (step * giant_step) % n;
+        step *= giant_step;
     }
     None
 }
@@ -69,4 +72,16 @@ mod tests {
             Some(14215560)
         );
     }
+
+    #[test]
+    fn large_modulus() {
+        // The products of two numbers below n overflow 64 bits
+        let p = 1_000_000_000_039;
+        let x = 12_345_678;
+        let b = crate::math::fast_power(2, x, p);
+        assert_eq!(
+            baby_step_giant_step(2, b, p).map(|y| b == crate::math::fast_power(2, y, p)),
+            Some(true)
+        );
+    }
 }
@@ -1,24 +1,27 @@
-use super::extended_euclidean_algorithm;
-
-fn mod_inv(x: i32, n: i32) -> Option<i32> {
-    let (g, x, _) = extended_euclidean_algorithm(x, n);
-    if g == 1 {
-        Some((x % n + n) % n)
-    } else {
-        None
-    }
-}
+use super::DynModInt;
 
+/// Returns the smallest non-negative x such that x = residues[i] mod
+/// modulli[i] for each i, or None if the modulli are not positive and
+/// pairwise coprime, if their product does not fit in a u64, or if x does
+/// not fit in an i32. The intermediate products are computed on 64 bits.
 pub fn chinese_remainder_theorem(residues: &[i32], modulli: &[i32]) -> Option<i32> {
-    let prod = modulli.iter().product::<i32>();
+    if modulli.iter().any(|&modulus| modulus <= 0) {
+        return None;
+    }
+    let prod = modulli
+        .iter()
+        .try_fold(1_u64, |prod, &modulus| prod.checked_mul(modulus as u64))?;
 
-    let mut sum = 0;
+    let mut sum = DynModInt::new(0, prod);
 
     for (&residue, &modulus) in residues.iter().zip(modulli) {
-        let p = prod / modulus;
-        sum += residue * mod_inv(p, modulus)? * p
+        let p = prod / modulus as u64;
+        let inverse = DynModInt::new(p, modulus as u64).inverse()?;
+        sum += DynModInt::from_i64(residue as i64, prod)
+            * DynModInt::new(inverse.value(), prod)
+            * DynModInt::new(p, prod);
     }
-    Some(sum % prod)
+    i32::try_from(sum.value()).ok()
 }
 
 #[cfg(test)]
@@ -31,5 +34,25 @@ mod tests {
         assert_eq!(chinese_remainder_theorem(&[1, 4, 6], &[3, 5, 7]), Some(34));
         assert_eq!(chinese_remainder_theorem(&[1, 4, 6], &[1, 2, 0]), None);
         assert_eq!(chinese_remainder_theorem(&[2, 5, 7], &[6, 9, 15]), None);
+        assert_eq!(chinese_remainder_theorem(&[-1, -1], &[4, 9]), Some(35));
+        // The intermediate products overflow 32 bits
+        let x = chinese_remainder_theorem(&[46000, 12345], &[46337, 46339]).unwrap();
+        assert_eq!((x % 46337, x % 46339), (46000, 12345));
+        // The product is above 2^31, but not the answer
+        assert_eq!(
+            chinese_remainder_theorem(&[1, 1], &[i32::MAX, i32::MAX - 1]),
+            Some(1)
+        );
+        // The answer is above 2^31
+        assert_eq!(
+            chinese_remainder_theorem(&[1, 2], &[i32::MAX, i32::MAX - 1]),
+            None
+        );
+        // The product is above 2^64
+        assert_eq!(
+            chinese_remainder_theorem(&[1, 2, 3], &[i32::MAX, i32::MAX - 1, i32::MAX - 2]),
+            None
+        );
+        assert_eq!(chinese_remainder_theorem(&[], &[]), Some(0));
     }
 }
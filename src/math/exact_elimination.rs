@@ -0,0 +1,483 @@
+/*
+Exact Gaussian elimination:
+Solves the linear system Ax = b over a field where the arithmetic is exact,
+like the rationals (big_integer::Rational), the integers modulo a prime p
+(ModInt<p>), or GF(2), so there are no rounding errors like with floats.
+The augmented matrix [A | b] is brought to its reduced row echelon form: for
+each column, a row with a nonzero value in it becomes the pivot row, which is
+divided by that value and subtracted from all the other rows. Then:
+- the system has no solution if a row is 0 = c with c != 0,
+- a particular solution takes the right-hand side of each pivot row for its
+  pivot column, and 0 for the other (free) columns,
+- each free column f gives a vector of the null space, {x | Ax = 0}, with 1
+  in column f and minus the column f of each pivot row in its pivot column.
+Every solution is the particular one plus a combination of the null space
+basis, and the rank of A is the number of columns minus its size.
+https://en.wikipedia.org/wiki/Gaussian_elimination
+https://en.wikipedia.org/wiki/Row_echelon_form#Reduced_row_echelon_form
+
+Over GF(2), the rows are bitsets, where adding a row is a xor of 64 columns at
+a time. It is the setting of the XOR-basis problems.
+*/
+
+use super::ModInt;
+use std::ops::{Add, BitXorAssign, Div, Mul, Sub};
+
+/// The numbers with exact addition, subtraction, multiplication and division
+/// by nonzero numbers.
+pub trait Field:
+    Clone
+    + PartialEq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+/// GF(p), for a prime M.
+impl<const M: u64> Field for ModInt<M> {
+    fn zero() -> Self {
+        ModInt::new(0)
+    }
+
+    fn one() -> Self {
+        ModInt::new(1)
+    }
+}
+
+/// The solutions of a linear system: `particular` plus any linear combination
+/// of the vectors of `null_space`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionSpace<V> {
+    pub particular: V,
+    // A basis of the solutions of Ax = 0
+    pub null_space: Vec<V>,
+}
+
+/// Solves Ax = b, where A is given by rows, or returns None if there is no
+/// solution. The number of unknowns is the length of the rows.
+pub fn solve_exact<F: Field>(a: &[Vec<F>], b: &[F]) -> Option<SolutionSpace<Vec<F>>> {
+    assert_eq!(
+        a.len(),
+        b.len(),
+        "A and b must have the same number of rows"
+    );
+    let n = a.first().map_or(0, |row| row.len());
+    assert!(
+        a.iter().all(|row| row.len() == n),
+        "the rows of A must have the same length"
+    );
+    // The augmented matrix [A | b]
+    let mut rows: Vec<Vec<F>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, value)| row.iter().cloned().chain([value.clone()]).collect())
+        .collect();
+    let mut pivot_columns = vec![];
+    for column in 0..n {
+        let rank = pivot_columns.len();
+        let Some(pivot) = (rank..rows.len()).find(|&i| !rows[i][column].is_zero()) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+        let value = rows[rank][column].clone();
+        rows[rank] = rows[rank]
+            .iter()
+            .map(|x| x.clone() / value.clone())
+            .collect();
+        for i in 0..rows.len() {
+            if i != rank && !rows[i][column].is_zero() {
+                let factor = rows[i][column].clone();
+                rows[i] = rows[i]
+                    .iter()
+                    .zip(&rows[rank])
+                    .map(|(x, y)| x.clone() - factor.clone() * y.clone())
+                    .collect();
+            }
+        }
+        pivot_columns.push(column);
+    }
+    if rows[pivot_columns.len()..]
+        .iter()
+        .any(|row| !row[n].is_zero())
+    {
+        return None;
+    }
+
+    let mut particular = vec![F::zero(); n];
+    for (row, &column) in rows.iter().zip(&pivot_columns) {
+        particular[column] = row[n].clone();
+    }
+    let null_space = (0..n)
+        .filter(|column| !pivot_columns.contains(column))
+        .map(|free| {
+            let mut vector = vec![F::zero(); n];
+            vector[free] = F::one();
+            for (row, &column) in rows.iter().zip(&pivot_columns) {
+                vector[column] = F::zero() - row[free].clone();
+            }
+            vector
+        })
+        .collect();
+    Some(SolutionSpace {
+        particular,
+        null_space,
+    })
+}
+
+/// A vector over GF(2), stored as bits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVector {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVector {
+    /// The zero vector of length `len`.
+    pub fn new(len: usize) -> Self {
+        BitVector {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    pub fn from_bools(bits: &[bool]) -> Self {
+        let mut vector = BitVector::new(bits.len());
+        for (i, &bit) in bits.iter().enumerate() {
+            vector.set(i, bit);
+        }
+        vector
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        assert!(i < self.len, "index out of bounds");
+        if bit {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// The dot product over GF(2), the parity of the common bits.
+    pub fn dot(&self, other: &BitVector) -> bool {
+        let common: u32 = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        common & 1 == 1
+    }
+}
+
+impl BitXorAssign<&BitVector> for BitVector {
+    fn bitxor_assign(&mut self, other: &BitVector) {
+        assert_eq!(self.len, other.len, "the vectors must have the same length");
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+}
+
+/// Solves Ax = b over GF(2), where A is given by rows of the same length, or
+/// returns None if there is no solution.
+pub fn solve_gf2(a: &[BitVector], b: &[bool]) -> Option<SolutionSpace<BitVector>> {
+    assert_eq!(
+        a.len(),
+        b.len(),
+        "A and b must have the same number of rows"
+    );
+    let n = a.first().map_or(0, |row| row.len());
+    assert!(
+        a.iter().all(|row| row.len() == n),
+        "the rows of A must have the same length"
+    );
+    let mut rows = a.to_vec();
+    let mut values = b.to_vec();
+    let mut pivot_columns = vec![];
+    for column in 0..n {
+        let rank = pivot_columns.len();
+        let Some(pivot) = (rank..rows.len()).find(|&i| rows[i].get(column)) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+        values.swap(rank, pivot);
+        let pivot_row = rows[rank].clone();
+        for i in 0..rows.len() {
+            if i != rank && rows[i].get(column) {
+                rows[i] ^= &pivot_row;
+                values[i] ^= values[rank];
+            }
+        }
+        pivot_columns.push(column);
+    }
+    if values[pivot_columns.len()..].iter().any(|&value| value) {
+        return None;
+    }
+
+    let mut particular = BitVector::new(n);
+    for (&value, &column) in values.iter().zip(&pivot_columns) {
+        particular.set(column, value);
+    }
+    let null_space = (0..n)
+        .filter(|column| !pivot_columns.contains(column))
+        .map(|free| {
+            let mut vector = BitVector::new(n);
+            vector.set(free, true);
+            for (row, &column) in rows.iter().zip(&pivot_columns) {
+                vector.set(column, row.get(free));
+            }
+            vector
+        })
+        .collect();
+    Some(SolutionSpace {
+        particular,
+        null_space,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    type Gf7 = ModInt<7>;
+
+    fn product<F: Field>(a: &[Vec<F>], x: &[F]) -> Vec<F> {
+        a.iter()
+            .map(|row| {
+                row.iter()
+                    .zip(x)
+                    .fold(F::zero(), |sum, (a, x)| sum + a.clone() * x.clone())
+            })
+            .collect()
+    }
+
+    fn to_gf7(rows: &[&[u64]]) -> Vec<Vec<Gf7>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&x| ModInt::new(x)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn prime_field() {
+        // x + 2y + 3z = 1, 2x + 4y + 6z = 2 and y + z = 5 modulo 7
+        let a = to_gf7(&[&[1, 2, 3], &[2, 4, 6], &[0, 1, 1]]);
+        let b: Vec<Gf7> = [1, 2, 5].iter().map(|&x| ModInt::new(x)).collect();
+        let solution = solve_exact(&a, &b).unwrap();
+        assert_eq!(product(&a, &solution.particular), b);
+        assert_eq!(solution.null_space.len(), 1);
+        assert_eq!(
+            product(&a, &solution.null_space[0]),
+            vec![ModInt::new(0); 3]
+        );
+
+        // Inconsistent
+        let b: Vec<Gf7> = [1, 3, 5].iter().map(|&x| ModInt::new(x)).collect();
+        assert_eq!(solve_exact(&a, &b), None);
+
+        // A unique solution
+        let a = to_gf7(&[&[2, 1], &[1, 1]]);
+        let b = vec![ModInt::new(3), ModInt::new(2)];
+        let solution = solve_exact(&a, &b).unwrap();
+        assert_eq!(solution.particular, vec![ModInt::new(1), ModInt::new(1)]);
+        assert!(solution.null_space.is_empty());
+
+        // No equations
+        let solution = solve_exact::<Gf7>(&[], &[]).unwrap();
+        assert!(solution.particular.is_empty());
+    }
+
+    #[test]
+    fn random_prime_field_systems() {
+        type Gf = ModInt<998_244_353>;
+        let mut rng = PCG32::new_default(45);
+        for _ in 0..20 {
+            let m = 1 + rng.get_u32() as usize % 6;
+            let n = 1 + rng.get_u32() as usize % 6;
+            // Rank at most k, to get null spaces
+            let k = 1 + rng.get_u32() as usize % n.min(m);
+            let left: Vec<Vec<Gf>> = (0..m)
+                .map(|_| (0..k).map(|_| ModInt::new(rng.get_u64())).collect())
+                .collect();
+            let right: Vec<Vec<Gf>> = (0..k)
+                .map(|_| (0..n).map(|_| ModInt::new(rng.get_u64())).collect())
+                .collect();
+            let a: Vec<Vec<Gf>> = left
+                .iter()
+                .map(|row| {
+                    (0..n)
+                        .map(|j| (0..k).map(|t| row[t] * right[t][j]).sum())
+                        .collect()
+                })
+                .collect();
+            let x: Vec<Gf> = (0..n).map(|_| ModInt::new(rng.get_u64())).collect();
+            let b = product(&a, &x);
+            let solution = solve_exact(&a, &b).unwrap();
+            assert_eq!(product(&a, &solution.particular), b);
+            assert!(solution.null_space.len() >= n - k);
+            for v in &solution.null_space {
+                assert!(product(&a, v).iter().all(|x| x.value() == 0));
+            }
+        }
+    }
+
+    #[test]
+    fn bit_vector() {
+        let mut v = BitVector::new(130);
+        assert!(v.is_zero());
+        v.set(0, true);
+        v.set(64, true);
+        v.set(129, true);
+        assert!(v.get(129) && !v.get(128));
+        assert_eq!(v.count_ones(), 3);
+        let w = BitVector::from_bools(
+            &(0..130)
+                .map(|i: usize| i.is_multiple_of(2))
+                .collect::<Vec<bool>>(),
+        );
+        // Bits 0 and 64 are common
+        assert!(!v.dot(&w));
+        v ^= &w;
+        assert_eq!(v.count_ones(), 64);
+        v.set(2, false);
+        assert!(!v.get(2));
+        assert!(BitVector::new(0).is_empty());
+    }
+
+    #[test]
+    fn gf2_systems() {
+        // x0 ^ x1 = 1, x1 ^ x2 = 0
+        let a = vec![
+            BitVector::from_bools(&[true, true, false]),
+            BitVector::from_bools(&[false, true, true]),
+        ];
+        let solution = solve_gf2(&a, &[true, false]).unwrap();
+        assert_eq!(
+            solution.particular,
+            BitVector::from_bools(&[true, false, false])
+        );
+        assert_eq!(
+            solution.null_space,
+            vec![BitVector::from_bools(&[true, true, true])]
+        );
+        // x0 = 1 and x0 = 0
+        let a = vec![
+            BitVector::from_bools(&[true]),
+            BitVector::from_bools(&[true]),
+        ];
+        assert_eq!(solve_gf2(&a, &[true, false]), None);
+
+        // Is 0b1011 the xor of some of 0b0011, 0b0110, 0b1100? The columns
+        // are the numbers, and the rows their bits
+        let numbers = [0b0011, 0b0110, 0b1100];
+        let bits = |target: u32| -> (Vec<BitVector>, Vec<bool>) {
+            let rows = (0..4)
+                .map(|bit| BitVector::from_bools(&numbers.map(|x: u32| x >> bit & 1 == 1)))
+                .collect();
+            (rows, (0..4).map(|bit| target >> bit & 1 == 1).collect())
+        };
+        let (a, b) = bits(0b1001);
+        let solution = solve_gf2(&a, &b).unwrap();
+        let xor = (0..3)
+            .filter(|&i| solution.particular.get(i))
+            .fold(0, |xor, i| xor ^ numbers[i]);
+        assert_eq!(xor, 0b1001);
+        let (a, b) = bits(0b1011);
+        assert_eq!(solve_gf2(&a, &b), None);
+    }
+
+    #[test]
+    fn random_gf2_systems() {
+        let mut rng = PCG32::new_default(2);
+        for _ in 0..20 {
+            let m = 1 + rng.get_u32() as usize % 100;
+            let n = 1 + rng.get_u32() as usize % 150;
+            let a: Vec<BitVector> = (0..m)
+                .map(|_| {
+                    let bits: Vec<bool> = (0..n).map(|_| rng.get_u32().is_multiple_of(3)).collect();
+                    BitVector::from_bools(&bits)
+                })
+                .collect();
+            let x = BitVector::from_bools(
+                &(0..n)
+                    .map(|_| rng.get_u32() & 1 == 1)
+                    .collect::<Vec<bool>>(),
+            );
+            let b: Vec<bool> = a.iter().map(|row| row.dot(&x)).collect();
+            let solution = solve_gf2(&a, &b).unwrap();
+            assert!(a
+                .iter()
+                .zip(&b)
+                .all(|(row, &value)| row.dot(&solution.particular) == value));
+            for v in &solution.null_space {
+                assert!(a.iter().all(|row| !row.dot(v)));
+            }
+            // rank + nullity = n, and the rank is at most m
+            assert!(n - solution.null_space.len() <= m);
+        }
+    }
+
+    #[test]
+    fn degenerate_systems() {
+        let zero = ModInt::new(0);
+        // 0x = 0 holds for every x, and 0x = 1 for none
+        let solution = solve_exact::<Gf7>(&[vec![zero]], &[zero]).unwrap();
+        assert_eq!(solution.particular, vec![zero]);
+        assert_eq!(solution.null_space, vec![vec![ModInt::new(1)]]);
+        assert_eq!(solve_exact::<Gf7>(&[vec![zero]], &[ModInt::new(1)]), None);
+        // An equation without unknowns
+        assert_eq!(solve_exact::<Gf7>(&[vec![]], &[ModInt::new(1)]), None);
+
+        let solution = solve_gf2(&[BitVector::new(2)], &[false]).unwrap();
+        assert_eq!(solution.particular, BitVector::new(2));
+        assert_eq!(solution.null_space.len(), 2);
+        assert_eq!(solve_gf2(&[BitVector::new(2)], &[true]), None);
+        assert_eq!(solve_gf2(&[BitVector::new(0)], &[true]), None);
+        let solution = solve_gf2(&[], &[]).unwrap();
+        assert!(solution.particular.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "A and b must have the same number of rows")]
+    fn mismatched_rows() {
+        solve_exact::<Gf7>(&[vec![ModInt::new(1)]], &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the rows of A must have the same length")]
+    fn ragged_rows() {
+        solve_gf2(&[BitVector::new(1), BitVector::new(2)], &[true, true]);
+    }
+}
@@ -0,0 +1,333 @@
+/*
+Integer factorization and arithmetic functions:
+`factorize` splits a 64 bit number into its prime powers. The small primes
+are removed by trial division, then the rest is tested with Miller-Rabin,
+which is deterministic below 2^64 with the first 12 primes as bases, and
+the composites are split with Pollard's rho using Brent's cycle detection.
+https://en.wikipedia.org/wiki/Integer_factorization
+https://en.wikipedia.org/wiki/Pollard%27s_rho_algorithm
+
+The multiplicative functions follow from the factorization:
+- Euler's totient phi(n) counts the numbers in [1, n] coprime with n.
+  https://en.wikipedia.org/wiki/Euler%27s_totient_function
+- Moebius mu(n) is 0 if n has a square factor, and (-1)^k for k primes.
+  https://en.wikipedia.org/wiki/M%C3%B6bius_function
+- The number and the sum of the divisors of n.
+  https://en.wikipedia.org/wiki/Divisor_function
+- A primitive root modulo n generates the multiplicative group modulo n. It
+  exists only for n = 1, 2, 4, p^k and 2 p^k with p an odd prime, and g is
+  one iff g^(phi(n) / q) != 1 for every prime q dividing phi(n).
+  https://en.wikipedia.org/wiki/Primitive_root_modulo_n
+Like `factorize`, the functions panic for n = 0, except `primitive_root`
+which returns None.
+*/
+
+use super::{miller_rabin, pollard_rho_get_one_factor, DynModInt};
+
+const SMALL_PRIMES: [u64; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+// Once the primes below 100 are removed, the numbers below 101^2 are primes
+const TRIAL_DIVISION_BOUND: u64 = 101 * 101;
+
+// Deterministic for every 64 bit number
+const MILLER_RABIN_BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+fn is_prime(number: u64) -> bool {
+    miller_rabin(number, &MILLER_RABIN_BASES) == 0
+}
+
+/// The prime factorization of `number` as (prime, exponent) pairs, sorted by
+/// prime. It is empty for 1, and panics for 0.
+pub fn factorize(mut number: u64) -> Vec<(u64, u32)> {
+    assert!(number > 0, "0 has no prime factorization");
+    let mut primes = Vec::new();
+    for p in SMALL_PRIMES {
+        while number.is_multiple_of(p) {
+            number /= p;
+            primes.push(p);
+        }
+    }
+    // The seed of Pollard's rho random numbers; any value works
+    let mut seed = 314159_u32;
+    let mut to_be_factored = Vec::new();
+    if number > 1 {
+        to_be_factored.push(number);
+    }
+    while let Some(last) = to_be_factored.pop() {
+        if last < TRIAL_DIVISION_BOUND || is_prime(last) {
+            primes.push(last);
+            continue;
+        }
+        // `last` is an odd composite, so this finds a proper factor
+        let factor = pollard_rho_get_one_factor(last, &mut seed, false);
+        to_be_factored.push(factor);
+        to_be_factored.push(last / factor);
+    }
+    primes.sort_unstable();
+    let mut result: Vec<(u64, u32)> = Vec::new();
+    for p in primes {
+        match result.last_mut() {
+            Some((prime, exponent)) if *prime == p => *exponent += 1,
+            _ => result.push((p, 1)),
+        }
+    }
+    result
+}
+
+fn totient_of(factorization: &[(u64, u32)]) -> u64 {
+    factorization
+        .iter()
+        .map(|&(p, e)| p.pow(e - 1) * (p - 1))
+        .product()
+}
+
+/// Euler's totient of `number`, the count of numbers in [1, number] which are
+/// coprime with it.
+pub fn euler_totient(number: u64) -> u64 {
+    totient_of(&factorize(number))
+}
+
+/// The Moebius function of `number`: 0 if it has a square factor, otherwise
+/// 1 or -1 for an even or odd number of prime factors.
+pub fn mobius(number: u64) -> i8 {
+    let factorization = factorize(number);
+    if factorization.iter().any(|&(_, e)| e > 1) {
+        0
+    } else if factorization.len().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+
+/// The number of divisors of `number`.
+pub fn divisor_count(number: u64) -> u64 {
+    factorize(number)
+        .iter()
+        .map(|&(_, e)| e as u64 + 1)
+        .product()
+}
+
+/// The sum of the divisors of `number`, which can be larger than a u64.
+pub fn divisor_sum(number: u64) -> u128 {
+    factorize(number)
+        .iter()
+        .map(|&(p, e)| {
+            // 1 + p + ... + p^e
+            let p = p as u128;
+            (p.pow(e + 1) - 1) / (p - 1)
+        })
+        .product()
+}
+
+/// The divisors of `number` in increasing order.
+pub fn divisors(number: u64) -> Vec<u64> {
+    let mut result = vec![1];
+    for (p, e) in factorize(number) {
+        let current = result.len();
+        let mut power = 1;
+        for _ in 0..e {
+            power *= p;
+            for i in 0..current {
+                result.push(result[i] * power);
+            }
+        }
+    }
+    result.sort_unstable();
+    result
+}
+
+/// The smallest primitive root modulo `modulus`, or None if the multiplicative
+/// group modulo `modulus` is not cyclic, or if `modulus` is 0. Modulo 1 the
+/// only residue, 0, is returned.
+pub fn primitive_root(modulus: u64) -> Option<u64> {
+    match modulus {
+        0 => return None,
+        1 => return Some(0),
+        _ => {}
+    }
+    let factorization = factorize(modulus);
+    let odd_primes = factorization.iter().filter(|&&(p, _)| p != 2).count();
+    let exists = match factorization[0] {
+        // 2 and 4
+        (2, e) if odd_primes == 0 => e <= 2,
+        // 2 p^k
+        (2, e) => e == 1 && odd_primes == 1,
+        // p^k
+        _ => odd_primes == 1,
+    };
+    if !exists {
+        return None;
+    }
+    let totient = totient_of(&factorization);
+    let exponents: Vec<u64> = factorize(totient)
+        .iter()
+        .map(|&(q, _)| totient / q)
+        .collect();
+    // The primes dividing the modulus are not candidates
+    let coprime = |g: u64| factorization.iter().all(|&(p, _)| !g.is_multiple_of(p));
+    (1..modulus).find(|&g| {
+        coprime(g)
+            && exponents
+                .iter()
+                .all(|&exponent| DynModInt::new(g, modulus).pow(exponent).value() != 1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    fn naive_divisors(number: u64) -> Vec<u64> {
+        (1..=number).filter(|d| number.is_multiple_of(*d)).collect()
+    }
+
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    fn check_factorization(number: u64, factorization: &[(u64, u32)]) {
+        let mut product = 1_u64;
+        for window in factorization.windows(2) {
+            assert!(window[0].0 < window[1].0);
+        }
+        for &(p, e) in factorization {
+            assert!(e > 0);
+            assert!(is_prime(p), "{p} is not prime");
+            product *= p.pow(e);
+        }
+        assert_eq!(product, number);
+    }
+
+    #[test]
+    fn small_numbers() {
+        assert_eq!(factorize(1), vec![]);
+        assert_eq!(factorize(2), vec![(2, 1)]);
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(10201), vec![(101, 2)]);
+        for number in 1..3000 {
+            let expected: Vec<u64> = crate::math::prime_factors(number)
+                .into_iter()
+                .filter(|&p| p > 1)
+                .collect();
+            let flattened: Vec<u64> = factorize(number)
+                .iter()
+                .flat_map(|&(p, e)| std::iter::repeat_n(p, e as usize))
+                .collect();
+            assert_eq!(flattened, expected);
+        }
+    }
+
+    #[test]
+    fn large_numbers() {
+        assert_eq!(
+            factorize(u64::MAX),
+            vec![
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65537, 1),
+                (6700417, 1)
+            ]
+        );
+        assert_eq!(
+            factorize(1_000_000_007 * 998_244_353),
+            vec![(998_244_353, 1), (1_000_000_007, 1)]
+        );
+        // The largest prime below 2^64, and the square of the largest prime
+        // below 2^32
+        assert_eq!(
+            factorize(18446744073709551557),
+            vec![(18446744073709551557, 1)]
+        );
+        assert_eq!(factorize(4294967291 * 4294967291), vec![(4294967291, 2)]);
+        assert_eq!(factorize(1 << 63), vec![(2, 63)]);
+        // 3215031751 is a strong pseudoprime to the bases 2, 3, 5 and 7
+        assert_eq!(factorize(3215031751), vec![(151, 1), (751, 1), (28351, 1)]);
+        let mut rng = PCG32::new_default(42);
+        for _ in 0..200 {
+            let number = rng.get_u64() | 1;
+            check_factorization(number, &factorize(number));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "0 has no prime factorization")]
+    fn zero() {
+        factorize(0);
+    }
+
+    #[test]
+    fn arithmetic_functions() {
+        for number in 1..500 {
+            let expected = naive_divisors(number);
+            assert_eq!(divisors(number), expected);
+            assert_eq!(divisor_count(number), expected.len() as u64);
+            assert_eq!(
+                divisor_sum(number),
+                expected.iter().map(|&d| d as u128).sum::<u128>()
+            );
+            let totient = (1..=number).filter(|&k| gcd(k, number) == 1).count();
+            assert_eq!(euler_totient(number), totient as u64);
+            // The Moebius function sums to 0 over the divisors of n > 1
+            let sum: i64 = expected.iter().map(|&d| mobius(d) as i64).sum();
+            assert_eq!(sum, (number == 1) as i64);
+        }
+        assert_eq!(mobius(30), -1);
+        assert_eq!(mobius(18), 0);
+        assert_eq!(euler_totient(998_244_353), 998_244_352);
+        assert_eq!(divisor_count(u64::MAX), 128);
+        // sigma(2^64 - 1) does not fit in a u64
+        assert_eq!(
+            divisor_sum(u64::MAX),
+            4 * 6 * 18 * 258 * 642 * 65538 * 6700418
+        );
+        assert_eq!(
+            divisors(1_000_000_007 * 3),
+            vec![1, 3, 1_000_000_007, 3_000_000_021]
+        );
+    }
+
+    #[test]
+    fn primitive_roots() {
+        assert_eq!(primitive_root(0), None);
+        assert_eq!(primitive_root(1), Some(0));
+        assert_eq!(primitive_root(2), Some(1));
+        assert_eq!(primitive_root(4), Some(3));
+        assert_eq!(primitive_root(8), None);
+        assert_eq!(primitive_root(12), None);
+        assert_eq!(primitive_root(15), None);
+        assert_eq!(primitive_root(7), Some(3));
+        assert_eq!(primitive_root(49), Some(3));
+        assert_eq!(primitive_root(50), Some(3));
+        assert_eq!(primitive_root(998_244_353), Some(3));
+        assert_eq!(primitive_root(1_000_000_007), Some(5));
+        // Compare with the order of each candidate for small moduli
+        for modulus in 2..300_u64 {
+            let totient = euler_totient(modulus);
+            let order = |g: u64| {
+                let mut x = g % modulus;
+                let mut k = 1;
+                while x != 1 {
+                    x = x * g % modulus;
+                    k += 1;
+                    if k > modulus {
+                        return 0;
+                    }
+                }
+                k
+            };
+            let expected = (1..modulus).find(|&g| gcd(g, modulus) == 1 && order(g) == totient);
+            assert_eq!(primitive_root(modulus), expected, "modulus {modulus}");
+        }
+    }
+}
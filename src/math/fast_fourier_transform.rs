@@ -23,6 +23,14 @@ impl Complex64 {
         self.square_norm().sqrt()
     }
 
+    #[inline]
+    pub fn conj(&self) -> Complex64 {
+        Complex64 {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
     #[inline]
     pub fn inverse(&self) -> Complex64 {
         let nrm = self.square_norm();
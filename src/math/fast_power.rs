@@ -1,16 +1,20 @@
-/// fast_power returns the result of base^power mod modulus
-pub fn fast_power(mut base: usize, mut power: usize, modulus: usize) -> usize {
-    assert!(base >= 1);
+use super::DynModInt;
 
-    let mut res = 1;
-    while power > 0 {
-        if power & 1 == 1 {
-            res = (res * base) % modulus;
-        }
-        base = (base * base) % modulus;
-        power >>= 1;
+/// fast_power returns the result of base^power mod modulus, and 1 when power
+/// is 0 whatever the modulus.
+///
+/// # Panics
+///
+/// Panics if base is 0, or if modulus is 0 and power is not.
+pub fn fast_power(base: usize, power: usize, modulus: usize) -> usize {
+    assert!(base >= 1);
+    if power == 0 {
+        return 1;
     }
-    res
+
+    DynModInt::new(base as u64, modulus as u64)
+        .pow(power as u64)
+        .value() as usize
 }
 
 #[cfg(test)]
@@ -25,5 +29,18 @@ mod tests {
         assert_eq!(fast_power(2, 4, MOD), 16);
         assert_eq!(fast_power(3, 4, MOD), 81);
         assert_eq!(fast_power(2, 100, MOD), 976371285);
+        // The products do not overflow with a large modulus
+        assert_eq!(fast_power(3, 2, usize::MAX), 9);
+        assert_eq!(fast_power(usize::MAX - 1, 2, usize::MAX), 1);
+        // base^0 is 1, even modulo 1 or 0
+        assert_eq!(fast_power(5, 0, 1), 1);
+        assert_eq!(fast_power(5, 0, 0), 1);
+        assert_eq!(fast_power(5, 3, 1), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the modulus must be positive")]
+    fn zero_modulus() {
+        fast_power(2, 3, 0);
     }
 }
@@ -0,0 +1,756 @@
+/*
+FFT planner:
+The Discrete Fourier Transform of x_0, ..., x_(n-1) is
+    X_k = sum x_j * e^(-2 pi i jk / n),
+and the Fast Fourier Transform computes it in O(n log(n)) for any n. A
+planner chooses the algorithm for each length once, precomputes the roots of
+unity it needs, and caches the plan so it can be reused:
+1. Radix-2 Cooley-Tukey, in place, when n is a power of two.
+2. Mixed radix Cooley-Tukey, when the prime factors of n are small: for
+   n = p * m, the transforms of the p subsequences x_(j + p * t) of length m
+   are combined with DFTs of length p.
+3. Bluestein's algorithm otherwise: with jk = (j^2 + k^2 - (k - j)^2) / 2, the
+   DFT becomes a convolution with the chirp e^(pi i d^2 / n), which is done
+   with radix-2 FFTs of a length at least 2n - 1.
+https://en.wikipedia.org/wiki/Cooley%E2%80%93Tukey_FFT_algorithm
+https://en.wikipedia.org/wiki/Chirp_Z-transform#Bluestein's_algorithm
+
+The inverse transform divides by n, so that it gives back the input. Every
+plan also has a variant taking a scratch buffer, which does not allocate.
+
+The FFT of n real numbers is conjugate-symmetric, X_(n-k) = conj(X_k), so only
+its first n / 2 + 1 values are returned. For an even n, the even and odd
+numbers are packed as the real and imaginary parts of n / 2 complex numbers,
+which are transformed with a complex FFT of half the length, and the two
+transforms are separated afterwards.
+The 2D FFT of an image is the FFT of each row, followed by the FFT of each
+column.
+*/
+
+use super::Complex64;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+// The lengths with a larger prime factor use Bluestein's algorithm
+const MAX_RADIX: usize = 13;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FftDirection {
+    Forward,
+    // Divided by the length
+    Inverse,
+}
+
+// e^(-2 pi i k / n)
+fn root_of_unity(k: usize, n: usize) -> Complex64 {
+    let angle = -2.0 * PI * k as f64 / n as f64;
+    Complex64::new(angle.cos(), angle.sin())
+}
+
+fn scale(z: Complex64, factor: f64) -> Complex64 {
+    Complex64::new(z.re * factor, z.im * factor)
+}
+
+struct Radix2 {
+    // The n / 2 first powers of e^(-2 pi i / n)
+    roots: Vec<Complex64>,
+}
+
+impl Radix2 {
+    fn new(n: usize) -> Self {
+        Radix2 {
+            roots: (0..n / 2).map(|k| root_of_unity(k, n)).collect(),
+        }
+    }
+
+    fn forward(&self, buffer: &mut [Complex64]) {
+        let n = buffer.len();
+        // Bit-reversal permutation
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j ^= bit;
+            if i < j {
+                buffer.swap(i, j);
+            }
+        }
+        let mut length = 2;
+        while length <= n {
+            let half = length / 2;
+            let step = n / length;
+            for start in (0..n).step_by(length) {
+                for k in 0..half {
+                    let u = buffer[start + k];
+                    let v = buffer[start + k + half] * self.roots[k * step];
+                    buffer[start + k] = u + v;
+                    buffer[start + k + half] = u - v;
+                }
+            }
+            length <<= 1;
+        }
+    }
+}
+
+struct MixedRadix {
+    // The prime factors of n
+    factors: Vec<usize>,
+    // The n powers of e^(-2 pi i / n)
+    roots: Vec<Complex64>,
+}
+
+impl MixedRadix {
+    // The DFT of input[0], input[stride], input[2 * stride]... into output,
+    // where the roots of unity of this length are one out of `root_stride`
+    fn work(
+        &self,
+        output: &mut [Complex64],
+        input: &[Complex64],
+        stride: usize,
+        factors: &[usize],
+        root_stride: usize,
+    ) {
+        let n = output.len();
+        let p = factors[0];
+        let m = n / p;
+        if m == 1 {
+            for (j, x) in output.iter_mut().enumerate() {
+                *x = input[j * stride];
+            }
+        } else {
+            for j in 0..p {
+                self.work(
+                    &mut output[j * m..(j + 1) * m],
+                    &input[j * stride..],
+                    stride * p,
+                    &factors[1..],
+                    root_stride * p,
+                );
+            }
+        }
+        // X[k + q * m] = sum over j of w^(j * (k + q * m)) * Y_j[k], where Y_j
+        // is the transform of the j-th subsequence
+        let mut values = [Complex64::default(); MAX_RADIX];
+        for k in 0..m {
+            for (j, value) in values.iter_mut().take(p).enumerate() {
+                *value = output[k + j * m];
+            }
+            for q in 0..p {
+                let index = k + q * m;
+                output[index] = values[..p]
+                    .iter()
+                    .enumerate()
+                    .fold(Complex64::default(), |sum, (j, &y)| {
+                        sum + y * self.roots[j * index % n * root_stride]
+                    });
+            }
+        }
+    }
+}
+
+struct Bluestein {
+    inner: Radix2,
+    inner_len: usize,
+    // e^(-pi i k^2 / n)
+    chirp: Vec<Complex64>,
+    // The transform of the conjugated chirp, divided by inner_len
+    kernel: Vec<Complex64>,
+}
+
+impl Bluestein {
+    fn new(n: usize) -> Self {
+        let inner_len = (2 * n - 1).next_power_of_two();
+        let inner = Radix2::new(inner_len);
+        // k^2 is reduced modulo 2n for the precision of the angles
+        let chirp: Vec<Complex64> = (0..n)
+            .map(|k| root_of_unity(k * k % (2 * n), 2 * n))
+            .collect();
+        let mut kernel = vec![Complex64::default(); inner_len];
+        for (k, w) in chirp.iter().enumerate() {
+            kernel[k] = scale(w.conj(), 1.0 / inner_len as f64);
+            kernel[(inner_len - k) % inner_len] = kernel[k];
+        }
+        inner.forward(&mut kernel);
+        Bluestein {
+            inner,
+            inner_len,
+            chirp,
+            kernel,
+        }
+    }
+
+    fn forward(&self, buffer: &mut [Complex64], scratch: &mut [Complex64]) {
+        let a = &mut scratch[..self.inner_len];
+        a.fill(Complex64::default());
+        for ((a, &x), &w) in a.iter_mut().zip(buffer.iter()).zip(&self.chirp) {
+            *a = x * w;
+        }
+        self.inner.forward(a);
+        // The inverse transform of the product, with conjugates
+        for (a, &b) in a.iter_mut().zip(&self.kernel) {
+            *a = (*a * b).conj();
+        }
+        self.inner.forward(a);
+        for ((x, a), &w) in buffer.iter_mut().zip(a.iter()).zip(&self.chirp) {
+            *x = a.conj() * w;
+        }
+    }
+}
+
+enum Algorithm {
+    // For the lengths 0 and 1
+    Identity,
+    Radix2(Radix2),
+    MixedRadix(MixedRadix),
+    Bluestein(Bluestein),
+}
+
+/// A plan for the complex FFT of a given length.
+pub struct Fft {
+    len: usize,
+    algorithm: Algorithm,
+}
+
+impl Fft {
+    pub fn new(len: usize) -> Self {
+        let algorithm = if len <= 1 {
+            Algorithm::Identity
+        } else if len.is_power_of_two() {
+            Algorithm::Radix2(Radix2::new(len))
+        } else {
+            let mut factors = vec![];
+            let mut n = len;
+            let mut p = 2;
+            while p <= MAX_RADIX && n > 1 {
+                while n.is_multiple_of(p) {
+                    factors.push(p);
+                    n /= p;
+                }
+                p += 1;
+            }
+            if n == 1 {
+                Algorithm::MixedRadix(MixedRadix {
+                    factors,
+                    roots: (0..len).map(|k| root_of_unity(k, len)).collect(),
+                })
+            } else {
+                Algorithm::Bluestein(Bluestein::new(len))
+            }
+        };
+        Fft { len, algorithm }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The length of the scratch buffer needed by `process_with_scratch`.
+    pub fn scratch_len(&self) -> usize {
+        match &self.algorithm {
+            Algorithm::Identity | Algorithm::Radix2(_) => 0,
+            Algorithm::MixedRadix(_) => self.len,
+            Algorithm::Bluestein(bluestein) => bluestein.inner_len,
+        }
+    }
+
+    /// Replaces `buffer` by its transform, without allocating.
+    pub fn process_with_scratch(
+        &self,
+        buffer: &mut [Complex64],
+        scratch: &mut [Complex64],
+        direction: FftDirection,
+    ) {
+        assert_eq!(buffer.len(), self.len, "the buffer has the wrong length");
+        assert!(
+            scratch.len() >= self.scratch_len(),
+            "the scratch buffer is too short"
+        );
+        // The inverse transform is the conjugate of the transform of the
+        // conjugate, divided by n
+        if direction == FftDirection::Inverse {
+            buffer.iter_mut().for_each(|x| *x = x.conj());
+        }
+        match &self.algorithm {
+            Algorithm::Identity => {}
+            Algorithm::Radix2(radix2) => radix2.forward(buffer),
+            Algorithm::MixedRadix(mixed_radix) => {
+                let input = &mut scratch[..self.len];
+                input.copy_from_slice(buffer);
+                mixed_radix.work(buffer, input, 1, &mixed_radix.factors, 1);
+            }
+            Algorithm::Bluestein(bluestein) => bluestein.forward(buffer, scratch),
+        }
+        if direction == FftDirection::Inverse {
+            let factor = 1.0 / self.len as f64;
+            buffer.iter_mut().for_each(|x| *x = scale(x.conj(), factor));
+        }
+    }
+
+    pub fn process(&self, buffer: &mut [Complex64]) {
+        let mut scratch = vec![Complex64::default(); self.scratch_len()];
+        self.process_with_scratch(buffer, &mut scratch, FftDirection::Forward);
+    }
+
+    pub fn process_inverse(&self, buffer: &mut [Complex64]) {
+        let mut scratch = vec![Complex64::default(); self.scratch_len()];
+        self.process_with_scratch(buffer, &mut scratch, FftDirection::Inverse);
+    }
+}
+
+/// A plan for the FFT of real numbers, which returns the first half of the
+/// spectrum.
+pub struct RealFft {
+    len: usize,
+    // Of length len / 2 when len is even, and len otherwise
+    inner: Rc<Fft>,
+    // e^(-2 pi i k / len) for k <= len / 2, when len is even
+    twiddles: Vec<Complex64>,
+}
+
+impl RealFft {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of values of the spectrum, len / 2 + 1.
+    pub fn spectrum_len(&self) -> usize {
+        if self.len == 0 {
+            0
+        } else {
+            self.len / 2 + 1
+        }
+    }
+
+    pub fn scratch_len(&self) -> usize {
+        self.inner.len() + self.inner.scratch_len()
+    }
+
+    /// Writes the first len / 2 + 1 values of the transform of `input` to
+    /// `output`, without allocating.
+    pub fn process_with_scratch(
+        &self,
+        input: &[f64],
+        output: &mut [Complex64],
+        scratch: &mut [Complex64],
+    ) {
+        assert_eq!(input.len(), self.len, "the input has the wrong length");
+        assert_eq!(
+            output.len(),
+            self.spectrum_len(),
+            "the output has the wrong length"
+        );
+        let (buffer, scratch) = scratch.split_at_mut(self.inner.len());
+        if !self.len.is_multiple_of(2) {
+            for (z, &x) in buffer.iter_mut().zip(input) {
+                *z = Complex64::new(x, 0.0);
+            }
+            self.inner
+                .process_with_scratch(buffer, scratch, FftDirection::Forward);
+            output.copy_from_slice(&buffer[..output.len()]);
+            return;
+        }
+        let half = self.len / 2;
+        for (z, pair) in buffer.iter_mut().zip(input.chunks_exact(2)) {
+            *z = Complex64::new(pair[0], pair[1]);
+        }
+        self.inner
+            .process_with_scratch(buffer, scratch, FftDirection::Forward);
+        for (k, x) in output.iter_mut().enumerate() {
+            // The transforms of the even and of the odd numbers
+            let z = buffer[k % half];
+            let z_mirror = buffer[(half - k) % half].conj();
+            let even = scale(z + z_mirror, 0.5);
+            let difference = z - z_mirror;
+            let odd = Complex64::new(difference.im * 0.5, -difference.re * 0.5);
+            *x = even + self.twiddles[k] * odd;
+        }
+    }
+
+    /// Writes the real numbers whose transform starts with `input` to
+    /// `output`, without allocating. The imaginary parts of input[0] and,
+    /// for an even length, of the last value are ignored.
+    pub fn process_inverse_with_scratch(
+        &self,
+        input: &[Complex64],
+        output: &mut [f64],
+        scratch: &mut [Complex64],
+    ) {
+        assert_eq!(
+            input.len(),
+            self.spectrum_len(),
+            "the input has the wrong length"
+        );
+        assert_eq!(output.len(), self.len, "the output has the wrong length");
+        let (buffer, scratch) = scratch.split_at_mut(self.inner.len());
+        if !self.len.is_multiple_of(2) {
+            buffer[0] = Complex64::new(input[0].re, 0.0);
+            for k in 1..input.len() {
+                buffer[k] = input[k];
+                buffer[self.len - k] = input[k].conj();
+            }
+            self.inner
+                .process_with_scratch(buffer, scratch, FftDirection::Inverse);
+            for (x, z) in output.iter_mut().zip(buffer.iter()) {
+                *x = z.re;
+            }
+            return;
+        }
+        let half = self.len / 2;
+        for (k, z) in buffer.iter_mut().enumerate() {
+            let x = input[k];
+            let x_mirror = input[half - k].conj();
+            let even = scale(x + x_mirror, 0.5);
+            let odd = scale(x - x_mirror, 0.5) * self.twiddles[k].conj();
+            // even + i * odd
+            *z = Complex64::new(even.re - odd.im, even.im + odd.re);
+        }
+        self.inner
+            .process_with_scratch(buffer, scratch, FftDirection::Inverse);
+        for (pair, z) in output.chunks_exact_mut(2).zip(buffer.iter()) {
+            pair[0] = z.re;
+            pair[1] = z.im;
+        }
+    }
+
+    pub fn process(&self, input: &[f64]) -> Vec<Complex64> {
+        let mut output = vec![Complex64::default(); self.spectrum_len()];
+        let mut scratch = vec![Complex64::default(); self.scratch_len()];
+        self.process_with_scratch(input, &mut output, &mut scratch);
+        output
+    }
+
+    pub fn process_inverse(&self, input: &[Complex64]) -> Vec<f64> {
+        let mut output = vec![0.0; self.len];
+        let mut scratch = vec![Complex64::default(); self.scratch_len()];
+        self.process_inverse_with_scratch(input, &mut output, &mut scratch);
+        output
+    }
+}
+
+/// A plan for the FFT of a matrix of complex numbers stored row by row.
+pub struct Fft2d {
+    rows: usize,
+    columns: usize,
+    // Transforms the rows, of length `columns`
+    row_fft: Rc<Fft>,
+    column_fft: Rc<Fft>,
+}
+
+impl Fft2d {
+    pub fn scratch_len(&self) -> usize {
+        self.rows
+            + self
+                .row_fft
+                .scratch_len()
+                .max(self.column_fft.scratch_len())
+    }
+
+    pub fn process_with_scratch(
+        &self,
+        data: &mut [Complex64],
+        scratch: &mut [Complex64],
+        direction: FftDirection,
+    ) {
+        assert_eq!(
+            data.len(),
+            self.rows * self.columns,
+            "the data has the wrong size"
+        );
+        if data.is_empty() {
+            return;
+        }
+        let (column, scratch) = scratch.split_at_mut(self.rows);
+        for row in data.chunks_exact_mut(self.columns) {
+            self.row_fft.process_with_scratch(row, scratch, direction);
+        }
+        for j in 0..self.columns {
+            for (i, z) in column.iter_mut().enumerate() {
+                *z = data[i * self.columns + j];
+            }
+            self.column_fft
+                .process_with_scratch(column, scratch, direction);
+            for (i, &z) in column.iter().enumerate() {
+                data[i * self.columns + j] = z;
+            }
+        }
+    }
+
+    pub fn process(&self, data: &mut [Complex64]) {
+        let mut scratch = vec![Complex64::default(); self.scratch_len()];
+        self.process_with_scratch(data, &mut scratch, FftDirection::Forward);
+    }
+
+    pub fn process_inverse(&self, data: &mut [Complex64]) {
+        let mut scratch = vec![Complex64::default(); self.scratch_len()];
+        self.process_with_scratch(data, &mut scratch, FftDirection::Inverse);
+    }
+}
+
+/// Creates the plans, and reuses the ones of the lengths already seen.
+#[derive(Default)]
+pub struct FftPlanner {
+    plans: HashMap<usize, Rc<Fft>>,
+}
+
+impl FftPlanner {
+    pub fn new() -> Self {
+        FftPlanner {
+            plans: HashMap::new(),
+        }
+    }
+
+    pub fn plan(&mut self, len: usize) -> Rc<Fft> {
+        self.plans
+            .entry(len)
+            .or_insert_with(|| Rc::new(Fft::new(len)))
+            .clone()
+    }
+
+    pub fn plan_real(&mut self, len: usize) -> RealFft {
+        if len.is_multiple_of(2) {
+            RealFft {
+                len,
+                inner: self.plan(len / 2),
+                twiddles: (0..=len / 2).map(|k| root_of_unity(k, len)).collect(),
+            }
+        } else {
+            RealFft {
+                len,
+                inner: self.plan(len),
+                twiddles: vec![],
+            }
+        }
+    }
+
+    pub fn plan_2d(&mut self, rows: usize, columns: usize) -> Fft2d {
+        Fft2d {
+            rows,
+            columns,
+            row_fft: self.plan(columns),
+            column_fft: self.plan(rows),
+        }
+    }
+}
+
+/// The convolution of `a` and `b`, which are the coefficients of the product
+/// of the polynomials with coefficients `a` and `b`.
+pub fn fft_convolution(a: &[f64], b: &[f64]) -> Vec<f64> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let len = a.len() + b.len() - 1;
+    let size = len.next_power_of_two();
+    let fft = FftPlanner::new().plan_real(size);
+    let transform = |values: &[f64]| {
+        let mut padded = values.to_vec();
+        padded.resize(size, 0.0);
+        fft.process(&padded)
+    };
+    let product: Vec<Complex64> = transform(a)
+        .into_iter()
+        .zip(transform(b))
+        .map(|(x, y)| x * y)
+        .collect();
+    let mut result = fft.process_inverse(&product);
+    result.truncate(len);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn random_complex(rng: &mut PCG32, len: usize) -> Vec<Complex64> {
+        (0..len)
+            .map(|_| Complex64::new(random_f64(rng), random_f64(rng)))
+            .collect()
+    }
+
+    // In [-1, 1)
+    fn random_f64(rng: &mut PCG32) -> f64 {
+        rng.get_u32() as f64 / (1u64 << 31) as f64 - 1.0
+    }
+
+    fn naive_dft(input: &[Complex64]) -> Vec<Complex64> {
+        let n = input.len();
+        (0..n)
+            .map(|k| {
+                input
+                    .iter()
+                    .enumerate()
+                    .fold(Complex64::default(), |sum, (j, &x)| {
+                        sum + x * root_of_unity(j * k % n, n)
+                    })
+            })
+            .collect()
+    }
+
+    fn assert_close(a: &[Complex64], b: &[Complex64]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!(
+                (*x - *y).norm() < EPSILON * (a.len() as f64 + 1.0),
+                "{x:?} != {y:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn any_length() {
+        let mut planner = FftPlanner::new();
+        let mut rng = PCG32::new_default(43);
+        // Powers of two, small factors, and large prime factors
+        let lengths = (0..=40).chain([64, 97, 120, 221, 256, 343, 1000]);
+        for len in lengths {
+            let fft = planner.plan(len);
+            assert_eq!(fft.len(), len);
+            let input = random_complex(&mut rng, len);
+            let mut buffer = input.clone();
+            fft.process(&mut buffer);
+            assert_close(&buffer, &naive_dft(&input));
+            fft.process_inverse(&mut buffer);
+            assert_close(&buffer, &input);
+        }
+        assert!(matches!(
+            planner.plan(97).algorithm,
+            Algorithm::Bluestein(_)
+        ));
+        assert!(matches!(
+            planner.plan(120).algorithm,
+            Algorithm::MixedRadix(_)
+        ));
+        assert!(Rc::ptr_eq(&planner.plan(1000), &planner.plan(1000)));
+    }
+
+    #[test]
+    fn no_allocation() {
+        let mut planner = FftPlanner::new();
+        let mut rng = PCG32::new_default(5);
+        for len in [64, 60, 101] {
+            let fft = planner.plan(len);
+            let mut scratch = vec![Complex64::default(); fft.scratch_len()];
+            let input = random_complex(&mut rng, len);
+            let mut buffer = input.clone();
+            fft.process_with_scratch(&mut buffer, &mut scratch, FftDirection::Forward);
+            assert_close(&buffer, &naive_dft(&input));
+            fft.process_with_scratch(&mut buffer, &mut scratch, FftDirection::Inverse);
+            assert_close(&buffer, &input);
+        }
+    }
+
+    #[test]
+    fn real_input() {
+        let mut planner = FftPlanner::new();
+        let mut rng = PCG32::new_default(8);
+        for len in (1..=20).chain([64, 97, 100, 202]) {
+            let fft = planner.plan_real(len);
+            let input: Vec<f64> = (0..len).map(|_| random_f64(&mut rng)).collect();
+            let complex: Vec<Complex64> = input.iter().map(|&x| Complex64::new(x, 0.0)).collect();
+            let spectrum = fft.process(&input);
+            assert_eq!(spectrum.len(), len / 2 + 1);
+            assert_close(&spectrum, &naive_dft(&complex)[..len / 2 + 1]);
+            let output = fft.process_inverse(&spectrum);
+            for (x, y) in output.iter().zip(&input) {
+                assert!((x - y).abs() < EPSILON);
+            }
+        }
+        assert!(planner.plan_real(0).process(&[]).is_empty());
+    }
+
+    #[test]
+    fn two_dimensions() {
+        let mut planner = FftPlanner::new();
+        let mut rng = PCG32::new_default(2);
+        for (rows, columns) in [(3, 4), (8, 6), (1, 7), (5, 1)] {
+            let fft = planner.plan_2d(rows, columns);
+            let input = random_complex(&mut rng, rows * columns);
+            let mut data = input.clone();
+            fft.process(&mut data);
+            let expected: Vec<Complex64> = (0..rows * columns)
+                .map(|index| {
+                    let (k, l) = (index / columns, index % columns);
+                    input
+                        .iter()
+                        .enumerate()
+                        .fold(Complex64::default(), |sum, (i, &x)| {
+                            let (r, c) = (i / columns, i % columns);
+                            sum + x
+                                * root_of_unity(r * k % rows, rows)
+                                * root_of_unity(c * l % columns, columns)
+                        })
+                })
+                .collect();
+            assert_close(&data, &expected);
+            fft.process_inverse(&mut data);
+            assert_close(&data, &input);
+        }
+    }
+
+    #[test]
+    fn convolution() {
+        assert_eq!(fft_convolution(&[], &[1.0]), vec![]);
+        let result = fft_convolution(&[1.0, 2.0, 3.0], &[4.0, 5.0]);
+        for (x, y) in result.iter().zip([4.0, 13.0, 22.0, 15.0]) {
+            assert!((x - y).abs() < EPSILON);
+        }
+        let mut rng = PCG32::new_default(9);
+        let a: Vec<f64> = (0..300).map(|_| random_f64(&mut rng)).collect();
+        let b: Vec<f64> = (0..77).map(|_| random_f64(&mut rng)).collect();
+        let result = fft_convolution(&a, &b);
+        assert_eq!(result.len(), 376);
+        for (k, x) in result.iter().enumerate() {
+            let expected: f64 = (0..=k)
+                .filter(|&i| i < a.len() && k - i < b.len())
+                .map(|i| a[i] * b[k - i])
+                .sum();
+            assert!((x - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn small_cases() {
+        let mut planner = FftPlanner::new();
+        let mut empty: Vec<Complex64> = vec![];
+        planner.plan(0).process_inverse(&mut empty);
+        assert!(planner.plan_real(0).process_inverse(&[]).is_empty());
+        planner.plan_2d(0, 3).process(&mut empty);
+
+        let mut buffer = vec![Complex64::new(5.0, 0.0), Complex64::new(3.0, 0.0)];
+        planner.plan(2).process(&mut buffer);
+        assert_close(
+            &buffer,
+            &[Complex64::new(8.0, 0.0), Complex64::new(2.0, 0.0)],
+        );
+
+        // The transform of an impulse is constant, for every algorithm
+        for len in [7, 12, 16, 101] {
+            let mut buffer = vec![Complex64::default(); len];
+            buffer[0] = Complex64::new(1.0, 0.0);
+            planner.plan(len).process(&mut buffer);
+            assert_close(&buffer, &vec![Complex64::new(1.0, 0.0); len]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the buffer has the wrong length")]
+    fn wrong_length() {
+        FftPlanner::new()
+            .plan(4)
+            .process(&mut [Complex64::default(); 3]);
+    }
+}
@@ -0,0 +1,500 @@
+/*
+Iterative solvers:
+Solve Ax = b for a large sparse matrix A, with only products of A and vectors,
+where a factorisation would fill the matrix in. Each one improves x until the
+relative residual |b - Ax| / |b| is below a tolerance:
+- Conjugate gradient, for a symmetric positive definite A, moves along
+  directions which are conjugate (p_i^T A p_j = 0), and minimises the A-norm
+  of the error over a growing Krylov space. It converges in at most n steps
+  without rounding errors, and in O(sqrt(condition number)) in practice.
+  https://en.wikipedia.org/wiki/Conjugate_gradient_method
+- BiCGSTAB works for any invertible A, with the recurrences of the
+  biconjugate gradient, smoothed by a step of minimal residual.
+  https://en.wikipedia.org/wiki/Biconjugate_gradient_stabilized_method
+- Jacobi computes each x_i from row i and the previous x, and Gauss-Seidel
+  with the x_j already updated in the same sweep. They converge for strictly
+  diagonally dominant matrices, and Gauss-Seidel also for the symmetric
+  positive definite ones.
+  https://en.wikipedia.org/wiki/Jacobi_method
+  https://en.wikipedia.org/wiki/Gauss%E2%80%93Seidel_method
+*/
+
+use super::{CsrMatrix, LinearAlgebraError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolverOptions {
+    // The target for |b - Ax| / |b|
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        SolverOptions {
+            tolerance: 1e-10,
+            max_iterations: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    // The residual is below the tolerance
+    Converged,
+    MaxIterations,
+    // A division by zero, when the method does not apply to the matrix, e.g.
+    // conjugate gradient on a matrix which is not positive definite
+    Breakdown,
+}
+
+/// The last iterate of a solver, with how it got there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IterationReport {
+    pub solution: Vec<f64>,
+    pub iterations: usize,
+    // The relative residual |b - Ax| / |b| of the solution
+    pub residual: f64,
+    pub stop_reason: StopReason,
+}
+
+impl IterationReport {
+    pub fn converged(&self) -> bool {
+        self.stop_reason == StopReason::Converged
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+// y += factor * x
+fn add_scaled(y: &mut [f64], factor: f64, x: &[f64]) {
+    for (y, x) in y.iter_mut().zip(x) {
+        *y += factor * x;
+    }
+}
+
+fn check_system(a: &CsrMatrix<f64>, b: &[f64]) -> Result<(), LinearAlgebraError> {
+    if a.rows() != a.cols() {
+        return Err(LinearAlgebraError::NotSquare);
+    }
+    if b.len() != a.rows() {
+        return Err(LinearAlgebraError::DimensionMismatch);
+    }
+    if b.iter().all(|x| x.is_finite()) && a.to_coo().entries().iter().all(|x| x.2.is_finite()) {
+        Ok(())
+    } else {
+        Err(LinearAlgebraError::NotFinite)
+    }
+}
+
+// The relative residual |b - Ax| / |b|, where |b| is not zero
+fn relative_residual(a: &CsrMatrix<f64>, b: &[f64], x: &[f64]) -> f64 {
+    let ax = a.mul_vector(x);
+    let residual: Vec<f64> = b.iter().zip(&ax).map(|(b, y)| b - y).collect();
+    norm(&residual) / norm(b)
+}
+
+// Runs `step` until the residual is below the tolerance. The step updates x
+// and returns the norm of the residual b - Ax it keeps track of, which saves
+// a product with A per iteration, or None on a breakdown. The true residual
+// is only computed for the report
+fn iterate(
+    a: &CsrMatrix<f64>,
+    b: &[f64],
+    options: &SolverOptions,
+    mut step: impl FnMut(&mut [f64]) -> Option<f64>,
+) -> IterationReport {
+    let mut x = vec![0.0; b.len()];
+    let b_norm = norm(b);
+    if b_norm == 0.0 {
+        return IterationReport {
+            solution: x,
+            iterations: 0,
+            residual: 0.0,
+            stop_reason: StopReason::Converged,
+        };
+    }
+    let mut residual = 1.0;
+    let mut iterations = 0;
+    let mut stop_reason = StopReason::MaxIterations;
+    while iterations < options.max_iterations {
+        if residual <= options.tolerance {
+            stop_reason = StopReason::Converged;
+            break;
+        }
+        iterations += 1;
+        match step(&mut x) {
+            Some(residual_norm) => residual = residual_norm / b_norm,
+            None => {
+                stop_reason = StopReason::Breakdown;
+                break;
+            }
+        }
+    }
+    if stop_reason == StopReason::MaxIterations && residual <= options.tolerance {
+        stop_reason = StopReason::Converged;
+    }
+    IterationReport {
+        residual: relative_residual(a, b, &x),
+        solution: x,
+        iterations,
+        stop_reason,
+    }
+}
+
+/// Conjugate gradient, for a symmetric positive definite matrix, from x = 0.
+pub fn conjugate_gradient(
+    a: &CsrMatrix<f64>,
+    b: &[f64],
+    options: &SolverOptions,
+) -> Result<IterationReport, LinearAlgebraError> {
+    check_system(a, b)?;
+    // The residual b - Ax and the search direction
+    let mut r = b.to_vec();
+    let mut p = b.to_vec();
+    let mut r_squared = dot(&r, &r);
+    Ok(iterate(a, b, options, |x| {
+        let ap = a.mul_vector(&p);
+        let curvature = dot(&p, &ap);
+        if curvature <= 0.0 {
+            return None;
+        }
+        let alpha = r_squared / curvature;
+        add_scaled(x, alpha, &p);
+        add_scaled(&mut r, -alpha, &ap);
+        let next_r_squared = dot(&r, &r);
+        let beta = next_r_squared / r_squared;
+        r_squared = next_r_squared;
+        for (p, r) in p.iter_mut().zip(&r) {
+            *p = r + beta * *p;
+        }
+        Some(r_squared.sqrt())
+    }))
+}
+
+/// BiCGSTAB, for any invertible matrix, from x = 0.
+pub fn bicgstab(
+    a: &CsrMatrix<f64>,
+    b: &[f64],
+    options: &SolverOptions,
+) -> Result<IterationReport, LinearAlgebraError> {
+    check_system(a, b)?;
+    let n = b.len();
+    let mut r = b.to_vec();
+    // The shadow residual, fixed
+    let r_hat = b.to_vec();
+    let (mut rho, mut alpha, mut omega) = (1.0, 1.0, 1.0);
+    let mut v = vec![0.0; n];
+    let mut p = vec![0.0; n];
+    Ok(iterate(a, b, options, |x| {
+        let next_rho = dot(&r_hat, &r);
+        if next_rho == 0.0 || omega == 0.0 {
+            return None;
+        }
+        let beta = next_rho / rho * alpha / omega;
+        rho = next_rho;
+        for ((p, r), v) in p.iter_mut().zip(&r).zip(&v) {
+            *p = r + beta * (*p - omega * v);
+        }
+        v = a.mul_vector(&p);
+        let denominator = dot(&r_hat, &v);
+        if denominator == 0.0 {
+            return None;
+        }
+        alpha = rho / denominator;
+        // s = r - alpha v, stored in r
+        add_scaled(&mut r, -alpha, &v);
+        add_scaled(x, alpha, &p);
+        let t = a.mul_vector(&r);
+        let t_squared = dot(&t, &t);
+        omega = if t_squared == 0.0 {
+            // s = 0, x is the solution
+            0.0
+        } else {
+            dot(&t, &r) / t_squared
+        };
+        add_scaled(x, omega, &r);
+        add_scaled(&mut r, -omega, &t);
+        Some(norm(&r))
+    }))
+}
+
+// The diagonal of A, which must not contain zeros
+fn diagonal(a: &CsrMatrix<f64>) -> Result<Vec<f64>, LinearAlgebraError> {
+    let diagonal: Vec<f64> = (0..a.rows()).map(|i| a.get(i, i)).collect();
+    if diagonal.contains(&0.0) {
+        Err(LinearAlgebraError::ZeroDiagonal)
+    } else {
+        Ok(diagonal)
+    }
+}
+
+/// The Jacobi method, for a matrix with a nonzero diagonal, from x = 0.
+pub fn jacobi(
+    a: &CsrMatrix<f64>,
+    b: &[f64],
+    options: &SolverOptions,
+) -> Result<IterationReport, LinearAlgebraError> {
+    check_system(a, b)?;
+    let diagonal = diagonal(a)?;
+    // The residual of row i before the update is d_i times the change of x_i,
+    // so each sweep returns the residual of the previous x
+    Ok(iterate(a, b, options, |x| {
+        let previous = x.to_vec();
+        let mut residual_squared = 0.0;
+        for (i, x) in x.iter_mut().enumerate() {
+            let off_diagonal: f64 = a
+                .row(i)
+                .filter(|&(j, _)| j != i)
+                .map(|(j, value)| value * previous[j])
+                .sum();
+            let next = (b[i] - off_diagonal) / diagonal[i];
+            residual_squared += (diagonal[i] * (next - *x)).powi(2);
+            *x = next;
+        }
+        Some(residual_squared.sqrt())
+    }))
+}
+
+/// The Gauss-Seidel method, for a matrix with a nonzero diagonal, from x = 0.
+pub fn gauss_seidel(
+    a: &CsrMatrix<f64>,
+    b: &[f64],
+    options: &SolverOptions,
+) -> Result<IterationReport, LinearAlgebraError> {
+    check_system(a, b)?;
+    let diagonal = diagonal(a)?;
+    // Like Jacobi, the changes of x estimate the residual, here of row i
+    // with the x_j already updated in the sweep
+    Ok(iterate(a, b, options, |x| {
+        let mut residual_squared = 0.0;
+        for i in 0..x.len() {
+            let off_diagonal: f64 = a
+                .row(i)
+                .filter(|&(j, _)| j != i)
+                .map(|(j, value)| value * x[j])
+                .sum();
+            let next = (b[i] - off_diagonal) / diagonal[i];
+            residual_squared += (diagonal[i] * (next - x[i])).powi(2);
+            x[i] = next;
+        }
+        Some(residual_squared.sqrt())
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{CooMatrix, PCG32};
+
+    // The matrix of -u'' = f on a grid of n points, tridiagonal with 2 on
+    // the diagonal and -1 next to it
+    fn poisson(n: usize) -> CsrMatrix<f64> {
+        let mut coo = CooMatrix::new(n, n);
+        for i in 0..n {
+            coo.push(i, i, 2.0);
+            if i > 0 {
+                coo.push(i, i - 1, -1.0);
+                coo.push(i - 1, i, -1.0);
+            }
+        }
+        coo.to_csr()
+    }
+
+    // The Laplacian of a random graph, plus the identity to make it
+    // invertible: symmetric positive definite and diagonally dominant
+    fn shifted_laplacian(rng: &mut PCG32, n: usize) -> CsrMatrix<f64> {
+        let mut coo = CooMatrix::new(n, n);
+        for i in 0..n {
+            coo.push(i, i, 1.0);
+        }
+        for _ in 0..3 * n {
+            let (u, v) = (rng.get_u32() as usize % n, rng.get_u32() as usize % n);
+            if u != v {
+                coo.push(u, u, 1.0);
+                coo.push(v, v, 1.0);
+                coo.push(u, v, -1.0);
+                coo.push(v, u, -1.0);
+            }
+        }
+        coo.to_csr()
+    }
+
+    // A random nonsymmetric, strictly diagonally dominant matrix
+    fn random_dominant(rng: &mut PCG32, n: usize) -> CsrMatrix<f64> {
+        let mut coo = CooMatrix::new(n, n);
+        for i in 0..n {
+            let mut sum = 0.0;
+            for _ in 0..3 {
+                let j = rng.get_u32() as usize % n;
+                let value = rng.get_u32() as f64 / u32::MAX as f64 - 0.5;
+                if j != i {
+                    coo.push(i, j, value);
+                    sum += value.abs();
+                }
+            }
+            coo.push(i, i, sum + 1.0);
+        }
+        coo.to_csr()
+    }
+
+    fn random_vector(rng: &mut PCG32, n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|_| rng.get_u32() as f64 / u32::MAX as f64 * 2.0 - 1.0)
+            .collect()
+    }
+
+    fn assert_solves(a: &CsrMatrix<f64>, b: &[f64], report: &IterationReport) {
+        assert!(report.converged(), "{report:?}");
+        assert!(report.residual <= 1e-10);
+        let expected = a.to_dense().solve(b).unwrap();
+        for (x, y) in report.solution.iter().zip(&expected) {
+            assert!((x - y).abs() < 1e-7, "{x} != {y}");
+        }
+    }
+
+    #[test]
+    fn conjugate_gradient_on_poisson() {
+        let n = 50;
+        let a = poisson(n);
+        let b = vec![1.0; n];
+        let report = conjugate_gradient(&a, &b, &SolverOptions::default()).unwrap();
+        assert_solves(&a, &b, &report);
+        // At most n steps, up to rounding errors
+        assert!(report.iterations <= n + 5);
+    }
+
+    #[test]
+    fn solvers_on_laplacians() {
+        let mut rng = PCG32::new_default(47);
+        let options = SolverOptions::default();
+        for n in [1, 10, 40] {
+            let a = shifted_laplacian(&mut rng, n);
+            let b = random_vector(&mut rng, n);
+            assert_solves(&a, &b, &conjugate_gradient(&a, &b, &options).unwrap());
+            assert_solves(&a, &b, &bicgstab(&a, &b, &options).unwrap());
+            assert_solves(&a, &b, &gauss_seidel(&a, &b, &options).unwrap());
+        }
+    }
+
+    #[test]
+    fn solvers_on_nonsymmetric_matrices() {
+        let mut rng = PCG32::new_default(5);
+        let options = SolverOptions::default();
+        for n in [2, 15, 60] {
+            let a = random_dominant(&mut rng, n);
+            let b = random_vector(&mut rng, n);
+            assert_solves(&a, &b, &bicgstab(&a, &b, &options).unwrap());
+            let jacobi = jacobi(&a, &b, &options).unwrap();
+            let gauss_seidel = gauss_seidel(&a, &b, &options).unwrap();
+            assert_solves(&a, &b, &jacobi);
+            assert_solves(&a, &b, &gauss_seidel);
+            assert!(gauss_seidel.iterations <= jacobi.iterations);
+        }
+    }
+
+    #[test]
+    fn reports() {
+        let a = poisson(100);
+        let b = vec![1.0; 100];
+        let options = SolverOptions {
+            tolerance: 1e-10,
+            max_iterations: 5,
+        };
+        let report = jacobi(&a, &b, &options).unwrap();
+        assert_eq!(report.stop_reason, StopReason::MaxIterations);
+        assert_eq!(report.iterations, 5);
+        assert!(report.residual > 1e-10);
+
+        let report = conjugate_gradient(&a, &[0.0; 100], &options).unwrap();
+        assert_eq!(report.iterations, 0);
+        assert_eq!(report.solution, vec![0.0; 100]);
+        assert!(report.converged());
+
+        // Indefinite: p^T A p = 0 on the first step
+        let mut coo = CooMatrix::new(2, 2);
+        coo.push(0, 0, 1.0);
+        coo.push(1, 1, -1.0);
+        let report = conjugate_gradient(&coo.to_csr(), &[1.0, 1.0], &options).unwrap();
+        assert_eq!(report.stop_reason, StopReason::Breakdown);
+    }
+
+    #[test]
+    fn edge_cases() {
+        let options = SolverOptions::default();
+        // An empty system is solved by the empty vector
+        let empty = CooMatrix::<f64>::new(0, 0).to_csr();
+        let report = bicgstab(&empty, &[], &options).unwrap();
+        assert!(report.converged());
+        assert!(report.solution.is_empty());
+        // 2x = 3 takes a single step with every method
+        let mut coo = CooMatrix::new(1, 1);
+        coo.push(0, 0, 2.0);
+        let a = coo.to_csr();
+        for report in [
+            conjugate_gradient(&a, &[3.0], &options).unwrap(),
+            bicgstab(&a, &[3.0], &options).unwrap(),
+        ] {
+            assert!(report.converged());
+            assert_eq!(report.iterations, 1);
+            assert_eq!(report.solution, vec![1.5]);
+            assert_eq!(report.residual, 0.0);
+        }
+        // Jacobi and Gauss-Seidel only see that the previous x was a solution
+        // on the next sweep
+        for report in [
+            jacobi(&a, &[3.0], &options).unwrap(),
+            gauss_seidel(&a, &[3.0], &options).unwrap(),
+        ] {
+            assert!(report.converged());
+            assert_eq!(report.iterations, 2);
+            assert_eq!(report.solution, vec![1.5]);
+        }
+        // No iteration is allowed
+        let options = SolverOptions {
+            tolerance: 1e-10,
+            max_iterations: 0,
+        };
+        let report = conjugate_gradient(&a, &[3.0], &options).unwrap();
+        assert_eq!(report.stop_reason, StopReason::MaxIterations);
+        assert_eq!(report.solution, vec![0.0]);
+        assert_eq!(report.residual, 1.0);
+    }
+
+    #[test]
+    fn errors() {
+        let options = SolverOptions::default();
+        let a = poisson(3);
+        assert_eq!(
+            jacobi(&a, &[1.0, 2.0], &options),
+            Err(LinearAlgebraError::DimensionMismatch)
+        );
+        assert_eq!(
+            bicgstab(&a, &[1.0, f64::NAN, 2.0], &options),
+            Err(LinearAlgebraError::NotFinite)
+        );
+        let mut coo = CooMatrix::new(2, 3);
+        coo.push(0, 0, 1.0);
+        assert_eq!(
+            conjugate_gradient(&coo.to_csr(), &[1.0, 1.0], &options),
+            Err(LinearAlgebraError::NotSquare)
+        );
+        // An invertible permutation matrix, but with zeros on the diagonal
+        let mut coo = CooMatrix::new(2, 2);
+        coo.push(0, 1, 1.0);
+        coo.push(1, 0, 1.0);
+        assert_eq!(
+            gauss_seidel(&coo.to_csr(), &[1.0, 1.0], &options),
+            Err(LinearAlgebraError::ZeroDiagonal)
+        );
+        assert_eq!(
+            jacobi(&coo.to_csr(), &[1.0, 1.0], &options),
+            Err(LinearAlgebraError::ZeroDiagonal)
+        );
+    }
+}
@@ -0,0 +1,623 @@
+/*
+Matrix decompositions:
+Dense linear algebra on Matrix<f64>, through the factorisations of a matrix A:
+- LU with partial pivoting, PA = LU, where L is lower triangular with ones on
+  its diagonal and U upper triangular. At each step, the row with the largest
+  value in the column becomes the pivot, which keeps the multipliers below 1
+  and the elimination stable. It gives the determinant, the product of the
+  diagonal of U (with the sign of the permutation), the solution of Ax = b by
+  two triangular solves, and the inverse, column by column.
+  https://en.wikipedia.org/wiki/LU_decomposition
+- QR, A = QR with Q orthonormal and R upper triangular, from Householder
+  reflections I - 2vv^T, each of which zeroes a column below the diagonal.
+  The least squares solution of Ax = b, which minimises |Ax - b|, solves
+  Rx = Q^T b without squaring the condition number like the normal equations.
+  https://en.wikipedia.org/wiki/QR_decomposition
+- Cholesky, A = LL^T, for a symmetric positive definite matrix, at half the
+  cost of LU.
+  https://en.wikipedia.org/wiki/Cholesky_decomposition
+- The rank, the number of pivots of Gaussian elimination.
+
+A pivot is considered zero when it is below n * epsilon times the largest
+absolute value of the matrix, so numerically singular matrices give an error
+instead of NaNs or meaningless results.
+*/
+
+use super::Matrix;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinearAlgebraError {
+    // The dimensions of the matrices or vectors do not fit together
+    DimensionMismatch,
+    NotSquare,
+    // The matrix or the right-hand side contains NaN or an infinity
+    NotFinite,
+    // The matrix is singular, or too close to singular
+    Singular,
+    NotSymmetric,
+    NotPositiveDefinite,
+    // The columns are linearly dependent, so the least squares solution is
+    // not unique
+    RankDeficient,
+    // The Jacobi and Gauss-Seidel methods divide by the diagonal, so it must
+    // not contain zeros, even if the matrix is invertible
+    ZeroDiagonal,
+}
+
+impl fmt::Display for LinearAlgebraError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            LinearAlgebraError::DimensionMismatch => "the dimensions do not match",
+            LinearAlgebraError::NotSquare => "the matrix is not square",
+            LinearAlgebraError::NotFinite => "the matrix or vector contains a NaN or an infinity",
+            LinearAlgebraError::Singular => "the matrix is singular",
+            LinearAlgebraError::NotSymmetric => "the matrix is not symmetric",
+            LinearAlgebraError::NotPositiveDefinite => "the matrix is not positive definite",
+            LinearAlgebraError::RankDeficient => "the columns are linearly dependent",
+            LinearAlgebraError::ZeroDiagonal => "the diagonal contains a zero",
+        };
+        write!(f, "{message}")
+    }
+}
+
+// The row-major values of the matrix
+pub(super) fn to_rows(a: &Matrix<f64>) -> Vec<Vec<f64>> {
+    (0..a.rows())
+        .map(|i| (0..a.cols()).map(|j| a[[i, j]]).collect())
+        .collect()
+}
+
+pub(super) fn from_rows(rows: Vec<Vec<f64>>, cols: usize) -> Matrix<f64> {
+    let n = rows.len();
+    Matrix::new(rows.into_iter().flatten().collect(), n, cols)
+}
+
+// The values below this are rounding errors
+pub(super) fn tolerance(a: &Matrix<f64>) -> f64 {
+    let largest = to_rows(a)
+        .iter()
+        .flatten()
+        .fold(0.0f64, |largest, x| largest.max(x.abs()));
+    a.rows().max(a.cols()) as f64 * f64::EPSILON * largest
+}
+
+pub(super) fn check_finite(a: &Matrix<f64>) -> Result<(), LinearAlgebraError> {
+    if to_rows(a).iter().flatten().all(|x| x.is_finite()) {
+        Ok(())
+    } else {
+        Err(LinearAlgebraError::NotFinite)
+    }
+}
+
+pub(super) fn check_square(a: &Matrix<f64>) -> Result<(), LinearAlgebraError> {
+    check_finite(a)?;
+    if a.rows() == a.cols() {
+        Ok(())
+    } else {
+        Err(LinearAlgebraError::NotSquare)
+    }
+}
+
+pub(super) fn check_symmetric(a: &Matrix<f64>) -> Result<(), LinearAlgebraError> {
+    check_square(a)?;
+    let tolerance = tolerance(a);
+    for i in 0..a.rows() {
+        for j in 0..i {
+            if (a[[i, j]] - a[[j, i]]).abs() > tolerance {
+                return Err(LinearAlgebraError::NotSymmetric);
+            }
+        }
+    }
+    Ok(())
+}
+
+// rows[target] -= factor * rows[source], from the column `from`, with
+// source < target
+fn subtract_row(rows: &mut [Vec<f64>], target: usize, source: usize, factor: f64, from: usize) {
+    let (top, bottom) = rows.split_at_mut(target);
+    for (x, &y) in bottom[0][from..].iter_mut().zip(&top[source][from..]) {
+        *x -= factor * y;
+    }
+}
+
+// Applies the reflection I - 2vv^T to the rows from k of a column
+fn reflect(rows: &mut [Vec<f64>], v: &[f64], k: usize, column: usize) {
+    let dot: f64 = rows[k..]
+        .iter()
+        .zip(v)
+        .map(|(row, x)| x * row[column])
+        .sum();
+    for (row, x) in rows[k..].iter_mut().zip(v) {
+        row[column] -= 2.0 * x * dot;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LuDecomposition {
+    // L below the diagonal, without its ones, and U on and above it
+    lu: Vec<Vec<f64>>,
+    // Row i of PA is row permutation[i] of A
+    pub permutation: Vec<usize>,
+    // 1 or -1, the parity of the permutation
+    sign: f64,
+}
+
+impl LuDecomposition {
+    pub fn l(&self) -> Matrix<f64> {
+        let n = self.lu.len();
+        let mut l = Matrix::identity(n);
+        for i in 0..n {
+            for j in 0..i {
+                l[[i, j]] = self.lu[i][j];
+            }
+        }
+        l
+    }
+
+    pub fn u(&self) -> Matrix<f64> {
+        let n = self.lu.len();
+        let mut u = Matrix::zero(n, n);
+        for i in 0..n {
+            for j in i..n {
+                u[[i, j]] = self.lu[i][j];
+            }
+        }
+        u
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.sign * (0..self.lu.len()).map(|i| self.lu[i][i]).product::<f64>()
+    }
+
+    /// The solution x of Ax = b.
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, LinearAlgebraError> {
+        let n = self.lu.len();
+        if b.len() != n {
+            return Err(LinearAlgebraError::DimensionMismatch);
+        }
+        if !b.iter().all(|x| x.is_finite()) {
+            return Err(LinearAlgebraError::NotFinite);
+        }
+        // Ly = Pb, then Ux = y
+        let mut x: Vec<f64> = self.permutation.iter().map(|&i| b[i]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.lu[i][j] * x[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] -= self.lu[i][j] * x[j];
+            }
+            x[i] /= self.lu[i][i];
+        }
+        Ok(x)
+    }
+}
+
+// The LU decomposition, and whether a pivot was zero
+fn decompose_lu(a: &Matrix<f64>) -> (LuDecomposition, bool) {
+    let n = a.rows();
+    let tolerance = tolerance(a);
+    let mut lu = to_rows(a);
+    let mut permutation: Vec<usize> = (0..n).collect();
+    let mut sign = 1.0;
+    let mut singular = false;
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| lu[i][k].abs().total_cmp(&lu[j][k].abs()))
+            .unwrap();
+        if pivot != k {
+            lu.swap(pivot, k);
+            permutation.swap(pivot, k);
+            sign = -sign;
+        }
+        if lu[k][k].abs() <= tolerance {
+            singular = true;
+            lu[k][k] = 0.0;
+            continue;
+        }
+        for i in k + 1..n {
+            let factor = lu[i][k] / lu[k][k];
+            subtract_row(&mut lu, i, k, factor, k + 1);
+            lu[i][k] = factor;
+        }
+    }
+    (
+        LuDecomposition {
+            lu,
+            permutation,
+            sign,
+        },
+        singular,
+    )
+}
+
+/// The thin QR decomposition of a matrix with at least as many rows as
+/// columns: `q` has orthonormal columns and the shape of the matrix, and `r`
+/// is square and upper triangular.
+#[derive(Debug, PartialEq)]
+pub struct QrDecomposition {
+    pub q: Matrix<f64>,
+    pub r: Matrix<f64>,
+}
+
+impl Matrix<f64> {
+    /// The LU decomposition with partial pivoting of a square matrix, or
+    /// `Singular` if it has no inverse.
+    pub fn lu(&self) -> Result<LuDecomposition, LinearAlgebraError> {
+        check_square(self)?;
+        match decompose_lu(self) {
+            (_, true) => Err(LinearAlgebraError::Singular),
+            (lu, false) => Ok(lu),
+        }
+    }
+
+    /// The determinant of a square matrix, which is 0 for a numerically
+    /// singular one.
+    pub fn determinant(&self) -> Result<f64, LinearAlgebraError> {
+        check_square(self)?;
+        Ok(decompose_lu(self).0.determinant())
+    }
+
+    pub fn inverse(&self) -> Result<Matrix<f64>, LinearAlgebraError> {
+        let lu = self.lu()?;
+        let n = self.rows();
+        let mut inverse = Matrix::zero(n, n);
+        for j in 0..n {
+            let mut e = vec![0.0; n];
+            e[j] = 1.0;
+            for (i, x) in lu.solve(&e)?.into_iter().enumerate() {
+                inverse[[i, j]] = x;
+            }
+        }
+        Ok(inverse)
+    }
+
+    /// The solution x of Ax = b, for a square and invertible A.
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, LinearAlgebraError> {
+        self.lu()?.solve(b)
+    }
+
+    /// The lower triangular L with LL^T = A, for a symmetric positive
+    /// definite A.
+    pub fn cholesky(&self) -> Result<Matrix<f64>, LinearAlgebraError> {
+        check_symmetric(self)?;
+        let n = self.rows();
+        let tolerance = tolerance(self);
+        let mut l = Matrix::zero(n, n);
+        for j in 0..n {
+            let diagonal = self[[j, j]] - (0..j).map(|k| l[[j, k]] * l[[j, k]]).sum::<f64>();
+            if diagonal <= tolerance {
+                return Err(LinearAlgebraError::NotPositiveDefinite);
+            }
+            l[[j, j]] = diagonal.sqrt();
+            for i in j + 1..n {
+                let sum = (0..j).map(|k| l[[i, k]] * l[[j, k]]).sum::<f64>();
+                l[[i, j]] = (self[[i, j]] - sum) / l[[j, j]];
+            }
+        }
+        Ok(l)
+    }
+
+    /// The QR decomposition with Householder reflections, for a matrix with
+    /// at least as many rows as columns.
+    pub fn qr(&self) -> Result<QrDecomposition, LinearAlgebraError> {
+        check_finite(self)?;
+        let (m, n) = (self.rows(), self.cols());
+        if m < n {
+            return Err(LinearAlgebraError::DimensionMismatch);
+        }
+        let mut r = to_rows(self);
+        // The unit vectors v of the reflections I - 2vv^T, zero when the
+        // column is already zero
+        let mut reflections = vec![];
+        for k in 0..n {
+            let norm = (k..m).map(|i| r[i][k] * r[i][k]).sum::<f64>().sqrt();
+            let mut v: Vec<f64> = (k..m).map(|i| r[i][k]).collect();
+            // The sign avoids the cancellation in v[0] - alpha
+            let alpha = if v[0] > 0.0 { -norm } else { norm };
+            v[0] -= alpha;
+            let v_norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if v_norm > 0.0 {
+                v.iter_mut().for_each(|x| *x /= v_norm);
+                for j in k..n {
+                    reflect(&mut r, &v, k, j);
+                }
+            }
+            reflections.push(v);
+        }
+        // Q = H_1 ... H_n applied to the first n columns of the identity
+        let mut q = vec![vec![0.0; n]; m];
+        (0..n).for_each(|i| q[i][i] = 1.0);
+        for (k, v) in reflections.iter().enumerate().rev() {
+            for j in 0..n {
+                reflect(&mut q, v, k, j);
+            }
+        }
+        r.truncate(n);
+        for (i, row) in r.iter_mut().enumerate() {
+            row[..i].fill(0.0);
+        }
+        Ok(QrDecomposition {
+            q: from_rows(q, n),
+            r: from_rows(r, n),
+        })
+    }
+
+    /// The x minimising |Ax - b|, for a matrix with at least as many rows as
+    /// columns, and linearly independent columns.
+    pub fn least_squares(&self, b: &[f64]) -> Result<Vec<f64>, LinearAlgebraError> {
+        if b.len() != self.rows() {
+            return Err(LinearAlgebraError::DimensionMismatch);
+        }
+        if !b.iter().all(|x| x.is_finite()) {
+            return Err(LinearAlgebraError::NotFinite);
+        }
+        let QrDecomposition { q, r } = self.qr()?;
+        let n = self.cols();
+        let tolerance = tolerance(&r);
+        if (0..n).any(|i| r[[i, i]].abs() <= tolerance) {
+            return Err(LinearAlgebraError::RankDeficient);
+        }
+        // Rx = Q^T b
+        let mut x: Vec<f64> = (0..n)
+            .map(|j| (0..b.len()).map(|i| q[[i, j]] * b[i]).sum())
+            .collect();
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] -= r[[i, j]] * x[j];
+            }
+            x[i] /= r[[i, i]];
+        }
+        Ok(x)
+    }
+
+    /// The number of linearly independent rows (or columns), up to rounding
+    /// errors.
+    pub fn rank(&self) -> Result<usize, LinearAlgebraError> {
+        check_finite(self)?;
+        let (m, n) = (self.rows(), self.cols());
+        let tolerance = tolerance(self);
+        let mut a = to_rows(self);
+        let mut rank = 0;
+        for k in 0..n {
+            if rank == m {
+                break;
+            }
+            let pivot = (rank..m)
+                .max_by(|&i, &j| a[i][k].abs().total_cmp(&a[j][k].abs()))
+                .unwrap();
+            if a[pivot][k].abs() <= tolerance {
+                continue;
+            }
+            a.swap(pivot, rank);
+            for i in rank + 1..m {
+                let factor = a[i][k] / a[rank][k];
+                subtract_row(&mut a, i, rank, factor, k);
+            }
+            rank += 1;
+        }
+        Ok(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn assert_close(a: &Matrix<f64>, b: &Matrix<f64>) {
+        assert_eq!((a.rows(), a.cols()), (b.rows(), b.cols()));
+        for i in 0..a.rows() {
+            for j in 0..a.cols() {
+                assert!((a[[i, j]] - b[[i, j]]).abs() < EPSILON, "{a:?} != {b:?}");
+            }
+        }
+    }
+
+    fn random_matrix(rng: &mut PCG32, rows: usize, cols: usize) -> Matrix<f64> {
+        let data = (0..rows * cols)
+            .map(|_| rng.get_u32() as f64 / u32::MAX as f64 * 2.0 - 1.0)
+            .collect();
+        Matrix::new(data, rows, cols)
+    }
+
+    #[test]
+    fn lu() {
+        let a = Matrix::from(vec![
+            vec![2.0, 1.0, 1.0],
+            vec![4.0, -6.0, 0.0],
+            vec![-2.0, 7.0, 2.0],
+        ]);
+        let lu = a.lu().unwrap();
+        // The largest value of the first column is the first pivot
+        assert_eq!(lu.permutation[0], 1);
+        let mut pa = Matrix::zero(3, 3);
+        for (i, &row) in lu.permutation.iter().enumerate() {
+            for j in 0..3 {
+                pa[[i, j]] = a[[row, j]];
+            }
+        }
+        assert_close(&(&lu.l() * &lu.u()), &pa);
+        assert!((a.determinant().unwrap() - -16.0).abs() < EPSILON);
+        let x = a.solve(&[5.0, -2.0, 9.0]).unwrap();
+        for (x, y) in x.iter().zip([1.0, 1.0, 2.0]) {
+            assert!((x - y).abs() < EPSILON);
+        }
+        assert_eq!(lu.solve(&[1.0]), Err(LinearAlgebraError::DimensionMismatch));
+    }
+
+    #[test]
+    fn inverse_and_determinant() {
+        let mut rng = PCG32::new_default(44);
+        for n in 1..8 {
+            let a = random_matrix(&mut rng, n, n);
+            let inverse = a.inverse().unwrap();
+            assert_close(&(&a * &inverse), &Matrix::identity(n));
+            let product = (a.determinant().unwrap() * inverse.determinant().unwrap() - 1.0).abs();
+            assert!(product < EPSILON);
+        }
+        assert_eq!(Matrix::<f64>::zero(0, 0).determinant(), Ok(1.0));
+    }
+
+    #[test]
+    fn singular_matrices() {
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+            vec![7.0, 8.0, 9.0],
+        ]);
+        assert_eq!(a.lu(), Err(LinearAlgebraError::Singular));
+        assert_eq!(a.inverse(), Err(LinearAlgebraError::Singular));
+        assert_eq!(a.solve(&[1.0, 2.0, 3.0]), Err(LinearAlgebraError::Singular));
+        assert_eq!(a.determinant(), Ok(0.0));
+        assert_eq!(a.rank(), Ok(2));
+
+        let zero = Matrix::<f64>::zero(2, 2);
+        assert_eq!(zero.inverse(), Err(LinearAlgebraError::Singular));
+        assert_eq!(zero.rank(), Ok(0));
+
+        let not_square = Matrix::from(vec![vec![1.0, 2.0]]);
+        assert_eq!(not_square.inverse(), Err(LinearAlgebraError::NotSquare));
+        assert_eq!(not_square.determinant(), Err(LinearAlgebraError::NotSquare));
+        let nan = Matrix::from(vec![vec![f64::NAN]]);
+        assert_eq!(nan.inverse(), Err(LinearAlgebraError::NotFinite));
+        assert_eq!(nan.rank(), Err(LinearAlgebraError::NotFinite));
+        let identity = Matrix::<f64>::identity(2);
+        assert_eq!(
+            identity.solve(&[1.0, f64::NAN]),
+            Err(LinearAlgebraError::NotFinite)
+        );
+        assert_eq!(
+            identity.least_squares(&[f64::INFINITY, 1.0]),
+            Err(LinearAlgebraError::NotFinite)
+        );
+    }
+
+    #[test]
+    fn cholesky() {
+        let a = Matrix::from(vec![
+            vec![4.0, 12.0, -16.0],
+            vec![12.0, 37.0, -43.0],
+            vec![-16.0, -43.0, 98.0],
+        ]);
+        let l = a.cholesky().unwrap();
+        let expected = Matrix::from(vec![
+            vec![2.0, 0.0, 0.0],
+            vec![6.0, 1.0, 0.0],
+            vec![-8.0, 5.0, 3.0],
+        ]);
+        assert_close(&l, &expected);
+        assert_close(&(&l * &l.transpose()), &a);
+
+        let not_symmetric = Matrix::from(vec![vec![1.0, 2.0], vec![0.0, 1.0]]);
+        assert_eq!(
+            not_symmetric.cholesky(),
+            Err(LinearAlgebraError::NotSymmetric)
+        );
+        let indefinite = Matrix::from(vec![vec![1.0, 2.0], vec![2.0, 1.0]]);
+        assert_eq!(
+            indefinite.cholesky(),
+            Err(LinearAlgebraError::NotPositiveDefinite)
+        );
+    }
+
+    #[test]
+    fn qr() {
+        let mut rng = PCG32::new_default(7);
+        for (m, n) in [(3, 3), (6, 4), (5, 1), (4, 2)] {
+            let a = random_matrix(&mut rng, m, n);
+            let QrDecomposition { q, r } = a.qr().unwrap();
+            assert_close(&(&q * &r), &a);
+            assert_close(&(&q.transpose() * &q), &Matrix::identity(n));
+            for i in 0..n {
+                for j in 0..i {
+                    assert_eq!(r[[i, j]], 0.0);
+                }
+            }
+        }
+        // A zero column
+        let a = Matrix::from(vec![vec![0.0, 1.0], vec![0.0, 2.0], vec![0.0, 2.0]]);
+        let QrDecomposition { q, r } = a.qr().unwrap();
+        assert_close(&(&q * &r), &a);
+        assert_eq!(
+            Matrix::<f64>::zero(2, 3).qr(),
+            Err(LinearAlgebraError::DimensionMismatch)
+        );
+    }
+
+    #[test]
+    fn least_squares() {
+        // The line y = 1 + 2x fitted through noisy points
+        let xs = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let ys = [1.1, 2.9, 5.2, 6.8, 9.1];
+        let a = Matrix::new(xs.iter().flat_map(|&x| [1.0, x]).collect(), 5, 2);
+        let solution = a.least_squares(&ys).unwrap();
+        // The normal equations A^T A x = A^T y
+        let at = a.transpose();
+        let expected = (&at * &a)
+            .solve(
+                &(0..2)
+                    .map(|i| (0..5).map(|j| at[[i, j]] * ys[j]).sum())
+                    .collect::<Vec<f64>>(),
+            )
+            .unwrap();
+        for (x, y) in solution.iter().zip(&expected) {
+            assert!((x - y).abs() < EPSILON);
+        }
+        assert!((solution[1] - 1.99).abs() < EPSILON);
+
+        // An exact square system
+        let a = Matrix::from(vec![vec![2.0, 1.0], vec![1.0, 3.0]]);
+        let x = a.least_squares(&[3.0, 5.0]).unwrap();
+        assert!((x[0] - 0.8).abs() < EPSILON && (x[1] - 1.4).abs() < EPSILON);
+
+        let dependent = Matrix::from(vec![vec![1.0, 2.0], vec![2.0, 4.0], vec![3.0, 6.0]]);
+        assert_eq!(
+            dependent.least_squares(&[1.0, 2.0, 3.0]),
+            Err(LinearAlgebraError::RankDeficient)
+        );
+        assert_eq!(
+            dependent.least_squares(&[1.0]),
+            Err(LinearAlgebraError::DimensionMismatch)
+        );
+    }
+
+    #[test]
+    fn rank() {
+        let mut rng = PCG32::new_default(3);
+        let a = random_matrix(&mut rng, 5, 3);
+        let b = random_matrix(&mut rng, 3, 6);
+        // A product through 3 dimensions has rank 3
+        assert_eq!((&a * &b).rank(), Ok(3));
+        assert_eq!(a.rank(), Ok(3));
+        assert_eq!(Matrix::<f64>::identity(4).rank(), Ok(4));
+        assert_eq!(Matrix::from(vec![vec![0.0, 1.0, 2.0]]).rank(), Ok(1));
+    }
+
+    #[test]
+    fn empty_and_one_by_one() {
+        let empty = Matrix::<f64>::zero(0, 0);
+        assert_eq!(empty.determinant(), Ok(1.0));
+        assert_eq!(empty.inverse(), Ok(Matrix::zero(0, 0)));
+        assert_eq!(empty.solve(&[]), Ok(vec![]));
+        assert_eq!(empty.cholesky(), Ok(Matrix::zero(0, 0)));
+        assert_eq!(empty.rank(), Ok(0));
+        assert_eq!(
+            Matrix::<f64>::zero(3, 0).least_squares(&[1.0; 3]),
+            Ok(vec![])
+        );
+
+        let a = Matrix::from(vec![vec![4.0]]);
+        assert_eq!(a.cholesky(), Ok(Matrix::from(vec![vec![2.0]])));
+        let negative = Matrix::from(vec![vec![-4.0]]);
+        assert_eq!(
+            negative.cholesky(),
+            Err(LinearAlgebraError::NotPositiveDefinite)
+        );
+    }
+}
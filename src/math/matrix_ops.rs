@@ -46,7 +46,7 @@ macro_rules! matrix_element_type_def {
 
 matrix_element_type_def!(i16, i32, i64, i128, u8, u16, u32, u128, f32, f64);
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Matrix<T: MatrixElement> {
     data: Vec<T>,
     rows: usize,
@@ -81,12 +81,41 @@ impl<T: MatrixElement> Matrix<T> {
         identity
     }
 
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
     pub fn transpose(&self) -> Self {
         // Transpose a matrix of any size
         let mut result = Matrix::zero(self.cols, self.rows);
         for i in 0..self.rows {
             for j in 0..self.cols {
-                result[[i, j]] = self[[j, i]];
+                result[[j, i]] = self[[i, j]];
+            }
+        }
+        result
+    }
+
+    pub fn pow(&self, mut exponent: u64) -> Self {
+        // Raise a square matrix to a power by repeated squaring, with
+        // O(log(exponent)) multiplications, e.g. to step a linear recurrence
+        if self.rows != self.cols {
+            panic!("Matrix must be square");
+        }
+        let mut result = Matrix::identity(self.rows);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            // The last square is not needed, and could overflow
+            if exponent > 0 {
+                base = &base * &base;
             }
         }
         result
@@ -514,6 +543,64 @@ mod tests {
         assert_eq!(a.transpose(), t);
     }
 
+    #[test]
+    fn test_transpose_non_square() {
+        let a = matrix![
+            [1, 2, 3],
+            [4, 5, 6],
+        ];
+
+        let t = matrix![
+            [1, 4],
+            [2, 5],
+            [3, 6],
+        ];
+
+        assert_eq!(a.transpose(), t);
+        assert_eq!(t.transpose(), a);
+    }
+
+    #[test]
+    fn test_pow_i32() {
+        let a = matrix![
+            [1, 1],
+            [1, 0],
+        ];
+
+        // The Fibonacci numbers F(11), F(10) and F(9)
+        let a_10 = matrix![
+            [89, 55],
+            [55, 34],
+        ];
+
+        assert_eq!(a.pow(0), Matrix::identity(2));
+        assert_eq!(a.pow(1), a);
+        assert_eq!(a.pow(10), a_10);
+    }
+
+    #[test]
+    fn test_pow_f64() {
+        let a = matrix![
+            [0.0, -1.0],
+            [1.0, 0.0],
+        ];
+
+        // A rotation by a quarter turn
+        assert_f64_eq!(a.pow(4), Matrix::<f64>::identity(2));
+        assert_f64_eq!(a.pow(3), (-1.0_f64 * &a));
+    }
+
+    #[test]
+    fn test_invalid_pow() {
+        let a = matrix![
+            [1, 2, 3],
+            [4, 5, 6],
+        ];
+
+        let result = panic::catch_unwind(|| a.pow(2));
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_matrix_scalar_zero_mul() {
         let a = matrix![
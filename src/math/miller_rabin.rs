@@ -1,30 +1,15 @@
-fn modulo_power(mut base: u64, mut power: u64, modulo: u64) -> u64 {
-    base %= modulo;
-    if base == 0 {
-        return 0; // return zero if base is divisible by modulo
-    }
-    let mut ans: u128 = 1;
-    let mut bbase: u128 = base as u128;
-    while power > 0 {
-        if (power % 2) == 1 {
-            ans = (ans * bbase) % (modulo as u128);
-        }
-        bbase = (bbase * bbase) % (modulo as u128);
-        power /= 2;
-    }
-    ans as u64
-}
+use super::DynModInt;
 
 fn check_prime_base(number: u64, base: u64, two_power: u64, odd_power: u64) -> bool {
     // returns false if base is a witness
-    let mut x: u128 = modulo_power(base, odd_power, number) as u128;
-    let bnumber: u128 = number as u128;
-    if x == 1 || x == (bnumber - 1) {
+    let mut x = DynModInt::new(base, number).pow(odd_power);
+    let minus_one = number - 1;
+    if x.value() == 1 || x.value() == minus_one {
         return true;
     }
     for _ in 1..two_power {
-        x = (x * x) % bnumber;
-        if x == (bnumber - 1) {
+        x *= x;
+        if x.value() == minus_one {
             return true;
         }
     }
@@ -7,35 +7,48 @@ mod ceil;
 mod chinese_remainder_theorem;
 mod collatz_sequence;
 mod doomsday;
+mod exact_elimination;
 mod extended_euclidean_algorithm;
+mod factorization;
 mod factors;
 mod fast_fourier_transform;
 mod fast_power;
 mod faster_perfect_numbers;
+mod fft_planner;
 mod gaussian_elimination;
 mod gcd_of_n_numbers;
 mod greatest_common_divisor;
 mod interest;
+mod iterative_solvers;
 mod karatsuba_multiplication;
 mod lcm_of_n_numbers;
 mod linear_sieve;
+mod matrix_decomposition;
 mod matrix_ops;
 mod mersenne_primes;
 mod miller_rabin;
+mod mod_int;
 mod newton_raphson;
 mod nthprime;
+mod number_theoretic_transform;
+mod ode_solvers;
 mod pascal_triangle;
 mod perfect_numbers;
 mod pollard_rho;
+mod polynomial;
 mod prime_check;
 mod prime_factors;
 mod prime_numbers;
 mod quadratic_residue;
+mod quadrature;
 mod random;
+mod root_finding;
 mod sieve_of_eratosthenes;
 mod signum;
 mod simpson_integration;
 mod sine;
+mod sparse_matrix;
+mod spectral_decomposition;
 mod square_root;
 mod trial_division;
 mod zellers_congruence_algorithm;
@@ -49,14 +62,19 @@ pub use self::ceil::ceil;
 pub use self::chinese_remainder_theorem::chinese_remainder_theorem;
 pub use self::collatz_sequence::sequence;
 pub use self::doomsday::get_week_day;
+pub use self::exact_elimination::{solve_exact, solve_gf2, BitVector, Field, SolutionSpace};
 pub use self::extended_euclidean_algorithm::extended_euclidean_algorithm;
+pub use self::factorization::{
+    divisor_count, divisor_sum, divisors, euler_totient, factorize, mobius, primitive_root,
+};
 pub use self::factors::factors;
 pub use self::fast_fourier_transform::{
     fast_fourier_transform, fast_fourier_transform_input_permutation,
-    inverse_fast_fourier_transform,
+    inverse_fast_fourier_transform, Complex64,
 };
 pub use self::fast_power::fast_power;
 pub use self::faster_perfect_numbers::generate_perfect_numbers;
+pub use self::fft_planner::{fft_convolution, Fft, Fft2d, FftDirection, FftPlanner, RealFft};
 pub use self::gaussian_elimination::gaussian_elimination;
 pub use self::gcd_of_n_numbers::gcd;
 pub use self::greatest_common_divisor::{
@@ -64,26 +82,46 @@ pub use self::greatest_common_divisor::{
     greatest_common_divisor_stein,
 };
 pub use self::interest::{compound_interest, simple_interest};
+pub use self::iterative_solvers::{
+    bicgstab, conjugate_gradient, gauss_seidel, jacobi, IterationReport, SolverOptions, StopReason,
+};
 pub use self::karatsuba_multiplication::multiply;
 pub use self::lcm_of_n_numbers::lcm;
 pub use self::linear_sieve::LinearSieve;
+pub use self::matrix_decomposition::{LinearAlgebraError, LuDecomposition, QrDecomposition};
 pub use self::matrix_ops::Matrix;
 pub use self::mersenne_primes::{get_mersenne_primes, is_mersenne_prime};
 pub use self::miller_rabin::miller_rabin;
+pub use self::mod_int::{DynModInt, ModInt, ModInt1000000007, ModInt998244353, Montgomery};
 pub use self::newton_raphson::find_root;
 pub use self::nthprime::nthprime;
+pub use self::number_theoretic_transform::{
+    convolution_mod, ntt_convolution, number_theoretic_transform, supports_ntt, NTT_PRIMES,
+};
+pub use self::ode_solvers::{dormand_prince, runge_kutta_4, OdeError, OdeOptions, OdeSolution};
 pub use self::pascal_triangle::pascal_triangle;
 pub use self::perfect_numbers::perfect_numbers;
 pub use self::pollard_rho::{pollard_rho_factorize, pollard_rho_get_one_factor};
+pub use self::polynomial::Polynomial;
 pub use self::prime_check::prime_check;
 pub use self::prime_factors::prime_factors;
 pub use self::prime_numbers::prime_numbers;
 pub use self::quadratic_residue::cipolla;
+pub use self::quadrature::{
+    adaptive_simpson, double_integral, gauss_kronrod, gauss_legendre, gauss_legendre_rule,
+    Integral, QuadratureError, QuadratureOptions,
+};
 pub use self::random::PCG32;
+pub use self::root_finding::{
+    bisection, brent, durand_kerner, safeguarded_newton, secant, PolynomialRoots, Root,
+    RootFindingError, RootOptions, RootStopReason,
+};
 pub use self::sieve_of_eratosthenes::sieve_of_eratosthenes;
 pub use self::signum::signum;
 pub use self::simpson_integration::simpson_integration;
 pub use self::sine::sine;
+pub use self::sparse_matrix::{CooMatrix, CscMatrix, CsrMatrix};
+pub use self::spectral_decomposition::{Svd, SymmetricEigen};
 pub use self::square_root::{fast_inv_sqrt, square_root};
 pub use self::trial_division::trial_division;
 pub use self::zellers_congruence_algorithm::zellers_congruence_algorithm;
@@ -0,0 +1,451 @@
+/*
+Modular integers:
+Integers modulo m, with +, -, * and / (by the numbers coprime to m) done
+modulo m, and the modular power and inverse. The products are computed on
+128 bits, so that any modulus below 2^64 works.
+- ModInt<M> has the modulus as a const generic parameter, for the fixed
+  moduli like 998244353 or 10^9 + 7.
+- DynModInt stores its modulus, for the moduli only known at run time.
+The inverse of a is found with the extended Euclidean algorithm, and exists
+iff gcd(a, m) = 1.
+https://en.wikipedia.org/wiki/Modular_arithmetic
+
+Montgomery multiplication avoids the division of the 128-bit product by m,
+which is slow, for an odd modulus m < 2^64. With R = 2^64, a number a is
+stored in Montgomery form as aR mod m, and the product of aR and bR is
+reduced to abR mod m (REDC) with only multiplications and shifts: for
+t < mR, with q = t * m^-1 mod R, t - qm is a multiple of R and
+(t - qm) / R = t / R mod m lies in (-m, m).
+https://en.wikipedia.org/wiki/Montgomery_modular_multiplication
+*/
+
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// The inverse of a modulo m, if gcd(a, m) = 1
+fn inverse_mod(a: u64, m: u64) -> Option<u64> {
+    let (mut old_r, mut r) = (a as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    (old_r == 1 || m == 1).then(|| old_s.rem_euclid(m as i128) as u64)
+}
+
+// `MODULUS` and `inverse` are not in the macro, as ModInt gets its modulus
+// from its type and DynModInt from its value
+macro_rules! impl_mod_int_ops {
+    ($t:ty, $($generics:tt)*) => {
+        impl<$($generics)*> $t {
+            pub fn value(self) -> u64 {
+                self.value
+            }
+
+            pub fn pow(self, mut exponent: u64) -> Self {
+                let mut base = self;
+                let mut result = self.with_value(1 % self.modulus());
+                while exponent > 0 {
+                    if exponent & 1 == 1 {
+                        result *= base;
+                    }
+                    base *= base;
+                    exponent >>= 1;
+                }
+                result
+            }
+
+            pub fn inverse(self) -> Option<Self> {
+                inverse_mod(self.value, self.modulus()).map(|value| self.with_value(value))
+            }
+        }
+
+        impl<$($generics)*> Add for $t {
+            type Output = Self;
+            fn add(self, other: Self) -> Self {
+                let m = self.modulus();
+                let (sum, overflow) = self.value.overflowing_add(other.value);
+                self.with_value(if overflow || sum >= m { sum.wrapping_sub(m) } else { sum })
+            }
+        }
+
+        impl<$($generics)*> Sub for $t {
+            type Output = Self;
+            fn sub(self, other: Self) -> Self {
+                let (difference, borrow) = self.value.overflowing_sub(other.value);
+                self.with_value(if borrow {
+                    difference.wrapping_add(self.modulus())
+                } else {
+                    difference
+                })
+            }
+        }
+
+        impl<$($generics)*> Mul for $t {
+            type Output = Self;
+            fn mul(self, other: Self) -> Self {
+                let product = self.value as u128 * other.value as u128 % self.modulus() as u128;
+                self.with_value(product as u64)
+            }
+        }
+
+        impl<$($generics)*> Div for $t {
+            type Output = Self;
+            /// Panics if `other` is not invertible.
+            fn div(self, other: Self) -> Self {
+                let inverse = other.inverse().unwrap_or_else(|| {
+                    panic!("{} is not invertible modulo {}", other.value, other.modulus())
+                });
+                Mul::mul(self, inverse)
+            }
+        }
+
+        impl<$($generics)*> Neg for $t {
+            type Output = Self;
+            fn neg(self) -> Self {
+                self.with_value(0) - self
+            }
+        }
+
+        impl<$($generics)*> AddAssign for $t {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl<$($generics)*> SubAssign for $t {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+
+        impl<$($generics)*> MulAssign for $t {
+            fn mul_assign(&mut self, other: Self) {
+                *self = *self * other;
+            }
+        }
+
+        impl<$($generics)*> DivAssign for $t {
+            fn div_assign(&mut self, other: Self) {
+                *self = *self / other;
+            }
+        }
+
+        impl<$($generics)*> fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.value)
+            }
+        }
+    };
+}
+
+/// An integer modulo `M`, with 0 < M.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModInt<const M: u64> {
+    // Always in [0, M)
+    value: u64,
+}
+
+pub type ModInt998244353 = ModInt<998_244_353>;
+pub type ModInt1000000007 = ModInt<1_000_000_007>;
+
+impl<const M: u64> ModInt<M> {
+    pub const MODULUS: u64 = M;
+
+    pub fn new(value: u64) -> Self {
+        ModInt { value: value % M }
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        ModInt {
+            value: (value as i128).rem_euclid(M as i128) as u64,
+        }
+    }
+
+    pub fn modulus(self) -> u64 {
+        M
+    }
+
+    fn with_value(self, value: u64) -> Self {
+        ModInt { value }
+    }
+}
+
+impl_mod_int_ops!(ModInt<M>, const M: u64);
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        ModInt::new(value)
+    }
+}
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt::new(0), |a, b| a + b)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt::new(1), |a, b| a * b)
+    }
+}
+
+/// An integer modulo a modulus chosen at run time. The operations between two
+/// of them expect the same modulus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DynModInt {
+    // Always in [0, modulus)
+    value: u64,
+    modulus: u64,
+}
+
+impl DynModInt {
+    pub fn new(value: u64, modulus: u64) -> Self {
+        assert!(modulus > 0, "the modulus must be positive");
+        DynModInt {
+            value: value % modulus,
+            modulus,
+        }
+    }
+
+    pub fn from_i64(value: i64, modulus: u64) -> Self {
+        assert!(modulus > 0, "the modulus must be positive");
+        DynModInt {
+            value: (value as i128).rem_euclid(modulus as i128) as u64,
+            modulus,
+        }
+    }
+
+    pub fn modulus(self) -> u64 {
+        self.modulus
+    }
+
+    fn with_value(self, value: u64) -> Self {
+        DynModInt {
+            value,
+            modulus: self.modulus,
+        }
+    }
+}
+
+impl_mod_int_ops!(DynModInt,);
+
+/// Montgomery multiplication modulo an odd `modulus`. The numbers are
+/// converted with `to_montgomery` and `from_montgomery`, and the operations
+/// take and return numbers in Montgomery form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Montgomery {
+    modulus: u64,
+    // modulus^-1 mod 2^64
+    inverse: u64,
+    // 2^128 mod modulus, to convert to Montgomery form
+    r_squared: u64,
+}
+
+impl Montgomery {
+    pub fn new(modulus: u64) -> Self {
+        assert!(modulus & 1 == 1, "the modulus must be odd");
+        // Newton's iteration doubles the number of correct low bits, and
+        // m * m = 1 mod 8 gives the first 3
+        let mut inverse = modulus;
+        for _ in 0..5 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(modulus.wrapping_mul(inverse)));
+        }
+        let r = (1u128 << 64) % modulus as u128;
+        Montgomery {
+            modulus,
+            inverse,
+            r_squared: (r * r % modulus as u128) as u64,
+        }
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    // t / 2^64 mod modulus, for t < modulus * 2^64
+    fn reduce(&self, t: u128) -> u64 {
+        let q = (t as u64).wrapping_mul(self.inverse);
+        let qm = ((q as u128 * self.modulus as u128) >> 64) as u64;
+        let (result, borrow) = ((t >> 64) as u64).overflowing_sub(qm);
+        if borrow {
+            result.wrapping_add(self.modulus)
+        } else {
+            result
+        }
+    }
+
+    pub fn to_montgomery(&self, a: u64) -> u64 {
+        self.reduce((a % self.modulus) as u128 * self.r_squared as u128)
+    }
+
+    pub fn from_montgomery(&self, a: u64) -> u64 {
+        self.reduce(a as u128)
+    }
+
+    pub fn add(&self, a: u64, b: u64) -> u64 {
+        let (sum, overflow) = a.overflowing_add(b);
+        if overflow || sum >= self.modulus {
+            sum.wrapping_sub(self.modulus)
+        } else {
+            sum
+        }
+    }
+
+    pub fn sub(&self, a: u64, b: u64) -> u64 {
+        let (difference, borrow) = a.overflowing_sub(b);
+        if borrow {
+            difference.wrapping_add(self.modulus)
+        } else {
+            difference
+        }
+    }
+
+    pub fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    pub fn pow(&self, mut base: u64, mut exponent: u64) -> u64 {
+        let mut result = self.to_montgomery(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = self.mul(result, base);
+            }
+            base = self.mul(base, base);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// base^exponent mod modulus, for numbers in the usual form.
+    pub fn pow_mod(&self, base: u64, exponent: u64) -> u64 {
+        self.from_montgomery(self.pow(self.to_montgomery(base), exponent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    #[test]
+    fn const_modulus() {
+        type Mint = ModInt<7>;
+        let a = Mint::new(5);
+        let b = Mint::from(13);
+        assert_eq!(b.value(), 6);
+        assert_eq!((a + b).value(), 4);
+        assert_eq!((a - b).value(), 6);
+        assert_eq!((a * b).value(), 2);
+        assert_eq!((a / b * b), a);
+        assert_eq!((-a).value(), 2);
+        assert_eq!(Mint::from_i64(-9).value(), 5);
+        assert_eq!(a.pow(6).value(), 1);
+        assert_eq!(a.inverse(), Some(Mint::new(3)));
+        assert_eq!(Mint::new(0).inverse(), None);
+        assert_eq!(Mint::MODULUS, 7);
+
+        let mut c = a;
+        c += b;
+        c *= b;
+        c -= a;
+        c /= b;
+        assert_eq!(c, (a + b) - a / b);
+        assert_eq!(format!("{c}"), c.value().to_string());
+
+        assert_eq!((1..=6).map(Mint::new).product::<Mint>().value(), 6);
+        assert_eq!((1..=6).map(Mint::new).sum::<Mint>().value(), 0);
+        assert_eq!(ModInt1000000007::new(2).pow(100).value(), 976371285);
+        // Fermat's little theorem
+        let x = ModInt998244353::new(123456789);
+        assert_eq!(x.pow(998244352).value(), 1);
+        assert_eq!(x.inverse(), Some(x.pow(998244351)));
+    }
+
+    #[test]
+    #[should_panic(expected = "is not invertible modulo")]
+    fn division_by_zero() {
+        let _ = ModInt::<11>::new(3) / ModInt::new(22);
+    }
+
+    #[test]
+    fn runtime_modulus() {
+        let m = 1 << 40;
+        let a = DynModInt::new(3, m);
+        assert_eq!(a.pow(40).value(), 3u64.pow(40) % m);
+        assert_eq!((a * a.inverse().unwrap()).value(), 1);
+        assert_eq!(DynModInt::new(6, m).inverse(), None);
+        assert_eq!(DynModInt::from_i64(-1, m).value(), m - 1);
+        assert_eq!((-DynModInt::new(0, m)).value(), 0);
+
+        // Near 2^64, the sums and products must not overflow
+        let m = u64::MAX - 58;
+        let a = DynModInt::new(m - 1, m);
+        assert_eq!((a + a).value(), m - 2);
+        assert_eq!((a * a).value(), 1);
+        assert_eq!(a.inverse(), Some(a));
+        assert_eq!((a - DynModInt::new(m - 2, m)).value(), 1);
+
+        // Everything is 0 modulo 1
+        let one = DynModInt::new(5, 1);
+        assert_eq!((one.pow(0).value(), one.inverse()), (0, Some(one)));
+    }
+
+    #[test]
+    fn montgomery() {
+        let mut rng = PCG32::new_default(41);
+        for _ in 0..200 {
+            let modulus = match rng.get_u32() % 3 {
+                0 => rng.get_u64() | 1,
+                1 => (rng.get_u32() | 1) as u64,
+                _ => u64::MAX - 2 * (rng.get_u32() % 16) as u64,
+            };
+            let montgomery = Montgomery::new(modulus);
+            let a = rng.get_u64();
+            let b = rng.get_u64();
+            let (x, y) = (montgomery.to_montgomery(a), montgomery.to_montgomery(b));
+            let (a, b) = (DynModInt::new(a, modulus), DynModInt::new(b, modulus));
+            assert_eq!(montgomery.from_montgomery(x), a.value());
+            assert_eq!(
+                montgomery.from_montgomery(montgomery.mul(x, y)),
+                (a * b).value()
+            );
+            assert_eq!(
+                montgomery.from_montgomery(montgomery.add(x, y)),
+                (a + b).value()
+            );
+            assert_eq!(
+                montgomery.from_montgomery(montgomery.sub(x, y)),
+                (a - b).value()
+            );
+            let e = rng.get_u64();
+            assert_eq!(montgomery.pow_mod(a.value(), e), a.pow(e).value());
+        }
+        let montgomery = Montgomery::new(1);
+        assert_eq!(montgomery.pow_mod(5, 3), 0);
+        assert_eq!(Montgomery::new(1_000_000_007).pow_mod(2, 100), 976371285);
+    }
+
+    #[test]
+    fn zero_exponent() {
+        assert_eq!(ModInt::<7>::new(0).pow(0).value(), 1);
+        assert_eq!(DynModInt::new(0, 10).pow(0).value(), 1);
+        assert_eq!(Montgomery::new(9).pow_mod(0, 0), 1);
+        assert_eq!(ModInt::<1>::new(3).pow(0).value(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the modulus must be positive")]
+    fn zero_modulus() {
+        DynModInt::new(3, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the modulus must be odd")]
+    fn even_montgomery_modulus() {
+        Montgomery::new(1 << 32);
+    }
+}
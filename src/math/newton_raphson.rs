@@ -1,12 +1,18 @@
-pub fn find_root(f: fn(f64) -> f64, fd: fn(f64) -> f64, guess: f64, iterations: i32) -> f64 {
+// See root_finding for methods with tolerances and safeguards
+pub fn find_root(
+    f: impl Fn(f64) -> f64,
+    fd: impl Fn(f64) -> f64,
+    guess: f64,
+    iterations: i32,
+) -> f64 {
     let mut result = guess;
     for _ in 0..iterations {
-        result = iteration(f, fd, result);
+        result = iteration(&f, &fd, result);
     }
     result
 }
 
-pub fn iteration(f: fn(f64) -> f64, fd: fn(f64) -> f64, guess: f64) -> f64 {
+pub fn iteration(f: impl Fn(f64) -> f64, fd: impl Fn(f64) -> f64, guess: f64) -> f64 {
     guess - f(guess) / fd(guess)
 }
 
@@ -0,0 +1,372 @@
+/*
+Number-theoretic transform:
+The Fast Fourier Transform over the integers modulo a prime p instead of the
+complex numbers: when 2^k divides p - 1, and g is a quadratic non-residue
+modulo p, w = g^((p - 1) / 2^k) is a primitive 2^k-th root of unity, and the
+same butterflies as the FFT evaluate a polynomial at the powers of w. Everything
+is exact, so the product of two integer polynomials is found in
+O(n log(n)) without rounding errors.
+The usual primes are 998244353 = 119 * 2^23 + 1, 167772161 = 5 * 2^25 + 1,
+469762049 = 7 * 2^26 + 1 and 754974721 = 45 * 2^24 + 1.
+https://en.wikipedia.org/wiki/Discrete_Fourier_transform_over_a_ring
+
+For any other modulus m, the convolution is computed exactly modulo the last
+three primes, and the results are combined with the Chinese remainder theorem
+(Garner's algorithm). It is exact while the coefficients of the product over
+the integers are below the product of the primes, about 2^85, which is the
+case for m up to 2^31. For larger moduli, the coefficients are split in two
+halves of 32 bits, and four such convolutions are combined.
+https://en.wikipedia.org/wiki/Sch%C3%B6nhage%E2%80%93Strassen_algorithm
+*/
+
+use super::{DynModInt, ModInt};
+
+pub const NTT_PRIMES: [u64; 4] = [998_244_353, 167_772_161, 469_762_049, 754_974_721];
+
+const P1: u64 = NTT_PRIMES[1];
+const P2: u64 = NTT_PRIMES[2];
+const P3: u64 = NTT_PRIMES[3];
+
+// Below this length, the product is computed with the schoolbook algorithm
+const NAIVE_THRESHOLD: usize = 32;
+
+// ModInt::pow, which is not a const fn
+const fn power_mod(base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let modulus = modulus as u128;
+    let mut result = 1 % modulus;
+    let mut base = base as u128 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result as u64
+}
+
+// Deterministic Miller-Rabin test, as `miller_rabin` is not a const fn
+const fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    let two_power = (n - 1).trailing_zeros();
+    let odd_power = (n - 1) >> two_power;
+    let mut i = 0;
+    while i < BASES.len() {
+        let base = BASES[i];
+        i += 1;
+        if n == base {
+            return true;
+        }
+        if n.is_multiple_of(base) {
+            return false;
+        }
+        let mut x = power_mod(base, odd_power, n);
+        let mut squarings = 1;
+        while x != 1 && x != n - 1 && squarings < two_power {
+            x = power_mod(x, 2, n);
+            squarings += 1;
+        }
+        if x != n - 1 && (x != 1 || squarings > 1) {
+            return false;
+        }
+    }
+    true
+}
+
+// The smallest quadratic non-residue modulo the prime p, or 0 if p is not a
+// prime. Its order is a multiple of the largest power of two dividing p - 1,
+// so its powers give every root of unity the transform needs. Unlike a
+// primitive root, it is found without factoring p - 1, which is too slow for
+// large primes. Every residue is a square modulo 2, where 1 is returned.
+const fn quadratic_non_residue(p: u64) -> u64 {
+    if !is_prime(p) {
+        return 0;
+    }
+    if p == 2 {
+        return 1;
+    }
+    // By Euler's criterion. There is one below 2 ln(p)^2 under the
+    // generalised Riemann hypothesis, so the loop is short
+    let mut g = 2;
+    while power_mod(g, (p - 1) / 2, p) != p - 1 {
+        g += 1;
+    }
+    g
+}
+
+// The generator of the roots of unity of each modulus is found once, at
+// compile time
+struct NttRoot<const M: u64>;
+
+impl<const M: u64> NttRoot<M> {
+    const ROOT: u64 = quadratic_non_residue(M);
+}
+
+/// Whether the transform of this length works modulo `M`, which must be a
+/// prime: it needs a primitive `length`-th root of unity.
+pub fn supports_ntt<const M: u64>(length: usize) -> bool {
+    length <= 1
+        || (length.is_power_of_two()
+            && length.trailing_zeros() <= (M - 1).trailing_zeros()
+            && NttRoot::<M>::ROOT != 0)
+}
+
+/// Replaces `values` by their transform, or by their inverse transform. The
+/// length must be a power of two dividing M - 1.
+pub fn number_theoretic_transform<const M: u64>(values: &mut [ModInt<M>], inverse: bool) {
+    let n = values.len();
+    assert!(
+        supports_ntt::<M>(n),
+        "no transform of length {n} modulo {M}"
+    );
+    if n <= 1 {
+        return;
+    }
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j ^= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+    let root = ModInt::<M>::new(NttRoot::<M>::ROOT);
+    let mut length = 2;
+    while length <= n {
+        let mut w_length = root.pow((M - 1) / length as u64);
+        if inverse {
+            w_length = w_length.inverse().unwrap();
+        }
+        for start in (0..n).step_by(length) {
+            let mut w = ModInt::new(1);
+            for i in start..start + length / 2 {
+                let u = values[i];
+                let v = values[i + length / 2] * w;
+                values[i] = u + v;
+                values[i + length / 2] = u - v;
+                w *= w_length;
+            }
+        }
+        length <<= 1;
+    }
+    if inverse {
+        let n_inverse = ModInt::new(n as u64).inverse().unwrap();
+        values.iter_mut().for_each(|x| *x *= n_inverse);
+    }
+}
+
+fn naive_convolution<const M: u64>(a: &[ModInt<M>], b: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    let mut result = vec![ModInt::new(0); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            result[i + j] += x * y;
+        }
+    }
+    result
+}
+
+/// The coefficients of the product of the polynomials `a` and `b`, modulo an
+/// NTT-friendly prime `M`.
+pub fn ntt_convolution<const M: u64>(a: &[ModInt<M>], b: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    if a.len().min(b.len()) <= NAIVE_THRESHOLD {
+        return naive_convolution(a, b);
+    }
+    let length = a.len() + b.len() - 1;
+    let size = length.next_power_of_two();
+    let mut fa = a.to_vec();
+    let mut fb = b.to_vec();
+    fa.resize(size, ModInt::new(0));
+    fb.resize(size, ModInt::new(0));
+    number_theoretic_transform(&mut fa, false);
+    number_theoretic_transform(&mut fb, false);
+    fa.iter_mut().zip(fb).for_each(|(x, y)| *x *= y);
+    number_theoretic_transform(&mut fa, true);
+    fa.truncate(length);
+    fa
+}
+
+fn convolution_modulo<const M: u64>(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let a: Vec<ModInt<M>> = a.iter().map(|&x| ModInt::new(x)).collect();
+    let b: Vec<ModInt<M>> = b.iter().map(|&x| ModInt::new(x)).collect();
+    ntt_convolution(&a, &b).iter().map(|x| x.value()).collect()
+}
+
+// The exact convolution, if its coefficients are below P1 * P2 * P3
+fn convolution_exact(a: &[u64], b: &[u64]) -> Vec<u128> {
+    let c1 = convolution_modulo::<P1>(a, b);
+    let c2 = convolution_modulo::<P2>(a, b);
+    let c3 = convolution_modulo::<P3>(a, b);
+    let p1_inverse = ModInt::<P2>::new(P1).inverse().unwrap();
+    let p1_p2_inverse = ModInt::<P3>::new(P1 * P2).inverse().unwrap();
+    c1.iter()
+        .zip(c2)
+        .zip(c3)
+        .map(|((&r1, r2), r3)| {
+            // x = r1 + P1 * t1 + P1 * P2 * t2
+            let t1 = (ModInt::<P2>::new(r2) - ModInt::new(r1)) * p1_inverse;
+            let x12 = r1 + P1 * t1.value();
+            let t2 = (ModInt::<P3>::new(r3) - ModInt::new(x12)) * p1_p2_inverse;
+            x12 as u128 + (P1 * P2) as u128 * t2.value() as u128
+        })
+        .collect()
+}
+
+/// The coefficients of the product of the polynomials `a` and `b` modulo any
+/// `modulus`. With a modulus above 2^31, the shorter polynomial must have at
+/// most 2^21 coefficients.
+pub fn convolution_mod(a: &[u64], b: &[u64], modulus: u64) -> Vec<u64> {
+    assert!(modulus > 0, "the modulus must be positive");
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let a: Vec<u64> = a.iter().map(|&x| x % modulus).collect();
+    let b: Vec<u64> = b.iter().map(|&x| x % modulus).collect();
+    let shortest = a.len().min(b.len()) as u128;
+    let primes_product = P1 as u128 * P2 as u128 * P3 as u128;
+    let reduce = |x: u128| (x % modulus as u128) as u64;
+    let largest_coefficient = (modulus as u128 - 1).pow(2).checked_mul(shortest);
+    if largest_coefficient.is_some_and(|largest| largest < primes_product) {
+        return convolution_exact(&a, &b).into_iter().map(reduce).collect();
+    }
+
+    assert!(
+        shortest <= 1 << 21,
+        "the polynomials are too long for a modulus of 64 bits"
+    );
+    let low = |v: &[u64]| -> Vec<u64> { v.iter().map(|&x| x & u32::MAX as u64).collect() };
+    let high = |v: &[u64]| -> Vec<u64> { v.iter().map(|&x| x >> 32).collect() };
+    let (a_low, a_high, b_low, b_high) = (low(&a), high(&a), low(&b), high(&b));
+    let low_low = convolution_exact(&a_low, &b_low);
+    let low_high = convolution_exact(&a_low, &b_high);
+    let high_low = convolution_exact(&a_high, &b_low);
+    let high_high = convolution_exact(&a_high, &b_high);
+    let shift = DynModInt::new(1 << 32, modulus);
+    (0..low_low.len())
+        .map(|i| {
+            let low = DynModInt::new(reduce(low_low[i]), modulus);
+            let middle = DynModInt::new(reduce(low_high[i] + high_low[i]), modulus);
+            let high = DynModInt::new(reduce(high_high[i]), modulus);
+            (low + (middle + high * shift) * shift).value()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{ModInt998244353, PCG32};
+
+    fn naive_mod(a: &[u64], b: &[u64], modulus: u64) -> Vec<u64> {
+        let mut result = vec![DynModInt::new(0, modulus); a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                result[i + j] += DynModInt::new(x, modulus) * DynModInt::new(y, modulus);
+            }
+        }
+        result.iter().map(|x| x.value()).collect()
+    }
+
+    #[test]
+    fn quadratic_non_residues() {
+        assert_eq!(quadratic_non_residue(998_244_353), 3);
+        assert_eq!(quadratic_non_residue(167_772_161), 3);
+        assert_eq!(quadratic_non_residue(469_762_049), 3);
+        assert_eq!(quadratic_non_residue(754_974_721), 11);
+        assert_eq!(quadratic_non_residue(7), 3);
+        assert_eq!(quadratic_non_residue(12), 0);
+        assert_eq!(quadratic_non_residue(561), 0);
+        assert_eq!(quadratic_non_residue(2), 1);
+        // A prime above 2^62, where p - 1 cannot be factored at compile time
+        assert_eq!(quadratic_non_residue(4_611_686_018_427_387_847), 3);
+        assert!((2..200).all(|n| is_prime(n) == crate::math::prime_check(n as usize)));
+        assert!(supports_ntt::<998_244_353>(1 << 23));
+        assert!(!supports_ntt::<998_244_353>(1 << 24));
+        assert!(!supports_ntt::<998_244_353>(12));
+        assert!(!supports_ntt::<1_000_000_007>(4));
+    }
+
+    #[test]
+    fn transform_round_trip() {
+        let mut rng = PCG32::new_default(42);
+        let values: Vec<ModInt998244353> = (0..256)
+            .map(|_| ModInt998244353::new(rng.get_u64()))
+            .collect();
+        let mut transformed = values.clone();
+        number_theoretic_transform(&mut transformed, false);
+        // The transform evaluates the polynomial at the powers of w
+        let w = ModInt998244353::new(3).pow((998_244_353 - 1) / 256);
+        let x = w.pow(5);
+        let value = values
+            .iter()
+            .rev()
+            .fold(ModInt::new(0), |acc, &c| acc * x + c);
+        assert_eq!(transformed[5], value);
+        number_theoretic_transform(&mut transformed, true);
+        assert_eq!(transformed, values);
+    }
+
+    #[test]
+    fn ntt_friendly_convolution() {
+        let a: Vec<ModInt998244353> = [1, 2, 3].iter().map(|&x| ModInt::new(x)).collect();
+        let b: Vec<ModInt998244353> = [4, 5].iter().map(|&x| ModInt::new(x)).collect();
+        let c: Vec<u64> = ntt_convolution(&a, &b).iter().map(|x| x.value()).collect();
+        assert_eq!(c, vec![4, 13, 22, 15]);
+        assert!(ntt_convolution(&a, &[]).is_empty());
+
+        let mut rng = PCG32::new_default(7);
+        for (n, m) in [(100, 100), (33, 500), (1000, 40)] {
+            let a: Vec<u64> = (0..n).map(|_| rng.get_u64()).collect();
+            let b: Vec<u64> = (0..m).map(|_| rng.get_u64()).collect();
+            assert_eq!(
+                convolution_modulo::<998_244_353>(&a, &b),
+                naive_mod(&a, &b, 998_244_353)
+            );
+        }
+    }
+
+    #[test]
+    fn arbitrary_modulus() {
+        let mut rng = PCG32::new_default(1);
+        for modulus in [1, 2, 1_000_000_007, 1 << 31, (1 << 40) + 15, u64::MAX] {
+            for (n, m) in [(1, 1), (50, 70), (300, 200)] {
+                let a: Vec<u64> = (0..n).map(|_| rng.get_u64()).collect();
+                let b: Vec<u64> = (0..m).map(|_| rng.get_u64()).collect();
+                assert_eq!(convolution_mod(&a, &b, modulus), naive_mod(&a, &b, modulus));
+            }
+        }
+        assert!(convolution_mod(&[], &[1], 5).is_empty());
+    }
+
+    #[test]
+    fn short_transforms() {
+        let mut values: Vec<ModInt998244353> = vec![];
+        number_theoretic_transform(&mut values, false);
+        // Of length 2, the sum and the difference
+        let mut values = vec![ModInt998244353::new(5), ModInt::new(7)];
+        number_theoretic_transform(&mut values, false);
+        assert_eq!(values, vec![ModInt::new(12), -ModInt::new(2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no transform of length 3 modulo 998244353")]
+    fn unsupported_length() {
+        number_theoretic_transform(&mut [ModInt998244353::new(1); 3], false);
+    }
+
+    #[test]
+    #[should_panic(expected = "the modulus must be positive")]
+    fn zero_modulus() {
+        convolution_mod(&[1], &[1], 0);
+    }
+}
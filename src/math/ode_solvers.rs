@@ -0,0 +1,499 @@
+/*
+ODE solvers:
+Approximate the solution y(t) of a system y' = f(t, y), with y(t0) = y0:
+- The classical Runge-Kutta method (RK4) takes fixed steps h, each from four
+  evaluations of f, with an error in O(h^4).
+  https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods
+- Dormand-Prince (RK45) computes a solution of order 5 and one of order 4 from
+  the same 7 evaluations, the last one being the first of the next step. Their
+  difference estimates the error of the step, which is accepted if it is below
+  the tolerance, and scales the next step h by 0.9 (1 / error)^(1/5). The
+  steps are then large where the solution is smooth, and small where it is
+  not. A polynomial of degree 4 through the stages gives the solution between
+  the steps (dense output), with the same order of accuracy.
+  https://en.wikipedia.org/wiki/Dormand%E2%80%93Prince_method
+  Hairer, Norsett and Wanner, Solving Ordinary Differential Equations I
+*/
+
+use std::fmt;
+
+/// The states of the classical Runge-Kutta method on [t0, t1], with `steps`
+/// steps of the same length, as (t, y) pairs from (t0, y0).
+pub fn runge_kutta_4(
+    f: impl Fn(f64, &[f64]) -> Vec<f64>,
+    t0: f64,
+    y0: &[f64],
+    t1: f64,
+    steps: usize,
+) -> Vec<(f64, Vec<f64>)> {
+    assert!(steps > 0, "at least one step is needed");
+    let h = (t1 - t0) / steps as f64;
+    let mut states = vec![(t0, y0.to_vec())];
+    let mut y = y0.to_vec();
+    for i in 0..steps {
+        let t = t0 + i as f64 * h;
+        let k1 = f(t, &y);
+        let k2 = f(t + h / 2.0, &add_scaled(&y, &[(h / 2.0, &k1)]));
+        let k3 = f(t + h / 2.0, &add_scaled(&y, &[(h / 2.0, &k2)]));
+        let k4 = f(t + h, &add_scaled(&y, &[(h, &k3)]));
+        y = add_scaled(
+            &y,
+            &[
+                (h / 6.0, &k1),
+                (h / 3.0, &k2),
+                (h / 3.0, &k3),
+                (h / 6.0, &k4),
+            ],
+        );
+        // Avoid accumulating rounding errors on t
+        let t = if i + 1 == steps {
+            t1
+        } else {
+            t0 + (i + 1) as f64 * h
+        };
+        states.push((t, y.clone()));
+    }
+    states
+}
+
+// y + sum of factor * k
+fn add_scaled(y: &[f64], terms: &[(f64, &Vec<f64>)]) -> Vec<f64> {
+    let mut result = y.to_vec();
+    for &(factor, k) in terms {
+        for (x, k) in result.iter_mut().zip(k) {
+            *x += factor * k;
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OdeOptions {
+    // A step is accepted when the error of each component is below
+    // absolute_tolerance + relative_tolerance * |y|
+    pub absolute_tolerance: f64,
+    pub relative_tolerance: f64,
+    // The first step, estimated from f(t0, y0) if None
+    pub initial_step: Option<f64>,
+    pub max_steps: usize,
+}
+
+impl Default for OdeOptions {
+    fn default() -> Self {
+        OdeOptions {
+            absolute_tolerance: 1e-8,
+            relative_tolerance: 1e-8,
+            initial_step: None,
+            max_steps: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OdeError {
+    // f returned a NaN or an infinity, or the input is not finite
+    NotFinite,
+    // The step needed for the tolerance underflows, e.g. at a blow up of the
+    // solution
+    StepSizeTooSmall,
+    MaxSteps,
+    // t1 is before t0
+    InvalidInterval,
+}
+
+impl fmt::Display for OdeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            OdeError::NotFinite => "a value is NaN or infinite",
+            OdeError::StepSizeTooSmall => "the step size became too small",
+            OdeError::MaxSteps => "the maximum number of steps was reached",
+            OdeError::InvalidInterval => "the end of the interval is before its start",
+        };
+        write!(f, "{message}")
+    }
+}
+
+/// The accepted steps of Dormand-Prince, with the interpolation between
+/// them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OdeSolution {
+    times: Vec<f64>,
+    states: Vec<Vec<f64>>,
+    // The coefficients of the interpolating polynomial of each step
+    interpolants: Vec<[Vec<f64>; 5]>,
+    // The number of evaluations of f, and of rejected steps
+    pub evaluations: usize,
+    pub rejected_steps: usize,
+}
+
+impl OdeSolution {
+    /// The times of the steps, from t0 to t1.
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    /// The solution at the times of the steps.
+    pub fn states(&self) -> &[Vec<f64>] {
+        &self.states
+    }
+
+    /// The solution at t1.
+    pub fn final_state(&self) -> &[f64] {
+        self.states.last().unwrap()
+    }
+
+    /// The solution at any t in [t0, t1], from the dense output.
+    pub fn evaluate(&self, t: f64) -> Vec<f64> {
+        let (t0, t1) = (self.times[0], *self.times.last().unwrap());
+        assert!(t0 <= t && t <= t1, "{t} is outside of [{t0}, {t1}]");
+        if self.interpolants.is_empty() {
+            return self.states[0].clone();
+        }
+        // The step [times[i], times[i + 1]] containing t
+        let i = self
+            .times
+            .partition_point(|&time| time <= t)
+            .saturating_sub(1)
+            .min(self.interpolants.len() - 1);
+        let h = self.times[i + 1] - self.times[i];
+        let theta = (t - self.times[i]) / h;
+        let theta1 = 1.0 - theta;
+        let [r1, r2, r3, r4, r5] = &self.interpolants[i];
+        (0..r1.len())
+            .map(|j| r1[j] + theta * (r2[j] + theta1 * (r3[j] + theta * (r4[j] + theta1 * r5[j]))))
+            .collect()
+    }
+}
+
+// The Butcher tableau of Dormand-Prince
+const C: [f64; 6] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0];
+const A: [[f64; 6]; 7] = [
+    [0.0; 6],
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+        0.0,
+        0.0,
+    ],
+    [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+        0.0,
+    ],
+    // The weights of the solution of order 5
+    [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+// The weights of order 5 minus the ones of order 4
+const E: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+// The weights of the dense output
+const D: [f64; 7] = [
+    -12715105075.0 / 11282082432.0,
+    0.0,
+    87487479700.0 / 32700410799.0,
+    -10690763975.0 / 1880347072.0,
+    701980252875.0 / 199316789632.0,
+    -1453857185.0 / 822651844.0,
+    69997945.0 / 29380423.0,
+];
+
+// The root mean square of the components of e, scaled by the tolerance
+fn error_norm(e: &[f64], y: &[f64], next: &[f64], options: &OdeOptions) -> f64 {
+    if e.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = e
+        .iter()
+        .zip(y.iter().zip(next))
+        .map(|(e, (y, next))| {
+            let scale =
+                options.absolute_tolerance + options.relative_tolerance * y.abs().max(next.abs());
+            (e / scale).powi(2)
+        })
+        .sum();
+    (sum / e.len() as f64).sqrt()
+}
+
+fn all_finite(y: &[f64]) -> Result<(), OdeError> {
+    if y.iter().all(|x| x.is_finite()) {
+        Ok(())
+    } else {
+        Err(OdeError::NotFinite)
+    }
+}
+
+/// Dormand-Prince with adaptive steps, from y(t0) = y0 to t1 >= t0.
+pub fn dormand_prince(
+    f: impl Fn(f64, &[f64]) -> Vec<f64>,
+    t0: f64,
+    y0: &[f64],
+    t1: f64,
+    options: &OdeOptions,
+) -> Result<OdeSolution, OdeError> {
+    if !t0.is_finite() || !t1.is_finite() {
+        return Err(OdeError::NotFinite);
+    }
+    if t1 < t0 {
+        return Err(OdeError::InvalidInterval);
+    }
+    all_finite(y0)?;
+    let mut solution = OdeSolution {
+        times: vec![t0],
+        states: vec![y0.to_vec()],
+        interpolants: vec![],
+        evaluations: 1,
+        rejected_steps: 0,
+    };
+    let (mut t, mut y) = (t0, y0.to_vec());
+    let mut k1 = f(t, &y);
+    all_finite(&k1)?;
+    let mut h = match options.initial_step {
+        Some(h) => h,
+        None => {
+            // A step where the first order change is 1% of y
+            let (d0, d1) = (
+                error_norm(&y, &y, &y, options),
+                error_norm(&k1, &y, &y, options),
+            );
+            if d0 < 1e-5 || d1 < 1e-5 {
+                1e-6
+            } else {
+                0.01 * d0 / d1
+            }
+        }
+    };
+    for _ in 0..options.max_steps {
+        if t >= t1 {
+            return Ok(solution);
+        }
+        h = h.min(t1 - t);
+        if h <= 4.0 * f64::EPSILON * t.abs().max(f64::MIN_POSITIVE) {
+            return Err(OdeError::StepSizeTooSmall);
+        }
+        // The stages k1 to k6, then the solution of order 5, and k7 = f on it
+        let mut k = vec![k1.clone()];
+        for (&c, a) in C.iter().zip(&A).skip(1) {
+            let terms: Vec<(f64, &Vec<f64>)> = a.iter().zip(&k).map(|(&a, k)| (h * a, k)).collect();
+            k.push(f(t + c * h, &add_scaled(&y, &terms)));
+        }
+        let terms: Vec<(f64, &Vec<f64>)> = A[6].iter().zip(&k).map(|(&a, k)| (h * a, k)).collect();
+        let next = add_scaled(&y, &terms);
+        k.push(f(t + h, &next));
+        solution.evaluations += 6;
+        let k7 = &k[6];
+        all_finite(&next).and(all_finite(k7))?;
+        let e_terms: Vec<(f64, &Vec<f64>)> = E.iter().zip(&k).map(|(&e, k)| (h * e, k)).collect();
+        let error = add_scaled(&vec![0.0; y.len()], &e_terms);
+        let error = error_norm(&error, &y, &next, options);
+        // The step factor, limited to [0.2, 5]
+        let factor = if error == 0.0 {
+            5.0
+        } else {
+            (0.9 * error.powf(-0.2)).clamp(0.2, 5.0)
+        };
+        if error <= 1.0 {
+            // The coefficients of the dense output
+            let r2: Vec<f64> = next.iter().zip(&y).map(|(a, b)| a - b).collect();
+            let r3: Vec<f64> = k[0].iter().zip(&r2).map(|(k, r2)| h * k - r2).collect();
+            let r4: Vec<f64> = (0..y.len()).map(|j| r2[j] - h * k7[j] - r3[j]).collect();
+            let d_terms: Vec<(f64, &Vec<f64>)> =
+                D.iter().zip(&k).map(|(&d, k)| (h * d, k)).collect();
+            let r5 = add_scaled(&vec![0.0; y.len()], &d_terms);
+            solution.interpolants.push([y.clone(), r2, r3, r4, r5]);
+            // Land exactly on t1
+            t = if t1 - (t + h) <= 4.0 * f64::EPSILON * t1.abs() {
+                t1
+            } else {
+                t + h
+            };
+            y = next;
+            k1 = k7.clone();
+            solution.times.push(t);
+            solution.states.push(y.clone());
+            h *= factor;
+        } else {
+            solution.rejected_steps += 1;
+            h *= factor.min(1.0);
+        }
+    }
+    if t >= t1 {
+        Ok(solution)
+    } else {
+        Err(OdeError::MaxSteps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // y'' = -y, as (y, y'), with y = cos(t)
+    fn oscillator(_: f64, y: &[f64]) -> Vec<f64> {
+        vec![y[1], -y[0]]
+    }
+
+    #[test]
+    fn runge_kutta_4_order() {
+        let decay = |_: f64, y: &[f64]| vec![-y[0]];
+        let states = runge_kutta_4(decay, 0.0, &[1.0], 1.0, 10);
+        assert_eq!(states.len(), 11);
+        assert_eq!(states[10].0, 1.0);
+        let error = |steps| {
+            let states = runge_kutta_4(decay, 0.0, &[1.0], 1.0, steps);
+            (states.last().unwrap().1[0] - (-1f64).exp()).abs()
+        };
+        assert!(error(10) < 1e-6);
+        // Halving the step divides the error by 2^4
+        let ratio = error(20) / error(40);
+        assert!((ratio - 16.0).abs() < 1.0, "{ratio}");
+    }
+
+    #[test]
+    fn runge_kutta_4_system() {
+        let states = runge_kutta_4(oscillator, 0.0, &[1.0, 0.0], 10.0, 1000);
+        let (t, y) = states.last().unwrap();
+        assert!((y[0] - t.cos()).abs() < 1e-7);
+        assert!((y[1] + t.sin()).abs() < 1e-7);
+    }
+
+    #[test]
+    fn dormand_prince_accuracy() {
+        let options = OdeOptions {
+            absolute_tolerance: 1e-10,
+            relative_tolerance: 1e-10,
+            ..OdeOptions::default()
+        };
+        let solution = dormand_prince(oscillator, 0.0, &[1.0, 0.0], 10.0, &options).unwrap();
+        let y = solution.final_state();
+        assert!((y[0] - 10f64.cos()).abs() < 1e-8);
+        assert!((y[1] + 10f64.sin()).abs() < 1e-8);
+        assert_eq!(*solution.times().last().unwrap(), 10.0);
+        assert_eq!(solution.times().len(), solution.states().len());
+
+        // Looser tolerances take fewer steps
+        let loose = dormand_prince(oscillator, 0.0, &[1.0, 0.0], 10.0, &OdeOptions::default());
+        assert!(loose.unwrap().times().len() < solution.times().len());
+    }
+
+    #[test]
+    fn dense_output() {
+        let options = OdeOptions::default();
+        let solution = dormand_prince(oscillator, 0.0, &[1.0, 0.0], 10.0, &options).unwrap();
+        // Many more points than steps
+        for i in 0..=1000 {
+            let t = i as f64 / 100.0;
+            let y = solution.evaluate(t);
+            assert!((y[0] - t.cos()).abs() < 1e-6, "{t}: {y:?}");
+        }
+        // The interpolation goes through the steps
+        for (&t, y) in solution.times().iter().zip(solution.states()) {
+            let interpolated = solution.evaluate(t);
+            assert!((interpolated[0] - y[0]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn adaptive_steps() {
+        // y' = -50 (y - cos(t)) is quickly attracted to cos(t), with a fast
+        // transient from y = 0 at the start where the steps are small
+        let f = |t: f64, y: &[f64]| vec![-50.0 * (y[0] - t.cos())];
+        let solution = dormand_prince(f, 0.0, &[0.0], 2.0, &OdeOptions::default()).unwrap();
+        let times = solution.times();
+        let first = times[1] - times[0];
+        let last = times[times.len() - 1] - times[times.len() - 2];
+        assert!(first < last);
+        // The exact solution, from the variation of constants
+        let exact = |t: f64| {
+            let steady = 50.0 * (50.0 * t.cos() + t.sin()) / 2501.0;
+            steady - 2500.0 / 2501.0 * (-50.0 * t).exp()
+        };
+        assert!((solution.final_state()[0] - exact(2.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn errors() {
+        let options = OdeOptions::default();
+        // y' = y^2 with y(0) = 1 is 1 / (1 - t), which blows up at t = 1
+        let blow_up = |_: f64, y: &[f64]| vec![y[0] * y[0]];
+        let result = dormand_prince(blow_up, 0.0, &[1.0], 2.0, &options);
+        assert!(matches!(
+            result,
+            Err(OdeError::StepSizeTooSmall) | Err(OdeError::NotFinite) | Err(OdeError::MaxSteps)
+        ));
+        assert_eq!(
+            dormand_prince(oscillator, 1.0, &[1.0, 0.0], 0.0, &options),
+            Err(OdeError::InvalidInterval)
+        );
+        assert_eq!(
+            dormand_prince(oscillator, 0.0, &[f64::NAN, 0.0], 1.0, &options),
+            Err(OdeError::NotFinite)
+        );
+        let few = OdeOptions {
+            max_steps: 3,
+            ..options
+        };
+        assert_eq!(
+            dormand_prince(oscillator, 0.0, &[1.0, 0.0], 100.0, &few),
+            Err(OdeError::MaxSteps)
+        );
+        // An empty interval
+        let solution = dormand_prince(oscillator, 1.0, &[1.0, 0.0], 1.0, &options).unwrap();
+        assert_eq!(solution.final_state(), &[1.0, 0.0]);
+        assert_eq!(solution.evaluate(1.0), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn edge_cases() {
+        // One step of y' = t is exact, since RK4 integrates quadratics exactly
+        let ramp = |t: f64, _: &[f64]| vec![t];
+        assert_eq!(
+            runge_kutta_4(ramp, 0.0, &[0.0], 2.0, 1),
+            vec![(0.0, vec![0.0]), (2.0, vec![2.0])]
+        );
+        // An empty state
+        let states = runge_kutta_4(|_, _| vec![], 0.0, &[], 1.0, 2);
+        assert_eq!(states, vec![(0.0, vec![]), (0.5, vec![]), (1.0, vec![])]);
+
+        let options = OdeOptions::default();
+        let solution = dormand_prince(|_, _| vec![], 0.0, &[], 1.0, &options).unwrap();
+        assert_eq!(*solution.times().last().unwrap(), 1.0);
+        assert!(solution.final_state().is_empty());
+        let none = OdeOptions {
+            max_steps: 0,
+            ..options
+        };
+        assert_eq!(
+            dormand_prince(oscillator, 0.0, &[1.0, 0.0], 1.0, &none),
+            Err(OdeError::MaxSteps)
+        );
+        assert!(dormand_prince(oscillator, 1.0, &[1.0, 0.0], 1.0, &none).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one step is needed")]
+    fn runge_kutta_4_without_steps() {
+        runge_kutta_4(oscillator, 0.0, &[1.0, 0.0], 1.0, 0);
+    }
+}
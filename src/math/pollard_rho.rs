@@ -1,4 +1,4 @@
-use super::miller_rabin;
+use super::{miller_rabin, Montgomery};
 
 struct LinearCongruenceGenerator {
     // modulus as 2 ^ 32
@@ -33,9 +33,10 @@ fn gcd(mut a: u64, mut b: u64) -> u64 {
     b
 }
 
+// x^2 + c, in Montgomery form
 #[inline]
-fn advance(x: u128, c: u64, number: u64) -> u128 {
-    ((x * x) + c as u128) % number as u128
+fn advance(x: u64, c: u64, montgomery: &Montgomery) -> u64 {
+    montgomery.add(montgomery.mul(x, x), c)
 }
 
 fn pollard_rho_customizable(
@@ -52,17 +53,22 @@ fn pollard_rho_customizable(
     We also wait to do a few iterations before calculating the GCD, because
     it is an expensive function. We will correct for overshooting later.
     This function may return either 1, `number` or a proper divisor of `number`
+
+    The numbers are kept in Montgomery form, which does not change their
+    gcd with `number`, so `number` must be odd.
      */
-    let mut x = x0 as u128; // tortoise
-    let mut x_start = 0_u128; // to save the starting tortoise if we overshoot
-    let mut y = 0_u128; // hare
-    let mut remainder = 1_u128;
+    let montgomery = Montgomery::new(number);
+    let c = montgomery.to_montgomery(c);
+    let mut x = montgomery.to_montgomery(x0); // tortoise
+    let mut x_start = 0_u64; // to save the starting tortoise if we overshoot
+    let mut y = 0_u64; // hare
+    let mut remainder = montgomery.to_montgomery(1);
     let mut current_gcd = 1_u64;
     let mut max_iterations = 1_u32;
     while current_gcd == 1 {
         y = x;
         for _ in 1..max_iterations {
-            x = advance(x, c, number);
+            x = advance(x, c, &montgomery);
         }
         let mut big_iteration = 0_u32;
         while big_iteration < max_iterations && current_gcd == 1 {
@@ -72,11 +78,11 @@ fn pollard_rho_customizable(
                 && small_iteration < (max_iterations - big_iteration)
             {
                 small_iteration += 1;
-                x = advance(x, c, number);
+                x = advance(x, c, &montgomery);
                 let diff = x.abs_diff(y);
-                remainder = (remainder * diff) % number as u128;
+                remainder = montgomery.mul(remainder, diff);
             }
-            current_gcd = gcd(remainder as u64, number);
+            current_gcd = gcd(remainder, number);
             big_iteration += iterations_before_check;
         }
         max_iterations *= 2;
@@ -85,9 +91,11 @@ fn pollard_rho_customizable(
         }
     }
     if current_gcd == number {
+        // Step again from the start of the last batch, one gcd at a time
+        current_gcd = 1;
         while current_gcd == 1 {
-            x_start = advance(x_start, c, number);
-            current_gcd = gcd(x_start.abs_diff(y) as u64, number);
+            x_start = advance(x_start, c, &montgomery);
+            current_gcd = gcd(x_start.abs_diff(y), number);
         }
     }
     current_gcd
@@ -116,6 +124,9 @@ pub fn pollard_rho_get_one_factor(number: u64, seed: &mut u32, check_is_prime: b
             return number;
         }
     }
+    if number & 1 == 0 {
+        return 2;
+    }
     let mut factor = 1u64;
     while factor == 1 || factor == number {
         let x = rng.get_64bits();
@@ -156,7 +167,8 @@ fn factor_using_mpf(mut number: usize, mpf: &[usize]) -> Vec<u64> {
 
 /*
 `primes` and `minimum_prime_factors` use usize because so does
-LinearSieve implementation in this repository
+LinearSieve implementation in this repository.
+See `factorize` for a version which does not need a sieve.
  */
 pub fn pollard_rho_factorize(
     mut number: u64,
@@ -247,6 +259,17 @@ mod test {
             ));
         }
     }
+    #[test]
+    fn overshooting_batch() {
+        // With these constants, the differences are multiples of both 83 and
+        // 97 in the same batch, so its gcd is 8051 itself, and the factor is
+        // found by stepping through the batch again one gcd at a time
+        for c in [3, 4, 6, 18] {
+            let factor = pollard_rho_customizable(8051, 2, c, 32, 1 << 18);
+            assert!(check_is_proper_factor(8051, factor));
+        }
+    }
+
     #[test]
     fn big_numbers() {
         // Bigger cases:
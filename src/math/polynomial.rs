@@ -0,0 +1,472 @@
+/*
+Polynomials:
+Polynomials with coefficients modulo a prime M, where the product is a
+convolution computed with the number-theoretic transform in O(n log(n)), or
+with three NTT-friendly primes and the Chinese remainder theorem when M is
+not NTT-friendly (like 10^9 + 7).
+
+Most of the other operations reduce to products with Newton's method, which
+doubles the number of correct terms at each step:
+- The inverse series g = 1 / f mod x^n, from g <- g (2 - f g), in O(n log(n)).
+- The quotient of a by b, from the inverse series of b with its
+  coefficients reversed, in O(n log(n)).
+- The logarithm log(f) = integral of f' / f, when f(0) = 1.
+- The exponential exp(f), from g <- g (1 - log(g) + f), when f(0) = 0.
+- The evaluation at n points, by taking the remainders of f by the products
+  of (x - point) over halves, quarters... of the points (subproduct tree) in
+  O(n log^2(n)).
+https://en.wikipedia.org/wiki/Polynomial_arithmetic
+https://cp-algorithms.com/algebra/polynomial.html
+*/
+
+use super::{convolution_mod, ntt_convolution, supports_ntt, ModInt};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+// Below this number of points, the remainder is evaluated with Horner's rule
+const EVALUATION_THRESHOLD: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial<T> {
+    // From the constant coefficient, without trailing zeros, so that the zero
+    // polynomial has none
+    coefficients: Vec<T>,
+}
+
+impl<T: Copy + Default + PartialEq> Polynomial<T> {
+    pub fn new(mut coefficients: Vec<T>) -> Self {
+        while coefficients.last() == Some(&T::default()) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    pub fn zero() -> Self {
+        Polynomial {
+            coefficients: vec![],
+        }
+    }
+
+    pub fn coefficients(&self) -> &[T] {
+        &self.coefficients
+    }
+
+    /// The coefficient of x^i, which is zero above the degree.
+    pub fn coefficient(&self, i: usize) -> T {
+        self.coefficients.get(i).copied().unwrap_or_default()
+    }
+
+    /// None for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    // The polynomial modulo x^n
+    fn prefix(&self, n: usize) -> Self {
+        Polynomial::new(self.coefficients[..n.min(self.coefficients.len())].to_vec())
+    }
+}
+
+// The product of the coefficient lists
+fn multiply<const M: u64>(a: &[ModInt<M>], b: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    if supports_ntt::<M>((a.len() + b.len() - 1).next_power_of_two()) {
+        return ntt_convolution(a, b);
+    }
+    let values = |v: &[ModInt<M>]| -> Vec<u64> { v.iter().map(|x| x.value()).collect() };
+    convolution_mod(&values(a), &values(b), M)
+        .into_iter()
+        .map(ModInt::new)
+        .collect()
+}
+
+impl<const M: u64> Polynomial<ModInt<M>> {
+    /// The constant polynomial c.
+    pub fn constant(c: ModInt<M>) -> Self {
+        Polynomial::new(vec![c])
+    }
+
+    pub fn evaluate(&self, x: ModInt<M>) -> ModInt<M> {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(ModInt::new(0), |acc, &c| acc * x + c)
+    }
+
+    pub fn derivative(&self) -> Self {
+        Polynomial::new(
+            self.coefficients
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, &c)| c * ModInt::new(i as u64))
+                .collect(),
+        )
+    }
+
+    /// The antiderivative with a zero constant coefficient. M must be larger
+    /// than the degree plus one.
+    pub fn integral(&self) -> Self {
+        let mut coefficients = vec![ModInt::new(0)];
+        coefficients.extend(
+            self.coefficients
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| c / ModInt::new(i as u64 + 1)),
+        );
+        Polynomial::new(coefficients)
+    }
+
+    /// The first n terms of the series 1 / self, or None if the constant
+    /// coefficient is zero.
+    pub fn inverse_series(&self, n: usize) -> Option<Self> {
+        let mut inverse = Polynomial::constant(self.coefficient(0).inverse()?);
+        let mut length = 1;
+        while length < n {
+            length *= 2;
+            // g (2 - f g)
+            let product = (&self.prefix(length) * &inverse).prefix(length);
+            let correction = &Polynomial::constant(ModInt::new(2)) - &product;
+            inverse = (&inverse * &correction).prefix(length);
+        }
+        Some(inverse.prefix(n))
+    }
+
+    /// The first n terms of log(self), or None if the constant coefficient is
+    /// not 1.
+    pub fn log_series(&self, n: usize) -> Option<Self> {
+        if self.coefficient(0) != ModInt::new(1) {
+            return None;
+        }
+        if n == 0 {
+            return Some(Polynomial::zero());
+        }
+        let quotient = &self.derivative() * &self.inverse_series(n)?;
+        Some(quotient.prefix(n - 1).integral())
+    }
+
+    /// The first n terms of exp(self), or None if the constant coefficient is
+    /// not 0.
+    pub fn exp_series(&self, n: usize) -> Option<Self> {
+        if self.coefficient(0) != ModInt::new(0) {
+            return None;
+        }
+        let one = Polynomial::constant(ModInt::new(1));
+        let mut exp = one.clone();
+        let mut length = 1;
+        while length < n {
+            length *= 2;
+            // g (1 - log(g) + f)
+            let log = exp.log_series(length).unwrap();
+            let correction = &(&one - &log) + &self.prefix(length);
+            exp = (&exp * &correction).prefix(length);
+        }
+        Some(exp.prefix(n))
+    }
+
+    /// The quotient and the remainder of the division by `divisor`, which
+    /// must not be zero.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let divisor_degree = divisor.degree().expect("division by the zero polynomial");
+        let degree = match self.degree() {
+            Some(degree) if degree >= divisor_degree => degree,
+            _ => return (Polynomial::zero(), self.clone()),
+        };
+        // The reversed quotient is the reversed self over the reversed
+        // divisor, modulo x^(degree of the quotient + 1)
+        let length = degree - divisor_degree + 1;
+        let reversed = |p: &Self| Polynomial::new(p.coefficients.iter().rev().copied().collect());
+        let divisor_inverse = reversed(divisor).inverse_series(length).unwrap();
+        let mut quotient = (&reversed(self).prefix(length) * &divisor_inverse)
+            .prefix(length)
+            .coefficients;
+        quotient.resize(length, ModInt::new(0));
+        quotient.reverse();
+        let quotient = Polynomial::new(quotient);
+        let remainder = (self - &(divisor * &quotient)).prefix(divisor_degree);
+        (quotient, remainder)
+    }
+
+    /// The values at each of the points.
+    pub fn multipoint_evaluate(&self, points: &[ModInt<M>]) -> Vec<ModInt<M>> {
+        if points.is_empty() {
+            return vec![];
+        }
+        let mut tree = vec![Polynomial::zero(); 4 * points.len()];
+        build_subproduct_tree(&mut tree, 1, points);
+        let mut values = Vec::with_capacity(points.len());
+        self.evaluate_with_tree(&tree, 1, points, &mut values);
+        values
+    }
+
+    fn evaluate_with_tree(
+        &self,
+        tree: &[Self],
+        node: usize,
+        points: &[ModInt<M>],
+        values: &mut Vec<ModInt<M>>,
+    ) {
+        let remainder = self % &tree[node];
+        if points.len() <= EVALUATION_THRESHOLD {
+            values.extend(points.iter().map(|&x| remainder.evaluate(x)));
+            return;
+        }
+        let (left, right) = points.split_at(points.len() / 2);
+        remainder.evaluate_with_tree(tree, 2 * node, left, values);
+        remainder.evaluate_with_tree(tree, 2 * node + 1, right, values);
+    }
+}
+
+// tree[node] is the product of the (x - point) for the points of the node,
+// whose children are 2 * node and 2 * node + 1
+fn build_subproduct_tree<const M: u64>(
+    tree: &mut [Polynomial<ModInt<M>>],
+    node: usize,
+    points: &[ModInt<M>],
+) {
+    if points.len() == 1 {
+        tree[node] = Polynomial::new(vec![-points[0], ModInt::new(1)]);
+        return;
+    }
+    let (left, right) = points.split_at(points.len() / 2);
+    build_subproduct_tree(tree, 2 * node, left);
+    build_subproduct_tree(tree, 2 * node + 1, right);
+    tree[node] = &tree[2 * node] * &tree[2 * node + 1];
+}
+
+impl<const M: u64> From<Vec<u64>> for Polynomial<ModInt<M>> {
+    fn from(coefficients: Vec<u64>) -> Self {
+        Polynomial::new(coefficients.into_iter().map(ModInt::new).collect())
+    }
+}
+
+impl<const M: u64> Add for &Polynomial<ModInt<M>> {
+    type Output = Polynomial<ModInt<M>>;
+
+    fn add(self, other: Self) -> Self::Output {
+        let length = self.coefficients.len().max(other.coefficients.len());
+        Polynomial::new(
+            (0..length)
+                .map(|i| self.coefficient(i) + other.coefficient(i))
+                .collect(),
+        )
+    }
+}
+
+impl<const M: u64> Sub for &Polynomial<ModInt<M>> {
+    type Output = Polynomial<ModInt<M>>;
+
+    fn sub(self, other: Self) -> Self::Output {
+        let length = self.coefficients.len().max(other.coefficients.len());
+        Polynomial::new(
+            (0..length)
+                .map(|i| self.coefficient(i) - other.coefficient(i))
+                .collect(),
+        )
+    }
+}
+
+impl<const M: u64> Neg for &Polynomial<ModInt<M>> {
+    type Output = Polynomial<ModInt<M>>;
+
+    fn neg(self) -> Self::Output {
+        Polynomial::new(self.coefficients.iter().map(|&c| -c).collect())
+    }
+}
+
+impl<const M: u64> Mul for &Polynomial<ModInt<M>> {
+    type Output = Polynomial<ModInt<M>>;
+
+    fn mul(self, other: Self) -> Self::Output {
+        Polynomial::new(multiply(&self.coefficients, &other.coefficients))
+    }
+}
+
+impl<const M: u64> Div for &Polynomial<ModInt<M>> {
+    type Output = Polynomial<ModInt<M>>;
+
+    fn div(self, other: Self) -> Self::Output {
+        self.div_rem(other).0
+    }
+}
+
+impl<const M: u64> Rem for &Polynomial<ModInt<M>> {
+    type Output = Polynomial<ModInt<M>>;
+
+    fn rem(self, other: Self) -> Self::Output {
+        self.div_rem(other).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{ModInt1000000007, ModInt998244353, PCG32};
+
+    type Poly = Polynomial<ModInt998244353>;
+
+    fn random_polynomial<const M: u64>(rng: &mut PCG32, length: usize) -> Polynomial<ModInt<M>> {
+        Polynomial::new((0..length).map(|_| ModInt::new(rng.get_u64())).collect())
+    }
+
+    fn naive_product<const M: u64>(
+        a: &Polynomial<ModInt<M>>,
+        b: &Polynomial<ModInt<M>>,
+    ) -> Polynomial<ModInt<M>> {
+        if a.is_zero() || b.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut product = vec![ModInt::new(0); a.coefficients().len() + b.coefficients().len() - 1];
+        for (i, &x) in a.coefficients().iter().enumerate() {
+            for (j, &y) in b.coefficients().iter().enumerate() {
+                product[i + j] += x * y;
+            }
+        }
+        Polynomial::new(product)
+    }
+
+    #[test]
+    fn basic_operations() {
+        let a = Poly::from(vec![1, 2, 3]);
+        let b = Poly::from(vec![4, 5]);
+        assert_eq!(&a * &b, Poly::from(vec![4, 13, 22, 15]));
+        assert_eq!(&a + &b, Poly::from(vec![5, 7, 3]));
+        assert_eq!(&(&a - &a), &Poly::zero());
+        assert_eq!(-&b, Poly::from(vec![998244349, 998244348]));
+        assert_eq!(a.degree(), Some(2));
+        assert_eq!(Poly::from(vec![0, 0]).degree(), None);
+        assert_eq!(a.coefficient(7).value(), 0);
+        assert_eq!(a.evaluate(ModInt::new(2)).value(), 17);
+        assert_eq!(a.derivative(), Poly::from(vec![2, 6]));
+        assert_eq!(a.derivative().integral(), Poly::from(vec![0, 2, 3]));
+
+        // (x^3 + 2x + 5) = (x - 1)(x^2 + x + 3) + 8
+        let (q, r) = Poly::from(vec![5, 2, 0, 1]).div_rem(&Poly::from(vec![998244352, 1]));
+        assert_eq!(q, Poly::from(vec![3, 1, 1]));
+        assert_eq!(r, Poly::from(vec![8]));
+        assert_eq!(&b / &a, Poly::zero());
+        assert_eq!(&b % &a, b);
+    }
+
+    #[test]
+    fn large_products() {
+        let mut rng = PCG32::new_default(3);
+        for (n, m) in [(200, 300), (1000, 33), (64, 64)] {
+            let a = random_polynomial::<998_244_353>(&mut rng, n);
+            let b = random_polynomial(&mut rng, m);
+            assert_eq!(&a * &b, naive_product(&a, &b));
+            // 10^9 + 7 is not NTT-friendly
+            let a = random_polynomial::<1_000_000_007>(&mut rng, n);
+            let b = random_polynomial(&mut rng, m);
+            assert_eq!(&a * &b, naive_product(&a, &b));
+            // Nor is this prime above 2^62
+            let a = random_polynomial::<4_611_686_018_427_387_847>(&mut rng, n);
+            let b = random_polynomial(&mut rng, m);
+            assert_eq!(&a * &b, naive_product(&a, &b));
+        }
+    }
+
+    #[test]
+    fn division() {
+        let mut rng = PCG32::new_default(5);
+        for (n, m) in [(500, 120), (300, 299), (90, 1), (40, 70)] {
+            let a = random_polynomial::<998_244_353>(&mut rng, n);
+            let b = random_polynomial(&mut rng, m);
+            let (q, r) = a.div_rem(&b);
+            assert!(r.degree() < b.degree());
+            assert_eq!(&(&b * &q) + &r, a);
+        }
+        let a = random_polynomial::<1_000_000_007>(&mut rng, 200);
+        let b = random_polynomial(&mut rng, 50);
+        assert_eq!(&(&b * &(&a / &b)) + &(&a % &b), a);
+    }
+
+    #[test]
+    #[should_panic(expected = "division by the zero polynomial")]
+    fn division_by_zero() {
+        let _ = &Poly::from(vec![1, 2]) / &Poly::zero();
+    }
+
+    #[test]
+    fn series() {
+        let mut rng = PCG32::new_default(8);
+        let f = random_polynomial::<998_244_353>(&mut rng, 300);
+        let g = f.inverse_series(500).unwrap();
+        assert_eq!((&f * &g).prefix(500), Poly::constant(ModInt::new(1)));
+        assert_eq!(Poly::from(vec![0, 1]).inverse_series(3), None);
+
+        // exp(x) = sum x^k / k!
+        let exp = Poly::from(vec![0, 1]).exp_series(10).unwrap();
+        let mut factorial = ModInt998244353::new(1);
+        for k in 0..10 {
+            assert_eq!(exp.coefficient(k) * factorial, ModInt::new(1));
+            factorial *= ModInt::new(k as u64 + 1);
+        }
+        // log(1 / (1 - x)) = sum x^k / k
+        let log = Poly::from(vec![1, 998244352])
+            .inverse_series(20)
+            .unwrap()
+            .log_series(20)
+            .unwrap();
+        for k in 1..20 {
+            assert_eq!(log.coefficient(k) * ModInt::new(k as u64), ModInt::new(1));
+        }
+
+        let mut f = random_polynomial::<998_244_353>(&mut rng, 200);
+        f.coefficients[0] = ModInt::new(0);
+        let exp = f.exp_series(256).unwrap();
+        assert_eq!(exp.log_series(256).unwrap(), f);
+        assert_eq!(f.log_series(5), None);
+        assert_eq!(exp.exp_series(5), None);
+
+        // Over 10^9 + 7
+        let mut f = random_polynomial::<1_000_000_007>(&mut rng, 100);
+        f.coefficients[0] = ModInt::new(0);
+        assert_eq!(f.exp_series(100).unwrap().log_series(100).unwrap(), f);
+    }
+
+    #[test]
+    fn multipoint_evaluation() {
+        let mut rng = PCG32::new_default(13);
+        let f = random_polynomial::<998_244_353>(&mut rng, 400);
+        let points: Vec<ModInt998244353> = (0..300).map(|_| ModInt::new(rng.get_u64())).collect();
+        let expected: Vec<ModInt998244353> = points.iter().map(|&x| f.evaluate(x)).collect();
+        assert_eq!(f.multipoint_evaluate(&points), expected);
+        assert!(f.multipoint_evaluate(&[]).is_empty());
+
+        let f = random_polynomial::<1_000_000_007>(&mut rng, 50);
+        let points: Vec<ModInt1000000007> = (0..100).map(ModInt::new).collect();
+        let expected: Vec<ModInt1000000007> = points.iter().map(|&x| f.evaluate(x)).collect();
+        assert_eq!(f.multipoint_evaluate(&points), expected);
+    }
+
+    #[test]
+    fn zero_and_constants() {
+        let zero = Poly::zero();
+        let a = Poly::from(vec![1, 2, 3]);
+        assert_eq!(&zero * &a, zero);
+        assert_eq!(zero.div_rem(&a), (Poly::zero(), Poly::zero()));
+        assert_eq!(
+            zero.multipoint_evaluate(&[ModInt::new(3)]),
+            vec![ModInt::new(0)]
+        );
+        assert_eq!(zero.inverse_series(4), None);
+        assert_eq!(zero.exp_series(3), Some(Poly::from(vec![1])));
+
+        // A constant divides everything, and has a constant inverse
+        let three = Poly::constant(ModInt::new(3));
+        let (q, r) = a.div_rem(&three);
+        assert_eq!((&q * &three, r), (a, Poly::zero()));
+        assert_eq!(three.inverse_series(5), Some(Poly::from(vec![332748118])));
+
+        // No terms at all
+        assert_eq!(three.inverse_series(0), Some(Poly::zero()));
+        assert_eq!(zero.exp_series(0), Some(Poly::zero()));
+        assert_eq!(Poly::from(vec![1, 1]).log_series(0), Some(Poly::zero()));
+    }
+}
@@ -7,53 +7,45 @@
 /// Wikipedia reference: https://en.wikipedia.org/wiki/Cipolla%27s_algorithm
 /// When a is the primitive root modulo n, the answer is unique.
 /// Otherwise it will return the smallest positive solution
-use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use super::{fast_power, PCG32};
+use super::{DynModInt, PCG32};
 
-#[derive(Debug)]
-struct CustomFiniteFiled {
-    modulus: u64,
-    i_square: u64,
-}
-
-impl CustomFiniteFiled {
-    pub fn new(modulus: u64, i_square: u64) -> Self {
-        Self { modulus, i_square }
-    }
-}
-
-#[derive(Clone, Debug)]
+// The numbers real + imag * i of the field extension F_p(i), with i^2 = i_square
+#[derive(Clone, Copy, Debug)]
 struct CustomComplexNumber {
-    real: u64,
-    imag: u64,
-    f: Rc<CustomFiniteFiled>,
+    real: DynModInt,
+    imag: DynModInt,
+    i_square: DynModInt,
 }
 
 impl CustomComplexNumber {
-    pub fn new(real: u64, imag: u64, f: Rc<CustomFiniteFiled>) -> Self {
-        Self { real, imag, f }
+    pub fn new(real: DynModInt, imag: DynModInt, i_square: DynModInt) -> Self {
+        Self {
+            real,
+            imag,
+            i_square,
+        }
     }
 
     pub fn mult_other(&mut self, rhs: &Self) {
-        let tmp = (self.imag * rhs.real + self.real * rhs.imag) % self.f.modulus;
-        self.real = (self.real * rhs.real
-            + ((self.imag * rhs.imag) % self.f.modulus) * self.f.i_square)
-            % self.f.modulus;
+        let tmp = self.imag * rhs.real + self.real * rhs.imag;
+        self.real = self.real * rhs.real + self.imag * rhs.imag * self.i_square;
         self.imag = tmp;
     }
 
     pub fn mult_self(&mut self) {
-        let tmp = (self.imag * self.real + self.real * self.imag) % self.f.modulus;
-        self.real = (self.real * self.real
-            + ((self.imag * self.imag) % self.f.modulus) * self.f.i_square)
-            % self.f.modulus;
-        self.imag = tmp;
+        let other = *self;
+        self.mult_other(&other);
     }
 
     pub fn fast_power(mut base: Self, mut power: u64) -> Self {
-        let mut result = CustomComplexNumber::new(1, 0, base.f.clone());
+        let modulus = base.real.modulus();
+        let mut result = CustomComplexNumber::new(
+            DynModInt::new(1, modulus),
+            DynModInt::new(0, modulus),
+            base.i_square,
+        );
         while power != 0 {
             if (power & 1) != 0 {
                 result.mult_other(&base); // result *= base;
@@ -67,7 +59,7 @@ impl CustomComplexNumber {
 
 fn is_residue(x: u64, modulus: u64) -> bool {
     let power = (modulus - 1) >> 1;
-    x != 0 && fast_power(x as usize, power as usize, modulus as usize) == 1
+    x != 0 && DynModInt::new(x, modulus).pow(power).value() == 1
 }
 
 // return two solutions (x1, x2) for Quadratic Residue problem x^2 = a (mod p), where p is an odd prime
@@ -97,10 +89,11 @@ pub fn cipolla(a: u32, p: u32, seed: Option<u64>) -> Option<(u32, u32)> {
             break r;
         }
     };
-    let filed = Rc::new(CustomFiniteFiled::new(p, (p + r * r - a) % p));
-    let comp = CustomComplexNumber::new(r, 1, filed);
+    let r = DynModInt::new(r, p);
+    let i_square = r * r - DynModInt::new(a, p);
+    let comp = CustomComplexNumber::new(r, DynModInt::new(1, p), i_square);
     let power = (p + 1) >> 1;
-    let x0 = CustomComplexNumber::fast_power(comp, power).real as u32;
+    let x0 = CustomComplexNumber::fast_power(comp, power).real.value() as u32;
     let x1 = p as u32 - x0;
     if x0 < x1 {
         Some((x0, x1))
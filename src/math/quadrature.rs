@@ -0,0 +1,601 @@
+/*
+Numerical integration:
+Approximations of the integral of f over [a, b], which adapt where to evaluate
+f to reach a tolerance, and return an estimate of their error:
+- Adaptive Simpson compares Simpson's rule on [a, b] with the sum of the rules
+  on both halves. Their difference is about 15 times the error of the latter,
+  so an interval is accepted when it is below 15 times its share of the
+  tolerance, and split in two otherwise.
+  https://en.wikipedia.org/wiki/Adaptive_Simpson%27s_method
+- Gauss-Legendre with n points is exact for the polynomials of degree up to
+  2n - 1. The nodes are the roots of the Legendre polynomial P_n, found with
+  Newton's method from the Chebyshev-like guesses cos(pi (i - 1/4) / (n + 1/2)).
+  Its error is estimated by comparing with the rule with n + 1 points.
+  https://en.wikipedia.org/wiki/Gauss%E2%80%93Legendre_quadrature
+- Gauss-Kronrod adds 8 points to the 7 of Gauss-Legendre to get a 15-point
+  rule exact up to degree 22, and the difference of the two estimates the
+  error for free. The interval with the largest error is split until the sum
+  of the errors is below the tolerance, like QUADPACK's QAG.
+  https://en.wikipedia.org/wiki/Gauss%E2%80%93Kronrod_quadrature_formula
+- Double integrals over a rectangle are iterated Gauss-Kronrod integrals.
+See simpson_integration for Simpson's rule with a fixed number of steps.
+*/
+
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::f64::consts::PI;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuadratureOptions {
+    // The integral is accepted when the error estimate is below
+    // max(absolute_tolerance, relative_tolerance * |integral|)
+    pub absolute_tolerance: f64,
+    pub relative_tolerance: f64,
+    // The maximum number of intervals
+    pub max_subdivisions: usize,
+}
+
+impl Default for QuadratureOptions {
+    fn default() -> Self {
+        QuadratureOptions {
+            absolute_tolerance: 1e-10,
+            relative_tolerance: 1e-10,
+            max_subdivisions: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Integral {
+    pub value: f64,
+    // An estimate of |value - the exact integral|
+    pub error: f64,
+    // The number of evaluations of the function
+    pub evaluations: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuadratureError {
+    // The function returned a NaN or an infinity, or a bound is not finite
+    NotFinite,
+    // The tolerance was not reached with max_subdivisions intervals, e.g.
+    // for a singularity
+    MaxSubdivisions,
+}
+
+impl fmt::Display for QuadratureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            QuadratureError::NotFinite => "the function is not finite on the interval",
+            QuadratureError::MaxSubdivisions => "the tolerance was not reached",
+        };
+        write!(f, "{message}")
+    }
+}
+
+impl QuadratureOptions {
+    fn tolerance(&self, value: f64) -> f64 {
+        self.absolute_tolerance
+            .max(self.relative_tolerance * value.abs())
+    }
+}
+
+// f(x), where a NaN or an infinity is an error
+fn evaluate(f: &impl Fn(f64) -> f64, x: f64) -> Result<f64, QuadratureError> {
+    let y = f(x);
+    if y.is_finite() {
+        Ok(y)
+    } else {
+        Err(QuadratureError::NotFinite)
+    }
+}
+
+fn check_bounds(a: f64, b: f64) -> Result<(), QuadratureError> {
+    if a.is_finite() && b.is_finite() {
+        Ok(())
+    } else {
+        Err(QuadratureError::NotFinite)
+    }
+}
+
+// An interval of adaptive Simpson, with f at its ends and middle, and the
+// rule on it
+#[derive(Clone, Copy)]
+struct SimpsonInterval {
+    a: f64,
+    b: f64,
+    fa: f64,
+    fm: f64,
+    fb: f64,
+    whole: f64,
+}
+
+impl SimpsonInterval {
+    fn new(a: f64, b: f64, fa: f64, fm: f64, fb: f64) -> Self {
+        SimpsonInterval {
+            a,
+            b,
+            fa,
+            fm,
+            fb,
+            whole: (b - a) / 6.0 * (fa + 4.0 * fm + fb),
+        }
+    }
+}
+
+/// Adaptive Simpson's rule.
+pub fn adaptive_simpson(
+    f: impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    options: &QuadratureOptions,
+) -> Result<Integral, QuadratureError> {
+    check_bounds(a, b)?;
+    let m = a + (b - a) / 2.0;
+    let root = SimpsonInterval::new(a, b, evaluate(&f, a)?, evaluate(&f, m)?, evaluate(&f, b)?);
+    let mut integral = Integral {
+        value: 0.0,
+        error: 0.0,
+        evaluations: 3,
+    };
+    let tolerance = options.tolerance(root.whole);
+    // The intervals to refine, with their share of the tolerance
+    let mut stack = vec![(root, tolerance)];
+    let mut subdivisions = 1;
+    while let Some((interval, tolerance)) = stack.pop() {
+        let SimpsonInterval {
+            a, b, fa, fm, fb, ..
+        } = interval;
+        let m = a + (b - a) / 2.0;
+        let (left_middle, right_middle) = (a + (m - a) / 2.0, m + (b - m) / 2.0);
+        let left = SimpsonInterval::new(a, m, fa, evaluate(&f, left_middle)?, fm);
+        let right = SimpsonInterval::new(m, b, fm, evaluate(&f, right_middle)?, fb);
+        integral.evaluations += 2;
+        let difference = left.whole + right.whole - interval.whole;
+        // Stop splitting when the interval is too small to split, the error
+        // is then the rounding error
+        if difference.abs() <= 15.0 * tolerance || m == a || m == b {
+            // Richardson extrapolation
+            integral.value += left.whole + right.whole + difference / 15.0;
+            integral.error += difference.abs() / 15.0;
+        } else {
+            subdivisions += 1;
+            if subdivisions > options.max_subdivisions {
+                return Err(QuadratureError::MaxSubdivisions);
+            }
+            stack.push((left, tolerance / 2.0));
+            stack.push((right, tolerance / 2.0));
+        }
+    }
+    Ok(integral)
+}
+
+/// The nodes and weights of the n-point Gauss-Legendre rule on [-1, 1], by
+/// increasing node.
+pub fn gauss_legendre_rule(n: usize) -> Vec<(f64, f64)> {
+    let mut rule = vec![(0.0, 0.0); n];
+    // The nodes are symmetric, only the positive half is computed
+    for i in 0..n.div_ceil(2) {
+        let mut x = (PI * (i as f64 + 0.75) / (n as f64 + 0.5)).cos();
+        let mut derivative = 0.0;
+        for _ in 0..100 {
+            // P_n(x) and P_n-1(x) with the recurrence
+            // k P_k = (2k - 1) x P_k-1 - (k - 1) P_k-2
+            let (mut p, mut previous) = (1.0, 0.0);
+            for k in 1..=n {
+                let k = k as f64;
+                (p, previous) = (((2.0 * k - 1.0) * x * p - (k - 1.0) * previous) / k, p);
+            }
+            derivative = n as f64 * (x * p - previous) / (x * x - 1.0);
+            let step = p / derivative;
+            x -= step;
+            if step.abs() <= 4.0 * f64::EPSILON {
+                break;
+            }
+        }
+        let weight = 2.0 / ((1.0 - x * x) * derivative * derivative);
+        rule[i] = (-x, weight);
+        rule[n - 1 - i] = (x, weight);
+    }
+    rule
+}
+
+// The (node, weight) pairs of a rule
+type Rule = Rc<[(f64, f64)]>;
+
+thread_local! {
+    // The rules computed so far, as finding the nodes takes O(n^2) time
+    static GAUSS_LEGENDRE_RULES: RefCell<HashMap<usize, Rule>> = RefCell::new(HashMap::new());
+}
+
+fn cached_gauss_legendre_rule(n: usize) -> Rule {
+    GAUSS_LEGENDRE_RULES.with(|rules| {
+        rules
+            .borrow_mut()
+            .entry(n)
+            .or_insert_with(|| gauss_legendre_rule(n).into())
+            .clone()
+    })
+}
+
+// The n-point Gauss-Legendre rule on [a, b]
+fn apply_gauss_legendre(
+    f: &impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    n: usize,
+) -> Result<f64, QuadratureError> {
+    let (center, half) = ((a + b) / 2.0, (b - a) / 2.0);
+    let mut sum = 0.0;
+    for &(x, w) in cached_gauss_legendre_rule(n).iter() {
+        sum += w * evaluate(f, center + half * x)?;
+    }
+    Ok(half * sum)
+}
+
+/// The n-point Gauss-Legendre rule on [a, b]. The error is estimated by
+/// |G_n - G_(n+1)|, the difference with the rule with one more point, which
+/// is usually much more accurate. The rules are only computed once for each n.
+pub fn gauss_legendre(
+    f: impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    n: usize,
+) -> Result<Integral, QuadratureError> {
+    check_bounds(a, b)?;
+    let value = apply_gauss_legendre(&f, a, b, n)?;
+    let finer = apply_gauss_legendre(&f, a, b, n + 1)?;
+    Ok(Integral {
+        value,
+        error: (value - finer).abs(),
+        evaluations: 2 * n + 1,
+    })
+}
+
+// The Kronrod nodes on [0, 1], the odd ones being the Gauss nodes, their
+// weights, and the weights of the 7-point Gauss rule
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.0,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_224,
+    0.063_092_092_629_978_55,
+    0.104_790_010_322_250_18,
+    0.140_653_259_715_525_92,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_83,
+];
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+struct KronrodInterval {
+    a: f64,
+    b: f64,
+    value: f64,
+    error: f64,
+}
+
+// The intervals are ordered by their error, for the heap
+impl PartialEq for KronrodInterval {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error
+    }
+}
+
+impl Eq for KronrodInterval {}
+
+impl PartialOrd for KronrodInterval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KronrodInterval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.error.total_cmp(&other.error)
+    }
+}
+
+// The 15-point Kronrod rule on [a, b], with the difference to the 7-point
+// Gauss rule as its error
+fn kronrod_15(f: &impl Fn(f64) -> f64, a: f64, b: f64) -> Result<KronrodInterval, QuadratureError> {
+    let (center, half) = ((a + b) / 2.0, (b - a) / 2.0);
+    let mut kronrod = 0.0;
+    let mut gauss = 0.0;
+    for (i, (&x, &w)) in KRONROD_NODES.iter().zip(&KRONROD_WEIGHTS).enumerate() {
+        let sum = if x == 0.0 {
+            evaluate(f, center)?
+        } else {
+            evaluate(f, center - half * x)? + evaluate(f, center + half * x)?
+        };
+        kronrod += w * sum;
+        if i % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[i / 2] * sum;
+        }
+    }
+    Ok(KronrodInterval {
+        a,
+        b,
+        value: half * kronrod,
+        error: (half * (kronrod - gauss)).abs(),
+    })
+}
+
+/// Adaptive Gauss-Kronrod quadrature with the 7 and 15-point rules.
+pub fn gauss_kronrod(
+    f: impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    options: &QuadratureOptions,
+) -> Result<Integral, QuadratureError> {
+    check_bounds(a, b)?;
+    let first = kronrod_15(&f, a, b)?;
+    let (mut value, mut error) = (first.value, first.error);
+    let mut intervals = BinaryHeap::from([first]);
+    while error > options.tolerance(value) {
+        if intervals.len() >= options.max_subdivisions {
+            return Err(QuadratureError::MaxSubdivisions);
+        }
+        let worst = intervals.pop().unwrap();
+        let middle = worst.a + (worst.b - worst.a) / 2.0;
+        if middle == worst.a || middle == worst.b {
+            // Too small to split, the error is then the rounding error
+            intervals.push(worst);
+            break;
+        }
+        let left = kronrod_15(&f, worst.a, middle)?;
+        let right = kronrod_15(&f, middle, worst.b)?;
+        value += left.value + right.value - worst.value;
+        error += left.error + right.error - worst.error;
+        intervals.push(left);
+        intervals.push(right);
+    }
+    // Sum again, without the cancellations of the updates
+    Ok(Integral {
+        value: intervals.iter().map(|interval| interval.value).sum(),
+        error: intervals.iter().map(|interval| interval.error).sum(),
+        evaluations: 15 * (2 * intervals.len() - 1),
+    })
+}
+
+/// The integral of f(x, y) over [x0, x1] x [y0, y1], with Gauss-Kronrod
+/// over y inside Gauss-Kronrod over x.
+pub fn double_integral(
+    f: impl Fn(f64, f64) -> f64,
+    (x0, x1): (f64, f64),
+    (y0, y1): (f64, f64),
+    options: &QuadratureOptions,
+) -> Result<Integral, QuadratureError> {
+    check_bounds(y0, y1)?;
+    // The inner integrals cannot return their errors through the outer
+    // function, so they are accumulated here
+    let inner_error = Cell::new(0.0f64);
+    let inner_evaluations = Cell::new(0);
+    let inner_failure = Cell::new(None);
+    let outer = gauss_kronrod(
+        |x| match gauss_kronrod(|y| f(x, y), y0, y1, options) {
+            Ok(inner) => {
+                inner_error.set(inner_error.get().max(inner.error));
+                inner_evaluations.set(inner_evaluations.get() + inner.evaluations);
+                inner.value
+            }
+            Err(error) => {
+                inner_failure.set(Some(error));
+                f64::NAN
+            }
+        },
+        x0,
+        x1,
+        options,
+    );
+    if let Some(error) = inner_failure.get() {
+        return Err(error);
+    }
+    let outer = outer?;
+    Ok(Integral {
+        value: outer.value,
+        error: outer.error + (x1 - x0).abs() * inner_error.get(),
+        evaluations: inner_evaluations.get(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::simpson_integration;
+
+    fn assert_integral(integral: Result<Integral, QuadratureError>, expected: f64, tolerance: f64) {
+        let integral = integral.unwrap();
+        assert!(
+            (integral.value - expected).abs() < tolerance,
+            "{integral:?} != {expected}"
+        );
+        // The estimate should bound the actual error, up to rounding
+        assert!((integral.value - expected).abs() <= integral.error.max(1e-13) * 10.0);
+    }
+
+    #[test]
+    fn adaptive_simpson_integrals() {
+        let options = QuadratureOptions::default();
+        assert_integral(adaptive_simpson(f64::sin, 0.0, PI, &options), 2.0, 1e-9);
+        assert_integral(
+            adaptive_simpson(f64::exp, 0.0, 1.0, &options),
+            1f64.exp() - 1.0,
+            1e-9,
+        );
+        // Reversed bounds give the opposite
+        assert_integral(adaptive_simpson(|x| x * x, 3.0, 0.0, &options), -9.0, 1e-9);
+        // A sharp peak, where the refinement concentrates
+        let peak = |x: f64| 1.0 / (1e-4 + x * x);
+        let expected = 2.0 * (1.0 / 1e-2) * (1.0f64 / 1e-2).atan();
+        assert_integral(adaptive_simpson(peak, -1.0, 1.0, &options), expected, 1e-6);
+    }
+
+    #[test]
+    fn adaptive_simpson_beats_fixed_steps() {
+        // sqrt has an infinite derivative at 0
+        let options = QuadratureOptions {
+            absolute_tolerance: 1e-8,
+            relative_tolerance: 0.0,
+            ..QuadratureOptions::default()
+        };
+        let adaptive = adaptive_simpson(f64::sqrt, 0.0, 1.0, &options).unwrap();
+        assert!((adaptive.value - 2.0 / 3.0).abs() < 1e-8);
+        let steps = adaptive.evaluations as u64 / 2 * 2;
+        let fixed = simpson_integration(0.0, 1.0, steps, f64::sqrt);
+        assert!((fixed - 2.0 / 3.0).abs() > (adaptive.value - 2.0 / 3.0).abs());
+    }
+
+    #[test]
+    fn gauss_legendre_rules() {
+        for n in 1..20 {
+            let rule = gauss_legendre_rule(n);
+            // The weights add up to the length of [-1, 1]
+            assert!((rule.iter().map(|r| r.1).sum::<f64>() - 2.0).abs() < 1e-12);
+            // Exact on x^(2n - 2), whose integral is 2 / (2n - 1)
+            let degree = 2 * n as i32 - 2;
+            let integral = gauss_legendre(|x| x.powi(degree), -1.0, 1.0, n).unwrap();
+            assert!((integral.value - 2.0 / (degree + 1) as f64).abs() < 1e-12);
+            // The rule with n + 1 points is exact as well
+            assert!(integral.error < 1e-12);
+            assert_eq!(integral.evaluations, 2 * n + 1);
+        }
+        let rule = gauss_legendre_rule(2);
+        assert!((rule[1].0 - 1.0 / 3f64.sqrt()).abs() < 1e-15);
+        let integral = gauss_legendre(f64::exp, 0.0, 2.0, 10).unwrap();
+        assert!((integral.value - (2f64.exp() - 1.0)).abs() < 1e-13);
+        // The error estimate is close to the actual error
+        for n in 1..6 {
+            let integral = gauss_legendre(f64::exp, 0.0, 2.0, n).unwrap();
+            let actual = (integral.value - (2f64.exp() - 1.0)).abs();
+            assert!(actual < 2.0 * integral.error && integral.error < 2.0 * actual);
+        }
+        // x^4 is beyond the 2-point rule, but the 3-point rule is exact
+        let integral = gauss_legendre(|x| x.powi(4), -1.0, 1.0, 2).unwrap();
+        assert!((integral.value - 2.0 / 9.0).abs() < 1e-14);
+        assert!((integral.error - (0.4 - 2.0 / 9.0)).abs() < 1e-14);
+    }
+
+    #[test]
+    fn gauss_kronrod_integrals() {
+        let options = QuadratureOptions::default();
+        let result = gauss_kronrod(f64::cos, 0.0, PI / 2.0, &options).unwrap();
+        assert!((result.value - 1.0).abs() < 1e-14);
+        assert_eq!(result.evaluations, 15);
+        assert_integral(
+            gauss_kronrod(f64::sqrt, 0.0, 1.0, &options),
+            2.0 / 3.0,
+            1e-10,
+        );
+        // An integrable singularity at 0
+        assert_integral(gauss_kronrod(|x| x.ln(), 0.0, 1.0, &options), -1.0, 1e-9);
+        // Oscillations
+        assert_integral(
+            gauss_kronrod(|x| (50.0 * x).sin() * x, 0.0, PI, &options),
+            -PI / 50.0,
+            1e-10,
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let options = QuadratureOptions::default();
+        assert_eq!(
+            gauss_kronrod(|x| 1.0 / x, -1.0, 1.0, &options).unwrap_err(),
+            QuadratureError::NotFinite
+        );
+        assert_eq!(
+            adaptive_simpson(f64::sin, 0.0, f64::INFINITY, &options).unwrap_err(),
+            QuadratureError::NotFinite
+        );
+        let few = QuadratureOptions {
+            max_subdivisions: 3,
+            ..QuadratureOptions::default()
+        };
+        assert_eq!(
+            gauss_kronrod(|x| (x.abs() + 1e-12).ln(), -1.0, 1.0, &few).unwrap_err(),
+            QuadratureError::MaxSubdivisions
+        );
+        assert_eq!(
+            adaptive_simpson(|x| (x.abs() + 1e-12).ln(), -1.0, 1.0, &few).unwrap_err(),
+            QuadratureError::MaxSubdivisions
+        );
+        // The logarithm of the negative nodes is NaN
+        assert_eq!(
+            gauss_legendre(f64::ln, -1.0, 1.0, 4).unwrap_err(),
+            QuadratureError::NotFinite
+        );
+        assert_eq!(
+            gauss_legendre(f64::sin, 0.0, f64::NAN, 4).unwrap_err(),
+            QuadratureError::NotFinite
+        );
+    }
+
+    #[test]
+    fn double_integrals() {
+        let options = QuadratureOptions::default();
+        // The integral of xy over [0, 1] x [0, 2] is 1/2 * 2
+        let result = double_integral(|x, y| x * y, (0.0, 1.0), (0.0, 2.0), &options).unwrap();
+        assert!((result.value - 1.0).abs() < 1e-13);
+        // e^-(x^2 + y^2) over [-5, 5]^2 is about pi
+        let result = double_integral(
+            |x, y| (-(x * x + y * y)).exp(),
+            (-5.0, 5.0),
+            (-5.0, 5.0),
+            &options,
+        )
+        .unwrap();
+        assert!((result.value - PI).abs() < 1e-9);
+        assert!(result.error < 1e-8);
+        assert_eq!(
+            double_integral(|x, y| 1.0 / (x + y), (-1.0, 1.0), (-1.0, 1.0), &options).unwrap_err(),
+            QuadratureError::NotFinite
+        );
+    }
+
+    #[test]
+    fn empty_and_reversed_intervals() {
+        let options = QuadratureOptions::default();
+        let simpson = adaptive_simpson(f64::exp, 1.0, 1.0, &options).unwrap();
+        assert_eq!((simpson.value, simpson.error), (0.0, 0.0));
+        let kronrod = gauss_kronrod(f64::exp, 1.0, 1.0, &options).unwrap();
+        assert_eq!((kronrod.value, kronrod.error), (0.0, 0.0));
+        assert_eq!(gauss_legendre(f64::exp, 1.0, 1.0, 3).unwrap().value, 0.0);
+        let result = double_integral(|x, y| x + y, (0.0, 1.0), (2.0, 2.0), &options).unwrap();
+        assert_eq!(result.value, 0.0);
+        // Swapping the bounds changes the sign
+        let result = gauss_kronrod(|x| x * x, 3.0, 0.0, &options).unwrap();
+        assert!((result.value + 9.0).abs() < 1e-12);
+        let result = adaptive_simpson(|x| x * x, 3.0, 0.0, &options).unwrap();
+        assert!((result.value + 9.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn smallest_gauss_legendre_rules() {
+        assert!(gauss_legendre_rule(0).is_empty());
+        assert_eq!(gauss_legendre(|x| x, 0.0, 1.0, 0).unwrap().value, 0.0);
+        // The midpoint rule
+        let rule = gauss_legendre_rule(1);
+        assert_eq!(rule.len(), 1);
+        assert!(rule[0].0.abs() < 1e-15 && (rule[0].1 - 2.0).abs() < 1e-15);
+        let integral = gauss_legendre(|x| 3.0 * x + 1.0, 0.0, 2.0, 1).unwrap();
+        assert!((integral.value - 8.0).abs() < 1e-13);
+        assert!(integral.error < 1e-13);
+    }
+}
@@ -0,0 +1,629 @@
+/*
+Root finding:
+Methods to find an x with f(x) = 0, for a continuous f given as a closure:
+- Bisection halves a bracket [a, b], where f(a) and f(b) have opposite
+  signs, keeping the half where the sign changes. It always converges, with
+  one bit of the root per iteration.
+  https://en.wikipedia.org/wiki/Bisection_method
+- Brent's method also keeps a bracket, but tries the inverse quadratic
+  interpolation of the last three points, or the secant through the last two,
+  and falls back to bisection when they do not shrink the bracket fast enough.
+  It converges superlinearly on smooth functions and never worse than
+  bisection.
+  https://en.wikipedia.org/wiki/Brent%27s_method
+- The secant method replaces the derivative of Newton's method with the slope
+  through the last two points. It needs no bracket, but may diverge.
+  https://en.wikipedia.org/wiki/Secant_method
+- Safeguarded Newton takes the Newton step x - f(x) / f'(x) when it stays in
+  the bracket and shrinks it fast enough, and a bisection step otherwise.
+  https://en.wikipedia.org/wiki/Newton%27s_method
+- Durand-Kerner finds all the complex roots of a polynomial at once, by
+  applying z_k -= p(z_k) / prod_{j != k} (z_k - z_j) to n approximations.
+  https://en.wikipedia.org/wiki/Durand%E2%80%93Kerner_method
+*/
+
+use super::Complex64;
+use std::f64::consts::PI;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RootOptions {
+    // The iterations stop when the step or the bracket is below this, plus a
+    // few units in the last place of x
+    pub x_tolerance: f64,
+    // The iterations stop when |f(x)| is below this
+    pub f_tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for RootOptions {
+    fn default() -> Self {
+        RootOptions {
+            x_tolerance: 1e-12,
+            f_tolerance: 0.0,
+            max_iterations: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootStopReason {
+    // |f(x)| is below f_tolerance, or f(x) is exactly zero
+    FunctionTolerance,
+    // The last step, or the bracket, is below x_tolerance
+    StepTolerance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Root {
+    pub root: f64,
+    pub iterations: usize,
+    pub stop_reason: RootStopReason,
+}
+
+/// All the roots of a polynomial, with their multiplicities.
+#[derive(Debug, Clone)]
+pub struct PolynomialRoots {
+    pub roots: Vec<Complex64>,
+    pub iterations: usize,
+    pub stop_reason: RootStopReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootFindingError {
+    // f has the same sign at both ends of the bracket
+    InvalidBracket,
+    // A bound, or a value of f or its derivative, is NaN or infinite
+    NotFinite,
+    // The secant or the derivative is flat, so the next step is undefined
+    ZeroDerivative,
+    MaxIterations,
+    // All the coefficients of the polynomial are zero
+    ZeroPolynomial,
+}
+
+impl fmt::Display for RootFindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            RootFindingError::InvalidBracket => "the function has the same sign at both bounds",
+            RootFindingError::NotFinite => "a value is NaN or infinite",
+            RootFindingError::ZeroDerivative => "the derivative is zero",
+            RootFindingError::MaxIterations => "the method did not converge",
+            RootFindingError::ZeroPolynomial => "the polynomial is zero",
+        };
+        write!(f, "{message}")
+    }
+}
+
+fn step_tolerance(options: &RootOptions, x: f64) -> f64 {
+    options.x_tolerance + 4.0 * f64::EPSILON * x.abs()
+}
+
+fn finite(x: f64) -> Result<f64, RootFindingError> {
+    if x.is_finite() {
+        Ok(x)
+    } else {
+        Err(RootFindingError::NotFinite)
+    }
+}
+
+fn found(
+    root: f64,
+    iterations: usize,
+    stop_reason: RootStopReason,
+) -> Result<Root, RootFindingError> {
+    Ok(Root {
+        root,
+        iterations,
+        stop_reason,
+    })
+}
+
+// f at both bounds, after checking that they bracket a root
+fn check_bracket(f: &impl Fn(f64) -> f64, a: f64, b: f64) -> Result<(f64, f64), RootFindingError> {
+    let (fa, fb) = (finite(f(finite(a)?))?, finite(f(finite(b)?))?);
+    if fa.signum() == fb.signum() && fa != 0.0 && fb != 0.0 {
+        Err(RootFindingError::InvalidBracket)
+    } else {
+        Ok((fa, fb))
+    }
+}
+
+/// Bisection on a bracket [a, b] where f changes sign.
+pub fn bisection(
+    f: impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    options: &RootOptions,
+) -> Result<Root, RootFindingError> {
+    let (fa, fb) = check_bracket(&f, a, b)?;
+    if fa == 0.0 {
+        return found(a, 0, RootStopReason::FunctionTolerance);
+    }
+    if fb == 0.0 {
+        return found(b, 0, RootStopReason::FunctionTolerance);
+    }
+    let (mut a, mut b, mut fa) = (a, b, fa);
+    for iteration in 1..=options.max_iterations {
+        let middle = a + (b - a) / 2.0;
+        let f_middle = finite(f(middle))?;
+        if f_middle.abs() <= options.f_tolerance || f_middle == 0.0 {
+            return found(middle, iteration, RootStopReason::FunctionTolerance);
+        }
+        if f_middle.signum() == fa.signum() {
+            (a, fa) = (middle, f_middle);
+        } else {
+            b = middle;
+        }
+        let middle = a + (b - a) / 2.0;
+        if (b - a).abs() <= 2.0 * step_tolerance(options, middle) {
+            return found(middle, iteration, RootStopReason::StepTolerance);
+        }
+    }
+    Err(RootFindingError::MaxIterations)
+}
+
+/// Brent's method on a bracket [a, b] where f changes sign.
+pub fn brent(
+    f: impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    options: &RootOptions,
+) -> Result<Root, RootFindingError> {
+    let (mut fa, mut fb) = check_bracket(&f, a, b)?;
+    let (mut a, mut b) = (a, b);
+    // b is the best estimate, and the root is between b and c. a is the
+    // previous value of b
+    let (mut c, mut fc) = (b, fb);
+    // The last step, and the one before
+    let (mut d, mut e) = (b - a, b - a);
+    for iteration in 0..=options.max_iterations {
+        if fb.signum() == fc.signum() && fb != 0.0 {
+            (c, fc) = (a, fa);
+            (d, e) = (b - a, b - a);
+        }
+        if fc.abs() < fb.abs() {
+            (a, fa) = (b, fb);
+            (b, fb) = (c, fc);
+            (c, fc) = (a, fa);
+        }
+        let tolerance = step_tolerance(options, b);
+        let half = (c - b) / 2.0;
+        if fb.abs() <= options.f_tolerance || fb == 0.0 {
+            return found(b, iteration, RootStopReason::FunctionTolerance);
+        }
+        if half.abs() <= tolerance {
+            return found(b, iteration, RootStopReason::StepTolerance);
+        }
+        if iteration == options.max_iterations {
+            break;
+        }
+        if e.abs() >= tolerance && fa.abs() > fb.abs() {
+            // Interpolation through a, b and c, as p / q
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                // Secant
+                (2.0 * half * s, 1.0 - s)
+            } else {
+                // Inverse quadratic
+                let (q, r) = (fa / fc, fb / fc);
+                (
+                    s * (2.0 * half * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+            // Accept the interpolation if it falls within the bracket, and
+            // shrinks faster than the step before the last one
+            if 2.0 * p < (3.0 * half * q - (tolerance * q).abs()).min((e * q).abs()) {
+                (e, d) = (d, p / q);
+            } else {
+                (d, e) = (half, half);
+            }
+        } else {
+            (d, e) = (half, half);
+        }
+        (a, fa) = (b, fb);
+        // Steps below the tolerance are wasted
+        b += if d.abs() > tolerance {
+            d
+        } else {
+            tolerance.copysign(half)
+        };
+        fb = finite(f(b))?;
+    }
+    Err(RootFindingError::MaxIterations)
+}
+
+/// The secant method from two initial points, which need not bracket a root.
+pub fn secant(
+    f: impl Fn(f64) -> f64,
+    x0: f64,
+    x1: f64,
+    options: &RootOptions,
+) -> Result<Root, RootFindingError> {
+    let (mut x0, mut f0) = (x0, finite(f(finite(x0)?))?);
+    let (mut x1, mut f1) = (x1, finite(f(finite(x1)?))?);
+    if f1.abs() <= options.f_tolerance || f1 == 0.0 {
+        return found(x1, 0, RootStopReason::FunctionTolerance);
+    }
+    for iteration in 1..=options.max_iterations {
+        if f1 == f0 {
+            return Err(RootFindingError::ZeroDerivative);
+        }
+        let x2 = finite(x1 - f1 * (x1 - x0) / (f1 - f0))?;
+        let f2 = finite(f(x2))?;
+        if f2.abs() <= options.f_tolerance || f2 == 0.0 {
+            return found(x2, iteration, RootStopReason::FunctionTolerance);
+        }
+        if (x2 - x1).abs() <= step_tolerance(options, x2) {
+            return found(x2, iteration, RootStopReason::StepTolerance);
+        }
+        (x0, f0) = (x1, f1);
+        (x1, f1) = (x2, f2);
+    }
+    Err(RootFindingError::MaxIterations)
+}
+
+/// Newton's method with the derivative df, kept in a bracket [a, b] where f
+/// changes sign by falling back to bisection.
+pub fn safeguarded_newton(
+    f: impl Fn(f64) -> f64,
+    df: impl Fn(f64) -> f64,
+    a: f64,
+    b: f64,
+    options: &RootOptions,
+) -> Result<Root, RootFindingError> {
+    let (fa, fb) = check_bracket(&f, a, b)?;
+    if fa == 0.0 {
+        return found(a, 0, RootStopReason::FunctionTolerance);
+    }
+    if fb == 0.0 {
+        return found(b, 0, RootStopReason::FunctionTolerance);
+    }
+    // f(low) < 0 < f(high)
+    let (mut low, mut high) = if fa < 0.0 { (a, b) } else { (b, a) };
+    let mut x = a + (b - a) / 2.0;
+    let (mut step, mut last_step) = ((b - a).abs(), (b - a).abs());
+    for iteration in 1..=options.max_iterations {
+        let (fx, dfx) = (finite(f(x))?, finite(df(x))?);
+        if fx.abs() <= options.f_tolerance || fx == 0.0 {
+            return found(x, iteration - 1, RootStopReason::FunctionTolerance);
+        }
+        if fx < 0.0 {
+            low = x;
+        } else {
+            high = x;
+        }
+        // Bisect if the Newton step leaves the bracket, or does not halve the
+        // step before the last one
+        let outside = ((x - high) * dfx - fx) * ((x - low) * dfx - fx) > 0.0;
+        last_step = std::mem::replace(&mut step, last_step);
+        if outside || (2.0 * fx).abs() > (last_step * dfx).abs() {
+            step = (high - low) / 2.0;
+            x = low + step;
+        } else {
+            step = fx / dfx;
+            x -= step;
+        }
+        if step.abs() <= step_tolerance(options, x) {
+            return found(x, iteration, RootStopReason::StepTolerance);
+        }
+    }
+    Err(RootFindingError::MaxIterations)
+}
+
+// p(z) by Horner's rule, with the coefficients by increasing degree
+fn evaluate(coefficients: &[f64], z: Complex64) -> Complex64 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Complex64::new(0.0, 0.0), |value, &c| {
+            value * z + Complex64::new(c, 0.0)
+        })
+}
+
+/// All the complex roots of the polynomial with the coefficients by
+/// increasing degree, with Durand-Kerner.
+pub fn durand_kerner(
+    coefficients: &[f64],
+    options: &RootOptions,
+) -> Result<PolynomialRoots, RootFindingError> {
+    if coefficients.iter().any(|c| !c.is_finite()) {
+        return Err(RootFindingError::NotFinite);
+    }
+    let degree = coefficients
+        .iter()
+        .rposition(|&c| c != 0.0)
+        .ok_or(RootFindingError::ZeroPolynomial)?;
+    let leading = coefficients[degree];
+    // The monic polynomial
+    let monic: Vec<f64> = coefficients[..=degree]
+        .iter()
+        .map(|c| c / leading)
+        .collect();
+    // All the roots are in the disc of radius 1 + max |a_i| (Cauchy), and the
+    // starting points are spread on its boundary, off the real axis
+    let radius = 1.0 + monic[..degree].iter().fold(0.0f64, |m, c| m.max(c.abs()));
+    let mut roots: Vec<Complex64> = (0..degree)
+        .map(|k| {
+            let angle = 2.0 * PI * k as f64 / degree as f64 + 0.4;
+            Complex64::new(radius * angle.cos(), radius * angle.sin())
+        })
+        .collect();
+    if degree == 0 {
+        return Ok(PolynomialRoots {
+            roots,
+            iterations: 0,
+            stop_reason: RootStopReason::StepTolerance,
+        });
+    }
+    for iteration in 1..=options.max_iterations {
+        let mut largest_step = 0.0f64;
+        let mut largest_value = 0.0f64;
+        for k in 0..degree {
+            let z = roots[k];
+            let value = evaluate(&monic, z);
+            let denominator = roots
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != k)
+                .fold(Complex64::new(1.0, 0.0), |product, (_, &w)| {
+                    product * (z - w)
+                });
+            let step = value * denominator.inverse();
+            if !step.re.is_finite() || !step.im.is_finite() {
+                return Err(RootFindingError::NotFinite);
+            }
+            roots[k] = z - step;
+            largest_step = largest_step.max(step.norm() / step_tolerance(options, z.norm()));
+            largest_value = largest_value.max(value.norm());
+        }
+        if largest_value <= options.f_tolerance {
+            return Ok(PolynomialRoots {
+                roots,
+                iterations: iteration,
+                stop_reason: RootStopReason::FunctionTolerance,
+            });
+        }
+        if largest_step <= 1.0 {
+            return Ok(PolynomialRoots {
+                roots,
+                iterations: iteration,
+                stop_reason: RootStopReason::StepTolerance,
+            });
+        }
+    }
+    Err(RootFindingError::MaxIterations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-10;
+
+    fn cubic(x: f64) -> f64 {
+        x.cos() - x * x * x
+    }
+
+    // The root of cos(x) = x^3
+    const CUBIC_ROOT: f64 = 0.865_474_033_101_614_4;
+
+    #[test]
+    fn bracketing_methods() {
+        let options = RootOptions::default();
+        type Method = fn(fn(f64) -> f64, f64, f64, &RootOptions) -> Result<Root, RootFindingError>;
+        let methods: [Method; 2] = [bisection, brent];
+        for method in methods {
+            let root = method(cubic, 0.0, 2.0, &options).unwrap();
+            assert!((root.root - CUBIC_ROOT).abs() < EPSILON, "{root:?}");
+            // The bracket may be given in any order
+            let root = method(cubic, 2.0, 0.0, &options).unwrap();
+            assert!((root.root - CUBIC_ROOT).abs() < EPSILON, "{root:?}");
+            assert_eq!(
+                method(cubic, 1.0, 2.0, &options),
+                Err(RootFindingError::InvalidBracket)
+            );
+            assert_eq!(
+                method(cubic, 0.0, f64::NAN, &options),
+                Err(RootFindingError::NotFinite)
+            );
+            // A root on a bound
+            let root = method(|x| x - 1.0, 1.0, 3.0, &options).unwrap();
+            assert_eq!(root.root, 1.0);
+            assert_eq!(root.iterations, 0);
+        }
+    }
+
+    #[test]
+    fn brent_beats_bisection() {
+        let options = RootOptions::default();
+        let bisection = bisection(cubic, 0.0, 2.0, &options).unwrap();
+        let brent = brent(cubic, 0.0, 2.0, &options).unwrap();
+        assert!(bisection.iterations >= 40);
+        assert!(brent.iterations <= 12, "{brent:?}");
+
+        // Closures can capture their environment
+        let target = 2.0f64;
+        let root = super::brent(|x| x * x - target, 0.0, 2.0, &options).unwrap();
+        assert!((root.root - target.sqrt()).abs() < EPSILON);
+
+        // A discontinuous function still gets its sign change
+        let root = super::brent(|x| if x < 0.3 { -1.0 } else { 1.0 }, 0.0, 1.0, &options).unwrap();
+        assert!((root.root - 0.3).abs() < 1e-9);
+        assert_eq!(root.stop_reason, RootStopReason::StepTolerance);
+    }
+
+    #[test]
+    fn tolerances_and_iterations() {
+        let loose = RootOptions {
+            x_tolerance: 1e-3,
+            ..RootOptions::default()
+        };
+        let root = bisection(cubic, 0.0, 2.0, &loose).unwrap();
+        assert!((root.root - CUBIC_ROOT).abs() < 1e-3);
+        assert_eq!(root.stop_reason, RootStopReason::StepTolerance);
+        assert!(root.iterations <= 11);
+
+        let residual = RootOptions {
+            f_tolerance: 1e-3,
+            ..RootOptions::default()
+        };
+        let root = secant(cubic, 0.0, 1.0, &residual).unwrap();
+        assert!(cubic(root.root).abs() <= 1e-3);
+        assert_eq!(root.stop_reason, RootStopReason::FunctionTolerance);
+
+        let few = RootOptions {
+            max_iterations: 3,
+            ..RootOptions::default()
+        };
+        assert_eq!(
+            bisection(cubic, 0.0, 2.0, &few),
+            Err(RootFindingError::MaxIterations)
+        );
+    }
+
+    #[test]
+    fn secant_method() {
+        let options = RootOptions::default();
+        let root = secant(cubic, 0.0, 1.0, &options).unwrap();
+        assert!((root.root - CUBIC_ROOT).abs() < EPSILON);
+        assert!(root.iterations < 15);
+        assert_eq!(
+            secant(|_| 1.0, 0.0, 1.0, &options),
+            Err(RootFindingError::ZeroDerivative)
+        );
+    }
+
+    #[test]
+    fn newton() {
+        let options = RootOptions::default();
+        let derivative = |x: f64| -x.sin() - 3.0 * x * x;
+        let root = safeguarded_newton(cubic, derivative, 0.0, 2.0, &options).unwrap();
+        assert!((root.root - CUBIC_ROOT).abs() < EPSILON);
+        assert!(root.iterations < 15);
+
+        // Plain Newton from x = 0 cycles between -1 and 1 on x^3 - 2x + 2,
+        // the bracket makes it converge to the root near -1.77
+        let f = |x: f64| x * x * x - 2.0 * x + 2.0;
+        let df = |x: f64| 3.0 * x * x - 2.0;
+        let root = safeguarded_newton(f, df, -3.0, 3.0, &options).unwrap();
+        assert!(f(root.root).abs() < 1e-9);
+
+        // A flat derivative at the middle falls back to bisection
+        let root = safeguarded_newton(|x| x * x * x, |x| 3.0 * x * x, -1.0, 1.0, &options).unwrap();
+        assert!(root.root.abs() < 1e-9);
+
+        assert_eq!(
+            safeguarded_newton(cubic, derivative, 1.0, 2.0, &options),
+            Err(RootFindingError::InvalidBracket)
+        );
+    }
+
+    #[test]
+    fn polynomial_roots() {
+        let options = RootOptions {
+            max_iterations: 500,
+            ..RootOptions::default()
+        };
+        // (x - 1)(x - 2)(x - 3) = x^3 - 6x^2 + 11x - 6
+        let result = durand_kerner(&[-6.0, 11.0, -6.0, 1.0], &options).unwrap();
+        let mut real: Vec<f64> = result.roots.iter().map(|z| z.re).collect();
+        real.sort_by(f64::total_cmp);
+        for (x, expected) in real.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((x - expected).abs() < 1e-9);
+        }
+        assert!(result.roots.iter().all(|z| z.im.abs() < 1e-9));
+
+        // 2x^2 + 2 has roots i and -i, and trailing zeros are ignored
+        let result = durand_kerner(&[2.0, 0.0, 2.0, 0.0], &options).unwrap();
+        assert_eq!(result.roots.len(), 2);
+        let mut imaginary: Vec<f64> = result.roots.iter().map(|z| z.im).collect();
+        imaginary.sort_by(f64::total_cmp);
+        assert!((imaginary[0] + 1.0).abs() < 1e-9 && (imaginary[1] - 1.0).abs() < 1e-9);
+
+        // x^5 - 1: the fifth roots of unity
+        let result = durand_kerner(&[-1.0, 0.0, 0.0, 0.0, 0.0, 1.0], &options).unwrap();
+        for z in &result.roots {
+            assert!((z.norm() - 1.0).abs() < 1e-9);
+            let z5 = (0..4).fold(*z, |w, _| w * *z);
+            assert!((z5.re - 1.0).abs() < 1e-9 && z5.im.abs() < 1e-9);
+        }
+
+        assert!(durand_kerner(&[5.0], &options).unwrap().roots.is_empty());
+        assert_eq!(
+            durand_kerner(&[0.0, 0.0], &options).unwrap_err(),
+            RootFindingError::ZeroPolynomial
+        );
+    }
+
+    #[test]
+    fn degenerate_inputs() {
+        let options = RootOptions::default();
+        let line = |x: f64| 2.0 * x - 4.0;
+        // An empty bracket only holds a root if it is the root
+        assert_eq!(bisection(line, 2.0, 2.0, &options).unwrap().root, 2.0);
+        assert_eq!(brent(line, 2.0, 2.0, &options).unwrap().root, 2.0);
+        assert_eq!(
+            bisection(line, 1.0, 1.0, &options),
+            Err(RootFindingError::InvalidBracket)
+        );
+        assert_eq!(
+            brent(line, 1.0, 1.0, &options),
+            Err(RootFindingError::InvalidBracket)
+        );
+        assert_eq!(
+            safeguarded_newton(line, |_| 2.0, 3.0, 3.0, &options),
+            Err(RootFindingError::InvalidBracket)
+        );
+        // A root on the upper bound, and a constant function without roots
+        let root = safeguarded_newton(line, |_| 2.0, 0.0, 2.0, &options).unwrap();
+        assert_eq!((root.root, root.iterations), (2.0, 0));
+        assert_eq!(
+            brent(|_| 1.0, 0.0, 1.0, &options),
+            Err(RootFindingError::InvalidBracket)
+        );
+        // Two equal starting points have no secant
+        assert_eq!(
+            secant(line, 1.0, 1.0, &options),
+            Err(RootFindingError::ZeroDerivative)
+        );
+
+        let none = RootOptions {
+            max_iterations: 0,
+            ..RootOptions::default()
+        };
+        assert_eq!(
+            bisection(line, 0.0, 3.0, &none),
+            Err(RootFindingError::MaxIterations)
+        );
+        assert_eq!(
+            brent(line, 0.0, 3.0, &none),
+            Err(RootFindingError::MaxIterations)
+        );
+    }
+
+    #[test]
+    fn low_degree_polynomials() {
+        let options = RootOptions::default();
+        // 2x - 4
+        let result = durand_kerner(&[-4.0, 2.0], &options).unwrap();
+        assert_eq!(result.roots.len(), 1);
+        assert!((result.roots[0].re - 2.0).abs() < EPSILON && result.roots[0].im.abs() < EPSILON);
+        // x^2: a double root at 0
+        let result = durand_kerner(&[0.0, 0.0, 1.0], &options).unwrap();
+        assert!(result.roots.iter().all(|z| z.norm() < 1e-6));
+        assert_eq!(
+            durand_kerner(&[], &options).unwrap_err(),
+            RootFindingError::ZeroPolynomial
+        );
+        assert_eq!(
+            durand_kerner(&[1.0, f64::INFINITY], &options).unwrap_err(),
+            RootFindingError::NotFinite
+        );
+    }
+}
@@ -0,0 +1,534 @@
+/*
+Sparse matrices:
+Matrices where most values are zero, like the Laplacian of a graph or the
+discretisation of a PDE, only store their nonzero values:
+- COO (coordinate) is a list of (row, column, value) triplets, in any order,
+  where duplicates add up. It is the easiest to build.
+- CSR (compressed sparse row) stores the values row after row, with their
+  columns, and the offset where each row starts. Row i is the range
+  row_offsets[i]..row_offsets[i + 1], so products with vectors and row
+  iterations are fast.
+- CSC (compressed sparse column) is the same, column after column. The CSC
+  arrays of a matrix are the CSR arrays of its transpose.
+The conversions sort the triplets, in O(nnz log(nnz)).
+https://en.wikipedia.org/wiki/Sparse_matrix
+*/
+
+use super::matrix_ops::MatrixElement;
+use super::Matrix;
+use std::ops::Mul;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CooMatrix<T: MatrixElement> {
+    rows: usize,
+    cols: usize,
+    entries: Vec<(usize, usize, T)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsrMatrix<T: MatrixElement> {
+    rows: usize,
+    cols: usize,
+    // Row i is at row_offsets[i]..row_offsets[i + 1] in the other arrays
+    row_offsets: Vec<usize>,
+    column_indices: Vec<usize>,
+    values: Vec<T>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CscMatrix<T: MatrixElement> {
+    rows: usize,
+    cols: usize,
+    // Column j is at column_offsets[j]..column_offsets[j + 1] in the other
+    // arrays
+    column_offsets: Vec<usize>,
+    row_indices: Vec<usize>,
+    values: Vec<T>,
+}
+
+// The offsets, minor indices and values of the (major, minor, value)
+// triplets, sorted by major then minor index, with the duplicates added up
+fn compress<T: MatrixElement>(
+    major_len: usize,
+    mut entries: Vec<(usize, usize, T)>,
+) -> (Vec<usize>, Vec<usize>, Vec<T>) {
+    entries.sort_by_key(|&(major, minor, _)| (major, minor));
+    let mut offsets = vec![0; major_len + 1];
+    let mut indices = Vec::with_capacity(entries.len());
+    let mut values: Vec<T> = Vec::with_capacity(entries.len());
+    let mut last = None;
+    for (major, minor, value) in entries {
+        if last == Some((major, minor)) {
+            *values.last_mut().unwrap() += value;
+        } else {
+            offsets[major + 1] += 1;
+            indices.push(minor);
+            values.push(value);
+            last = Some((major, minor));
+        }
+    }
+    for i in 0..major_len {
+        offsets[i + 1] += offsets[i];
+    }
+    (offsets, indices, values)
+}
+
+// The (major, minor, value) triplets of compressed arrays
+fn expand<T: MatrixElement>(
+    offsets: &[usize],
+    indices: &[usize],
+    values: &[T],
+) -> Vec<(usize, usize, T)> {
+    offsets
+        .windows(2)
+        .enumerate()
+        .flat_map(|(major, range)| {
+            (range[0]..range[1]).map(move |k| (major, indices[k], values[k]))
+        })
+        .collect()
+}
+
+impl<T: MatrixElement> CooMatrix<T> {
+    /// An empty rows x cols matrix, where all values are zero.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        CooMatrix {
+            rows,
+            cols,
+            entries: vec![],
+        }
+    }
+
+    /// Adds value at (row, col), to the value already there if any.
+    pub fn push(&mut self, row: usize, col: usize, value: T) {
+        if row >= self.rows || col >= self.cols {
+            panic!("Matrix index out of bounds");
+        }
+        self.entries.push((row, col, value));
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The number of stored triplets, duplicates included.
+    pub fn nnz(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn entries(&self) -> &[(usize, usize, T)] {
+        &self.entries
+    }
+
+    pub fn transpose(&self) -> Self {
+        CooMatrix {
+            rows: self.cols,
+            cols: self.rows,
+            entries: self.entries.iter().map(|&(i, j, x)| (j, i, x)).collect(),
+        }
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix<T> {
+        let (row_offsets, column_indices, values) = compress(self.rows, self.entries.clone());
+        CsrMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            row_offsets,
+            column_indices,
+            values,
+        }
+    }
+
+    pub fn to_csc(&self) -> CscMatrix<T> {
+        let (column_offsets, row_indices, values) = compress(self.cols, self.transpose().entries);
+        CscMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            column_offsets,
+            row_indices,
+            values,
+        }
+    }
+
+    pub fn to_dense(&self) -> Matrix<T> {
+        let mut dense = Matrix::zero(self.rows, self.cols);
+        for &(i, j, x) in &self.entries {
+            dense[[i, j]] += x;
+        }
+        dense
+    }
+}
+
+impl<T: MatrixElement + PartialEq> CooMatrix<T> {
+    /// The nonzero values of a dense matrix.
+    pub fn from_dense(dense: &Matrix<T>) -> Self {
+        let mut coo = CooMatrix::new(dense.rows(), dense.cols());
+        for i in 0..dense.rows() {
+            for j in 0..dense.cols() {
+                if dense[[i, j]] != 0.into() {
+                    coo.push(i, j, dense[[i, j]]);
+                }
+            }
+        }
+        coo
+    }
+}
+
+impl<T: MatrixElement> CsrMatrix<T> {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The number of stored values.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The (column, value) pairs of a row, by increasing column.
+    pub fn row(&self, i: usize) -> impl Iterator<Item = (usize, T)> + '_ {
+        let range = self.row_offsets[i]..self.row_offsets[i + 1];
+        self.column_indices[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.values[range].iter().copied())
+    }
+
+    /// The value at (i, j), with a binary search in the row.
+    pub fn get(&self, i: usize, j: usize) -> T {
+        if i >= self.rows || j >= self.cols {
+            panic!("Matrix index out of bounds");
+        }
+        let range = self.row_offsets[i]..self.row_offsets[i + 1];
+        match self.column_indices[range.clone()].binary_search(&j) {
+            Ok(k) => self.values[range.start + k],
+            Err(_) => 0.into(),
+        }
+    }
+
+    pub fn to_coo(&self) -> CooMatrix<T> {
+        CooMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            entries: expand(&self.row_offsets, &self.column_indices, &self.values),
+        }
+    }
+
+    pub fn to_csc(&self) -> CscMatrix<T> {
+        self.to_coo().to_csc()
+    }
+
+    pub fn to_dense(&self) -> Matrix<T> {
+        self.to_coo().to_dense()
+    }
+
+    pub fn transpose(&self) -> Self {
+        let csc = self.to_csc();
+        CsrMatrix {
+            rows: self.cols,
+            cols: self.rows,
+            row_offsets: csc.column_offsets,
+            column_indices: csc.row_indices,
+            values: csc.values,
+        }
+    }
+
+    /// The product with a column vector.
+    pub fn mul_vector(&self, x: &[T]) -> Vec<T> {
+        if x.len() != self.cols {
+            panic!("Matrix dimensions do not match");
+        }
+        (0..self.rows)
+            .map(|i| {
+                let mut sum = 0.into();
+                for (j, value) in self.row(i) {
+                    sum += value * x[j];
+                }
+                sum
+            })
+            .collect()
+    }
+}
+
+impl<T: MatrixElement + PartialEq> CsrMatrix<T> {
+    pub fn from_dense(dense: &Matrix<T>) -> Self {
+        CooMatrix::from_dense(dense).to_csr()
+    }
+}
+
+impl<T: MatrixElement> CscMatrix<T> {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The number of stored values.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The (row, value) pairs of a column, by increasing row.
+    pub fn column(&self, j: usize) -> impl Iterator<Item = (usize, T)> + '_ {
+        let range = self.column_offsets[j]..self.column_offsets[j + 1];
+        self.row_indices[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.values[range].iter().copied())
+    }
+
+    /// The value at (i, j), with a binary search in the column.
+    pub fn get(&self, i: usize, j: usize) -> T {
+        if i >= self.rows || j >= self.cols {
+            panic!("Matrix index out of bounds");
+        }
+        let range = self.column_offsets[j]..self.column_offsets[j + 1];
+        match self.row_indices[range.clone()].binary_search(&i) {
+            Ok(k) => self.values[range.start + k],
+            Err(_) => 0.into(),
+        }
+    }
+
+    pub fn to_coo(&self) -> CooMatrix<T> {
+        CooMatrix {
+            rows: self.cols,
+            cols: self.rows,
+            entries: expand(&self.column_offsets, &self.row_indices, &self.values),
+        }
+        .transpose()
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix<T> {
+        self.to_coo().to_csr()
+    }
+
+    pub fn to_dense(&self) -> Matrix<T> {
+        self.to_coo().to_dense()
+    }
+
+    pub fn transpose(&self) -> Self {
+        let csr = self.to_csr();
+        CscMatrix {
+            rows: self.cols,
+            cols: self.rows,
+            column_offsets: csr.row_offsets,
+            row_indices: csr.column_indices,
+            values: csr.values,
+        }
+    }
+
+    /// The product with a column vector.
+    pub fn mul_vector(&self, x: &[T]) -> Vec<T> {
+        if x.len() != self.cols {
+            panic!("Matrix dimensions do not match");
+        }
+        let mut result = vec![0.into(); self.rows];
+        for (j, &x) in x.iter().enumerate() {
+            for (i, value) in self.column(j) {
+                result[i] += value * x;
+            }
+        }
+        result
+    }
+}
+
+impl<T: MatrixElement + PartialEq> CscMatrix<T> {
+    pub fn from_dense(dense: &Matrix<T>) -> Self {
+        CooMatrix::from_dense(dense).to_csc()
+    }
+}
+
+impl<T: MatrixElement> Mul<&Matrix<T>> for &CsrMatrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, rhs: &Matrix<T>) -> Self::Output {
+        // Each value a_ik of row i adds a_ik times row k of rhs to row i
+        if self.cols != rhs.rows() {
+            panic!("Matrix dimensions do not match");
+        }
+        let mut result = Matrix::zero(self.rows, rhs.cols());
+        for i in 0..self.rows {
+            for (k, value) in self.row(i) {
+                for j in 0..rhs.cols() {
+                    result[[i, j]] += value * rhs[[k, j]];
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<T: MatrixElement> Mul<&Matrix<T>> for &CscMatrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, rhs: &Matrix<T>) -> Self::Output {
+        // Each value a_ik of column k adds a_ik times row k of rhs to row i
+        if self.cols != rhs.rows() {
+            panic!("Matrix dimensions do not match");
+        }
+        let mut result = Matrix::zero(self.rows, rhs.cols());
+        for k in 0..self.cols {
+            for (i, value) in self.column(k) {
+                for j in 0..rhs.cols() {
+                    result[[i, j]] += value * rhs[[k, j]];
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    fn random_sparse(rng: &mut PCG32, rows: usize, cols: usize) -> Matrix<i64> {
+        let data = (0..rows * cols)
+            .map(|_| match rng.get_u32() % 4 {
+                0 => rng.get_u32() as i64 % 19 - 9,
+                _ => 0,
+            })
+            .collect();
+        Matrix::new(data, rows, cols)
+    }
+
+    #[test]
+    fn coo_duplicates_add_up() {
+        let mut coo = CooMatrix::new(2, 3);
+        coo.push(1, 2, 5);
+        coo.push(0, 0, 1);
+        coo.push(1, 2, -2);
+        coo.push(0, 1, 4);
+        assert_eq!(coo.nnz(), 4);
+        let dense = Matrix::from(vec![vec![1, 4, 0], vec![0, 0, 3]]);
+        assert_eq!(coo.to_dense(), dense);
+        let csr = coo.to_csr();
+        assert_eq!(csr.nnz(), 3);
+        assert_eq!(csr.row(0).collect::<Vec<_>>(), vec![(0, 1), (1, 4)]);
+        assert_eq!(csr.row(1).collect::<Vec<_>>(), vec![(2, 3)]);
+        assert_eq!(csr.to_dense(), dense);
+        let csc = coo.to_csc();
+        assert_eq!(csc.nnz(), 3);
+        assert_eq!(csc.column(1).collect::<Vec<_>>(), vec![(0, 4)]);
+        assert_eq!(csc.column(2).collect::<Vec<_>>(), vec![(1, 3)]);
+        assert_eq!(csc.to_dense(), dense);
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrix index out of bounds")]
+    fn coo_out_of_bounds() {
+        CooMatrix::new(2, 2).push(2, 0, 1.0);
+    }
+
+    #[test]
+    fn conversions() {
+        let mut rng = PCG32::new_default(47);
+        for (rows, cols) in [(1, 1), (4, 4), (5, 8), (9, 3), (0, 0)] {
+            let dense = random_sparse(&mut rng, rows, cols);
+            let csr = CsrMatrix::from_dense(&dense);
+            let csc = CscMatrix::from_dense(&dense);
+            assert_eq!(csr.to_dense(), dense);
+            assert_eq!(csc.to_dense(), dense);
+            assert_eq!(csr.to_csc(), csc);
+            assert_eq!(csc.to_csr(), csr);
+            assert_eq!(csr.to_coo().to_csr(), csr);
+            assert_eq!(csc.to_coo().to_csc(), csc);
+            for i in 0..rows {
+                for j in 0..cols {
+                    assert_eq!(csr.get(i, j), dense[[i, j]]);
+                    assert_eq!(csc.get(i, j), dense[[i, j]]);
+                }
+            }
+            let nonzero = (0..rows)
+                .flat_map(|i| (0..cols).map(move |j| (i, j)))
+                .filter(|&(i, j)| dense[[i, j]] != 0)
+                .count();
+            assert_eq!(csr.nnz(), nonzero);
+        }
+    }
+
+    #[test]
+    fn transpose() {
+        let mut rng = PCG32::new_default(3);
+        let dense = random_sparse(&mut rng, 6, 4);
+        let transposed = dense.transpose();
+        assert_eq!(
+            CsrMatrix::from_dense(&dense).transpose().to_dense(),
+            transposed
+        );
+        assert_eq!(
+            CscMatrix::from_dense(&dense).transpose().to_dense(),
+            transposed
+        );
+        assert_eq!(
+            CooMatrix::from_dense(&dense).transpose().to_dense(),
+            transposed
+        );
+    }
+
+    #[test]
+    fn products() {
+        let mut rng = PCG32::new_default(11);
+        for (m, k, n) in [(3, 3, 3), (5, 7, 2), (1, 6, 4), (6, 1, 1)] {
+            let a = random_sparse(&mut rng, m, k);
+            let b = Matrix::new(
+                (0..k * n).map(|_| rng.get_u32() as i64 % 10).collect(),
+                k,
+                n,
+            );
+            let expected = &a * &b;
+            assert_eq!(&CsrMatrix::from_dense(&a) * &b, expected);
+            assert_eq!(&CscMatrix::from_dense(&a) * &b, expected);
+
+            let x: Vec<i64> = (0..k).map(|_| rng.get_u32() as i64 % 10).collect();
+            let column = Matrix::new(x.clone(), k, 1);
+            let expected: Vec<i64> = (0..m).map(|i| (&a * &column)[[i, 0]]).collect();
+            assert_eq!(CsrMatrix::from_dense(&a).mul_vector(&x), expected);
+            assert_eq!(CscMatrix::from_dense(&a).mul_vector(&x), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrix dimensions do not match")]
+    fn invalid_product() {
+        let a = CsrMatrix::from_dense(&Matrix::from(vec![vec![1.0, 2.0]]));
+        a.mul_vector(&[1.0]);
+    }
+
+    #[test]
+    fn empty_and_single_entry() {
+        // No rows, or no columns
+        let coo = CooMatrix::<i64>::new(0, 3);
+        assert_eq!(coo.to_csr().mul_vector(&[1, 2, 3]), vec![]);
+        assert_eq!(coo.to_csc().mul_vector(&[1, 2, 3]), vec![]);
+        let coo = CooMatrix::<i64>::new(3, 0);
+        assert_eq!(coo.to_csr().nnz(), 0);
+        assert_eq!(coo.to_csr().mul_vector(&[]), vec![0, 0, 0]);
+        assert_eq!(coo.to_csc().mul_vector(&[]), vec![0, 0, 0]);
+        assert_eq!(coo.to_csr().row(2).count(), 0);
+
+        let mut coo = CooMatrix::new(1, 1);
+        coo.push(0, 0, 7);
+        // Duplicates which cancel out are kept as a stored zero
+        coo.push(0, 0, -7);
+        let csr = coo.to_csr();
+        assert_eq!(csr.nnz(), 1);
+        assert_eq!(csr.get(0, 0), 0);
+        assert_eq!(csr.to_dense(), Matrix::zero(1, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrix index out of bounds")]
+    fn csc_out_of_bounds() {
+        CooMatrix::<f64>::new(1, 1).to_csc().get(0, 1);
+    }
+}
@@ -0,0 +1,449 @@
+/*
+Spectral decompositions and matrix functions:
+- The eigen-decomposition A = V diag(w) V^T of a symmetric matrix, with the
+  cyclic Jacobi method: each rotation in a plane (p, q) zeroes the value at
+  (p, q), and the sum of the squares off the diagonal decreases until it is a
+  rounding error. The product of the rotations is V, whose columns are
+  orthonormal eigenvectors.
+  https://en.wikipedia.org/wiki/Jacobi_eigenvalue_algorithm
+- The singular value decomposition A = U diag(s) V^T of any matrix, with the
+  one-sided Jacobi method: rotations of pairs of columns make all the columns
+  orthogonal, then their norms are the singular values, and the rotations
+  give V.
+  https://en.wikipedia.org/wiki/Singular_value_decomposition
+- The exponential e^A = I + A + A^2/2! + ..., by scaling and squaring: the
+  Taylor series converges fast for A / 2^s with a small norm, and
+  e^A = (e^(A / 2^s))^(2^s).
+  https://en.wikipedia.org/wiki/Matrix_exponential
+- The integer powers, where the negative ones are the powers of the inverse.
+*/
+
+use super::matrix_decomposition::{
+    check_finite, check_square, check_symmetric, to_rows, tolerance,
+};
+use super::{LinearAlgebraError, Matrix};
+
+// Bound on the number of sweeps of the Jacobi methods, which converge
+// quadratically and need less than 10 of them in practice
+const MAX_SWEEPS: usize = 100;
+
+// The Taylor series is summed for a norm below this
+const EXPONENTIAL_NORM: f64 = 0.5;
+// With the norm below 1/2, the next term is below 0.5^19 / 19! < 1e-22
+const EXPONENTIAL_TERMS: usize = 18;
+
+/// The eigenvalues of a symmetric matrix in decreasing order, and the
+/// eigenvectors as the columns of an orthogonal matrix, in the same order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymmetricEigen {
+    pub eigenvalues: Vec<f64>,
+    pub eigenvectors: Matrix<f64>,
+}
+
+/// The thin singular value decomposition A = U diag(s) V^T of a m x n
+/// matrix, where with k = min(m, n), U is m x k and V is n x k with
+/// orthonormal columns, and the singular values are in decreasing order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Svd {
+    pub u: Matrix<f64>,
+    pub singular_values: Vec<f64>,
+    pub v: Matrix<f64>,
+}
+
+// vectors[p], vectors[q] = c vectors[p] - s vectors[q], s vectors[p] + c vectors[q],
+// with p < q
+fn rotate(vectors: &mut [Vec<f64>], p: usize, q: usize, c: f64, s: f64) {
+    let (top, bottom) = vectors.split_at_mut(q);
+    for (x, y) in top[p].iter_mut().zip(bottom[0].iter_mut()) {
+        (*x, *y) = (c * *x - s * *y, s * *x + c * *y);
+    }
+}
+
+// The tangent t of the rotation with (1 - t^2) / 2t = theta, the smallest
+// of the two solutions
+fn rotation_tangent(theta: f64) -> f64 {
+    if theta.abs() > 1e150 {
+        // theta^2 would overflow
+        0.5 / theta
+    } else {
+        theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt())
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+// The matrix whose columns are the vectors, in the given order
+fn from_columns(columns: &[Vec<f64>], order: &[usize], rows: usize) -> Matrix<f64> {
+    let mut matrix = Matrix::zero(rows, order.len());
+    for (j, &k) in order.iter().enumerate() {
+        for (i, &x) in columns[k].iter().enumerate() {
+            matrix[[i, j]] = x;
+        }
+    }
+    matrix
+}
+
+// The indices of the values in decreasing order
+fn decreasing_order(values: &[f64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&i, &j| values[j].total_cmp(&values[i]));
+    order
+}
+
+// A unit vector orthogonal to the orthonormal `basis`: the residual of the
+// coordinate vector furthest from its span, orthogonalised twice for accuracy
+fn orthogonal_complement(basis: &[Vec<f64>], len: usize) -> Vec<f64> {
+    let residual = |i: usize| {
+        let mut e = vec![0.0; len];
+        e[i] = 1.0;
+        for _ in 0..2 {
+            for b in basis {
+                let projection = dot(&e, b);
+                for (x, y) in e.iter_mut().zip(b) {
+                    *x -= projection * y;
+                }
+            }
+        }
+        e
+    };
+    let best = (0..len)
+        .map(residual)
+        .max_by(|a, b| dot(a, a).total_cmp(&dot(b, b)))
+        .expect("the basis does not span the space");
+    let norm = dot(&best, &best).sqrt();
+    best.into_iter().map(|x| x / norm).collect()
+}
+
+impl Matrix<f64> {
+    /// The eigenvalues and eigenvectors of a symmetric matrix.
+    pub fn symmetric_eigen(&self) -> Result<SymmetricEigen, LinearAlgebraError> {
+        check_symmetric(self)?;
+        let n = self.rows();
+        let mut a = to_rows(self);
+        // The columns of V, stored as rows
+        let mut v = to_rows(&Matrix::identity(n));
+        let norm = a.iter().flatten().map(|x| x * x).sum::<f64>().sqrt();
+        for _ in 0..MAX_SWEEPS {
+            let off_diagonal: f64 = (0..n)
+                .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+                .map(|(p, q)| a[p][q] * a[p][q])
+                .sum();
+            if off_diagonal.sqrt() <= f64::EPSILON * norm {
+                break;
+            }
+            for p in 0..n {
+                for q in p + 1..n {
+                    if a[p][q] == 0.0 {
+                        continue;
+                    }
+                    let t = rotation_tangent((a[q][q] - a[p][p]) / (2.0 * a[p][q]));
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    // A = J^T A J, where J rotates the columns p and q
+                    for row in a.iter_mut() {
+                        (row[p], row[q]) = (c * row[p] - s * row[q], s * row[p] + c * row[q]);
+                    }
+                    rotate(&mut a, p, q, c, s);
+                    rotate(&mut v, p, q, c, s);
+                }
+            }
+        }
+        let diagonal: Vec<f64> = a.iter().enumerate().map(|(i, row)| row[i]).collect();
+        let order = decreasing_order(&diagonal);
+        Ok(SymmetricEigen {
+            eigenvalues: order.iter().map(|&i| diagonal[i]).collect(),
+            eigenvectors: from_columns(&v, &order, n),
+        })
+    }
+
+    /// The thin singular value decomposition.
+    pub fn svd(&self) -> Result<Svd, LinearAlgebraError> {
+        check_finite(self)?;
+        let (m, n) = (self.rows(), self.cols());
+        if m < n {
+            // A^T = V diag(s) U^T
+            let svd = self.transpose().svd()?;
+            return Ok(Svd {
+                u: svd.v,
+                singular_values: svd.singular_values,
+                v: svd.u,
+            });
+        }
+        let mut columns = to_rows(&self.transpose());
+        let mut v = to_rows(&Matrix::identity(n));
+        for _ in 0..MAX_SWEEPS {
+            let mut rotated = false;
+            for i in 0..n {
+                for j in i + 1..n {
+                    let alpha = dot(&columns[i], &columns[i]);
+                    let beta = dot(&columns[j], &columns[j]);
+                    let gamma = dot(&columns[i], &columns[j]);
+                    if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+                    let t = rotation_tangent((beta - alpha) / (2.0 * gamma));
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    rotate(&mut columns, i, j, c, s);
+                    rotate(&mut v, i, j, c, s);
+                }
+            }
+            if !rotated {
+                break;
+            }
+        }
+        let norms: Vec<f64> = columns.iter().map(|c| dot(c, c).sqrt()).collect();
+        let order = decreasing_order(&norms);
+        // The columns of U are the normalised columns, completed to an
+        // orthonormal set where the singular values are zero
+        let tolerance = tolerance(self);
+        let mut u: Vec<Vec<f64>> = vec![];
+        for &k in &order {
+            let column = if norms[k] > tolerance {
+                columns[k].iter().map(|x| x / norms[k]).collect()
+            } else {
+                orthogonal_complement(&u, m)
+            };
+            u.push(column);
+        }
+        let identity: Vec<usize> = (0..n).collect();
+        Ok(Svd {
+            u: from_columns(&u, &identity, m),
+            singular_values: order.iter().map(|&k| norms[k]).collect(),
+            v: from_columns(&v, &order, n),
+        })
+    }
+
+    /// The exponential of a square matrix.
+    pub fn exp(&self) -> Result<Matrix<f64>, LinearAlgebraError> {
+        check_square(self)?;
+        let n = self.rows();
+        // The maximum absolute column sum
+        let norm = (0..n)
+            .map(|j| (0..n).map(|i| self[[i, j]].abs()).sum::<f64>())
+            .fold(0.0, f64::max);
+        let squarings = if norm > EXPONENTIAL_NORM {
+            (norm / EXPONENTIAL_NORM).log2().ceil() as i32
+        } else {
+            0
+        };
+        let scaled = self * 0.5f64.powi(squarings);
+        let mut result = Matrix::identity(n);
+        let mut term = Matrix::identity(n);
+        for k in 1..=EXPONENTIAL_TERMS {
+            term = &(&term * &scaled) * (1.0 / k as f64);
+            result = &result + &term;
+        }
+        for _ in 0..squarings {
+            result = &result * &result;
+        }
+        Ok(result)
+    }
+
+    /// The power of a square matrix, where a negative exponent is a power of
+    /// the inverse.
+    pub fn powi(&self, exponent: i64) -> Result<Matrix<f64>, LinearAlgebraError> {
+        check_square(self)?;
+        if exponent < 0 {
+            Ok(self.inverse()?.pow(exponent.unsigned_abs()))
+        } else {
+            Ok(self.pow(exponent as u64))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::PCG32;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn assert_close(a: &Matrix<f64>, b: &Matrix<f64>) {
+        assert_eq!((a.rows(), a.cols()), (b.rows(), b.cols()));
+        for i in 0..a.rows() {
+            for j in 0..a.cols() {
+                assert!((a[[i, j]] - b[[i, j]]).abs() < EPSILON, "{a:?} != {b:?}");
+            }
+        }
+    }
+
+    fn random_matrix(rng: &mut PCG32, rows: usize, cols: usize) -> Matrix<f64> {
+        let data = (0..rows * cols)
+            .map(|_| rng.get_u32() as f64 / u32::MAX as f64 * 2.0 - 1.0)
+            .collect();
+        Matrix::new(data, rows, cols)
+    }
+
+    fn diagonal(values: &[f64]) -> Matrix<f64> {
+        let mut d = Matrix::zero(values.len(), values.len());
+        for (i, &x) in values.iter().enumerate() {
+            d[[i, i]] = x;
+        }
+        d
+    }
+
+    fn assert_orthonormal_columns(a: &Matrix<f64>) {
+        assert_close(&(&a.transpose() * a), &Matrix::identity(a.cols()));
+    }
+
+    #[test]
+    fn symmetric_eigen() {
+        let a = Matrix::from(vec![vec![2.0, 1.0], vec![1.0, 2.0]]);
+        let eigen = a.symmetric_eigen().unwrap();
+        assert!((eigen.eigenvalues[0] - 3.0).abs() < EPSILON);
+        assert!((eigen.eigenvalues[1] - 1.0).abs() < EPSILON);
+        let x = eigen.eigenvectors[[0, 0]] / eigen.eigenvectors[[1, 0]];
+        assert!((x - 1.0).abs() < EPSILON);
+
+        // Already diagonal
+        let eigen = diagonal(&[1.0, 5.0, -2.0]).symmetric_eigen().unwrap();
+        assert_eq!(eigen.eigenvalues, vec![5.0, 1.0, -2.0]);
+
+        let mut rng = PCG32::new_default(46);
+        for n in 1..8 {
+            let b = random_matrix(&mut rng, n, n);
+            let a = &b + &b.transpose();
+            let eigen = a.symmetric_eigen().unwrap();
+            let v = &eigen.eigenvectors;
+            assert_orthonormal_columns(v);
+            assert_close(&(&a * v), &(v * &diagonal(&eigen.eigenvalues)));
+            assert!(eigen.eigenvalues.windows(2).all(|w| w[0] >= w[1]));
+            let trace: f64 = (0..n).map(|i| a[[i, i]]).sum();
+            assert!((eigen.eigenvalues.iter().sum::<f64>() - trace).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn symmetric_eigen_errors() {
+        let a = Matrix::from(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        assert_eq!(a.symmetric_eigen(), Err(LinearAlgebraError::NotSymmetric));
+        let a = Matrix::from(vec![vec![1.0, 2.0]]);
+        assert_eq!(a.symmetric_eigen(), Err(LinearAlgebraError::NotSquare));
+        let a = Matrix::from(vec![vec![f64::NAN]]);
+        assert_eq!(a.symmetric_eigen(), Err(LinearAlgebraError::NotFinite));
+    }
+
+    #[test]
+    fn svd() {
+        let a = Matrix::from(vec![vec![3.0, 0.0], vec![0.0, -4.0]]);
+        let svd = a.svd().unwrap();
+        assert_eq!(svd.singular_values, vec![4.0, 3.0]);
+        assert_close(
+            &(&(&svd.u * &diagonal(&svd.singular_values)) * &svd.v.transpose()),
+            &a,
+        );
+
+        let mut rng = PCG32::new_default(6);
+        for (m, n) in [(1, 1), (3, 3), (6, 3), (3, 6), (5, 1), (1, 5), (7, 4)] {
+            let a = random_matrix(&mut rng, m, n);
+            let svd = a.svd().unwrap();
+            let k = m.min(n);
+            assert_eq!((svd.u.rows(), svd.u.cols()), (m, k));
+            assert_eq!((svd.v.rows(), svd.v.cols()), (n, k));
+            assert_orthonormal_columns(&svd.u);
+            assert_orthonormal_columns(&svd.v);
+            assert!(svd.singular_values.windows(2).all(|w| w[0] >= w[1]));
+            assert!(svd.singular_values.iter().all(|&s| s >= 0.0));
+            let s = diagonal(&svd.singular_values);
+            assert_close(&(&(&svd.u * &s) * &svd.v.transpose()), &a);
+        }
+    }
+
+    #[test]
+    fn svd_rank_deficient() {
+        // An outer product has rank 1
+        let x = Matrix::from(vec![vec![1.0], vec![2.0], vec![-1.0], vec![0.5]]);
+        let y = Matrix::from(vec![vec![3.0, -1.0, 2.0]]);
+        let a = &x * &y;
+        let svd = a.svd().unwrap();
+        assert!((svd.singular_values[0] - 6.25f64.sqrt() * 14.0f64.sqrt()).abs() < EPSILON);
+        assert!(svd.singular_values[1..].iter().all(|&s| s < EPSILON));
+        assert_orthonormal_columns(&svd.u);
+        assert_orthonormal_columns(&svd.v);
+        let s = diagonal(&svd.singular_values);
+        assert_close(&(&(&svd.u * &s) * &svd.v.transpose()), &a);
+
+        let svd = Matrix::zero(3, 2).svd().unwrap();
+        assert_eq!(svd.singular_values, vec![0.0, 0.0]);
+        assert_orthonormal_columns(&svd.u);
+    }
+
+    #[test]
+    fn exponential() {
+        assert_close(&Matrix::zero(3, 3).exp().unwrap(), &Matrix::identity(3));
+        let e = diagonal(&[1.0, -2.0, 0.5]).exp().unwrap();
+        assert_close(&e, &diagonal(&[1f64.exp(), (-2f64).exp(), 0.5f64.exp()]));
+
+        // A nilpotent matrix: e^A = I + A
+        let a = Matrix::from(vec![vec![0.0, 1.0], vec![0.0, 0.0]]);
+        assert_close(&a.exp().unwrap(), &(&Matrix::identity(2) + &a));
+
+        // The generator of the rotations, with a large angle to exercise the
+        // squarings
+        let t = 20.0f64;
+        let a = Matrix::from(vec![vec![0.0, -t], vec![t, 0.0]]);
+        let rotation = Matrix::from(vec![vec![t.cos(), -t.sin()], vec![t.sin(), t.cos()]]);
+        assert_close(&a.exp().unwrap(), &rotation);
+
+        // e^A e^-A = I
+        let mut rng = PCG32::new_default(7);
+        let a = &random_matrix(&mut rng, 5, 5) * 3.0;
+        let product = &a.exp().unwrap() * &(&a * -1.0).exp().unwrap();
+        assert_close(&product, &Matrix::identity(5));
+
+        let a = Matrix::from(vec![vec![1.0, 2.0]]);
+        assert_eq!(a.exp(), Err(LinearAlgebraError::NotSquare));
+    }
+
+    #[test]
+    fn integer_powers() {
+        let a = Matrix::from(vec![vec![2.0, 1.0], vec![1.0, 1.0]]);
+        assert_close(&a.powi(0).unwrap(), &Matrix::identity(2));
+        assert_close(&a.powi(3).unwrap(), &(&(&a * &a) * &a));
+        let inverse = a.inverse().unwrap();
+        assert_close(&a.powi(-2).unwrap(), &(&inverse * &inverse));
+        assert_close(
+            &(&a.powi(-5).unwrap() * &a.powi(5).unwrap()),
+            &Matrix::identity(2),
+        );
+
+        let singular = Matrix::from(vec![vec![1.0, 2.0], vec![2.0, 4.0]]);
+        assert_eq!(singular.powi(-1), Err(LinearAlgebraError::Singular));
+        assert!(singular.powi(2).is_ok());
+    }
+
+    #[test]
+    fn empty_and_one_by_one() {
+        let empty = Matrix::<f64>::zero(0, 0);
+        let eigen = empty.symmetric_eigen().unwrap();
+        assert!(eigen.eigenvalues.is_empty());
+        assert!(empty.svd().unwrap().singular_values.is_empty());
+        assert_close(&empty.exp().unwrap(), &empty);
+        assert_close(&empty.powi(-3).unwrap(), &empty);
+        // No columns, so no singular values
+        let svd = Matrix::<f64>::zero(3, 0).svd().unwrap();
+        assert!(svd.singular_values.is_empty());
+        assert_eq!((svd.u.rows(), svd.u.cols()), (3, 0));
+        let svd = Matrix::<f64>::zero(0, 3).svd().unwrap();
+        assert_eq!((svd.v.rows(), svd.v.cols()), (3, 0));
+
+        let a = Matrix::from(vec![vec![-2.0]]);
+        let eigen = a.symmetric_eigen().unwrap();
+        assert_eq!(eigen.eigenvalues, vec![-2.0]);
+        assert_eq!(eigen.eigenvectors[[0, 0]].abs(), 1.0);
+        let svd = a.svd().unwrap();
+        assert_eq!(svd.singular_values, vec![2.0]);
+        assert_eq!(svd.u[[0, 0]] * svd.v[[0, 0]], -1.0);
+        let zero = Matrix::<f64>::zero(1, 1);
+        assert_eq!(zero.powi(-1), Err(LinearAlgebraError::Singular));
+        assert_close(&zero.exp().unwrap(), &Matrix::identity(1));
+        // The zero column gets a unit vector of U all the same
+        let svd = Matrix::<f64>::zero(2, 1).svd().unwrap();
+        assert_eq!(svd.singular_values, vec![0.0]);
+        assert_orthonormal_columns(&svd.u);
+    }
+}